@@ -1,6 +1,5 @@
 use crate::client::archive::CArchive;
 use crate::client::cdn::CContentDeployment;
-use crate::client::client::CClient;
 use crate::core::ig_ark_core::EGame;
 use crate::core::ig_external_ref::igExternalReferenceSystem;
 use crate::core::ig_file_context::{get_file_name, igFileContext};
@@ -35,6 +34,18 @@ pub trait CResourcePreCacher: Send + Sync {
     fn uncache(&self);
 }
 
+/// The managers [CPrecacheManager::precache_packages] loads packages with
+pub struct CPrecacheContext<'a> {
+    pub archive_loader: &'a CArchive,
+    pub cdn: &'a CContentDeployment,
+    pub ig_registry: &'a igRegistry,
+    pub ig_file_context: &'a mut igFileContext,
+    pub ig_object_stream_manager: &'a mut igObjectStreamManager,
+    pub ig_ext_ref_system: &'a mut igExternalReferenceSystem,
+    pub ig_object_handle_manager: &'a mut igObjectHandleManager,
+    pub ig_metadata_manager: &'a mut igMetadataManager,
+}
+
 pub struct CPrecacheManager {
     pub resource_pre_cachers: Vec<Arc<dyn CResourcePreCacher>>,
     pub resource_pre_cacher_lookup: HashMap<Arc<str>, Arc<dyn CResourcePreCacher>>,
@@ -642,86 +653,133 @@ impl CPrecacheManager {
         package_name: String,
         pool_id: EMemoryPoolID,
     ) {
+        let ctx = CPrecacheContext {
+            archive_loader,
+            cdn,
+            ig_registry,
+            ig_file_context,
+            ig_object_stream_manager,
+            ig_ext_ref_system,
+            ig_object_handle_manager,
+            ig_metadata_manager,
+        };
+        self.precache_packages(ctx, vec![package_name], pool_id);
+    }
+
+    /// Same as [CPrecacheManager::precache_package] for several packages. With [igObjectStreamManager::parallel_loading] the packages are loaded together with [igObjectStreamManager::load_many]
+    pub fn precache_packages(&self, ctx: CPrecacheContext, package_names: Vec<String>, pool_id: EMemoryPoolID) {
+        let CPrecacheContext {
+            archive_loader,
+            cdn,
+            ig_registry,
+            ig_file_context,
+            ig_object_stream_manager,
+            ig_ext_ref_system,
+            ig_object_handle_manager,
+            ig_metadata_manager,
+        } = ctx;
+
         match ig_registry.build_tool {
             BuildTool::AlchemyLaboratory => {
-                let mut package_path = package_name.to_lowercase();
+                let mut package_paths = Vec::with_capacity(package_names.len());
+                for package_name in package_names {
+                    let mut package_path = package_name.to_lowercase();
 
-                if !package_path.starts_with("packages") {
-                    package_path = format!("packages/{}", package_path);
-                }
+                    if !package_path.starts_with("packages") {
+                        package_path = format!("packages/{}", package_path);
+                    }
 
-                if !package_path.ends_with("_pkg.igz") {
-                    package_path = format!("{}_pkg.igz", package_path);
-                }
+                    if !package_path.ends_with("_pkg.igz") {
+                        package_path = format!("{}_pkg.igz", package_path);
+                    }
 
-                if self.package_cached(&package_path, pool_id) {
-                    return;
-                }
+                    if self.package_cached(&package_path, &pool_id) || package_paths.contains(&package_path) {
+                        continue;
+                    }
 
-                // igCauldron removed the extension here however it never has one so ???
-                archive_loader
-                    .open(
-                        cdn,
-                        ig_file_context,
-                        ig_registry,
-                        get_file_name(&package_path.trim_end_matches("_pkg.igz")).unwrap(),
-                        0,
-                    )
-                    .unwrap();
+                    // igCauldron removed the extension here however it never has one so ???
+                    archive_loader
+                        .open(
+                            cdn,
+                            ig_file_context,
+                            ig_registry,
+                            get_file_name(package_path.trim_end_matches("_pkg.igz")).unwrap(),
+                            0,
+                        )
+                        .unwrap();
+                    package_paths.push(package_path);
+                }
 
-                let pkg_dir = ig_object_stream_manager
-                    .load(
+                let pkg_dirs = if ig_object_stream_manager.parallel_loading {
+                    ig_object_stream_manager.load_many(
                         ig_file_context,
                         ig_registry,
                         ig_metadata_manager,
                         ig_ext_ref_system,
                         ig_object_handle_manager,
-                        package_path.clone(),
+                        package_paths,
                     )
-                    .unwrap();
-
-                let guard = pkg_dir.read().unwrap();
-                let ig_object_list = guard.object_list.read().unwrap();
-                let objects = &ig_object_list.list.read().unwrap();
-                let ig_string_ref_list = objects[0].clone().downcast::<igStringRefList>().unwrap();
-                let ig_string_ref_guard = ig_string_ref_list.read().unwrap();
-                let data = ig_string_ref_guard.list.read().unwrap();
-                for i in (0..data.len()).step_by(2) {
-                    let package_name = &package_path;
-                    let file_data_type = &data[i];
-                    let file_name = data[i + 1].clone();
-
-                    if let Some(precacher) = self.resource_pre_cacher_lookup.get(file_data_type) {
-                        debug!("Precache type = {}, value = {}", file_data_type, file_name);
-                        precacher.precache(
-                            archive_loader,
-                            cdn,
-                            ig_registry,
-                            ig_file_context,
-                            ig_object_stream_manager,
-                            ig_ext_ref_system,
-                            ig_metadata_manager
-                        );
-                    } else {
-                        // error!("file type {} has no registered loader", file_data_type);
+                } else {
+                    package_paths
+                        .into_iter()
+                        .map(|package_path| {
+                            ig_object_stream_manager.load(
+                                ig_file_context,
+                                ig_registry,
+                                ig_metadata_manager,
+                                ig_ext_ref_system,
+                                ig_object_handle_manager,
+                                package_path,
+                            )
+                        })
+                        .collect()
+                };
+
+                for pkg_dir in pkg_dirs {
+                    let pkg_dir = pkg_dir.unwrap();
+                    let guard = pkg_dir.read().unwrap();
+                    let ig_object_list = guard.object_list.read().unwrap();
+                    let objects = &ig_object_list.list.read().unwrap();
+                    let ig_string_ref_list = objects[0].clone().downcast::<igStringRefList>().unwrap();
+                    let ig_string_ref_guard = ig_string_ref_list.read().unwrap();
+                    let data = ig_string_ref_guard.list.read().unwrap();
+                    for i in (0..data.len()).step_by(2) {
+                        let file_data_type = &data[i];
+                        let file_name = data[i + 1].clone();
+
+                        if let Some(precacher) = self.resource_pre_cacher_lookup.get(file_data_type) {
+                            debug!("Precache type = {}, value = {}", file_data_type, file_name);
+                            precacher.precache(
+                                archive_loader,
+                                cdn,
+                                ig_registry,
+                                ig_file_context,
+                                ig_object_stream_manager,
+                                ig_ext_ref_system,
+                                ig_metadata_manager
+                            );
+                        } else {
+                            // error!("file type {} has no registered loader", file_data_type);
+                        }
                     }
                 }
-
             }
             BuildTool::TfbTool => {
-                // TODO: move to where tfb handles loading stuff
-                ig_file_context.load_archive(ig_registry, &package_name);
+                for package_name in package_names {
+                    // TODO: move to where tfb handles loading stuff
+                    ig_file_context.load_archive(ig_registry, &package_name);
 
-                let _pkg_dir = ig_object_stream_manager
-                    .load(
-                        ig_file_context,
-                        ig_registry,
-                        ig_metadata_manager,
-                        ig_ext_ref_system,
-                        ig_object_handle_manager,
-                        format!("{}/level.bld", package_name),
-                    )
-                    .unwrap();
+                    let _pkg_dir = ig_object_stream_manager
+                        .load(
+                            ig_file_context,
+                            ig_registry,
+                            ig_metadata_manager,
+                            ig_ext_ref_system,
+                            ig_object_handle_manager,
+                            format!("{}/level.bld", package_name),
+                        )
+                        .unwrap();
+                }
             }
 
             BuildTool::None => {
@@ -730,8 +788,8 @@ impl CPrecacheManager {
         }
     }
 
-    pub fn package_cached(&self, package_name: &str, pool_id: EMemoryPoolID) -> bool {
-        let pool_packages = &self.pool_package_lookup[&pool_id];
+    pub fn package_cached(&self, package_name: &str, pool_id: &EMemoryPoolID) -> bool {
+        let pool_packages = &self.pool_package_lookup[pool_id];
         pool_packages.contains(&package_name.to_lowercase())
     }
 }
@@ -767,6 +825,8 @@ pub fn load_init_script(game: EGame, is_weakly_loaded: bool, ig_alchemy: &mut ig
 
     let mut task = LoaderTask::LooseIga;
     let mut line_number = 0;
+    // Lines are handed to their task a section at a time so packages can be loaded together
+    let mut task_lines = Vec::new();
     for raw_line in file_lines {
         let line = raw_line.unwrap();
         line_number += 1;
@@ -783,6 +843,7 @@ pub fn load_init_script(game: EGame, is_weakly_loaded: bool, ig_alchemy: &mut ig
                 break;
             }
 
+            process_task(ig_alchemy, task, std::mem::take(&mut task_lines));
             task = parse_task(line.clone(), is_weakly_loaded);
 
             if task == LoaderTask::Unknown {
@@ -801,19 +862,11 @@ pub fn load_init_script(game: EGame, is_weakly_loaded: bool, ig_alchemy: &mut ig
                 );
             }
 
-            process_task(
-                &mut ig_alchemy.client,
-                &mut ig_alchemy.file_context,
-                &mut ig_alchemy.registry,
-                &mut ig_alchemy.object_stream_manager,
-                &mut ig_alchemy.ig_ext_ref_system,
-                &mut ig_alchemy.ig_object_handle_manager,
-                &mut ig_alchemy.ark_core.metadata_manager,
-                task.clone(),
-                path.unwrap(),
-            );
+            task_lines.push(path.unwrap());
         }
     }
+    process_task(ig_alchemy, task, task_lines);
+    ig_alchemy.object_stream_manager.write_directory_cache(&ig_alchemy.file_context, &mut ig_alchemy.ark_core.metadata_manager);
     info!("initscript -> done");
 }
 
@@ -873,50 +926,55 @@ pub fn parse_file_path(line: String, ig_registry: &igRegistry) -> Option<String>
     Some(processed)
 }
 
-fn process_task(
-    client: &mut CClient,
-    ig_file_context: &mut igFileContext,
-    ig_registry: &mut igRegistry,
-    ig_object_stream_manager: &mut igObjectStreamManager,
-    ig_ext_ref_system: &mut igExternalReferenceSystem,
-    ig_object_handle_manager: &mut igObjectHandleManager,
-    ig_metadata_manager: &mut igMetadataManager,
-    task: LoaderTask,
-    line: String,
-) {
-    info!("initscript -> {:?} {}", task, line);
+fn process_task(ig_alchemy: &mut igAlchemy, task: LoaderTask, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    for line in &lines {
+        info!("initscript -> {:?} {}", task, line);
+    }
+    let client = &mut ig_alchemy.client;
+    let ig_file_context = &mut ig_alchemy.file_context;
+    let ig_registry = &mut ig_alchemy.registry;
     let precache_manager = &mut client.precache_manager;
 
     match task {
         LoaderTask::LooseIga => {
-            ig_file_context.load_archive(ig_registry, &line);
+            for line in lines {
+                ig_file_context.load_archive(ig_registry, &line);
+            }
         }
         LoaderTask::FullPackage => {
-            precache_manager.precache_package(
-                &client.archive_loader,
-                &client.content_deployment,
+            let ctx = CPrecacheContext {
+                archive_loader: &client.archive_loader,
+                cdn: &client.content_deployment,
                 ig_registry,
                 ig_file_context,
-                ig_object_stream_manager,
-                ig_ext_ref_system,
-                ig_object_handle_manager,
-                ig_metadata_manager,
-                line,
-                EMemoryPoolID::MP_DEFAULT,
-            );
+                ig_object_stream_manager: &mut ig_alchemy.object_stream_manager,
+                ig_ext_ref_system: &mut ig_alchemy.ig_ext_ref_system,
+                ig_object_handle_manager: &mut ig_alchemy.ig_object_handle_manager,
+                ig_metadata_manager: &mut ig_alchemy.ark_core.metadata_manager,
+            };
+            precache_manager.precache_packages(ctx, lines, EMemoryPoolID::MP_DEFAULT);
         }
         LoaderTask::LoosePackage => {
-            let full_path = format!("app:/archives/{}.pak", line);
-            ig_file_context.load_archive(ig_registry, &full_path);
+            for line in lines {
+                let full_path = format!("app:/archives/{}.pak", line);
+                ig_file_context.load_archive(ig_registry, &full_path);
+            }
         }
-        LoaderTask::EngineType => match line.as_str() {
-            "None" => ig_registry.build_tool = BuildTool::None,
-            "AlchemyLaboratory" => ig_registry.build_tool = BuildTool::AlchemyLaboratory,
-            "TfbTool" => ig_registry.build_tool = BuildTool::TfbTool,
-            _ => {
-                error!("Invalid initscript. {} is not a valid EngineType", line);
+        LoaderTask::EngineType => {
+            for line in lines {
+                match line.as_str() {
+                    "None" => ig_registry.build_tool = BuildTool::None,
+                    "AlchemyLaboratory" => ig_registry.build_tool = BuildTool::AlchemyLaboratory,
+                    "TfbTool" => ig_registry.build_tool = BuildTool::TfbTool,
+                    _ => {
+                        error!("Invalid initscript. {} is not a valid EngineType", line);
+                    }
+                }
             }
-        },
+        }
         LoaderTask::NoOp | LoaderTask::Unknown => {}
     }
 }
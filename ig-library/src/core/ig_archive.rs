@@ -1,3 +1,4 @@
+use crate::core::ig_directory_cache::{fnv1a, FNV_OFFSET};
 use crate::core::ig_file_context::WorkStatus::{
     kStatusComplete, kStatusGeneralError, kStatusInvalidPath, kStatusUnsupported,
 };
@@ -120,6 +121,22 @@ impl igArchive {
         .is_some()
     }
 
+    /// Identifies the copy of a file stored in this archive from the archive's path and the file's stored (still compressed) bytes, so a cached form of it can be checked without decompressing it. Returns None when the file isn't in this archive
+    pub fn file_stamp(&self, path: &str) -> Option<u64> {
+        let file_idx = Self::hash_search(
+            &self._files,
+            self._archive_header._hash_search_divider,
+            self._archive_header._hash_search_slop,
+            self.hash_file_path(path),
+        )?;
+
+        let mut stamp = fnv1a(FNV_OFFSET, self._path.to_lowercase().as_bytes());
+        for value in [self._archive_header._version, self._archive_header._toc_size, self._archive_header._num_files] {
+            stamp = fnv1a(stamp, &value.to_le_bytes());
+        }
+        Some(self._files[file_idx].stamp(stamp))
+    }
+
     fn decompress_as_handle(&self, file_info: &FileInfo) -> Cursor<Vec<u8>> {
         Cursor::new(self.decompress(file_info, self._archive_header._version))
     }
@@ -1052,6 +1069,17 @@ pub struct FileInfo {
 }
 
 impl FileInfo {
+    /// Continues `stamp` with the table of contents entry and stored bytes of this file. Hashing the stored bytes means an edit that keeps the length and modification time still changes the stamp
+    pub(crate) fn stamp(&self, mut stamp: u64) -> u64 {
+        for value in [self._hash, self._offset, self._length, self._block_index, self._modification_time] {
+            stamp = fnv1a(stamp, &value.to_le_bytes());
+        }
+        for block in self._blocks.iter().flatten() {
+            stamp = fnv1a(stamp, &block.to_le_bytes());
+        }
+        fnv1a(stamp, &self._compressed_data)
+    }
+
     pub fn get_block_type(&self, sector_size: u32) -> EBlockType {
         if self._blocks.is_none() {
             return EBlockType::kNone;
@@ -1,16 +1,25 @@
 use crate::core::ig_ark_core::EGame::*;
-use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
 use crate::core::meta::ig_xml_metadata::load_xml_metadata;
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 use std::sync::Arc;
 use serde::Serialize;
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::meta::field::r#impl::ig_enum_meta_field::igEnumMetaField;
+use crate::core::meta::field::r#impl::ig_bit_field_meta_field::igBitFieldMetaField;
+use crate::core::meta::field::r#impl::ig_vector_meta_field::igVectorMetaField;
 use crate::core::meta::field::r#impl::ig_int_meta_field::igIntMetaField;
 use crate::core::meta::field::r#impl::ig_memory_ref_meta_field::igMemoryRefMetaField;
+use crate::core::meta::field::r#impl::ig_memory_ref_handle_meta_field::igMemoryRefHandleMetaField;
+use crate::core::meta::field::r#impl::ig_handle_meta_field::igHandleMetaField;
+use crate::core::meta::field::r#impl::ig_raw_ref_meta_field::igRawRefMetaField;
 use crate::core::meta::field::r#impl::ig_object_ref_meta_field::igObjectRefMetaField;
+use crate::core::meta::field::r#impl::ig_math_meta_field::{igVec2fMetaField, igVec3fMetaField, igVec3fAlignedMetaField, igVec3dMetaField, igVec4fMetaField, igVec4fUnalignedMetaField, igVec4iMetaField, igVec2ucMetaField, igVec3ucMetaField, igVec4ucMetaField, igQuaternionfMetaField, igMatrix44fMetaField, igRangedFloatMetaField, igRangedVectorMetaField};
+use crate::core::meta::field::r#impl::ig_primitive_meta_field::{igBoolMetaField, igCharMetaField, igDoubleMetaField, igFloatMetaField, igLongMetaField, igShortMetaField, igUnsignedCharMetaField, igUnsignedIntMetaField, igUnsignedLongMetaField, igUnsignedShortMetaField};
 use crate::core::meta::field::r#impl::ig_size_type_meta_field::igSizeTypeMetaField;
 use crate::core::meta::field::r#impl::ig_string_meta_field::igStringMetaField;
+use crate::core::meta::field::r#impl::ig_struct_meta_field::igStructMetaField;
 use crate::util::ig_name::igNameMetaField;
 
 /// Contains reflection metadata information. Stands for Application Runtime Kernel.
@@ -28,6 +37,16 @@ impl igArkCore {
     }
 }
 
+/// Registers metafields that are built from their own metadata with `new(name, imm)`, under their type name
+macro_rules! register_built_metafields {
+    ($imm:ident, $($metafield:ident),* $(,)?) => {
+        $(
+            let metafield = $metafield::new(stringify!($metafield), $imm);
+            $imm.meta_field_registry.register::<$metafield>(Arc::from(stringify!($metafield)), Arc::new(metafield));
+        )*
+    };
+}
+
 /// Registers all built in meta fields to the [core::meta::field::ig_metafield_registry::igMetafieldRegistry]
 fn register_metafields(imm: &mut igMetadataManager) {
     imm.meta_field_registry.register::<igIntMetaField>(Arc::from("igIntMetaField"), Arc::new(igIntMetaField));
@@ -35,18 +54,58 @@ fn register_metafields(imm: &mut igMetadataManager) {
     imm.meta_field_registry.register::<igNameMetaField>(Arc::from("igNameMetaField"), Arc::new(igNameMetaField));
     imm.meta_field_registry.register::<igSizeTypeMetaField>(Arc::from("igSizeTypeMetaField"), Arc::new(igSizeTypeMetaField));
     imm.meta_field_registry.register::<igObjectRefMetaField>(Arc::from("igObjectRefMetaField"), Arc::new(igObjectRefMetaField));
+    register_built_metafields!(
+        imm,
+        igBoolMetaField,
+        igCharMetaField,
+        igUnsignedCharMetaField,
+        igShortMetaField,
+        igUnsignedShortMetaField,
+        igUnsignedIntMetaField,
+        igLongMetaField,
+        igUnsignedLongMetaField,
+        igFloatMetaField,
+        igDoubleMetaField,
+        igVec2fMetaField,
+        igVec3fMetaField,
+        igVec3fAlignedMetaField,
+        igVec3dMetaField,
+        igVec4fMetaField,
+        igVec4fUnalignedMetaField,
+        igVec4iMetaField,
+        igVec2ucMetaField,
+        igVec3ucMetaField,
+        igVec4ucMetaField,
+        igQuaternionfMetaField,
+        igMatrix44fMetaField,
+        igRangedFloatMetaField,
+        igRangedVectorMetaField,
+    );
     imm.meta_field_registry.register_complex::<igMemoryRefMetaField>(Arc::from("igMemoryRefMetaField"), |ark_field, imm, _metafield_registry, platform| {
-        let raw_internal_metafield = &ark_field.ark_info.read().unwrap().clone().ig_memory_ref_info.unwrap();
-        // TODO: i need a better system for this. so many types here it really is ugly but the oop side of this makes it hard to work through
-        let updated_internal_metafield = igMetaFieldInfo {
-            ark_info: raw_internal_metafield.clone(),
-            _type: raw_internal_metafield.read().unwrap().clone()._type,
-            name: raw_internal_metafield.read().unwrap().clone().name,
-            size: imm.calculate_size(&raw_internal_metafield.read().unwrap(), platform),
-            alignment: raw_internal_metafield.read().unwrap().required_alignment.unwrap_or_else(|| 4),
-            offset: raw_internal_metafield.read().unwrap().clone().offset, // should always be 0 but just in case.
-        };
-        Arc::new(igMemoryRefMetaField(Arc::new(updated_internal_metafield)))
+        let raw_internal_metafield = ark_field.ark_info.read().unwrap().ig_memory_ref_info.clone().unwrap();
+        Arc::new(igMemoryRefMetaField(Arc::new(imm.inner_field_info(&raw_internal_metafield, platform))))
+    });
+    imm.meta_field_registry.register_complex::<igMemoryRefHandleMetaField>(Arc::from("igMemoryRefHandleMetaField"), |ark_field, imm, _metafield_registry, platform| {
+        let raw_internal_metafield = ark_field.ark_info.read().unwrap().ig_memory_ref_info.clone().unwrap();
+        Arc::new(igMemoryRefHandleMetaField(igMemoryRefMetaField(Arc::new(imm.inner_field_info(&raw_internal_metafield, platform)))))
+    });
+    imm.meta_field_registry.register_complex::<igHandleMetaField>(Arc::from("igHandleMetaField"), |ark_field, _imm, _metafield_registry, _platform| {
+        Arc::new(igHandleMetaField { size: ark_field.size })
+    });
+    imm.meta_field_registry.register_complex::<igRawRefMetaField>(Arc::from("igRawRefMetaField"), |ark_field, _imm, _metafield_registry, _platform| {
+        Arc::new(igRawRefMetaField { size: ark_field.size })
+    });
+    imm.meta_field_registry.register_complex::<igVectorMetaField>(Arc::from("igVectorMetaField"), |ark_field, imm, _metafield_registry, platform| {
+        Arc::new(igVectorMetaField::new(ark_field, imm, platform))
+    });
+    imm.meta_field_registry.register_complex::<igEnumMetaField>(Arc::from("igEnumMetaField"), |ark_field, imm, _metafield_registry, _platform| {
+        Arc::new(igEnumMetaField::new(ark_field, imm))
+    });
+    imm.meta_field_registry.register_complex::<igBitFieldMetaField>(Arc::from("igBitFieldMetaField"), |ark_field, imm, metafield_registry, platform| {
+        Arc::new(igBitFieldMetaField::new(ark_field, imm, metafield_registry, platform))
+    });
+    imm.meta_field_registry.register_complex::<igStructMetaField>(Arc::from("igStructMetaField"), |ark_field, _imm, _metafield_registry, _platform| {
+        Arc::new(igStructMetaField { size: ark_field.size })
     });
 }
 
@@ -0,0 +1,38 @@
+use crate::core::ig_objects::igAny;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The value of a compound metafield such as ColorDataMetaField. A compound metafield is a small struct described by the `compoundfields` of its metaobject, the value holds each of its fields by name the same way an object does
+#[derive(Clone)]
+pub struct igCompoundValue {
+    /// Name of the compound metafield the value belongs to
+    pub type_name: Arc<str>,
+    fields: HashMap<Arc<str>, igAny>,
+}
+
+impl igCompoundValue {
+    pub fn new(type_name: Arc<str>) -> igCompoundValue {
+        igCompoundValue {
+            type_name,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Returns the value of the field `name`, or [None] when it is null or doesn't exist
+    pub fn get_field(&self, name: &str) -> Option<igAny> {
+        self.fields.get(name).cloned()
+    }
+
+    /// Sets the value of the field `name`. Setting [None] makes the field null
+    pub fn set_field(&mut self, name: Arc<str>, value: Option<igAny>) {
+        match value {
+            Some(value) => self.fields.insert(name, value),
+            None => self.fields.remove(&name),
+        };
+    }
+
+    /// Every field that isn't null, in no particular order
+    pub fn fields(&self) -> impl Iterator<Item = (&Arc<str>, &igAny)> {
+        self.fields.iter()
+    }
+}
@@ -1,12 +1,16 @@
-/// Custom implementations of objects to make usage more ergonomic. Currently just focused around objects using igDataList
+/// Custom implementations of objects to make usage more ergonomic. Currently focused around objects using igDataList and igHashTable
 use crate::core::ig_archive::igArchive;
+use crate::core::ig_math::{igMatrix44f, igQuaternionf, igRangedFloat, igRangedVector, igVec2f, igVec2uc, igVec3d, igVec3f, igVec3uc, igVec4f, igVec4i, igVec4uc};
 use crate::core::ig_memory::igMemoryPool;
 use crate::core::ig_objects::{igAny, igObject, igObjectDirectory, ObjectExt};
-use crate::core::memory::igMemory;
-use crate::core::meta::ig_metadata_manager::{__internalObjectBase, igMetaInstantiationError, igMetaObject, igMetadataManager, FieldDoesntExist, SetObjectFieldError};
+use crate::core::memory::{element_or_null, element_value, igMemory, igNullElement};
+use crate::core::meta::ig_metadata_manager::{__internalObjectBase, igMetaInstantiationError, igMetaObject, igMetadataManager, FieldDoesntExist, MetaObjectConstructor, SetObjectFieldError};
+use crate::core::meta::field::r#impl::ig_math_meta_field::igMathValue;
+use crate::util::ig_hash::{hash, hash_lower, hash_int};
+use crate::core::meta::field::r#impl::ig_enum_meta_field::igEnumValue;
 use crate::util::ig_name::igName;
 use log::{error, warn};
-use std::any::Any;
+use std::any::{type_name, Any};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
 
 /// This type is a placeholder in places where we need an igObject, but one failed to load. If you see this type, you can basically guarantee you can't edit the igz containing it without causing issues.
@@ -54,6 +58,11 @@ pub struct igDataList<T> {
     pub list: Arc<RwLock<Vec<T>>>,
     object_name: Arc<str>,
     pool: igMemoryPool,
+    /// The `_count` and `_capacity` read from a file. Checked against `_data` once it is known then cleared, see [igDataList::validate]
+    loaded_count: Option<usize>,
+    loaded_capacity: Option<usize>,
+    /// Amount of elements in the `_data` read from a file
+    loaded_memory_length: Option<usize>,
 }
 
 pub type igObjectList = igDataList<igObject>;
@@ -62,6 +71,39 @@ pub type igArchiveList = igDataList<Arc<igArchive>>;
 pub type igObjectDirectoryList = igDataList<Arc<RwLock<igObjectDirectory>>>;
 pub type igNameList = igDataList<igName>;
 
+/// Returns the constructor of the igDataList holding the rust type `element_metafield` reads. Used for list types with no constructor in [TYPE_TO_METAOBJECT_LOOKUP](crate::core::meta::ig_metadata_manager::TYPE_TO_METAOBJECT_LOOKUP). Elements with no matching rust type are kept as [igAny]
+pub(crate) fn data_list_constructor(element_metafield: &str) -> MetaObjectConstructor {
+    match element_metafield {
+        "igObjectRefMetaField" => igObjectList::construct,
+        "igStringMetaField" => igStringRefList::construct,
+        "igNameMetaField" => igNameList::construct,
+        "igBoolMetaField" => igDataList::<bool>::construct,
+        "igCharMetaField" => igDataList::<i8>::construct,
+        "igUnsignedCharMetaField" => igDataList::<u8>::construct,
+        "igShortMetaField" => igDataList::<i16>::construct,
+        "igUnsignedShortMetaField" => igDataList::<u16>::construct,
+        "igIntMetaField" => igDataList::<i32>::construct,
+        "igUnsignedIntMetaField" => igDataList::<u32>::construct,
+        "igLongMetaField" => igDataList::<i64>::construct,
+        "igUnsignedLongMetaField" | "igSizeTypeMetaField" => igDataList::<u64>::construct,
+        "igFloatMetaField" => igDataList::<f32>::construct,
+        "igDoubleMetaField" => igDataList::<f64>::construct,
+        "igVec2fMetaField" => igDataList::<igVec2f>::construct,
+        "igVec3fMetaField" | "igVec3fAlignedMetaField" => igDataList::<igVec3f>::construct,
+        "igVec3dMetaField" => igDataList::<igVec3d>::construct,
+        "igVec4fMetaField" | "igVec4fUnalignedMetaField" => igDataList::<igVec4f>::construct,
+        "igVec4iMetaField" => igDataList::<igVec4i>::construct,
+        "igVec2ucMetaField" => igDataList::<igVec2uc>::construct,
+        "igVec3ucMetaField" => igDataList::<igVec3uc>::construct,
+        "igVec4ucMetaField" => igDataList::<igVec4uc>::construct,
+        "igQuaternionfMetaField" => igDataList::<igQuaternionf>::construct,
+        "igMatrix44fMetaField" => igDataList::<igMatrix44f>::construct,
+        "igRangedFloatMetaField" => igDataList::<igRangedFloat>::construct,
+        "igRangedVectorMetaField" => igDataList::<igRangedVector>::construct,
+        _ => igDataList::<igAny>::construct,
+    }
+}
+
 pub struct QueryGuard<'a, T>(RwLockReadGuard<'a, Vec<T>>);
 pub struct MutableQueryGuard<'a, T>(RwLockWriteGuard<'a, Vec<T>>);
 
@@ -115,6 +157,9 @@ where
             list: Arc::new(RwLock::new(new_vec)),
             object_name: old.object_name.clone(),
             pool: old.pool,
+            loaded_count: None,
+            loaded_capacity: None,
+            loaded_memory_length: None,
         };
 
         // 4) wrap back in Arc<RwLock<…>>
@@ -144,17 +189,41 @@ impl<T: Send + Sync + 'static + Clone> __internalObjectBase for igDataList<T> {
             match name {
                 "_data" => {
                     let guard = value.read().unwrap();
-                    let memory = guard.downcast_ref::<igMemory<igAny>>().unwrap();
-                    let mut data_writer = self.list.write().unwrap();
+                    let memory = guard.downcast_ref::<igMemory<igAny>>().ok_or(SetObjectFieldError::InvalidValueType)?;
+                    let mut data = Vec::with_capacity(memory.data.len());
                     for value in memory.data.iter() {
                         let ig_any = value.read().unwrap();
-                        let correct_type_val= ig_any.downcast_ref::<T>().expect("igMemory generic does not match _data. TODO: generate these with macros and have an error message that says what the generic is");
-                        data_writer.push(correct_type_val.clone());
+                        // Lists of igAny keep the elements as they are
+                        let correct_type_val = match ig_any.downcast_ref::<T>() {
+                            Some(correct_type_val) => correct_type_val.clone(),
+                            None => match (value as &dyn Any).downcast_ref::<T>() {
+                                Some(correct_type_val) => correct_type_val.clone(),
+                                None if ig_any.is::<igNullElement>() => {
+                                    warn!("{} holds a null element which can't be stored as {}. It is skipped", self.object_name, type_name::<T>());
+                                    continue;
+                                }
+                                None => {
+                                    warn!("{} holds elements of type {}, but an element of _data is another type", self.object_name, type_name::<T>());
+                                    return Err(SetObjectFieldError::InvalidValueType);
+                                }
+                            },
+                        };
+                        data.push(correct_type_val);
                     }
-                    return Ok(());
+                    self.loaded_memory_length = Some(data.len());
+                    *self.list.write().unwrap() = data;
+                    return self.validate();
                 }
                 "_count" | "_capacity" => {
-                    // we dont care about these. TODO: sanity check compare these against the igMemory's values.
+                    let guard = value.read().unwrap();
+                    let value = guard.downcast_ref::<i32>().ok_or(SetObjectFieldError::InvalidValueType)?;
+                    let value = usize::try_from(*value).map_err(|_| SetObjectFieldError::InvalidValue)?;
+                    match name {
+                        "_count" => self.loaded_count = Some(value),
+                        _ => self.loaded_capacity = Some(value),
+                    }
+                    drop(guard);
+                    return self.validate();
                 },
                 &_ => {
                     warn!(
@@ -169,16 +238,30 @@ impl<T: Send + Sync + 'static + Clone> __internalObjectBase for igDataList<T> {
     }
 
     #[inline]
-    fn get_non_null_field(&self, _name: &str) -> Result<igAny, FieldDoesntExist> {
-        todo!()
+    fn get_non_null_field(&self, name: &str) -> Result<igAny, FieldDoesntExist> {
+        Ok(self
+            .get_field(name)?
+            .expect("called get_non_null_field on a null value"))
     }
 
     #[inline]
     fn get_field(
         &self,
-        _name: &str,
+        name: &str,
     ) -> Result<Option<Arc<RwLock<(dyn Any + Send + Sync + 'static)>>>, FieldDoesntExist> {
-        todo!()
+        match name {
+            "_data" => {
+                let mut memory: igMemory<igAny> = igMemory::new();
+                memory.pool = self.pool;
+                for value in self.list.read().unwrap().iter() {
+                    memory.data.push(Arc::new(RwLock::new(value.clone())));
+                }
+                Ok(Some(Arc::new(RwLock::new(memory))))
+            }
+            // these are derived from the length of _data. Savers write null ints as 0, which would empty the list when loaded back
+            "_count" | "_capacity" => Ok(Some(Arc::new(RwLock::new(self.list.read().unwrap().len() as i32)))),
+            &_ => Err(FieldDoesntExist),
+        }
     }
 
     fn as_any(&self) -> &(dyn Any + Send + Sync) {
@@ -191,6 +274,29 @@ impl<T: Send + Sync + 'static + Clone> __internalObjectBase for igDataList<T> {
 }
 
 impl<T: Send + Sync + 'static + Clone> igDataList<T> {
+    /// Compares the loaded `_count` and `_capacity` against the length of the loaded `_data`, and drops the elements past `_count`. Waits until `_data` has been set since fields can be set in any order
+    fn validate(&mut self) -> Result<(), SetObjectFieldError> {
+        let Some(memory_length) = self.loaded_memory_length else {
+            return Ok(());
+        };
+
+        if let Some(capacity) = self.loaded_capacity.take() {
+            if capacity != memory_length {
+                warn!("{} has a _capacity of {} but _data holds {} elements", self.object_name, capacity, memory_length);
+            }
+        }
+
+        if let Some(count) = self.loaded_count.take() {
+            if count > memory_length {
+                warn!("{} has a _count of {} but _data only holds {} elements", self.object_name, count, memory_length);
+                return Err(SetObjectFieldError::InvalidValue);
+            }
+            self.list.write().unwrap().truncate(count);
+        }
+
+        Ok(())
+    }
+
     pub fn construct(
         meta: &igMetaObject,
         pool: igMemoryPool,
@@ -199,6 +305,9 @@ impl<T: Send + Sync + 'static + Clone> igDataList<T> {
             list: Arc::new(RwLock::new(Vec::<T>::new())),
             object_name: meta.name.clone(),
             pool,
+            loaded_count: None,
+            loaded_capacity: None,
+            loaded_memory_length: None,
         })))
     }
 }
@@ -209,6 +318,9 @@ impl<T: Clone> igDataList<T> {
             list: Arc::new(RwLock::new(Vec::new())),
             object_name: Arc::from("INVALID"),
             pool: Default::default(),
+            loaded_count: None,
+            loaded_capacity: None,
+            loaded_memory_length: None,
         }
     }
 
@@ -217,6 +329,9 @@ impl<T: Clone> igDataList<T> {
             list: Arc::new(RwLock::new(Vec::with_capacity(capacity))),
             object_name: Arc::from("INVALID"),
             pool: Default::default(),
+            loaded_count: None,
+            loaded_capacity: None,
+            loaded_memory_length: None,
         }
     }
 
@@ -324,3 +439,427 @@ impl<'a, T: Clone> Iterator for QueryGuardIter<'a, T> {
         }
     }
 }
+
+/// Returns the constructor of the igHashTable whose keys are read by `key_metafield`, or [None] when the keys have no rust type that can be hashed the way the game does (object references for example). Those tables are loaded as generic objects
+pub(crate) fn hash_table_constructor(key_metafield: &str) -> Option<MetaObjectConstructor> {
+    match key_metafield {
+        "igIntMetaField" => Some(igHashTable::<i32>::construct),
+        "igUnsignedIntMetaField" => Some(igHashTable::<u32>::construct),
+        "igUnsignedLongMetaField" => Some(igHashTable::<u64>::construct),
+        "igStringMetaField" => Some(igHashTable::<Arc<str>>::construct),
+        "igNameMetaField" => Some(igHashTable::<igName>::construct),
+        "igEnumMetaField" => Some(igHashTable::<igEnumValue>::construct),
+        _ => None,
+    }
+}
+
+/// How an [igHashTable] hashes its keys. Picked from the name of the hash table type (igStringInsensitiveIntHashTable, igIntIntPassThroughHashTable...)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum igHashMode {
+    Default,
+    /// Strings are hashed and compared lower case
+    CaseInsensitive,
+    /// Integers are their own hash
+    PassThrough,
+}
+
+impl igHashMode {
+    fn from_object_name(name: &str) -> igHashMode {
+        if name.contains("Insensitive") {
+            igHashMode::CaseInsensitive
+        } else if name.contains("PassThrough") {
+            igHashMode::PassThrough
+        } else {
+            igHashMode::Default
+        }
+    }
+}
+
+/// A rust type that can be the key of an [igHashTable]
+pub trait igHashTableKey: Clone + Send + Sync + 'static {
+    /// Parses the `invalidkey` of the metadata. [None] when the invalid key is null
+    fn parse_invalid_key(text: &str) -> Option<Self>;
+
+    fn hash_key(&self, mode: igHashMode) -> u32;
+
+    fn same_key(&self, other: &Self, mode: igHashMode) -> bool;
+}
+
+impl igHashTableKey for i32 {
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        text.parse().ok()
+    }
+
+    fn hash_key(&self, mode: igHashMode) -> u32 {
+        (*self as u32).hash_key(mode)
+    }
+
+    fn same_key(&self, other: &Self, _mode: igHashMode) -> bool {
+        self == other
+    }
+}
+
+impl igHashTableKey for u32 {
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        text.parse().ok()
+    }
+
+    fn hash_key(&self, mode: igHashMode) -> u32 {
+        match mode {
+            igHashMode::PassThrough => *self,
+            _ => hash_int(*self),
+        }
+    }
+
+    fn same_key(&self, other: &Self, _mode: igHashMode) -> bool {
+        self == other
+    }
+}
+
+impl igHashTableKey for u64 {
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        text.parse().ok()
+    }
+
+    fn hash_key(&self, mode: igHashMode) -> u32 {
+        ((*self ^ (*self >> 32)) as u32).hash_key(mode)
+    }
+
+    fn same_key(&self, other: &Self, _mode: igHashMode) -> bool {
+        self == other
+    }
+}
+
+impl igHashTableKey for Arc<str> {
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        match text {
+            "(null)" => None,
+            _ => Some(Arc::from(text)),
+        }
+    }
+
+    fn hash_key(&self, mode: igHashMode) -> u32 {
+        match mode {
+            igHashMode::CaseInsensitive => hash_lower(self),
+            _ => hash(self),
+        }
+    }
+
+    fn same_key(&self, other: &Self, mode: igHashMode) -> bool {
+        match mode {
+            igHashMode::CaseInsensitive => self.to_lowercase() == other.to_lowercase(),
+            _ => self == other,
+        }
+    }
+}
+
+impl igHashTableKey for igName {
+    /// The metadata writes names as `{ string: hash }`
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        let (_, hash) = text.trim_matches(|c| c == '{' || c == '}').split_once(':')?;
+        hash.trim().parse().ok().map(igName::from_hash)
+    }
+
+    fn hash_key(&self, _mode: igHashMode) -> u32 {
+        self.hash
+    }
+
+    fn same_key(&self, other: &Self, _mode: igHashMode) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl igHashTableKey for igEnumValue {
+    /// The metadata writes the name of an entry, which is turned into its value when the metaobject is built
+    fn parse_invalid_key(text: &str) -> Option<Self> {
+        text.parse().ok().map(|value| igEnumValue { meta_enum: None, value, name: None })
+    }
+
+    /// Enums are hashed as the integer they are stored as
+    fn hash_key(&self, mode: igHashMode) -> u32 {
+        self.value.hash_key(mode)
+    }
+
+    fn same_key(&self, other: &Self, _mode: igHashMode) -> bool {
+        self.value == other.value
+    }
+}
+
+/// Parses the `invalidvalue` of the metadata as the rust type `value_metafield` reads. [None] when the invalid value is null or of a type with no text form
+fn parse_invalid_value(text: &str, value_metafield: &str) -> Option<igAny> {
+    fn wrap<T: Send + Sync + 'static>(value: T) -> igAny {
+        Arc::new(RwLock::new(value))
+    }
+    fn math<T: igMathValue<Component = f32>>(text: &str) -> Option<igAny> {
+        let components: Vec<f32> = text.split(',').map(|x| x.trim().parse().ok()).collect::<Option<_>>()?;
+        (components.len() == T::COUNT).then(|| wrap(T::from_components(&components)))
+    }
+
+    match value_metafield {
+        "igIntMetaField" => text.parse::<i32>().ok().map(wrap),
+        "igUnsignedIntMetaField" => text.parse::<u32>().ok().map(wrap),
+        "igUnsignedShortMetaField" => text.parse::<u16>().ok().map(wrap),
+        "igUnsignedLongMetaField" => text.parse::<u64>().ok().map(wrap),
+        "igFloatMetaField" => text.parse::<f32>().ok().map(wrap),
+        "igBoolMetaField" => text.parse::<bool>().ok().map(wrap),
+        "igVec4fMetaField" => math::<igVec4f>(text),
+        "igMatrix44fMetaField" => math::<igMatrix44f>(text),
+        _ => None,
+    }
+}
+
+/// An open addressed hash table (igHashTable and every type extending it). `_keys` and `_values` hold one slot per entry of the table, the slots that aren't used hold the invalid key and value from the metadata.
+///
+/// Only the used slots are kept as entries, the layout of the slots is built again with the hash of the keys when the table is saved
+#[derive(Clone)]
+pub struct igHashTable<K> {
+    entries: Vec<(K, Option<igAny>)>,
+    object_name: Arc<str>,
+    pool: igMemoryPool,
+    hash_mode: igHashMode,
+    invalid_key: Option<K>,
+    invalid_value: Option<igAny>,
+    pub auto_rehash: bool,
+    pub load_factor: f32,
+    /// Amount of slots of the table read from a file. Kept when saving as long as the entries still fit
+    capacity: usize,
+    /// The slots read from a file. Turned into entries once both are known, see [igHashTable::validate]
+    loaded_keys: Option<Vec<Option<K>>>,
+    loaded_values: Option<Vec<Option<igAny>>>,
+    /// The `_hashItemCount` read from a file. Checked against the entries once they are known then cleared
+    loaded_count: Option<usize>,
+    loaded_entries: bool,
+}
+
+impl<K: igHashTableKey> __internalObjectBase for igHashTable<K> {
+    fn object_name(&self) -> Arc<str> {
+        self.object_name.clone()
+    }
+
+    fn meta_type(&self, metadata_manager: &mut igMetadataManager) -> Arc<RwLock<igMetaObject>> {
+        metadata_manager.get_or_create_meta(self.object_name.as_ref()).unwrap()
+    }
+
+    fn internal_pool(&self) -> &igMemoryPool {
+        &self.pool
+    }
+
+    fn set_pool(&mut self, pool: igMemoryPool) {
+        self.pool = pool;
+    }
+
+    fn set_field(&mut self, name: &str, value: Option<igAny>) -> Result<(), SetObjectFieldError> {
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        match name {
+            "_keys" => {
+                let memory = guard.downcast_ref::<igMemory<igAny>>().ok_or(SetObjectFieldError::InvalidValueType)?;
+                let mut keys = Vec::with_capacity(memory.data.len());
+                for element in memory.data.iter() {
+                    let element = element.read().unwrap();
+                    match element.downcast_ref::<K>() {
+                        Some(key) => keys.push(Some(key.clone())),
+                        None if element.is::<igNullElement>() => keys.push(None),
+                        None => {
+                            warn!("{} has keys of type {}, but an element of _keys is another type", self.object_name, type_name::<K>());
+                            return Err(SetObjectFieldError::InvalidValueType);
+                        }
+                    }
+                }
+                self.loaded_keys = Some(keys);
+            }
+            "_values" => {
+                let memory = guard.downcast_ref::<igMemory<igAny>>().ok_or(SetObjectFieldError::InvalidValueType)?;
+                self.loaded_values = Some(memory.data.iter().map(element_value).collect());
+            }
+            "_hashItemCount" => {
+                let count = guard.downcast_ref::<i32>().ok_or(SetObjectFieldError::InvalidValueType)?;
+                self.loaded_count = Some(usize::try_from(*count).map_err(|_| SetObjectFieldError::InvalidValue)?);
+            }
+            "_autoRehash" => self.auto_rehash = *guard.downcast_ref::<bool>().ok_or(SetObjectFieldError::InvalidValueType)?,
+            "_loadFactor" => self.load_factor = *guard.downcast_ref::<f32>().ok_or(SetObjectFieldError::InvalidValueType)?,
+            &_ => {
+                warn!("igHashTable<K> attempted to set unknown field with name {} ", name);
+                return Ok(());
+            }
+        }
+        drop(guard);
+        self.validate();
+        Ok(())
+    }
+
+    #[inline]
+    fn get_non_null_field(&self, name: &str) -> Result<igAny, FieldDoesntExist> {
+        Ok(self.get_field(name)?.expect("called get_non_null_field on a null value"))
+    }
+
+    fn get_field(&self, name: &str) -> Result<Option<igAny>, FieldDoesntExist> {
+        match name {
+            "_keys" => {
+                let (keys, _) = self.slots();
+                let keys = keys.into_iter().map(|key| key.map(|key| Arc::new(RwLock::new(key)) as igAny));
+                Ok(Some(self.memory(keys)))
+            }
+            "_values" => {
+                let (_, values) = self.slots();
+                Ok(Some(self.memory(values.into_iter())))
+            }
+            "_hashItemCount" => Ok(Some(Arc::new(RwLock::new(self.entries.len() as i32)))),
+            "_autoRehash" => Ok(Some(Arc::new(RwLock::new(self.auto_rehash)))),
+            "_loadFactor" => Ok(Some(Arc::new(RwLock::new(self.load_factor)))),
+            &_ => Err(FieldDoesntExist),
+        }
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut (dyn Any + Send + Sync) {
+        self
+    }
+}
+
+impl<K: igHashTableKey> igHashTable<K> {
+    pub fn construct(meta: &igMetaObject, pool: igMemoryPool) -> Result<igObject, igMetaInstantiationError> {
+        let info = meta.hash_table_info.as_ref();
+        let invalid_key = info.and_then(|info| K::parse_invalid_key(&info.invalid_key));
+        let invalid_value = info
+            .zip(meta.field_storage.memory_element_type("_values"))
+            .and_then(|(info, value_type)| parse_invalid_value(&info.invalid_value, &value_type));
+
+        Ok(Arc::new(RwLock::new(igHashTable {
+            entries: Vec::new(),
+            object_name: meta.name.clone(),
+            pool,
+            hash_mode: igHashMode::from_object_name(&meta.name),
+            invalid_key,
+            invalid_value,
+            auto_rehash: true,
+            load_factor: 0.5,
+            capacity: 0,
+            loaded_keys: None,
+            loaded_values: None,
+            loaded_count: None,
+            loaded_entries: false,
+        })))
+    }
+
+    /// Turns the loaded slots into entries, skipping the unused ones, and compares the loaded `_hashItemCount` against them. Waits until `_keys` and `_values` have been set since fields can be set in any order
+    fn validate(&mut self) {
+        if let (Some(keys), Some(values)) = (&self.loaded_keys, &self.loaded_values) {
+            if keys.len() != values.len() {
+                warn!("{} has {} keys but {} values", self.object_name, keys.len(), values.len());
+            }
+            self.capacity = keys.len().min(values.len());
+            let keys = self.loaded_keys.take().unwrap();
+            let values = self.loaded_values.take().unwrap();
+            let mut slots: Vec<_> = keys
+                .into_iter()
+                .zip(values)
+                .map(|(key, value)| key.filter(|key| !self.is_invalid_key(key)).map(|key| (key, value)))
+                .collect();
+            // Entries are kept starting after an unused slot. Probing places them back in the slots they were loaded from, even when a run of used slots wraps around the end of the table
+            let start = slots.iter().position(|slot| slot.is_none()).unwrap_or(0);
+            slots.rotate_left(start);
+            self.entries = slots.into_iter().flatten().collect();
+            self.loaded_entries = true;
+        }
+
+        if !self.loaded_entries {
+            return;
+        }
+        if let Some(count) = self.loaded_count.take() {
+            if count != self.entries.len() {
+                warn!("{} has a _hashItemCount of {} but {} slots are used", self.object_name, count, self.entries.len());
+            }
+        }
+    }
+
+    fn is_invalid_key(&self, key: &K) -> bool {
+        self.invalid_key.as_ref().is_some_and(|invalid| invalid.same_key(key, self.hash_mode))
+    }
+
+    /// Amount of slots to save the table with. Keeps the loaded amount when the entries still fit under the load factor, otherwise grows to a power of two
+    fn slot_count(&self) -> usize {
+        let len = self.entries.len();
+        if len == 0 {
+            return self.capacity;
+        }
+        let load_factor = if self.load_factor > 0.0 && self.load_factor <= 1.0 { self.load_factor } else { 0.5 };
+        if self.capacity > len && len as f32 <= self.capacity as f32 * load_factor {
+            return self.capacity;
+        }
+        ((len as f32 / load_factor).ceil() as usize).max(len + 1).next_power_of_two()
+    }
+
+    /// Lays the entries out in slots. Each entry goes in the slot `hash % slot count`, or the next free one after it
+    fn slots(&self) -> (Vec<Option<K>>, Vec<Option<igAny>>) {
+        let slot_count = self.slot_count();
+        let mut keys = vec![self.invalid_key.clone(); slot_count];
+        let mut values = vec![self.invalid_value.clone(); slot_count];
+        let mut used = vec![false; slot_count];
+        for (key, value) in &self.entries {
+            let mut slot = key.hash_key(self.hash_mode) as usize % slot_count;
+            while used[slot] {
+                slot = (slot + 1) % slot_count;
+            }
+            used[slot] = true;
+            keys[slot] = Some(key.clone());
+            values[slot] = value.clone();
+        }
+        (keys, values)
+    }
+
+    fn memory(&self, elements: impl Iterator<Item = Option<igAny>>) -> igAny {
+        let mut memory: igMemory<igAny> = igMemory::new();
+        memory.pool = self.pool;
+        memory.data = elements.map(element_or_null).collect();
+        Arc::new(RwLock::new(memory))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value stored with `key`. [None] when the key isn't in the table or its value is null
+    pub fn get(&self, key: &K) -> Option<igAny> {
+        self.entries.iter().find(|(x, _)| x.same_key(key, self.hash_mode)).and_then(|(_, value)| value.clone())
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.iter().any(|(x, _)| x.same_key(key, self.hash_mode))
+    }
+
+    /// Stores `value` with `key`, returning the value previously stored with it. The invalid key of the table marks unused slots so it can't be inserted
+    pub fn insert(&mut self, key: K, value: Option<igAny>) -> Option<igAny> {
+        if self.is_invalid_key(&key) {
+            warn!("{} can't hold its invalid key. The entry is ignored", self.object_name);
+            return None;
+        }
+        match self.entries.iter_mut().find(|(x, _)| x.same_key(&key, self.hash_mode)) {
+            Some((_, old)) => std::mem::replace(old, value),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes `key` from the table, returning the value stored with it
+    pub fn remove(&mut self, key: &K) -> Option<igAny> {
+        let index = self.entries.iter().position(|(x, _)| x.same_key(key, self.hash_mode))?;
+        self.entries.remove(index).1
+    }
+
+    /// Iterates over the entries. Loaded entries come in slot order starting after an unused slot, inserted ones after them in the order they were inserted
+    pub fn iter(&self) -> std::slice::Iter<'_, (K, Option<igAny>)> {
+        self.entries.iter()
+    }
+}
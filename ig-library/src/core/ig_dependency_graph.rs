@@ -0,0 +1,262 @@
+use crate::core::ig_file_context::get_native_path;
+use crate::core::ig_objects::igObjectStreamManager;
+use crate::util::ig_hash::hash_lower;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Why one directory depends on another
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, Serialize)]
+pub enum igDependencyReason {
+    /// Listed in the T_DEPENDENCIES fixup
+    Declared,
+    /// An object reference resolved through the EXID fixup
+    ExternalById,
+    /// An object reference resolved through the EXNM fixup
+    ExternalByName,
+}
+
+/// A single directed edge in an [igDependencyGraph]
+#[derive(Debug, Clone, Serialize)]
+pub struct igDependencyEdge {
+    /// Path of the directory that holds the reference
+    pub from: Arc<str>,
+    /// Path of the directory being referenced. When the target is not loaded this is the missing path for declared dependencies, or the namespace name (or hash) that was referenced for externals
+    pub to: Arc<str>,
+    pub reason: igDependencyReason,
+    /// False when no loaded directory matches the target
+    pub resolved: bool,
+}
+
+/// Snapshot of how every directory loaded by an [igObjectStreamManager] depends on each other. Can be exported as JSON with [igDependencyGraph::to_json] or as graphviz with [igDependencyGraph::to_dot].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct igDependencyGraph {
+    /// Paths of every loaded directory
+    pub nodes: Vec<Arc<str>>,
+    pub edges: Vec<igDependencyEdge>,
+}
+
+impl igObjectStreamManager {
+    /// Builds the dependency graph of every directory currently loaded
+    pub fn build_dependency_graph(&self) -> igDependencyGraph {
+        let mut graph = igDependencyGraph::default();
+        let mut seen_edges = HashSet::new();
+
+        let mut dirs: Vec<_> = self.path_to_directory_lookup.values().cloned().collect();
+        dirs.sort_by_key(|dir| dir.read().unwrap().path.clone());
+
+        for dir in dirs {
+            let dir = dir.read().unwrap();
+            let from: Arc<str> = Arc::from(dir.path.as_str());
+            graph.nodes.push(from.clone());
+
+            let mut push_edge = |to: Arc<str>, reason: igDependencyReason, resolved: bool| {
+                if to == from {
+                    return;
+                }
+                if seen_edges.insert((from.clone(), to.clone(), reason)) {
+                    graph.edges.push(igDependencyEdge {
+                        from: from.clone(),
+                        to,
+                        reason,
+                        resolved,
+                    });
+                }
+            };
+
+            for dependency in dir.dependencies.iter() {
+                let dependency = dependency.read().unwrap();
+                push_edge(Arc::from(dependency.path.as_str()), igDependencyReason::Declared, true);
+            }
+
+            // Declared dependencies are looked up by path, as that's how they are loaded. One may have been loaded since it went missing
+            for path in &dir.missing_dependencies {
+                let path = get_native_path(path.clone());
+                match self.path_to_directory_lookup.get(&hash_lower(&path)) {
+                    Some(target) => {
+                        let target = target.read().unwrap();
+                        push_edge(Arc::from(target.path.as_str()), igDependencyReason::Declared, true);
+                    }
+                    None => push_edge(Arc::from(path), igDependencyReason::Declared, false),
+                }
+            }
+
+            for (reason, namespace) in &dir.external_namespaces {
+                let target = self
+                    .name_to_directory_lookup
+                    .get(&namespace.hash)
+                    .and_then(|list| list.get(0));
+
+                match target {
+                    Some(target) => {
+                        let target = target.read().unwrap();
+                        push_edge(Arc::from(target.path.as_str()), *reason, true);
+                    }
+                    None => {
+                        let name = match &namespace.string {
+                            Some(name) => name.clone(),
+                            None => format!("{:#010X}", namespace.hash),
+                        };
+                        push_edge(Arc::from(name), *reason, false);
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+}
+
+impl igDependencyGraph {
+    /// Exports the graph as JSON, in the same shape the graph is serialized with serde
+    pub fn to_json(&self) -> String {
+        sonic_rs::to_string_pretty(self).unwrap()
+    }
+
+    /// Every edge pointing at something that isn't loaded
+    pub fn missing_targets(&self) -> Vec<&igDependencyEdge> {
+        self.edges.iter().filter(|edge| !edge.resolved).collect()
+    }
+
+    /// Every directory that (directly or indirectly) depends on the directory at `path`. These are the files that may break if `path` is removed.
+    pub fn dependents_of(&self, path: &str) -> Vec<Arc<str>> {
+        let mut reverse: HashMap<&str, Vec<&Arc<str>>> = HashMap::new();
+        for edge in &self.edges {
+            reverse.entry(edge.to.as_ref()).or_default().push(&edge.from);
+        }
+
+        let mut found = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![path];
+        while let Some(current) = stack.pop() {
+            if let Some(dependents) = reverse.get(current) {
+                for dependent in dependents {
+                    if visited.insert(dependent.as_ref()) && dependent.as_ref() != path {
+                        found.push((*dependent).clone());
+                        stack.push(dependent.as_ref());
+                    }
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Finds every group of directories that depend on each other in a loop. Uses Tarjan's strongly connected components algorithm
+    pub fn find_cycles(&self) -> Vec<Vec<Arc<str>>> {
+        let index_of: HashMap<&str, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.as_ref(), i))
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); self.nodes.len()];
+        for edge in self.edges.iter().filter(|edge| edge.resolved) {
+            if let (Some(from), Some(to)) = (index_of.get(edge.from.as_ref()), index_of.get(edge.to.as_ref())) {
+                adjacency[*from].push(*to);
+            }
+        }
+
+        let mut state = TarjanState {
+            adjacency: &adjacency,
+            next_index: 0,
+            indices: vec![None; self.nodes.len()],
+            low_links: vec![0; self.nodes.len()],
+            on_stack: vec![false; self.nodes.len()],
+            stack: Vec::new(),
+            components: Vec::new(),
+        };
+
+        for node in 0..self.nodes.len() {
+            if state.indices[node].is_none() {
+                state.connect(node);
+            }
+        }
+
+        state
+            .components
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| component.into_iter().map(|i| self.nodes[i].clone()).collect())
+            .collect()
+    }
+
+    /// Exports the graph in graphviz DOT format. Missing targets are drawn dashed and red.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n");
+        for node in &self.nodes {
+            writeln!(out, "    \"{}\";", escape_dot(node)).unwrap();
+        }
+        for edge in self.missing_targets() {
+            writeln!(out, "    \"{}\" [style=dashed, color=red];", escape_dot(&edge.to)).unwrap();
+        }
+        for edge in &self.edges {
+            let label = match edge.reason {
+                igDependencyReason::Declared => "TDEP",
+                igDependencyReason::ExternalById => "EXID",
+                igDependencyReason::ExternalByName => "EXNM",
+            };
+            writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                label
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+struct TarjanState<'a> {
+    adjacency: &'a [Vec<usize>],
+    next_index: usize,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+}
+
+impl TarjanState<'_> {
+    fn connect(&mut self, node: usize) {
+        self.indices[node] = Some(self.next_index);
+        self.low_links[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &next in &self.adjacency[node] {
+            match self.indices[next] {
+                None => {
+                    self.connect(next);
+                    self.low_links[node] = self.low_links[node].min(self.low_links[next]);
+                }
+                Some(index) if self.on_stack[next] => {
+                    self.low_links[node] = self.low_links[node].min(index);
+                }
+                _ => {}
+            }
+        }
+
+        if Some(self.low_links[node]) == self.indices[node] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
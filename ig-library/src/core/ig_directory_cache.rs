@@ -0,0 +1,798 @@
+use crate::core::ig_compound::igCompoundValue;
+use crate::core::ig_custom::{igNameList, igNull, igObjectList, CastTo};
+use crate::core::ig_dependency_graph::igDependencyReason;
+use crate::core::ig_handle::{igHandle, igObjectHandleManager};
+use crate::core::ig_math::{igMatrix44f, igQuaternionf, igRangedFloat, igRangedVector, igVec2f, igVec2uc, igVec3d, igVec3f, igVec3uc, igVec4f, igVec4i, igVec4uc};
+use crate::core::ig_memory::igMemoryPool;
+use crate::core::ig_objects::{igAny, igObject, igObjectDirectory, igObjectStreamManager, igThumbnail};
+use crate::core::memory::{element_or_null, igMemory, igNullElement, igRawRef};
+use crate::core::meta::field::r#impl::ig_enum_meta_field::igEnumValue;
+use crate::core::meta::ig_metadata_manager::{igMetaObject, igMetadataManager};
+use crate::util::ig_name::igName;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use log::{debug, warn};
+use std::any::Any;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+const CACHE_MAGIC: u32 = u32::from_le_bytes(*b"IGDC");
+/// Bump when the entry layout changes so old entries are thrown away
+const CACHE_VERSION: u32 = 2;
+
+/// Optional on-disk cache of decoded igObjectDirectories. Each entry holds every object of the directory in a compact serialized form, so the next launch can rebuild the directory without decompressing or decoding the igz.
+///
+/// Entries are keyed by the native path of the file. An entry is thrown away when the file's stamp changes (see [igArchive::file_stamp](crate::core::ig_archive::igArchive::file_stamp)) or when the ArkCore XML it was cached against changes. Files only have a stamp in games built with Alchemy Laboratory, so nothing is cached for other games.
+pub struct igDirectoryCache {
+    root: PathBuf,
+    metadata_version: u64,
+}
+
+/// Everything stored in a cache entry
+pub struct igCachedDirectory {
+    pub use_name_list: bool,
+    /// Namespace and path of every directory in [igObjectDirectory::dependencies]
+    pub dependencies: Vec<(igName, String)>,
+    /// Namespace and path of every other directory the objects reference. They are loaded before the objects are restored
+    pub referenced_directories: Vec<(igName, String)>,
+    pub thumbnails: Vec<igThumbnail>,
+    pub external_namespaces: Vec<(igDependencyReason, igName)>,
+    pub missing_dependencies: Vec<String>,
+    /// The encoded objects, decoded by [igCachedDirectory::restore]
+    objects: Vec<u8>,
+}
+
+impl igDirectoryCache {
+    /// Creates a cache stored in `root`. `meta_directory` is the ArkCore folder of the loaded game and is used to invalidate entries when the metadata changes
+    pub fn new(root: impl Into<PathBuf>, meta_directory: &Path) -> igDirectoryCache {
+        igDirectoryCache::with_metadata_version(root, igDirectoryCache::metadata_version(meta_directory))
+    }
+
+    /// Same as [igDirectoryCache::new] with an already computed metadata version
+    pub fn with_metadata_version(root: impl Into<PathBuf>, metadata_version: u64) -> igDirectoryCache {
+        let root = root.into();
+        if let Err(e) = fs::create_dir_all(&root) {
+            warn!("Failed to create directory cache at {}: {}", root.display(), e);
+        }
+
+        igDirectoryCache { root, metadata_version }
+    }
+
+    /// Computes a version from the contents of the ArkCore XML files. Missing files still contribute so adding one changes the version
+    pub fn metadata_version(meta_directory: &Path) -> u64 {
+        let mut version = FNV_OFFSET;
+        for file_name in ["metaenums.xml", "metafields.xml", "metaobjects.xml"] {
+            version = fnv1a(version, file_name.as_bytes());
+            if let Ok(contents) = fs::read(meta_directory.join(file_name)) {
+                version = fnv1a(version, &contents);
+            }
+        }
+        version
+    }
+
+    /// True when a valid entry exists for `file_path`. Only reads the entry's header
+    pub fn contains(&self, file_path: &str, stamp: u64) -> bool {
+        File::open(self.entry_path(file_path))
+            .ok()
+            .is_some_and(|reader| self.read_header(&mut BufReader::new(reader), stamp).is_some())
+    }
+
+    /// Returns the cached directory for `file_path` if the entry is still valid. Stale or unreadable entries are removed
+    pub fn get(&self, file_path: &str, stamp: u64) -> Option<igCachedDirectory> {
+        let entry_path = self.entry_path(file_path);
+        let reader = File::open(&entry_path).ok()?;
+
+        match self.read_entry(&mut BufReader::new(reader), stamp) {
+            Some(entry) => {
+                debug!("Directory cache hit for {}", file_path);
+                Some(entry)
+            }
+            None => {
+                debug!("Directory cache entry for {} is stale", file_path);
+                let _ = fs::remove_file(entry_path);
+                None
+            }
+        }
+    }
+
+    /// Stores `dir`. Directories that [can't be safely saved](igObjectDirectory::can_safely_save) or hold values the cache can't encode are not cached. Diagnostics that don't block saving aren't stored, so a restored directory has none
+    pub fn put(
+        &self,
+        dir: &igObjectDirectory,
+        stamp: u64,
+        object_stream_manager: &igObjectStreamManager,
+        metadata_manager: &mut igMetadataManager,
+    ) {
+        if !dir.can_safely_save() {
+            return;
+        }
+        let mut cache_writer = igCacheWriter::new(dir, object_stream_manager, metadata_manager);
+        let mut objects = Vec::new();
+        if let Err(e) = cache_writer.write_objects(&mut objects, dir) {
+            debug!("{} can't be cached: {}", dir.path, e);
+            return;
+        }
+
+        let entry_path = self.entry_path(&dir.path);
+        let result = File::create(&entry_path).and_then(|writer| {
+            let mut writer = BufWriter::new(writer);
+            writer.write_u32::<LittleEndian>(CACHE_MAGIC)?;
+            writer.write_u32::<LittleEndian>(CACHE_VERSION)?;
+            writer.write_u64::<LittleEndian>(self.metadata_version)?;
+            writer.write_u64::<LittleEndian>(stamp)?;
+
+            writer.write_u32::<LittleEndian>(dir.external_namespaces.len() as u32)?;
+            for (reason, namespace) in &dir.external_namespaces {
+                writer.write_u8(*reason as u8)?;
+                write_name(&mut writer, namespace)?;
+            }
+
+            writer.write_u32::<LittleEndian>(dir.missing_dependencies.len() as u32)?;
+            for path in &dir.missing_dependencies {
+                write_bytes(&mut writer, path.as_bytes())?;
+            }
+
+            writer.write_u32::<LittleEndian>(dir.thumbnails.len() as u32)?;
+            for thumbnail in &dir.thumbnails {
+                writer.write_u64::<LittleEndian>(thumbnail.offset)?;
+                write_bytes(&mut writer, &thumbnail.data)?;
+            }
+
+            let dependencies: Vec<(igName, String)> = dir
+                .dependencies
+                .iter()
+                .map(|dependency| {
+                    let dependency = dependency.read().unwrap();
+                    (dependency.name.clone(), dependency.path.clone())
+                })
+                .collect();
+            for directories in [&dependencies, &cache_writer.referenced_directories] {
+                writer.write_u32::<LittleEndian>(directories.len() as u32)?;
+                for (namespace, path) in directories {
+                    write_name(&mut writer, namespace)?;
+                    write_bytes(&mut writer, path.as_bytes())?;
+                }
+            }
+
+            writer.write_u8(dir.use_name_list as u8)?;
+            write_bytes(&mut writer, &objects)?;
+            writer.flush()
+        });
+
+        if let Err(e) = result {
+            warn!("Failed to write directory cache entry {}: {}", entry_path.display(), e);
+            let _ = fs::remove_file(entry_path);
+        }
+    }
+
+    /// Removes every entry in the cache
+    pub fn clear(&self) {
+        if let Ok(entries) = fs::read_dir(&self.root) {
+            for entry in entries.flatten() {
+                if entry.path().extension().is_some_and(|extension| extension == "igdc") {
+                    let _ = fs::remove_file(entry.path());
+                }
+            }
+        }
+    }
+
+    fn entry_path(&self, file_path: &str) -> PathBuf {
+        let path_hash = fnv1a(FNV_OFFSET, file_path.replace('\\', "/").to_lowercase().as_bytes());
+        self.root.join(format!("{:016x}.igdc", path_hash))
+    }
+
+    fn read_header(&self, reader: &mut impl Read, stamp: u64) -> Option<()> {
+        (reader.read_u32::<LittleEndian>().ok()? == CACHE_MAGIC
+            && reader.read_u32::<LittleEndian>().ok()? == CACHE_VERSION
+            && reader.read_u64::<LittleEndian>().ok()? == self.metadata_version
+            && reader.read_u64::<LittleEndian>().ok()? == stamp)
+            .then_some(())
+    }
+
+    fn read_entry(&self, reader: &mut impl Read, stamp: u64) -> Option<igCachedDirectory> {
+        self.read_header(reader, stamp)?;
+
+        let namespace_count = reader.read_u32::<LittleEndian>().ok()?;
+        let mut external_namespaces = Vec::new();
+        for _ in 0..namespace_count {
+            let reason = match reader.read_u8().ok()? {
+                0 => igDependencyReason::Declared,
+                1 => igDependencyReason::ExternalById,
+                2 => igDependencyReason::ExternalByName,
+                _ => return None,
+            };
+            external_namespaces.push((reason, read_name(reader)?));
+        }
+
+        let dependency_count = reader.read_u32::<LittleEndian>().ok()?;
+        let mut missing_dependencies = Vec::new();
+        for _ in 0..dependency_count {
+            missing_dependencies.push(read_string(reader)?);
+        }
+
+        let thumbnail_count = reader.read_u32::<LittleEndian>().ok()?;
+        let mut thumbnails = Vec::new();
+        for _ in 0..thumbnail_count {
+            let offset = reader.read_u64::<LittleEndian>().ok()?;
+            thumbnails.push(igThumbnail { offset, data: read_bytes(reader)?.into() });
+        }
+
+        let mut directories = [Vec::new(), Vec::new()];
+        for list in &mut directories {
+            let count = reader.read_u32::<LittleEndian>().ok()?;
+            for _ in 0..count {
+                list.push((read_name(reader)?, read_string(reader)?));
+            }
+        }
+        let [dependencies, referenced_directories] = directories;
+
+        let use_name_list = reader.read_u8().ok()? != 0;
+        let objects = read_bytes(reader)?;
+        Some(igCachedDirectory {
+            use_name_list,
+            dependencies,
+            referenced_directories,
+            thumbnails,
+            external_namespaces,
+            missing_dependencies,
+            objects,
+        })
+    }
+}
+
+impl igCachedDirectory {
+    /// Rebuilds the cached objects into `dir`. `dependencies` and `directories` are the loaded [igCachedDirectory::dependencies] and [igCachedDirectory::referenced_directories]. Handles are looked up in `handle_manager`, so they resolve like handles read from the file. Returns false and leaves `dir` untouched if the objects no longer fit the loaded metadata or directories
+    pub fn restore(
+        self,
+        dir: &mut igObjectDirectory,
+        metadata_manager: &mut igMetadataManager,
+        handle_manager: &mut igObjectHandleManager,
+        dependencies: Vec<Arc<RwLock<igObjectDirectory>>>,
+        directories: &[Arc<RwLock<igObjectDirectory>>],
+    ) -> bool {
+        let Some(objects) = read_objects(&mut self.objects.as_slice(), metadata_manager, handle_manager, directories) else {
+            return false;
+        };
+
+        dir.use_name_list = self.use_name_list;
+        for dependency in dependencies {
+            dir.dependencies.push(dependency);
+        }
+        if let Some(object_list) = objects.object_list {
+            dir.object_list = object_list;
+        }
+        if let Some(name_list) = objects.name_list {
+            dir.name_list = name_list;
+        }
+        dir.all_objects = objects.all_objects;
+        dir.thumbnails = self.thumbnails;
+        dir.external_namespaces = self.external_namespaces;
+        dir.missing_dependencies = self.missing_dependencies;
+        true
+    }
+}
+
+const VALUE_NULL: u8 = 0;
+const VALUE_BOOL: u8 = 1;
+const VALUE_STRING: u8 = 12;
+const VALUE_NAME: u8 = 13;
+/// An object of the directory itself, stored as its index
+const VALUE_OBJECT: u8 = 14;
+/// A named object of another directory, stored as the index of the directory in [igCachedDirectory::referenced_directories] and its name
+const VALUE_EXTERNAL: u8 = 15;
+const VALUE_META_OBJECT: u8 = 16;
+const VALUE_MEMORY: u8 = 17;
+/// The bytes of a field with no metafield implementation
+const VALUE_BYTES: u8 = 18;
+const VALUE_ENUM: u8 = 19;
+const VALUE_COMPOUND: u8 = 32;
+/// The elements of a fixed-size array, where null elements are stored as [VALUE_NULL]
+const VALUE_ARRAY: u8 = 33;
+/// A handle, stored as its namespace and alias
+const VALUE_HANDLE: u8 = 34;
+const VALUE_RAW_REF: u8 = 35;
+
+/// Marks a missing object or name list
+const NO_LIST: u32 = u32::MAX;
+
+/// Stores the number types metafields read by their tag and little endian bytes
+macro_rules! primitive_values {
+    ($($tag:literal => $type:ty),* $(,)?) => {
+        fn write_primitive(writer: &mut Vec<u8>, value: &(dyn Any + Send + Sync)) -> io::Result<bool> {
+            $(
+                if let Some(value) = value.downcast_ref::<$type>() {
+                    writer.write_u8($tag)?;
+                    writer.write_all(&value.to_le_bytes())?;
+                    return Ok(true);
+                }
+            )*
+            Ok(false)
+        }
+
+        fn read_primitive(tag: u8, reader: &mut impl Read) -> Option<igAny> {
+            match tag {
+                $(
+                    $tag => {
+                        let mut bytes = [0u8; size_of::<$type>()];
+                        reader.read_exact(&mut bytes).ok()?;
+                        Some(Arc::new(RwLock::new(<$type>::from_le_bytes(bytes))))
+                    }
+                )*
+                _ => None,
+            }
+        }
+    };
+}
+
+primitive_values!(
+    2 => u8,
+    3 => i8,
+    4 => u16,
+    5 => i16,
+    6 => u32,
+    7 => i32,
+    8 => u64,
+    9 => i64,
+    10 => f32,
+    11 => f64,
+);
+
+/// Stores the math types metafields read by their tag and the little endian bytes of each component
+macro_rules! math_values {
+    ($($tag:literal => $type:ty: $component:ty [$(($($access:tt)+))+]),* $(,)?) => {
+        fn write_math(writer: &mut Vec<u8>, value: &(dyn Any + Send + Sync)) -> io::Result<bool> {
+            $(
+                if let Some(value) = value.downcast_ref::<$type>() {
+                    writer.write_u8($tag)?;
+                    $(writer.write_all(&value$($access)+.to_le_bytes())?;)+
+                    return Ok(true);
+                }
+            )*
+            Ok(false)
+        }
+
+        fn read_math(tag: u8, reader: &mut impl Read) -> Option<igAny> {
+            match tag {
+                $(
+                    $tag => {
+                        let mut value = <$type>::default();
+                        $(
+                            let mut bytes = [0u8; size_of::<$component>()];
+                            reader.read_exact(&mut bytes).ok()?;
+                            value$($access)+ = <$component>::from_le_bytes(bytes);
+                        )+
+                        Some(Arc::new(RwLock::new(value)))
+                    }
+                )*
+                _ => None,
+            }
+        }
+    };
+}
+
+math_values!(
+    20 => igVec2f: f32 [(.x) (.y)],
+    21 => igVec3f: f32 [(.x) (.y) (.z)],
+    22 => igVec3d: f64 [(.x) (.y) (.z)],
+    23 => igVec4f: f32 [(.x) (.y) (.z) (.w)],
+    24 => igVec4i: i32 [(.x) (.y) (.z) (.w)],
+    25 => igVec2uc: u8 [(.x) (.y)],
+    26 => igVec3uc: u8 [(.x) (.y) (.z)],
+    27 => igVec4uc: u8 [(.x) (.y) (.z) (.w)],
+    28 => igQuaternionf: f32 [(.x) (.y) (.z) (.w)],
+    29 => igMatrix44f: f32 [
+        (.rows[0][0]) (.rows[0][1]) (.rows[0][2]) (.rows[0][3])
+        (.rows[1][0]) (.rows[1][1]) (.rows[1][2]) (.rows[1][3])
+        (.rows[2][0]) (.rows[2][1]) (.rows[2][2]) (.rows[2][3])
+        (.rows[3][0]) (.rows[3][1]) (.rows[3][2]) (.rows[3][3])
+    ],
+    30 => igRangedFloat: f32 [(.min) (.max)],
+    31 => igRangedVector: f32 [(.min.x) (.min.y) (.min.z) (.max.x) (.max.y) (.max.z)],
+);
+
+fn address(object: &igObject) -> usize {
+    Arc::as_ptr(object) as *const () as usize
+}
+
+/// Encodes the objects of one directory. Objects in the directory are written as their index in [igObjectDirectory::all_objects], objects in other directories as the directory and the name they are found by
+struct igCacheWriter<'a> {
+    metadata_manager: &'a mut igMetadataManager,
+    object_indices: HashMap<usize, u32>,
+    /// Named objects of the directories the directory references, keyed by address. Holds the namespace and path of their directory and their name
+    external_objects: HashMap<usize, (igName, String, igName)>,
+    referenced_directories: Vec<(igName, String)>,
+}
+
+impl<'a> igCacheWriter<'a> {
+    fn new(
+        dir: &igObjectDirectory,
+        object_stream_manager: &igObjectStreamManager,
+        metadata_manager: &'a mut igMetadataManager,
+    ) -> igCacheWriter<'a> {
+        let object_indices = dir.all_objects.iter().enumerate().map(|(i, object)| (address(object), i as u32)).collect();
+
+        let mut external_objects = HashMap::new();
+        for (_, namespace) in &dir.external_namespaces {
+            let Some(others) = object_stream_manager.name_to_directory_lookup.get(&namespace.hash) else {
+                continue;
+            };
+            for other in others.iter() {
+                let Ok(other) = other.try_read() else {
+                    continue;
+                };
+                if other.path == dir.path || !other.use_name_list {
+                    continue;
+                }
+                let names = other.name_list.read().unwrap();
+                for (object, name) in other.object_list.read().unwrap().iter().zip(names.iter()) {
+                    external_objects.insert(address(&object), (other.name.clone(), other.path.clone(), name.clone()));
+                }
+            }
+        }
+
+        igCacheWriter {
+            metadata_manager,
+            object_indices,
+            external_objects,
+            referenced_directories: Vec::new(),
+        }
+    }
+
+    fn write_objects(&mut self, writer: &mut Vec<u8>, dir: &igObjectDirectory) -> io::Result<()> {
+        writer.write_u32::<LittleEndian>(dir.all_objects.len() as u32)?;
+        for object in &dir.all_objects {
+            let object = object.read().unwrap();
+            if object.as_any().is::<igNull>() {
+                return Err(io::Error::other("the directory holds a placeholder object"));
+            }
+            let meta = object.meta_type(self.metadata_manager);
+            write_string(writer, &meta.read().unwrap().name)?;
+            write_string(writer, &format!("{:?}", object.internal_pool()))?;
+        }
+
+        let object_list: igObject = dir.object_list.clone();
+        let object_list_empty = dir.object_list.read().unwrap().len() == 0;
+        self.write_list_index(writer, &object_list, object_list_empty)?;
+        let name_list: igObject = dir.name_list.clone();
+        let name_list_empty = !dir.use_name_list || dir.name_list.read().unwrap().len() == 0;
+        self.write_list_index(writer, &name_list, name_list_empty)?;
+
+        for object in &dir.all_objects {
+            // The object is unlocked before its values are written, since they can reference it
+            let fields: Vec<(Arc<str>, Option<igAny>)> = {
+                let object = object.read().unwrap();
+                let meta = object.meta_type(self.metadata_manager);
+                let meta = meta.read().unwrap();
+                meta.fields_by_offset()
+                    .iter()
+                    .filter_map(|field| {
+                        let name = field.name.clone()?;
+                        let value = object.get_field(&name).ok()?;
+                        Some((name, value))
+                    })
+                    .collect()
+            };
+
+            writer.write_u32::<LittleEndian>(fields.len() as u32)?;
+            for (name, value) in &fields {
+                write_string(writer, name)?;
+                self.write_value(writer, value.as_ref())
+                    .map_err(|e| io::Error::other(format!("{}: {}", name, e)))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the index of the directory's object or name list. Lists that aren't one of the directory's objects are only allowed when they are empty
+    fn write_list_index(&self, writer: &mut Vec<u8>, list: &igObject, empty: bool) -> io::Result<()> {
+        match self.object_indices.get(&address(list)) {
+            Some(index) => writer.write_u32::<LittleEndian>(*index),
+            None if empty => writer.write_u32::<LittleEndian>(NO_LIST),
+            None => Err(io::Error::other("the object or name list isn't one of the directory's objects")),
+        }
+    }
+
+    fn write_value(&mut self, writer: &mut Vec<u8>, value: Option<&igAny>) -> io::Result<()> {
+        let Some(value) = value else {
+            return writer.write_u8(VALUE_NULL);
+        };
+        let value = value.read().unwrap();
+        if write_primitive(writer, &*value)? || write_math(writer, &*value)? {
+            return Ok(());
+        }
+
+        if let Some(value) = value.downcast_ref::<bool>() {
+            writer.write_u8(VALUE_BOOL)?;
+            writer.write_u8(*value as u8)?;
+        } else if let Some(string) = value.downcast_ref::<Arc<str>>() {
+            writer.write_u8(VALUE_STRING)?;
+            write_string(writer, string)?;
+        } else if let Some(name) = value.downcast_ref::<igName>() {
+            writer.write_u8(VALUE_NAME)?;
+            write_name(writer, name)?;
+        } else if let Some(object) = value.downcast_ref::<igObject>() {
+            self.write_object(writer, object)?;
+        } else if let Some(memory) = value.downcast_ref::<igMemory<igAny>>() {
+            writer.write_u8(VALUE_MEMORY)?;
+            write_string(writer, &format!("{:?}", memory.pool))?;
+            writer.write_u8(memory.implicit_memory_pool as u8 | (memory.optimal_cpuread_write as u8) << 1 | (memory.optimal_gpuread as u8) << 2)?;
+            writer.write_u32::<LittleEndian>(memory.alignment_multiple)?;
+            writer.write_u32::<LittleEndian>(memory.data.len() as u32)?;
+            for element in &memory.data {
+                if element.read().unwrap().is::<igNullElement>() {
+                    writer.write_u8(VALUE_NULL)?;
+                } else {
+                    self.write_value(writer, Some(element))?;
+                }
+            }
+        } else if let Some(value) = value.downcast_ref::<igEnumValue>() {
+            writer.write_u8(VALUE_ENUM)?;
+            write_optional_string(writer, value.meta_enum.as_deref())?;
+            writer.write_i32::<LittleEndian>(value.value)?;
+            write_optional_string(writer, value.name.as_deref())?;
+        } else if let Some(compound) = value.downcast_ref::<igCompoundValue>() {
+            writer.write_u8(VALUE_COMPOUND)?;
+            write_string(writer, &compound.type_name)?;
+            let fields: Vec<(&Arc<str>, &igAny)> = compound.fields().collect();
+            writer.write_u32::<LittleEndian>(fields.len() as u32)?;
+            for (name, value) in fields {
+                write_string(writer, name)?;
+                self.write_value(writer, Some(value))?;
+            }
+        } else if let Some(elements) = value.downcast_ref::<Vec<Option<igAny>>>() {
+            writer.write_u8(VALUE_ARRAY)?;
+            writer.write_u32::<LittleEndian>(elements.len() as u32)?;
+            for element in elements {
+                self.write_value(writer, element.as_ref())?;
+            }
+        } else if let Some(handle) = value.downcast_ref::<Arc<RwLock<igHandle>>>() {
+            let handle = handle.read().unwrap();
+            writer.write_u8(VALUE_HANDLE)?;
+            write_name(writer, &handle.namespace)?;
+            write_name(writer, &handle.alias)?;
+        } else if let Some(raw_ref) = value.downcast_ref::<igRawRef>() {
+            writer.write_u8(VALUE_RAW_REF)?;
+            write_string(writer, &format!("{:?}", raw_ref.pool))?;
+            writer.write_u64::<LittleEndian>(raw_ref.offset)?;
+        } else if let Some(bytes) = value.downcast_ref::<Vec<u8>>() {
+            writer.write_u8(VALUE_BYTES)?;
+            write_bytes(writer, bytes)?;
+        } else {
+            return Err(io::Error::other("the value's type can't be cached"));
+        }
+
+        Ok(())
+    }
+
+    fn write_object(&mut self, writer: &mut Vec<u8>, object: &igObject) -> io::Result<()> {
+        if let Some(index) = self.object_indices.get(&address(object)) {
+            writer.write_u8(VALUE_OBJECT)?;
+            writer.write_u32::<LittleEndian>(*index)?;
+        } else if let Some((namespace, path, name)) = self.external_objects.get(&address(object)) {
+            let index = match self.referenced_directories.iter().position(|(_, x)| x == path) {
+                Some(index) => index,
+                None => {
+                    self.referenced_directories.push((namespace.clone(), path.clone()));
+                    self.referenced_directories.len() - 1
+                }
+            };
+            writer.write_u8(VALUE_EXTERNAL)?;
+            writer.write_u32::<LittleEndian>(index as u32)?;
+            write_name(writer, name)?;
+        } else if let Some(meta) = object.read().unwrap().as_any().downcast_ref::<igMetaObject>() {
+            writer.write_u8(VALUE_META_OBJECT)?;
+            write_string(writer, &meta.name)?;
+        } else {
+            let object_name = object.read().unwrap().object_name();
+            return Err(io::Error::other(format!("a {} outside of the directories it references", object_name)));
+        }
+
+        Ok(())
+    }
+}
+
+/// The objects of a directory decoded from [igCachedDirectory::objects]
+struct igCachedObjects {
+    all_objects: Vec<igObject>,
+    object_list: Option<Arc<RwLock<igObjectList>>>,
+    name_list: Option<Arc<RwLock<igNameList>>>,
+}
+
+/// Instantiates and fills the objects written by [igCacheWriter::write_objects]
+fn read_objects(
+    reader: &mut impl Read,
+    metadata_manager: &mut igMetadataManager,
+    handle_manager: &mut igObjectHandleManager,
+    directories: &[Arc<RwLock<igObjectDirectory>>],
+) -> Option<igCachedObjects> {
+    let object_count = reader.read_u32::<LittleEndian>().ok()?;
+    let mut all_objects = Vec::new();
+    for _ in 0..object_count {
+        let type_name = read_string(reader)?;
+        let pool = igMemoryPool::from_str(&read_string(reader)?).ok()?;
+        let meta = metadata_manager.get_or_create_meta(&type_name).ok()?;
+        let object = meta.read().unwrap().raw_instantiate(pool, false).ok()?;
+        all_objects.push(object);
+    }
+
+    let mut lists = [None, None];
+    for list in &mut lists {
+        let index = reader.read_u32::<LittleEndian>().ok()?;
+        if index != NO_LIST {
+            *list = Some(all_objects.get(index as usize)?.clone());
+        }
+    }
+    let [object_list, name_list] = lists;
+
+    for object in &all_objects {
+        let field_count = reader.read_u32::<LittleEndian>().ok()?;
+        for _ in 0..field_count {
+            let name = read_string(reader)?;
+            let value = read_value(reader, metadata_manager, handle_manager, &all_objects, directories)?;
+            object.write().unwrap().set_field(&name, value).ok()?;
+        }
+    }
+
+    Some(igCachedObjects {
+        object_list: match object_list {
+            Some(list) => Some(list.cast_to().ok()?),
+            None => None,
+        },
+        name_list: match name_list {
+            Some(list) => Some(list.cast_to().ok()?),
+            None => None,
+        },
+        all_objects,
+    })
+}
+
+/// Reads a value written by [igCacheWriter::write_value]. The outer [Option] is [None] when the value can't be read
+fn read_value(
+    reader: &mut impl Read,
+    metadata_manager: &mut igMetadataManager,
+    handle_manager: &mut igObjectHandleManager,
+    objects: &[igObject],
+    directories: &[Arc<RwLock<igObjectDirectory>>],
+) -> Option<Option<igAny>> {
+    let value: igAny = match reader.read_u8().ok()? {
+        VALUE_NULL => return Some(None),
+        VALUE_BOOL => Arc::new(RwLock::new(reader.read_u8().ok()? != 0)),
+        VALUE_STRING => Arc::new(RwLock::new(Arc::<str>::from(read_string(reader)?))),
+        VALUE_NAME => Arc::new(RwLock::new(read_name(reader)?)),
+        VALUE_OBJECT => {
+            let object = objects.get(reader.read_u32::<LittleEndian>().ok()? as usize)?.clone();
+            Arc::new(RwLock::new(object))
+        }
+        VALUE_EXTERNAL => {
+            let directory = directories.get(reader.read_u32::<LittleEndian>().ok()? as usize)?;
+            let name = read_name(reader)?;
+            // A directory still being loaded further up is locked, its objects couldn't have been referenced by name when this one was cached
+            let directory = directory.try_read().ok()?;
+            let names = directory.name_list.read().unwrap();
+            let index = names.iter().position(|x| x.hash == name.hash)?;
+            let object = directory.object_list.read().unwrap().iter().nth(index)?.clone();
+            Arc::new(RwLock::new(object))
+        }
+        VALUE_META_OBJECT => {
+            let object: igObject = metadata_manager.get_or_create_meta(&read_string(reader)?).ok()?;
+            Arc::new(RwLock::new(object))
+        }
+        VALUE_MEMORY => {
+            let mut memory: igMemory<igAny> = igMemory::new();
+            memory.pool = igMemoryPool::from_str(&read_string(reader)?).ok()?;
+            let flags = reader.read_u8().ok()?;
+            memory.implicit_memory_pool = flags & 1 != 0;
+            memory.optimal_cpuread_write = flags & 2 != 0;
+            memory.optimal_gpuread = flags & 4 != 0;
+            memory.alignment_multiple = reader.read_u32::<LittleEndian>().ok()?;
+            let count = reader.read_u32::<LittleEndian>().ok()?;
+            for _ in 0..count {
+                memory.data.push(element_or_null(read_value(reader, metadata_manager, handle_manager, objects, directories)?));
+            }
+            Arc::new(RwLock::new(memory))
+        }
+        VALUE_BYTES => Arc::new(RwLock::new(read_bytes(reader)?)),
+        VALUE_ENUM => Arc::new(RwLock::new(igEnumValue {
+            meta_enum: read_optional_string(reader)?.map(Arc::from),
+            value: reader.read_i32::<LittleEndian>().ok()?,
+            name: read_optional_string(reader)?.map(Arc::from),
+        })),
+        VALUE_COMPOUND => {
+            let mut compound = igCompoundValue::new(Arc::from(read_string(reader)?));
+            let count = reader.read_u32::<LittleEndian>().ok()?;
+            for _ in 0..count {
+                let name = read_string(reader)?;
+                compound.set_field(Arc::from(name), read_value(reader, metadata_manager, handle_manager, objects, directories)?);
+            }
+            Arc::new(RwLock::new(compound))
+        }
+        VALUE_ARRAY => {
+            let count = reader.read_u32::<LittleEndian>().ok()?;
+            let mut elements: Vec<Option<igAny>> = Vec::new();
+            for _ in 0..count {
+                elements.push(read_value(reader, metadata_manager, handle_manager, objects, directories)?);
+            }
+            Arc::new(RwLock::new(elements))
+        }
+        VALUE_HANDLE => {
+            let namespace = read_name(reader)?;
+            let alias = read_name(reader)?;
+            Arc::new(RwLock::new(handle_manager.lookup_handle(namespace, alias)))
+        }
+        VALUE_RAW_REF => {
+            let pool = igMemoryPool::from_str(&read_string(reader)?).ok()?;
+            let offset = reader.read_u64::<LittleEndian>().ok()?;
+            Arc::new(RwLock::new(igRawRef { offset, pool }))
+        }
+        tag => read_primitive(tag, reader).or_else(|| read_math(tag, reader))?,
+    };
+    Some(Some(value))
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(bytes.len() as u32)?;
+    writer.write_all(bytes)
+}
+
+fn read_bytes(reader: &mut impl Read) -> Option<Vec<u8>> {
+    let length = reader.read_u32::<LittleEndian>().ok()?;
+    let mut bytes = Vec::new();
+    // Reading through take() means a corrupt length can't allocate more than the entry holds
+    reader.take(length as u64).read_to_end(&mut bytes).ok()?;
+    (bytes.len() == length as usize).then_some(bytes)
+}
+
+fn write_string(writer: &mut impl Write, string: &str) -> io::Result<()> {
+    write_bytes(writer, string.as_bytes())
+}
+
+fn read_string(reader: &mut impl Read) -> Option<String> {
+    String::from_utf8(read_bytes(reader)?).ok()
+}
+
+fn write_optional_string(writer: &mut impl Write, string: Option<&str>) -> io::Result<()> {
+    match string {
+        Some(string) => {
+            writer.write_u8(1)?;
+            write_string(writer, string)
+        }
+        None => writer.write_u8(0),
+    }
+}
+
+/// Reads a string written by [write_optional_string]. The outer [Option] is [None] when the string can't be read
+fn read_optional_string(reader: &mut impl Read) -> Option<Option<String>> {
+    match reader.read_u8().ok()? {
+        0 => Some(None),
+        _ => Some(Some(read_string(reader)?)),
+    }
+}
+
+fn write_name(writer: &mut impl Write, name: &igName) -> io::Result<()> {
+    writer.write_u32::<LittleEndian>(name.hash)?;
+    write_optional_string(writer, name.string.as_deref())
+}
+
+fn read_name(reader: &mut impl Read) -> Option<igName> {
+    let hash = reader.read_u32::<LittleEndian>().ok()?;
+    let string = read_optional_string(reader)?;
+    Some(igName { string, hash })
+}
+
+pub(crate) const FNV_OFFSET: u64 = 0xCBF29CE484222325;
+const FNV_PRIME: u64 = 0x100000001B3;
+
+/// 64-bit FNV-1a. Used over [std::hash::DefaultHasher] because its output has to stay the same between builds
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
@@ -1,8 +1,9 @@
 use crate::core::ig_archive::igArchive;
 use crate::core::ig_archive_manager::igArchiveManager;
 use crate::core::ig_archive_mount_manager::igArchiveMountManager;
+use crate::core::ig_directory_cache::igDirectoryCache;
 use crate::core::ig_file_context::WorkItemBuffer::Invalid;
-use crate::core::ig_fs::{igFileDescriptor, igFileWorkItemProcessor, Endian};
+use crate::core::ig_fs::{igFileDescriptor, igFileWorkItemProcessor, igStorageDevice, Endian};
 use crate::core::ig_registry::{igRegistry, BuildTool};
 use crate::core::ig_std_lib_storage_device::igStdLibStorageDevice;
 use log::{debug, error, warn, Metadata};
@@ -44,6 +45,10 @@ pub struct igFileContext {
     pub _root: String,
     pub archive_manager: Arc<RwLock<igArchiveManager>>,
     processor_stack: Arc<Mutex<dyn igFileWorkItemProcessor>>,
+    /// True when a TFB update folder sits in front of the archives in the processor stack
+    has_update_folder: bool,
+    /// When set, igz files loaded from archives are cached on disk after being decoded. See [igDirectoryCache]. Only games built with Alchemy Laboratory are cached, since [igFileContext::archive_file_stamp] can't identify files in other archives
+    pub directory_cache: Option<Arc<igDirectoryCache>>,
 }
 
 #[derive(Debug)]
@@ -123,9 +128,39 @@ pub struct igFileWorkItem<'a> {
 impl igFileContext {
     pub fn open(&self, ig_registry: &igRegistry, path: &str, flags: u32) -> igFileDescriptor {
         debug!("Opening path \"{}\"", path);
-        let path = interpret_path(path);
+        let mut work_item = self.open_work_item(ig_registry, path, flags);
+        let processor_stack = self.processor_stack.lock().unwrap();
+        processor_stack.process(self.processor_stack.clone(), &mut work_item);
+        drop(processor_stack);
+
+        Self::opened_file(work_item)
+    }
+
+    /// Same as [igFileContext::open], but files stored in archives are read without locking the processor stack so several threads can decompress files at once. Anything else goes through [igFileContext::open]
+    pub fn open_in_parallel(&self, ig_registry: &igRegistry, path: &str, flags: u32) -> igFileDescriptor {
+        // The update folder has to be checked before the archives, which only the processor stack does
+        if self.has_update_folder {
+            return self.open(ig_registry, path, flags);
+        }
+
+        debug!("Opening path \"{}\" in parallel", path);
+        let mut work_item = self.open_work_item(ig_registry, path, flags);
+        {
+            let archive_manager = self.archive_manager.read().unwrap();
+            for archive in archive_manager._patch_archives.iter().chain(archive_manager._archive_list.iter()) {
+                igStorageDevice::process(&archive, self.processor_stack.clone(), &mut work_item);
+                if work_item._status == WorkStatus::kStatusComplete {
+                    return Self::opened_file(work_item);
+                }
+            }
+        }
 
-        let mut work_item = igFileWorkItem {
+        self.open(ig_registry, path, flags)
+    }
+
+    fn open_work_item<'a>(&'a self, ig_registry: &'a igRegistry, path: &str, flags: u32) -> igFileWorkItem<'a> {
+        let path = interpret_path(path);
+        igFileWorkItem {
             file_context: &self,
             ig_registry,
             _file: igFileDescriptor {
@@ -144,10 +179,11 @@ impl igFileContext {
             _status: WorkStatus::kStatusActive,
             _offset: 0,
             _buffer: Invalid(),
-        };
-        let processor_stack = self.processor_stack.lock().unwrap();
-        processor_stack.process(self.processor_stack.clone(), &mut work_item);
+        }
+    }
 
+    /// Logs how opening the file went and returns it
+    fn opened_file(work_item: igFileWorkItem) -> igFileDescriptor {
         match work_item._status {
             WorkStatus::kStatusComplete => {}
             WorkStatus::kStatusActive => error!(
@@ -162,6 +198,23 @@ impl igFileContext {
         work_item._file
     }
 
+    /// Returns the [stamp](igArchive::file_stamp) of the archived copy of `path` that [igFileContext::open] would read, checking patch archives first like the archive manager does. Returns None for files that aren't in an archive, and for every file in games not built with Alchemy Laboratory
+    pub fn archive_file_stamp(&self, ig_registry: &igRegistry, path: &str) -> Option<u64> {
+        // TfbTool archives are addressed by archive path and file name instead of hashes
+        if ig_registry.build_tool != BuildTool::AlchemyLaboratory {
+            return None;
+        }
+
+        let path = interpret_path(path);
+        let archive_manager = self.archive_manager.read().unwrap();
+        let stamp = archive_manager
+            ._patch_archives
+            .iter()
+            .chain(archive_manager._archive_list.iter())
+            .find_map(|archive| archive.file_stamp(&path));
+        stamp
+    }
+
     pub fn load_archive(&self, ig_registry: &igRegistry, path: &str) -> Arc<igArchive> {
         igArchiveManager::load_archive(self.archive_manager.clone(), self, ig_registry, path)
     }
@@ -191,6 +244,8 @@ impl igFileContext {
             _root,
             archive_manager,
             processor_stack,
+            has_update_folder: update_folder.is_some(),
+            directory_cache: None,
         }
     }
 
@@ -1,8 +1,9 @@
 use crate::core::ig_custom::igStringRefList;
-use crate::core::ig_objects::{igObject, igObjectStreamManager};
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
 use crate::util::ig_name::igName;
-use log::error;
+use log::{error, warn};
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::sync::{Arc, RwLock};
 
 pub struct igHandleName {
@@ -32,6 +33,7 @@ impl igHandle {
         }))
     }
 
+    /// Resolves the handle by searching the name list of every loaded directory in its namespace. Handles from [igObjectHandleManager] already point at their object once its directory is added, so this is the fallback for directories the manager never saw. The result is cached on the handle. When several directories hold an object with the alias, the first one loaded wins and the rest are reported
     pub fn get_object_alias(
         &mut self,
         object_stream_manager: &igObjectStreamManager,
@@ -40,47 +42,79 @@ impl igHandle {
             return self.object.clone();
         }
 
-        let name_to_dir = &object_stream_manager.name_to_directory_lookup;
-        if let Some(dirs) = name_to_dir.get(&self.namespace.hash) {
-            for dir in dirs.iter() {
-                if let Ok(dir) = dir.read() {
-                    if !dir.use_name_list {
-                        return None; // why? this seems weird. why not check the other igz's?
-                    }
+        let matches = self.find_matches(object_stream_manager);
+        if matches.len() > 1 {
+            warn!(
+                "{} is ambiguous. It exists in {}. Using the one from {}",
+                self,
+                matches.iter().map(|(_, path)| path.as_str()).collect::<Vec<_>>().join(", "),
+                matches[0].1
+            );
+        }
 
-                    let name_list = dir.name_list.read().unwrap();
-                    for i in 0..name_list.len() {
-                        let handle = name_list.get(i).unwrap();
-                        if handle.hash == self.alias.hash {
-                            self.object = dir.object_list.read().unwrap().get(i)
-                        }
-                    }
+        match matches.into_iter().next() {
+            Some((object, _)) => {
+                self.object = Some(object);
+                self.object.clone()
+            }
+            None => {
+                error!("get_object_alias failed to load {}", self);
+                None
+            }
+        }
+    }
+
+    /// Returns every distinct object (and the path of the directory holding it) the handle could refer to, in the order the directories were loaded. Directories that are still being loaded are skipped
+    pub fn find_matches(&self, object_stream_manager: &igObjectStreamManager) -> Vec<(igObject, String)> {
+        let mut matches: Vec<(igObject, String)> = Vec::new();
+        let Some(dirs) = object_stream_manager.name_to_directory_lookup.get(&self.namespace.hash) else {
+            return matches;
+        };
+
+        for dir in dirs.iter() {
+            let Ok(dir) = dir.try_read() else {
+                continue;
+            };
+            // Without a name list the objects have no names to be found by
+            if !dir.use_name_list {
+                continue;
+            }
+
+            let objects = dir.object_list.read().unwrap();
+            let names = dir.name_list.read().unwrap();
+            for (object, name) in objects.iter().zip(names.iter()) {
+                if name.hash == self.alias.hash && !matches.iter().any(|(existing, _)| Arc::ptr_eq(existing, &object)) {
+                    matches.push((object, dir.path.clone()));
                 }
             }
+        }
 
-            None
-        } else {
-            error!(
-                "get_object_alias failed to load {}.{}",
-                self.namespace
-                    .string
-                    .clone()
-                    .unwrap_or_else(move || "(null)".to_string()),
-                self.alias
-                    .string
-                    .clone()
-                    .unwrap_or_else(move || "(null)".to_string())
-            );
-            None
+        matches
+    }
+}
+
+impl Display for igHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.namespace.string, &self.alias.string) {
+            (Some(namespace), Some(alias)) => write!(f, "{}::{}", namespace, alias),
+            (Some(namespace), None) => write!(f, "{}::{:#010X}", namespace, self.alias.hash),
+            (None, Some(alias)) => write!(f, "{:#010X}::{}", self.namespace.hash, alias),
+            (None, None) => write!(f, "{:#010X}::{:#010X}", self.namespace.hash, self.alias.hash),
         }
     }
 }
 
 pub struct igObjectHandleManager {
     system_namespaces: igStringRefList,
-    handle_list: Vec<u32>,
-    object_to_handle_map: HashMap<igObject, igHandle>,
-    handle_map: HashMap<u64, igHandle>,
+    /// Every handle in the order it was created
+    handle_list: Vec<Arc<RwLock<igHandle>>>,
+    /// The handle of every named object keyed by the address of the object. Used to turn objects back into handles when saving
+    object_to_handle_map: HashMap<usize, Arc<RwLock<igHandle>>>,
+    handle_map: HashMap<u64, Arc<RwLock<igHandle>>>,
+}
+
+fn address(object: &igObject) -> usize {
+    Arc::as_ptr(object) as *const () as usize
 }
 
 impl igObjectHandleManager {
@@ -93,32 +127,114 @@ impl igObjectHandleManager {
         }
     }
 
-    pub fn lookup_handle_name(&mut self, name: &igHandleName) -> igHandle {
-        self.lookup_handle(name.namespace.clone(), name.name.clone()).clone()
+    pub fn lookup_handle_name(&mut self, name: &igHandleName) -> Arc<RwLock<igHandle>> {
+        self.lookup_handle(name.namespace.clone(), name.name.clone())
     }
 
     fn get_handle_key(ns: &igName, name: &igName) -> u64 {
         ((ns.hash as u64) << 32) | (name.hash as u64)
     }
 
-    pub fn lookup_handle(&mut self, namespace: igName, name: igName) -> &mut igHandle {
+    /// Returns the handle for `namespace::name`, creating an empty one if it doesn't exist yet. The handle is shared, so it will point at the object once the directory holding it is added
+    pub fn lookup_handle(&mut self, namespace: igName, name: igName) -> Arc<RwLock<igHandle>> {
         let key = igObjectHandleManager::get_handle_key(&namespace, &name);
 
         // If missing, create and insert
-        let handle = self.handle_map.entry(key).or_insert_with(|| igHandle {
-            namespace: namespace.clone(),
-            alias: name.clone(),
-            object: None,
+        let handle = self.handle_map.entry(key).or_insert_with(|| {
+            let handle = Arc::new(RwLock::new(igHandle {
+                namespace: namespace.clone(),
+                alias: name.clone(),
+                object: None,
+            }));
+            self.handle_list.push(handle.clone());
+            handle
         });
 
         // Attempt to set up the strings properly
-        if namespace.string.is_some() && handle.namespace.string.is_none() {
-            handle.namespace.string = namespace.string.clone();
+        {
+            let mut handle = handle.write().unwrap();
+            if namespace.string.is_some() && handle.namespace.string.is_none() {
+                handle.namespace.string = namespace.string.clone();
+            }
+            if name.string.is_some() && handle.alias.string.is_none() {
+                handle.alias.string = name.string.clone();
+            }
+        }
+
+        handle.clone()
+    }
+
+    /// Returns the handle for `namespace::name` without creating one
+    pub fn find_handle(&self, namespace: &igName, name: &igName) -> Option<Arc<RwLock<igHandle>>> {
+        self.handle_map
+            .get(&igObjectHandleManager::get_handle_key(namespace, name))
+            .cloned()
+    }
+
+    /// Returns the object `namespace::name` points at, if the directory holding it has been added
+    pub fn resolve(&mut self, namespace: igName, name: igName) -> Option<igObject> {
+        self.lookup_handle(namespace, name).read().unwrap().object.clone()
+    }
+
+    /// Returns the object a handle points at. Handles the manager didn't create (ones read from an igx for example) are matched to the manager's handle by name, so they resolve once the directory holding the object is added. The result is cached on the handle
+    pub fn resolve_handle(&self, handle: &Arc<RwLock<igHandle>>) -> Option<igObject> {
+        let shared = {
+            let guard = handle.read().unwrap();
+            if guard.object.is_some() {
+                return guard.object.clone();
+            }
+            self.find_handle(&guard.namespace, &guard.alias)?
+        };
+        if Arc::ptr_eq(&shared, handle) {
+            return None;
+        }
+
+        let object = shared.read().unwrap().object.clone();
+        handle.write().unwrap().object = object.clone();
+        object
+    }
+
+    /// Returns the handle of a named object. Used when saving RUNTIME_HANDLES
+    pub fn get_handle(&self, object: &igObject) -> Option<Arc<RwLock<igHandle>>> {
+        self.object_to_handle_map.get(&address(object)).cloned()
+    }
+
+    /// Every handle in the order it was created
+    pub fn handles(&self) -> &[Arc<RwLock<igHandle>>] {
+        &self.handle_list
+    }
+
+    /// Gives every named object in the directory a handle. Directories without a name list have nothing to register. When two directories share a namespace and a name, the first one added keeps the handle
+    pub fn add_directory(&mut self, dir: &igObjectDirectory) {
+        if !dir.use_name_list {
+            return;
         }
-        if name.string.is_some() && handle.alias.string.is_none() {
-            handle.alias.string = name.string.clone();
+
+        let objects = dir.object_list.read().unwrap();
+        let names = dir.name_list.read().unwrap();
+        if objects.len() != names.len() {
+            warn!(
+                "{} has {} objects but {} names. Only the first {} get handles",
+                dir.path,
+                objects.len(),
+                names.len(),
+                objects.len().min(names.len())
+            );
         }
 
-        handle
+        for (object, name) in objects.iter().zip(names.iter()) {
+            let handle = self.lookup_handle(dir.name.clone(), name);
+            {
+                let mut handle = handle.write().unwrap();
+                match &handle.object {
+                    None => handle.object = Some(object.clone()),
+                    Some(existing) if !Arc::ptr_eq(existing, &object) => {
+                        warn!("{} is ambiguous. {} also holds it, keeping the one loaded first", handle, dir.path)
+                    }
+                    _ => {}
+                }
+            }
+            self.object_to_handle_map.entry(address(&object)).or_insert(handle);
+        }
     }
 }
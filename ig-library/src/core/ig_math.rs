@@ -0,0 +1,71 @@
+/// Defines a vector type with one named field per component
+macro_rules! define_vector {
+    ($(#[$doc:meta])* $name:ident, $component:ty, $($field:ident),+) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Default)]
+        pub struct $name {
+            $(pub $field: $component,)+
+        }
+
+        impl $name {
+            pub fn new($($field: $component),+) -> $name {
+                $name { $($field),+ }
+            }
+        }
+    };
+}
+
+define_vector!(igVec2f, f32, x, y);
+define_vector!(
+    /// Also used by igVec3fAlignedMetaField, which pads the vector to 16 bytes
+    igVec3f, f32, x, y, z
+);
+define_vector!(igVec3d, f64, x, y, z);
+define_vector!(igVec4f, f32, x, y, z, w);
+define_vector!(igVec4i, i32, x, y, z, w);
+define_vector!(igVec2uc, u8, x, y);
+define_vector!(igVec3uc, u8, x, y, z);
+define_vector!(
+    /// Mostly used to store colors, where x, y, z and w are the red, green, blue and alpha channels
+    igVec4uc, u8, x, y, z, w
+);
+define_vector!(
+    /// A rotation. Stored in the same order as the fields
+    igQuaternionf, f32, x, y, z, w
+);
+
+impl igQuaternionf {
+    pub fn identity() -> igQuaternionf {
+        igQuaternionf::new(0.0, 0.0, 0.0, 1.0)
+    }
+}
+
+/// A 4x4 matrix of floats stored row by row. Used for transforms
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct igMatrix44f {
+    pub rows: [[f32; 4]; 4],
+}
+
+impl igMatrix44f {
+    pub fn identity() -> igMatrix44f {
+        let mut rows = [[0.0; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        igMatrix44f { rows }
+    }
+}
+
+/// A float picked randomly between min and max at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct igRangedFloat {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// A vector picked randomly between min and max at runtime (per component)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct igRangedVector {
+    pub min: igVec3f,
+    pub max: igVec3f,
+}
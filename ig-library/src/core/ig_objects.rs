@@ -1,17 +1,20 @@
 use crate::core::ig_custom::{igNameList, igObjectDirectoryList, igObjectList};
+use crate::core::ig_dependency_graph::igDependencyReason;
+use crate::core::ig_directory_cache::igCachedDirectory;
 use crate::core::ig_external_ref::igExternalReferenceSystem;
 use crate::core::ig_file_context::{get_native_path, igFileContext};
+use crate::core::ig_fs::igFileDescriptor;
 use crate::core::ig_registry::{igRegistry, BuildTool};
-use crate::core::load::ig_igz_loader::igIGZObjectLoader;
-use crate::core::load::ig_loader;
-use crate::core::load::ig_loader::igObjectLoader;
+use crate::core::load::ig_igz_loader::{igIGZDeferredFields, igIGZObjectLoader};
+use crate::core::load::ig_loader::{igLoadDiagnostic, igObjectLoader, igObjectLoaderRegistry};
 use crate::core::meta::ig_metadata_manager::{__internalObjectBase, igMetadataManager};
 use crate::util::ig_hash::hash_lower;
 use crate::util::ig_name::igName;
 use log::warn;
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::mem;
+use std::thread;
 use std::sync::{Arc, RwLock};
 use crate::core::ig_handle::igObjectHandleManager;
 
@@ -61,6 +64,25 @@ pub struct igObjectDirectory {
     /// Only filled when use_name_list is equal to true and length should match the object list
     pub name_list: Arc<RwLock<igNameList>>,
     pub loader: Arc<RwLock<dyn igObjectLoader>>,
+    /// Every problem the loader ran into that didn't stop the file from loading. Only really fills up when [igObjectStreamManager::lenient_loading] is enabled
+    pub diagnostics: Vec<igLoadDiagnostic>,
+    /// Preview images stored in the file. Laboratory games store these for a lot of assets, and they are much faster to show than loading the full texture
+    pub thumbnails: Vec<igThumbnail>,
+    /// Every object stored in the directory in the order it appears in the file, including ones not referenced by [igObjectDirectory::object_list]
+    pub all_objects: Vec<igObject>,
+    /// Namespaces referenced by this directory's fixups. Kept even if they failed to resolve so [igObjectStreamManager::build_dependency_graph] can report missing targets
+    pub external_namespaces: Vec<(igDependencyReason, igName)>,
+    /// Paths listed in the T_DEPENDENCIES fixup that failed to load. They aren't in [igObjectDirectory::dependencies], so they are kept here for [igObjectStreamManager::build_dependency_graph]
+    pub missing_dependencies: Vec<String>,
+}
+
+/// A preview image stored inside an igz's THUMBNAIL fixup.
+#[derive(Clone, Debug)]
+pub struct igThumbnail {
+    /// The (deserialized) offset in the igz the thumbnail was read from
+    pub offset: u64,
+    /// The encoded image. This is the raw blob from the file, use an image decoder to turn it into RGBA.
+    pub data: Arc<[u8]>,
 }
 
 impl igObjectDirectory {
@@ -69,7 +91,7 @@ impl igObjectDirectory {
     }
 
     /// Allows specifying a custom file loader. Handy for custom formats or formats that are not igz such as igXml, igBinary, and igAscii
-    fn with_loader(path: &str, name: igName, loader: Arc<RwLock<dyn igObjectLoader>>) -> Self {
+    pub fn with_loader(path: &str, name: igName, loader: Arc<RwLock<dyn igObjectLoader>>) -> Self {
         igObjectDirectory {
             path: path.to_string(),
             name,
@@ -78,13 +100,61 @@ impl igObjectDirectory {
             object_list: Arc::new(RwLock::new(igObjectList::new())),
             name_list: Arc::new(RwLock::new(igNameList::new())),
             loader,
+            diagnostics: Vec::new(),
+            thumbnails: Vec::new(),
+            all_objects: Vec::new(),
+            external_namespaces: Vec::new(),
+            missing_dependencies: Vec::new(),
         }
     }
+
+    /// Returns true when the loader ran into no problems that [block saving](crate::core::load::ig_loader::igLoadDiagnosticKind::blocks_saving), meaning the directory can be saved without losing data
+    pub fn can_safely_save(&self) -> bool {
+        !self.diagnostics.iter().any(|diagnostic| diagnostic.kind.blocks_saving())
+    }
+
+    /// Returns the number of diagnostics that stop the directory from being saved
+    pub fn blocking_diagnostic_count(&self) -> usize {
+        self.diagnostics.iter().filter(|diagnostic| diagnostic.kind.blocks_saving()).count()
+    }
+
+    /// Returns all diagnostics tied to the object passed in. If any of them [block saving](crate::core::load::ig_loader::igLoadDiagnosticKind::blocks_saving), the object cannot be safely re-saved
+    pub fn object_diagnostics(&self, object: &igObject) -> Vec<&igLoadDiagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.object.as_ref().is_some_and(|x| Arc::ptr_eq(x, object)))
+            .collect()
+    }
+}
+
+/// A [igDirectoryCache](crate::core::ig_directory_cache::igDirectoryCache) entry and the file it is restored as. See [igObjectStreamManager::restore_cached]
+pub(crate) struct igCachedLoad<'a> {
+    pub file_path: &'a str,
+    pub namespace: &'a igName,
+    pub entry: igCachedDirectory,
 }
 
 pub struct igObjectStreamManager {
     pub name_to_directory_lookup: HashMap<u32, igObjectDirectoryList>,
     pub path_to_directory_lookup: HashMap<u32, Arc<RwLock<igObjectDirectory>>>,
+    /// When true, loaders will keep going past data they fail to understand instead of aborting. Anything skipped is recorded in [igObjectDirectory::diagnostics]
+    pub lenient_loading: bool,
+    /// When true, the dependencies of a file are loaded with [igObjectStreamManager::load_many], so their fields are decoded on worker threads
+    pub parallel_loading: bool,
+    /// Files read ahead of time by [igObjectStreamManager::prefetch], keyed by the hash of their native path. Loaders take from here before opening the file themselves
+    prefetched_files: HashMap<u32, igFileDescriptor>,
+    /// Native path hashes of the files the current [igObjectStreamManager::load_many] call is loading. igz files in here leave their fields to be read later
+    deferred_paths: HashSet<u32>,
+    /// Set while loading one of [igObjectStreamManager::deferred_paths]. Files loaded as its dependencies are always read in full
+    defer_current_load: bool,
+    /// Loaded igz files whose objects exist but whose fields haven't been read yet, keyed by the hash of their native path
+    pub(crate) deferred_fields: HashMap<u32, igIGZDeferredFields>,
+    /// Every loader files can be loaded with. Register custom formats here before loading them.
+    ///
+    /// This lives here rather than on [igAlchemy](crate::util::ig_common::igAlchemy) because loaders load dependencies through the stream manager, which is the only state they are all handed. Every igAlchemy owns its own stream manager, so registering a loader still only affects that igAlchemy
+    pub loader_registry: igObjectLoaderRegistry,
+    /// Directories waiting to be written to [igFileContext::directory_cache] along with their stamps. Written by [igObjectStreamManager::write_directory_cache] so saving them doesn't slow down loading
+    uncached_directories: Vec<(Arc<RwLock<igObjectDirectory>>, u64)>,
 }
 
 impl igObjectStreamManager {
@@ -92,6 +162,14 @@ impl igObjectStreamManager {
         igObjectStreamManager {
             name_to_directory_lookup: HashMap::new(),
             path_to_directory_lookup: HashMap::new(),
+            lenient_loading: false,
+            parallel_loading: false,
+            prefetched_files: HashMap::new(),
+            deferred_paths: HashSet::new(),
+            defer_current_load: false,
+            deferred_fields: HashMap::new(),
+            loader_registry: igObjectLoaderRegistry::new(),
+            uncached_directories: Vec::new(),
         }
     }
 
@@ -129,33 +207,389 @@ impl igObjectStreamManager {
         let file_path_hash = hash_lower(&file_path);
 
         if self.path_to_directory_lookup.contains_key(&file_path_hash) {
+            // Something depends on it, so it has to be fully read first
+            self.finish_deferred_fields(ig_metadata_manager, ig_object_handle_manager, file_path_hash);
             Ok(self.path_to_directory_lookup[&file_path_hash].clone())
+        } else if let Some(entry) = self.cached_entry(ig_file_context, ig_registry, &file_path) {
+            Ok(self.restore_cached(
+                ig_file_context,
+                ig_registry,
+                ig_metadata_manager,
+                ig_ext_ref_system,
+                ig_object_handle_manager,
+                igCachedLoad { file_path: &file_path, namespace: &namespace, entry },
+            ))
         } else {
             let dir = Arc::new(RwLock::new(igObjectDirectory::new(&file_path, namespace)));
             self.push_dir(dir.clone());
-            let loader_result = ig_loader::get_loader(&file_path);
-            if let Some(loader) = loader_result {
-                let loader_guard = loader.read().unwrap();
-                let mut dir_guard = dir.write().unwrap();
-                loader_guard.read_file(
-                    ig_file_context,
-                    ig_registry,
-                    self,
-                    ig_ext_ref_system,
-                    ig_object_handle_manager,
-                    ig_metadata_manager,
-                    &mut dir_guard,
-                    &file_path,
-                );
-                // todo!("igObjectHandleManager.Singleton.AddDirectory(objDir);");
-            } else {
-                warn!("No loader found for file {}", file_path);
+            let outer_paths = mem::take(&mut self.deferred_paths);
+            self.defer_current_load = outer_paths.contains(&file_path_hash);
+            self.read_directory(ig_file_context, ig_registry, ig_metadata_manager, ig_ext_ref_system, ig_object_handle_manager, &dir);
+            self.deferred_paths = outer_paths;
+            self.defer_current_load = false;
+            // Deferred directories get their handles once their names have been read, and are cached once load_many is done with them
+            if !self.deferred_fields.contains_key(&file_path_hash) {
+                ig_object_handle_manager.add_directory(&dir.read().unwrap());
+                self.cache_directory(ig_file_context, ig_registry, &dir);
             }
+            // A loader that didn't take the file leaves it behind
+            self.prefetched_files.remove(&file_path_hash);
 
             Ok(dir)
         }
     }
 
+    /// Reads the file of `dir` into it with the loader picked for its path
+    fn read_directory(
+        &mut self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_metadata_manager: &mut igMetadataManager,
+        ig_ext_ref_system: &mut igExternalReferenceSystem,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        dir: &Arc<RwLock<igObjectDirectory>>,
+    ) {
+        let file_path = dir.read().unwrap().path.clone();
+        // The file is opened here so the loader can be picked from its contents. It is handed to the loader through the prefetched files
+        let file_path_hash = hash_lower(&file_path);
+        let fd = match self.prefetched_files.remove(&file_path_hash) {
+            Some(fd) => fd,
+            None => ig_file_context.open(ig_registry, &file_path, 0),
+        };
+        let loader_result = {
+            let data = fd._handle.as_ref().map(|handle| handle.get_ref().as_slice()).unwrap_or(&[]);
+            self.loader_registry.get_loader(&file_path, data)
+        };
+        if fd._handle.is_some() {
+            self.prefetched_files.insert(file_path_hash, fd);
+        }
+        if let Some(loader) = loader_result {
+            let loader_guard = loader.read().unwrap();
+            let mut dir_guard = dir.write().unwrap();
+            dir_guard.loader = loader.clone();
+            loader_guard.read_file(
+                ig_file_context,
+                ig_registry,
+                self,
+                ig_ext_ref_system,
+                ig_object_handle_manager,
+                ig_metadata_manager,
+                &mut dir_guard,
+                &file_path,
+            );
+        } else {
+            warn!("No loader found for file {}", file_path);
+        }
+    }
+
+    /// Returns the [igFileContext::directory_cache] entry for `file_path` if the cache holds a valid one
+    fn cached_entry(&self, ig_file_context: &igFileContext, ig_registry: &igRegistry, file_path: &str) -> Option<igCachedDirectory> {
+        let cache = ig_file_context.directory_cache.as_ref()?;
+        let stamp = ig_file_context.archive_file_stamp(ig_registry, file_path)?;
+        cache.get(file_path, stamp)
+    }
+
+    /// Rebuilds a directory from a [igDirectoryCache](crate::core::ig_directory_cache::igDirectoryCache) entry and registers it as the file it was cached from. The directories it depends on or references are loaded first. If the entry no longer fits the loaded metadata, the file is loaded instead and cached again
+    pub(crate) fn restore_cached(
+        &mut self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_metadata_manager: &mut igMetadataManager,
+        ig_ext_ref_system: &mut igExternalReferenceSystem,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        cached: igCachedLoad,
+    ) -> Arc<RwLock<igObjectDirectory>> {
+        let igCachedLoad { file_path, namespace, entry } = cached;
+        // The cached copy is used instead of the file
+        self.prefetched_files.remove(&hash_lower(file_path));
+
+        let dir = Arc::new(RwLock::new(igObjectDirectory::new(file_path, namespace.clone())));
+        self.push_dir(dir.clone());
+        let outer_paths = mem::take(&mut self.deferred_paths);
+        let mut load_all = |this: &mut igObjectStreamManager, directories: &[(igName, String)]| -> Vec<Arc<RwLock<igObjectDirectory>>> {
+            directories
+                .iter()
+                .filter_map(|(namespace, path)| {
+                    this.load_with_namespace(
+                        ig_file_context,
+                        ig_registry,
+                        ig_metadata_manager,
+                        ig_ext_ref_system,
+                        ig_object_handle_manager,
+                        path.clone(),
+                        namespace.clone(),
+                    )
+                    .ok()
+                })
+                .collect()
+        };
+        let dependencies = load_all(self, &entry.dependencies);
+        let directories = load_all(self, &entry.referenced_directories);
+
+        let restored = entry.restore(&mut dir.write().unwrap(), ig_metadata_manager, ig_object_handle_manager, dependencies, &directories);
+        if !restored {
+            warn!("The directory cache entry for {} doesn't match the loaded metadata, loading the file instead", file_path);
+            self.read_directory(ig_file_context, ig_registry, ig_metadata_manager, ig_ext_ref_system, ig_object_handle_manager, &dir);
+            self.cache_directory(ig_file_context, ig_registry, &dir);
+        }
+        self.deferred_paths = outer_paths;
+        ig_object_handle_manager.add_directory(&dir.read().unwrap());
+
+        dir
+    }
+
+    /// Queues `dir` to be stored in [igFileContext::directory_cache] if it was loaded from an igz in an archive
+    fn cache_directory(&mut self, ig_file_context: &igFileContext, ig_registry: &igRegistry, dir: &Arc<RwLock<igObjectDirectory>>) {
+        if ig_file_context.directory_cache.is_none() {
+            return;
+        }
+        let stamp = {
+            let dir = dir.read().unwrap();
+            if dir.loader.read().unwrap().get_name() != igIGZObjectLoader.get_name() {
+                return;
+            }
+            ig_file_context.archive_file_stamp(ig_registry, &dir.path)
+        };
+        if let Some(stamp) = stamp {
+            self.uncached_directories.push((dir.clone(), stamp));
+        }
+    }
+
+    /// Writes every directory loaded since the last call to [igFileContext::directory_cache]. Call once a batch of loads is done so saving them doesn't hold up loading
+    pub fn write_directory_cache(&mut self, ig_file_context: &igFileContext, ig_metadata_manager: &mut igMetadataManager) {
+        let uncached = mem::take(&mut self.uncached_directories);
+        let Some(cache) = ig_file_context.directory_cache.as_ref() else {
+            return;
+        };
+        for (dir, stamp) in uncached {
+            cache.put(&dir.read().unwrap(), stamp, self, ig_metadata_manager);
+        }
+    }
+
+    /// Loads several independent files. The files are read and decompressed in parallel and loaded in order on the current thread, since registering directories and resolving externals needs every manager. Fields are then decoded on worker threads, waiting on any file in the batch they reference.
+    pub fn load_many(
+        &mut self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_metadata_manager: &mut igMetadataManager,
+        ig_ext_ref_system: &mut igExternalReferenceSystem,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        paths: Vec<String>,
+    ) -> Vec<Result<Arc<RwLock<igObjectDirectory>>, String>> {
+        let paths = paths.into_iter().map(|path| (path.clone(), igName::new(path))).collect();
+        self.load_many_with_namespace(ig_file_context, ig_registry, ig_metadata_manager, ig_ext_ref_system, ig_object_handle_manager, paths)
+    }
+
+    /// Same as [igObjectStreamManager::load_many], loading every `(path, namespace)` pair with [igObjectStreamManager::load_with_namespace]
+    pub fn load_many_with_namespace(
+        &mut self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_metadata_manager: &mut igMetadataManager,
+        ig_ext_ref_system: &mut igExternalReferenceSystem,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        paths: Vec<(String, igName)>,
+    ) -> Vec<Result<Arc<RwLock<igObjectDirectory>>, String>> {
+        let native_paths: Vec<String> = paths.iter().map(|(path, _)| path.clone()).collect();
+        self.prefetch(ig_file_context, ig_registry, &native_paths);
+
+        let batch: Vec<u32> = native_paths.iter().map(|path| hash_lower(&get_native_path(path.clone()))).collect();
+        let outer_paths = mem::replace(&mut self.deferred_paths, batch.iter().copied().collect());
+        let mut results = Vec::with_capacity(paths.len());
+        for (path, namespace) in paths {
+            results.push(self.load_with_namespace(
+                ig_file_context,
+                ig_registry,
+                ig_metadata_manager,
+                ig_ext_ref_system,
+                ig_object_handle_manager,
+                path,
+                namespace,
+            ));
+        }
+        self.deferred_paths = outer_paths;
+
+        let mut remaining: Vec<u32> = Vec::new();
+        for hash in batch {
+            if self.deferred_fields.contains_key(&hash) && !remaining.contains(&hash) {
+                remaining.push(hash);
+            }
+        }
+        let deferred = remaining.clone();
+        while !remaining.is_empty() {
+            let (ready, waiting): (Vec<u32>, Vec<u32>) = remaining
+                .iter()
+                .partition(|hash| !self.references_any(**hash, &remaining));
+            if ready.is_empty() {
+                // The files reference each other, so whichever loaded first is read first
+                self.finish_deferred_fields(ig_metadata_manager, ig_object_handle_manager, remaining.remove(0));
+                continue;
+            }
+
+            self.read_deferred_fields_in_parallel(ig_metadata_manager, &ready);
+            for hash in &ready {
+                ig_object_handle_manager.add_directory(&self.path_to_directory_lookup[hash].read().unwrap());
+            }
+            remaining = waiting;
+        }
+
+        for hash in deferred {
+            let dir = self.path_to_directory_lookup[&hash].clone();
+            self.cache_directory(ig_file_context, ig_registry, &dir);
+        }
+
+        results
+    }
+
+    /// Reads the fields of the deferred files in `hashes` on worker threads
+    fn read_deferred_fields_in_parallel(&mut self, ig_metadata_manager: &igMetadataManager, hashes: &[u32]) {
+        let work: Vec<(Arc<RwLock<igObjectDirectory>>, igIGZDeferredFields)> = hashes
+            .iter()
+            .map(|hash| (self.path_to_directory_lookup[hash].clone(), self.deferred_fields.remove(hash).unwrap()))
+            .collect();
+        let worker_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(work.len());
+        let chunk_size = work.len().div_ceil(worker_count);
+
+        let this = &*self;
+        let mut work = work.into_iter();
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..worker_count)
+                .map(|_| {
+                    let chunk: Vec<_> = work.by_ref().take(chunk_size).collect();
+                    scope.spawn(move || {
+                        for (dir, fields) in chunk {
+                            fields.read(ig_metadata_manager, this, &mut dir.write().unwrap());
+                        }
+                    })
+                })
+                .collect();
+
+            for worker in workers {
+                if let Err(panic) = worker.join() {
+                    // Keep the loader's own panic so strict loading fails the same way it does on one thread
+                    std::panic::resume_unwind(panic);
+                }
+            }
+        });
+    }
+
+    /// True when the file at `hash` references a namespace one of the `others` was loaded into
+    fn references_any(&self, hash: u32, others: &[u32]) -> bool {
+        let dir = self.path_to_directory_lookup[&hash].read().unwrap();
+        dir.external_namespaces.iter().any(|(_, namespace)| {
+            self.name_to_directory_lookup.get(&namespace.hash).is_some_and(|dirs| {
+                dirs.iter().any(|other| {
+                    let other_hash = hash_lower(&other.read().unwrap().path);
+                    other_hash != hash && others.contains(&other_hash)
+                })
+            })
+        })
+    }
+
+    /// Reads the fields of a file [igObjectStreamManager::load_many] deferred and registers its handles. Does nothing if the file's fields were already read
+    pub(crate) fn finish_deferred_fields(
+        &mut self,
+        ig_metadata_manager: &igMetadataManager,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        file_path_hash: u32,
+    ) {
+        let Some(fields) = self.deferred_fields.remove(&file_path_hash) else {
+            return;
+        };
+        let dir = self.path_to_directory_lookup[&file_path_hash].clone();
+        fields.read(ig_metadata_manager, self, &mut dir.write().unwrap());
+        ig_object_handle_manager.add_directory(&dir.read().unwrap());
+    }
+
+    /// Same as [igObjectStreamManager::finish_deferred_fields] for every file loaded into `namespace`
+    pub(crate) fn finish_deferred_namespace(
+        &mut self,
+        ig_metadata_manager: &igMetadataManager,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        namespace: u32,
+    ) {
+        if self.deferred_fields.is_empty() {
+            return;
+        }
+        let Some(dirs) = self.name_to_directory_lookup.get(&namespace) else {
+            return;
+        };
+        let hashes: Vec<u32> = dirs.iter().filter_map(|dir| dir.try_read().ok().map(|dir| hash_lower(&dir.path))).collect();
+        for hash in hashes {
+            self.finish_deferred_fields(ig_metadata_manager, ig_object_handle_manager, hash);
+        }
+    }
+
+    /// True when the file being loaded was asked for by [igObjectStreamManager::load_many] and its fields should be read once the whole batch is loaded. Only the first call for a load returns true
+    pub(crate) fn take_defer_current_load(&mut self) -> bool {
+        mem::take(&mut self.defer_current_load)
+    }
+
+    /// Reads the files at `paths` on worker threads so a later load doesn't have to wait on the file system. Files that are already loaded, prefetched or [cached](igFileContext::directory_cache) are skipped.
+    pub fn prefetch(&mut self, ig_file_context: &igFileContext, ig_registry: &igRegistry, paths: &[String]) {
+        let mut pending: Vec<(u32, String)> = Vec::new();
+        for path in paths {
+            let file_path = get_native_path(path.clone());
+            let file_path_hash = hash_lower(&file_path);
+            let cached = ig_file_context.directory_cache.as_ref().is_some_and(|cache| {
+                ig_file_context
+                    .archive_file_stamp(ig_registry, &file_path)
+                    .is_some_and(|stamp| cache.contains(&file_path, stamp))
+            });
+            if cached
+                || self.path_to_directory_lookup.contains_key(&file_path_hash)
+                || self.prefetched_files.contains_key(&file_path_hash)
+                || pending.iter().any(|(hash, _)| *hash == file_path_hash)
+            {
+                continue;
+            }
+            pending.push((file_path_hash, file_path));
+        }
+
+        if pending.is_empty() {
+            return;
+        }
+
+        let worker_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1)
+            .min(pending.len());
+        let chunk_size = pending.len().div_ceil(worker_count);
+
+        let fetched: Vec<(u32, igFileDescriptor)> = thread::scope(|scope| {
+            let workers: Vec<_> = pending
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|(hash, file_path)| (*hash, ig_file_context.open_in_parallel(ig_registry, file_path, 0)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        for (hash, fd) in fetched {
+            if fd._handle.is_some() {
+                self.prefetched_files.insert(hash, fd);
+            }
+        }
+    }
+
+    /// Takes the prefetched descriptor for `file_path` (a native path) if one exists
+    pub fn take_prefetched(&mut self, file_path: &str) -> Option<igFileDescriptor> {
+        self.prefetched_files.remove(&hash_lower(file_path))
+    }
+
     fn push_dir(&mut self, dir: Arc<RwLock<igObjectDirectory>>) {
         let hash = dir.read().unwrap().name.hash;
         let file_path = dir.read().unwrap().path.clone();
@@ -1 +1,576 @@
-pub struct IgbLoaderContext;
\ No newline at end of file
+use crate::core::ig_custom::{igNull, igObjectList, CastTo};
+use crate::core::ig_external_ref::igExternalReferenceSystem;
+use crate::core::ig_file_context::igFileContext;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_fs::Endian::{Big, Little};
+use crate::core::ig_handle::{igHandle, igHandleName, igObjectHandleManager};
+use crate::core::ig_memory::igMemoryPool;
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
+use crate::core::ig_registry::igRegistry;
+use crate::core::load::ig_loader::{report_unreadable, igLoadDiagnostic, igLoadDiagnosticKind, igObjectLoader};
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetaObject, igMetadataManager};
+use crate::core::meta::ig_xml_metadata::RawArkMetaObjectField;
+use crate::util::byteorder_fixes::{read_struct_array_u8, read_u16, read_u32};
+use crate::util::ig_name::igName;
+use log::{debug, error, warn};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+/// Stored at 0x28 of every igb header
+pub(crate) const IGB_MAGIC_COOKIE: u32 = 0xFADA;
+/// Size of the igb header. Every section follows it back to back
+const IGB_HEADER_SIZE: u64 = 0x30;
+
+/// Loads igb files, the binary format used by Alchemy before igz (X-Men Legends, Marvel Ultimate Alliance, Spider-Man titles...).
+///
+/// Unlike igz, an igb describes its own types: it stores the name of every metafield and metaobject it uses, along with the type, slot and size of every field. Objects are matched to the loaded metadata by name and their fields by slot order, so the resulting [igObjectDirectory] looks the same as one loaded from an igz. Types the loaded metadata doesn't have, which is every type of a game ArkCore doesn't cover, are built from the igb's own tables instead.
+pub struct igIGBObjectLoader;
+
+/// A metaobject as described by the igb
+#[derive(Debug, Clone)]
+pub struct igIGBMetaObject {
+    pub name: Arc<str>,
+    /// Index of the parent metaobject in [IgbLoaderContext::meta_objects]
+    pub parent: Option<usize>,
+    /// Fields declared by this metaobject. Does not include the fields of the parent
+    pub fields: Vec<igIGBMetaObjectField>,
+}
+
+/// A field of an [igIGBMetaObject]
+#[derive(Debug, Clone)]
+pub struct igIGBMetaObjectField {
+    /// Index of the field's type in [IgbLoaderContext::meta_fields]
+    pub type_index: u16,
+    pub slot: u16,
+    /// Size of the field when serialized
+    pub size: u16,
+}
+
+/// An entry of the igb directory. Object and memory ref fields store an index into the directory
+#[derive(Debug, Clone)]
+pub enum igIGBDirectoryEntry {
+    /// An object, with the index of its type in [IgbLoaderContext::meta_objects]
+    Object { type_index: u32 },
+    /// A block of memory, with its size and the index of its element type in [IgbLoaderContext::meta_fields]
+    Memory { size: u32, type_index: u32 },
+    /// A named object of another directory, referenced as `namespace::name`. Alchemy has no such entry; it is ig-library's own `igExternalObjectDirEntry` so saved igbs don't have to copy objects they don't own
+    External { namespace: Arc<str>, name: Arc<str> },
+    /// Any other kind of entry (Alchemy's own external references for example). Holds the name of the entry type
+    Unsupported(Arc<str>),
+}
+
+/// Internal type to store while jumping around to other methods. Also shared with loading metafields
+pub struct IgbLoaderContext {
+    /// igb version
+    pub version: u32,
+    /// The type name of every metafield in the igb
+    pub meta_fields: Vec<Arc<str>>,
+    /// Every metaobject in the igb
+    pub meta_objects: Vec<igIGBMetaObject>,
+    /// The igb directory. Object and memory refs are indices into this
+    pub directory: Vec<igIGBDirectoryEntry>,
+    /// Every object in the igb keyed by its directory index
+    pub objects: HashMap<u32, igObject>,
+    /// The raw contents of every memory block keyed by its directory index
+    pub memory_blocks: HashMap<u32, Vec<u8>>,
+    /// The serialized size the igb gives the field being read. Used by metafields with no igb implementation to skip the field
+    pub field_size: u32,
+    /// Setting decides if problems with the igb abort the load or get recorded into [IgbLoaderContext::diagnostics]. See [igObjectStreamManager::lenient_loading]
+    pub lenient: bool,
+    /// All problems found while loading. Moved into [igObjectDirectory::diagnostics] once loading is finished
+    pub diagnostics: Vec<igLoadDiagnostic>,
+    /// The directory index and instance of the object having its fields read. Used to tie diagnostics to objects
+    pub current_object: Option<(u64, igObject)>,
+    /// The name of the field currently being read. Used to tie diagnostics to fields
+    pub current_field: Option<Arc<str>>,
+}
+
+impl IgbLoaderContext {
+    pub(crate) fn new(version: u32, lenient: bool) -> IgbLoaderContext {
+        IgbLoaderContext {
+            version,
+            meta_fields: vec![],
+            meta_objects: vec![],
+            directory: vec![],
+            objects: HashMap::new(),
+            memory_blocks: HashMap::new(),
+            field_size: 0,
+            lenient,
+            diagnostics: vec![],
+            current_object: None,
+            current_field: None,
+        }
+    }
+
+    /// Every field of the metaobject at `type_index` including inherited ones, in slot order
+    pub fn all_fields(&self, type_index: usize) -> Vec<igIGBMetaObjectField> {
+        let mut fields = Vec::new();
+        let mut current = Some(type_index);
+        while let Some(index) = current {
+            let Some(meta_object) = self.meta_objects.get(index) else {
+                break;
+            };
+            fields.extend(meta_object.fields.iter().cloned());
+            current = meta_object.parent.filter(|parent| *parent != index);
+        }
+        fields.sort_by_key(|field| field.slot);
+        fields
+    }
+
+    /// Records a problem that doesn't stop the igb from loading against the current object and field.
+    pub fn report(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        let (object_offset, object) = match &self.current_object {
+            Some((offset, object)) => (Some(*offset), Some(object.clone())),
+            None => (None, None),
+        };
+
+        self.diagnostics.push(igLoadDiagnostic {
+            kind,
+            object_offset,
+            object,
+            field: self.current_field.clone(),
+            message,
+        });
+    }
+
+    /// Used when the igb contains something we can't understand. Aborts the load unless [IgbLoaderContext::lenient] is set, where the problem is recorded instead and the caller is expected to carry on.
+    pub fn report_failure(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        if !self.lenient {
+            error!("{}", message);
+            panic!("Alchemy Error! Check the logs.")
+        }
+
+        warn!("{}", message);
+        self.report(kind, message);
+    }
+}
+
+/// The sizes and counts stored in the igb header
+struct IgbHeader {
+    entry_buffer_size: u32,
+    entry_count: u32,
+    meta_object_buffer_size: u32,
+    meta_object_count: u32,
+    object_buffer_size: u32,
+    memory_buffer_size: u32,
+    meta_field_buffer_size: u32,
+    meta_field_count: u32,
+    version: u32,
+}
+
+impl igObjectLoader for igIGBObjectLoader {
+    fn can_read(&self, file_name: &str) -> bool {
+        file_name.ends_with(".igb")
+    }
+
+    fn can_read_data(&self, data: &[u8]) -> bool {
+        data.get(0x28..0x2C)
+            .map(|cookie| u32::from_le_bytes(cookie.try_into().unwrap()))
+            .is_some_and(|cookie| cookie == IGB_MAGIC_COOKIE || cookie == IGB_MAGIC_COOKIE.swap_bytes())
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Alchemy Binary"
+    }
+
+    fn get_type(&self) -> &'static str {
+        "Alchemy"
+    }
+
+    fn read_file(
+        &self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_object_stream_manager: &mut igObjectStreamManager,
+        _ig_ext_ref_system: &mut igExternalReferenceSystem,
+        _ig_object_handle_manager: &mut igObjectHandleManager,
+        ig_metadata_manager: &mut igMetadataManager,
+        dir: &mut igObjectDirectory,
+        file_path: &str,
+    ) {
+        let fd = match ig_object_stream_manager.take_prefetched(file_path) {
+            Some(fd) => fd,
+            None => ig_file_context.open(ig_registry, file_path, 0),
+        };
+        let lenient = ig_object_stream_manager.lenient_loading;
+        let Some(handle) = fd._handle else {
+            report_unreadable(dir, lenient, format!("Failed to load igb {}. File could not be read.", file_path));
+            return;
+        };
+
+        debug!("Loading igb {}", file_path);
+        if let Err(e) = self.read_bytes(handle.into_inner(), ig_object_stream_manager, ig_metadata_manager, dir) {
+            report_unreadable(dir, lenient, format!("Failed to load igb {}. {}", file_path, e));
+        }
+    }
+}
+
+impl igIGBObjectLoader {
+    /// Reads the igb in `data` into `dir`. Returns an error when `data` isn't an igb or one of its sections is cut short, in which case `dir` is left untouched. Problems with the objects themselves are reported through [igObjectDirectory::diagnostics] the same way igz does
+    pub fn read_bytes(
+        &self,
+        data: Vec<u8>,
+        ig_object_stream_manager: &igObjectStreamManager,
+        ig_metadata_manager: &mut igMetadataManager,
+        dir: &mut igObjectDirectory,
+    ) -> Result<(), String> {
+        let mut handle = Cursor::new(data);
+        handle.set_position(0x28);
+        let endian = match read_u32(&mut handle, Little).map_err(truncated("header"))? {
+            IGB_MAGIC_COOKIE => Little,
+            magic if magic == IGB_MAGIC_COOKIE.swap_bytes() => Big,
+            magic => return Err(format!("Not an igb. Magic value was wrong. Got: {:#X}", magic)),
+        };
+
+        let header = read_header(&mut handle, endian.clone())?;
+        debug!("igb version is {}", header.version);
+        let mut ctx = IgbLoaderContext::new(header.version, ig_object_stream_manager.lenient_loading);
+
+        let mut section_start = IGB_HEADER_SIZE;
+        handle.set_position(section_start);
+        read_meta_fields(&mut handle, endian.clone(), &header, &mut ctx)?;
+
+        section_start += header.meta_field_buffer_size as u64;
+        handle.set_position(section_start);
+        read_meta_objects(&mut handle, endian.clone(), &header, &mut ctx)?;
+
+        section_start += header.meta_object_buffer_size as u64;
+        handle.set_position(section_start);
+        let entries = read_entries(&mut handle, endian.clone(), &header, &mut ctx)?;
+
+        section_start += header.entry_buffer_size as u64;
+        handle.set_position(section_start);
+        let directory_count = read_u32(&mut handle, endian.clone()).map_err(truncated("directory"))?;
+        for _ in 0..directory_count {
+            let entry = read_u32(&mut handle, endian.clone()).map_err(truncated("directory"))?;
+            match entries.get(entry as usize) {
+                Some(entry) => ctx.directory.push(entry.clone()),
+                None => {
+                    ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Directory references entry {} which doesn't exist", entry));
+                    ctx.directory.push(igIGBDirectoryEntry::Unsupported(Arc::from("missing")));
+                }
+            }
+        }
+        let info_list_index = read_u32(&mut handle, endian.clone()).map_err(truncated("directory"))?;
+
+        // Memory blocks are stored after the objects, but objects point into them so they have to be read first
+        let object_start = handle.position();
+        let object_end = object_start + header.object_buffer_size as u64;
+        ensure_remaining(&handle, object_start, header.object_buffer_size as u64, "object")?;
+        handle.set_position(object_end);
+        read_memory_blocks(&mut handle, endian.clone(), &header, &mut ctx)?;
+
+        // Checked up front so the metafields, which expect their bytes to be there, never read past the object section
+        let mut object_size = 0u64;
+        for entry in &ctx.directory {
+            if let igIGBDirectoryEntry::Object { type_index } = entry {
+                object_size += ctx.all_fields(*type_index as usize).iter().map(|field| field.size as u64).sum::<u64>();
+            }
+        }
+        if object_size > header.object_buffer_size as u64 {
+            return Err(format!(
+                "The objects need {:#X} bytes but the object section is only {:#X} bytes",
+                object_size, header.object_buffer_size
+            ));
+        }
+
+        instantiate_objects(ig_metadata_manager, &mut ctx);
+        let externals = resolve_externals(ig_object_stream_manager, &mut ctx);
+
+        handle.set_position(object_start);
+        for (index, entry) in ctx.directory.clone().into_iter().enumerate() {
+            let igIGBDirectoryEntry::Object { type_index } = entry else {
+                continue;
+            };
+
+            let object = ctx.objects[&(index as u32)].clone();
+            let fields = ctx.all_fields(type_index as usize);
+            let size: u64 = fields.iter().map(|field| field.size as u64).sum();
+            let start = handle.position();
+            ctx.current_object = Some((index as u64, object.clone()));
+            if !object.read().unwrap().as_any().is::<igNull>() {
+                ig_metadata_manager.read_igb_fields(ig_object_stream_manager, &mut handle, endian.clone(), &mut ctx, object, &fields);
+            }
+            // Objects that failed to instantiate were already reported. Either way the next object starts after this one's fields
+            handle.set_position(start + size);
+        }
+        ctx.current_object = None;
+
+        match ctx.objects.get(&info_list_index) {
+            Some(root) => {
+                let object_list: Result<Arc<RwLock<igObjectList>>, _> = root.clone().cast_to();
+                match object_list {
+                    Ok(object_list) => dir.object_list = object_list,
+                    Err(_) => {
+                        // Usually an igInfoList, which we don't have a programmer-made type for
+                        let object_list = igObjectList::new();
+                        object_list.push(root.clone());
+                        dir.object_list = Arc::new(RwLock::new(object_list));
+                    }
+                }
+            }
+            None => {
+                ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("The info list {} is not an object in the directory", info_list_index));
+            }
+        }
+
+        // Objects of other directories stay with their own directory
+        let mut objects: Vec<_> = ctx.objects.into_iter().filter(|(index, _)| !externals.contains(index)).collect();
+        objects.sort_by_key(|(index, _)| *index);
+        dir.all_objects = objects.into_iter().map(|(_, object)| object).collect();
+        dir.diagnostics.append(&mut ctx.diagnostics);
+        Ok(())
+    }
+}
+
+/// Turns a failed read into the error returned by [igIGBObjectLoader::read_bytes]
+fn truncated(section: &'static str) -> impl Fn(std::io::Error) -> String {
+    move |_| format!("The {} section is cut short", section)
+}
+
+/// Errors when `size` bytes starting at `start` don't fit in the file
+fn ensure_remaining(handle: &Cursor<Vec<u8>>, start: u64, size: u64, section: &'static str) -> Result<(), String> {
+    if start.saturating_add(size) > handle.get_ref().len() as u64 {
+        return Err(format!("The {} section is cut short", section));
+    }
+    Ok(())
+}
+
+fn read_header(handle: &mut Cursor<Vec<u8>>, endian: Endian) -> Result<IgbHeader, String> {
+    handle.set_position(0);
+    let mut values = [0u32; 12];
+    for value in values.iter_mut() {
+        *value = read_u32(handle, endian.clone()).map_err(truncated("header"))?;
+    }
+
+    Ok(IgbHeader {
+        entry_buffer_size: values[0],
+        entry_count: values[1],
+        meta_object_buffer_size: values[2],
+        meta_object_count: values[3],
+        object_buffer_size: values[4],
+        memory_buffer_size: values[6],
+        meta_field_buffer_size: values[8],
+        meta_field_count: values[9],
+        // the top bits are flags
+        version: values[11] & 0xFFFF,
+    })
+}
+
+/// Reads a name stored by length (including the null terminator)
+fn read_name(handle: &mut Cursor<Vec<u8>>, endian: Endian, length: u32, section: &'static str) -> Result<Arc<str>, String> {
+    ensure_remaining(handle, handle.position(), length as u64, section)?;
+    let bytes = read_struct_array_u8(handle, endian, length as usize).map_err(truncated(section))?;
+    let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+    Ok(Arc::from(String::from_utf8_lossy(&bytes[..end]).as_ref()))
+}
+
+/// Metafields are stored as a list of (name length, major version, minor version) followed by every name
+fn read_meta_fields(handle: &mut Cursor<Vec<u8>>, endian: Endian, header: &IgbHeader, ctx: &mut IgbLoaderContext) -> Result<(), String> {
+    let mut name_lengths = Vec::new();
+    for _ in 0..header.meta_field_count {
+        name_lengths.push(read_u32(handle, endian.clone()).map_err(truncated("metafield"))?);
+        let _major_version = read_u32(handle, endian.clone()).map_err(truncated("metafield"))?;
+        let _minor_version = read_u32(handle, endian.clone()).map_err(truncated("metafield"))?;
+    }
+
+    for length in name_lengths {
+        let name = read_name(handle, endian.clone(), length, "metafield")?;
+        debug!("igb contains metafield {}", name);
+        ctx.meta_fields.push(name);
+    }
+    Ok(())
+}
+
+/// Each metaobject is stored as (name length, major version, minor version, field count, parent index, slot count), its fields, then its name
+fn read_meta_objects(handle: &mut Cursor<Vec<u8>>, endian: Endian, header: &IgbHeader, ctx: &mut IgbLoaderContext) -> Result<(), String> {
+    for _ in 0..header.meta_object_count {
+        let name_length = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+        let _major_version = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+        let _minor_version = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+        let field_count = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+        let parent = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+        let _slot_count = read_u32(handle, endian.clone()).map_err(truncated("metaobject"))?;
+
+        let mut fields = Vec::new();
+        for _ in 0..field_count {
+            fields.push(igIGBMetaObjectField {
+                type_index: read_u16(handle, endian.clone()).map_err(truncated("metaobject"))?,
+                slot: read_u16(handle, endian.clone()).map_err(truncated("metaobject"))?,
+                size: read_u16(handle, endian.clone()).map_err(truncated("metaobject"))?,
+            });
+        }
+
+        let name = read_name(handle, endian.clone(), name_length, "metaobject")?;
+        debug!("igb contains metaobject {}", name);
+        ctx.meta_objects.push(igIGBMetaObject {
+            name,
+            parent: (parent != u32::MAX).then_some(parent as usize),
+            fields,
+        });
+    }
+    Ok(())
+}
+
+/// Each entry is stored as (type index, length) followed by its fields. The type is one of the metaobjects in the igb
+fn read_entries(handle: &mut Cursor<Vec<u8>>, endian: Endian, header: &IgbHeader, ctx: &mut IgbLoaderContext) -> Result<Vec<igIGBDirectoryEntry>, String> {
+    let mut entries = Vec::new();
+    for _ in 0..header.entry_count {
+        let start = handle.position();
+        let type_index = read_u32(handle, endian.clone()).map_err(truncated("entry"))?;
+        let length = read_u32(handle, endian.clone()).map_err(truncated("entry"))?;
+        let type_name = ctx
+            .meta_objects
+            .get(type_index as usize)
+            .map(|meta_object| meta_object.name.clone())
+            .unwrap_or_else(|| Arc::from("unknown"));
+
+        let entry = match type_name.as_ref() {
+            "igObjectDirEntry" => igIGBDirectoryEntry::Object {
+                type_index: read_u32(handle, endian.clone()).map_err(truncated("entry"))?,
+            },
+            "igMemoryDirEntry" => igIGBDirectoryEntry::Memory {
+                size: read_u32(handle, endian.clone()).map_err(truncated("entry"))?,
+                type_index: read_u32(handle, endian.clone()).map_err(truncated("entry"))?,
+            },
+            "igExternalObjectDirEntry" => {
+                let namespace_length = read_u32(handle, endian.clone()).map_err(truncated("entry"))?;
+                let name_length = read_u32(handle, endian.clone()).map_err(truncated("entry"))?;
+                igIGBDirectoryEntry::External {
+                    namespace: read_name(handle, endian.clone(), namespace_length, "entry")?,
+                    name: read_name(handle, endian.clone(), name_length, "entry")?,
+                }
+            }
+            _ => {
+                warn!("igb contains an unsupported directory entry {}", type_name);
+                igIGBDirectoryEntry::Unsupported(type_name)
+            }
+        };
+        entries.push(entry);
+        handle.set_position(start + length as u64);
+    }
+    Ok(entries)
+}
+
+/// Memory blocks are stored back to back in directory order
+fn read_memory_blocks(handle: &mut Cursor<Vec<u8>>, endian: Endian, header: &IgbHeader, ctx: &mut IgbLoaderContext) -> Result<(), String> {
+    let end = handle.position() + header.memory_buffer_size as u64;
+    ensure_remaining(handle, handle.position(), header.memory_buffer_size as u64, "memory")?;
+    for (index, entry) in ctx.directory.iter().enumerate() {
+        if let igIGBDirectoryEntry::Memory { size, .. } = entry {
+            if handle.position() + *size as u64 > end {
+                return Err(format!("Memory block {} runs past the end of the memory section", index));
+            }
+            let data = read_struct_array_u8(handle, endian.clone(), *size as usize).map_err(truncated("memory"))?;
+            ctx.memory_blocks.insert(index as u32, data);
+        }
+    }
+    Ok(())
+}
+
+fn instantiate_objects(imm: &mut igMetadataManager, ctx: &mut IgbLoaderContext) {
+    for (index, entry) in ctx.directory.clone().into_iter().enumerate() {
+        let igIGBDirectoryEntry::Object { type_index } = entry else {
+            continue;
+        };
+
+        let type_name = ctx
+            .meta_objects
+            .get(type_index as usize)
+            .map(|meta_object| meta_object.name.clone())
+            .unwrap_or_else(|| Arc::from("unknown"));
+
+        let meta = if imm.contains_meta(&type_name) {
+            Some(imm.get_or_create_meta(&type_name).unwrap())
+        } else if (type_index as usize) < ctx.meta_objects.len() {
+            // Games from before igz aren't in ArkCore, but the igb describes every type it uses
+            debug!("{} is not in the loaded metadata, laying it out from the igb", type_name);
+            Some(igb_meta_object(imm, ctx, type_index as usize))
+        } else {
+            None
+        };
+        let object = match meta {
+            Some(meta) => meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).map_err(|e| e.to_string()),
+            None => Err(format!("{} is not a metaobject in the igb", type_index)),
+        };
+
+        match object {
+            Ok(object) => {
+                ctx.objects.insert(index as u32, object);
+            }
+            Err(e) => {
+                ctx.report_failure(
+                    igLoadDiagnosticKind::InstantiationFailed,
+                    format!("Failed to instantiate {} (directory index {}): {}", type_name, index, e),
+                );
+                ctx.objects.insert(index as u32, Arc::new(RwLock::new(igNull)));
+            }
+        }
+    }
+}
+
+/// Looks up the object of every [igIGBDirectoryEntry::External] in the directories loaded so far. Returns the directory indices of the entries
+fn resolve_externals(ig_object_stream_manager: &igObjectStreamManager, ctx: &mut IgbLoaderContext) -> Vec<u32> {
+    let mut externals = Vec::new();
+    for (index, entry) in ctx.directory.clone().into_iter().enumerate() {
+        let igIGBDirectoryEntry::External { namespace, name } = entry else {
+            continue;
+        };
+
+        let handle_name = igHandleName::new(igName::new(name.to_string()), igName::new(namespace.to_string()));
+        let object = igHandle::from_handle_name(&handle_name).write().unwrap().get_object_alias(ig_object_stream_manager);
+        let object = object.unwrap_or_else(|| {
+            ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("igb failed to resolve the external reference {}::{}", namespace, name));
+            Arc::new(RwLock::new(igNull))
+        });
+        ctx.objects.insert(index as u32, object);
+        externals.push(index as u32);
+    }
+    externals
+}
+
+/// Builds a type from the igb's own metaobject and metafield tables. The igb doesn't store field names, so fields are named after their slot (`_slot0`, `_slot1`...) and laid out back to back in slot order. Memory blocks are kept as bytes since the element type is only known per block
+fn igb_meta_object(imm: &mut igMetadataManager, ctx: &IgbLoaderContext, type_index: usize) -> Arc<RwLock<igMetaObject>> {
+    let meta_object = &ctx.meta_objects[type_index];
+    let parent = meta_object.parent.and_then(|parent| ctx.meta_objects.get(parent)).map(|parent| parent.name.clone());
+
+    let mut fields = Vec::new();
+    let mut offset = 0u16;
+    for field in ctx.all_fields(type_index) {
+        let _type = ctx.meta_fields.get(field.type_index as usize).cloned().unwrap_or_else(|| Arc::from("unknown"));
+        let name: Arc<str> = Arc::from(format!("_slot{}", field.slot));
+        let memory_ref_info = (_type.as_ref() == "igMemoryRefMetaField").then(|| Arc::new(RwLock::new(igb_ark_field(Arc::from("igUnsignedCharMetaField"), None, 0))));
+        let mut ark_info = igb_ark_field(_type.clone(), Some(name.clone()), offset);
+        ark_info.ig_memory_ref_info = memory_ref_info;
+        fields.push(Arc::new(igMetaFieldInfo {
+            ark_info: Arc::new(RwLock::new(ark_info)),
+            _type,
+            name: Some(name),
+            size: field.size as u32,
+            alignment: 4,
+            offset,
+        }));
+        offset = offset.saturating_add(field.size);
+    }
+
+    imm.create_generic_meta(meta_object.name.clone(), parent, fields)
+}
+
+fn igb_ark_field(_type: Arc<str>, name: Option<Arc<str>>, offset: u16) -> RawArkMetaObjectField {
+    RawArkMetaObjectField {
+        _type,
+        offset,
+        name,
+        meta_object: None,
+        required_alignment: None,
+        ig_vector_info: None,
+        ig_memory_ref_info: None,
+        ig_bit_shift_info: None,
+        ig_property_info: None,
+        ig_meta_enum: None,
+        ig_static_info: None,
+        type_size: None,
+        num: None,
+    }
+}
@@ -1 +1,401 @@
-pub struct IgxLoaderContext;
\ No newline at end of file
+use crate::core::ig_custom::{igNameList, igNull, CastTo};
+use crate::core::ig_external_ref::igExternalReferenceSystem;
+use crate::core::ig_file_context::igFileContext;
+use crate::core::ig_handle::igObjectHandleManager;
+use crate::core::ig_memory::igMemoryPool;
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
+use crate::core::ig_registry::igRegistry;
+use crate::core::load::ig_loader::{report_unreadable, igLoadDiagnostic, igLoadDiagnosticKind, igObjectLoader};
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::util::ig_name::igName;
+use log::{debug, error, warn};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::escape::unescape;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// Written in place of a value for a field without one. An element can't be mistaken for text, so a string that reads "null" stays a string
+pub const IGX_NULL: &str = "<null/>";
+/// The igx version this loader reads. Files with any other version are rejected, so a later change to the layout can bump it
+pub const IGX_VERSION: u32 = 1;
+
+/// Loads igx files, an XML form of igObjectDirectory defined by ig-library. It is not the XML Alchemy's own tools write, it exists so directories can be edited by hand and kept in version control. The layout is:
+///
+/// ```xml
+/// <igx version="1" root="0" names="1">
+///     <dependency name="common" path="common.igz"/>
+///     <object type="igObjectList" id="0">
+///         <field name="_data"><element>@2</element></field>
+///     </object>
+///     <object type="igFoo" id="2">
+///         <field name="_bar">12</field>
+///         <field name="_external">common::igBar</field>
+///     </object>
+/// </igx>
+/// ```
+///
+/// `root` is the id of the directory's [igObjectList] and `names` (optional) is the id of its [igNameList]. Object references are written as `@id` for objects in the same file and `namespace::name` for objects in other directories. Fields and elements without a value hold [IGX_NULL].
+pub struct igIGXObjectLoader;
+
+/// Internal type to store while jumping around to other methods. Also shared with loading metafields
+pub struct IgxLoaderContext<'a> {
+    /// Every object in the igx keyed by its id
+    pub objects: HashMap<u32, igObject>,
+    /// All problems found while loading. Moved into [igObjectDirectory::diagnostics] once loading is finished
+    pub diagnostics: Vec<igLoadDiagnostic>,
+    /// The object having its fields read. Used to tie diagnostics to objects
+    pub current_object: Option<igObject>,
+    /// The name of the field currently being read. Used to tie diagnostics to fields
+    pub current_field: Option<Arc<str>>,
+    /// Resolves `namespace::name` references to other directories. [None] when fields are read outside of a load
+    pub handle_manager: Option<&'a mut igObjectHandleManager>,
+    /// Setting decides if problems with the igx abort the load or get recorded into [IgxLoaderContext::diagnostics]. See [igObjectStreamManager::lenient_loading]
+    pub lenient: bool,
+}
+
+impl<'a> IgxLoaderContext<'a> {
+    pub fn new() -> IgxLoaderContext<'a> {
+        IgxLoaderContext {
+            objects: HashMap::new(),
+            diagnostics: Vec::new(),
+            current_object: None,
+            current_field: None,
+            handle_manager: None,
+            lenient: false,
+        }
+    }
+
+    /// Same as [IgxLoaderContext::new], resolving references to other directories through `handle_manager`
+    pub fn with_handle_manager(handle_manager: &'a mut igObjectHandleManager) -> IgxLoaderContext<'a> {
+        IgxLoaderContext {
+            handle_manager: Some(handle_manager),
+            ..IgxLoaderContext::new()
+        }
+    }
+
+    /// Records a problem that doesn't stop the igx from loading against the current object and field.
+    pub fn report(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        warn!("{}", message);
+        self.diagnostics.push(igLoadDiagnostic {
+            kind,
+            object_offset: None,
+            object: self.current_object.clone(),
+            field: self.current_field.clone(),
+            message,
+        });
+    }
+
+    /// Used when the igx contains something we can't understand. Aborts the load unless [IgxLoaderContext::lenient] is set, where the problem is recorded instead and the caller is expected to carry on.
+    pub fn report_failure(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        if !self.lenient {
+            error!("{}", message);
+            panic!("Alchemy Error! Check the logs.")
+        }
+
+        self.report(kind, message);
+    }
+}
+
+impl Default for IgxLoaderContext<'_> {
+    fn default() -> Self {
+        IgxLoaderContext::new()
+    }
+}
+
+/// An object read from an igx before it is instantiated
+struct IgxObjectNode {
+    id: u32,
+    _type: String,
+    /// (name, raw inner xml) of every field
+    fields: Vec<(Arc<str>, Vec<u8>)>,
+}
+
+impl igObjectLoader for igIGXObjectLoader {
+    fn can_read(&self, file_name: &str) -> bool {
+        file_name.ends_with(".igx")
+    }
+
+    fn can_read_data(&self, data: &[u8]) -> bool {
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        let data = &data[data.iter().position(|byte| !byte.is_ascii_whitespace()).unwrap_or(data.len())..];
+        data.starts_with(b"<?xml") || data.starts_with(b"<igx")
+    }
+
+    fn get_name(&self) -> &'static str {
+        "Alchemy XML"
+    }
+
+    fn get_type(&self) -> &'static str {
+        "Alchemy"
+    }
+
+    fn read_file(
+        &self,
+        ig_file_context: &igFileContext,
+        ig_registry: &igRegistry,
+        ig_object_stream_manager: &mut igObjectStreamManager,
+        ig_ext_ref_system: &mut igExternalReferenceSystem,
+        ig_object_handle_manager: &mut igObjectHandleManager,
+        ig_metadata_manager: &mut igMetadataManager,
+        dir: &mut igObjectDirectory,
+        file_path: &str,
+    ) {
+        let fd = ig_file_context.open(ig_registry, file_path, 0);
+        let lenient = ig_object_stream_manager.lenient_loading;
+        let Some(handle) = fd._handle else {
+            report_unreadable(dir, lenient, format!("Failed to load igx {}. File could not be read.", file_path));
+            return;
+        };
+
+        let document = match parse_document(handle.get_ref()) {
+            Ok(document) => document,
+            Err(e) => {
+                report_unreadable(dir, lenient, format!("Failed to load igx {}. {}", file_path, e));
+                return;
+            }
+        };
+
+        for (name, path) in document.dependencies {
+            if let Ok(dependency) = ig_object_stream_manager.load_with_namespace(
+                ig_file_context,
+                ig_registry,
+                ig_metadata_manager,
+                ig_ext_ref_system,
+                ig_object_handle_manager,
+                path.clone(),
+                igName::new(name),
+            ) {
+                dir.dependencies.push(dependency)
+            } else {
+                error!("Failed to find dependency {}", path);
+                dir.missing_dependencies.push(path);
+            }
+        }
+
+        let mut ctx = IgxLoaderContext::with_handle_manager(ig_object_handle_manager);
+        ctx.lenient = lenient;
+        for node in &document.objects {
+            let object = if ig_metadata_manager.contains_meta(&node._type) {
+                let meta = ig_metadata_manager.get_or_create_meta(&node._type).unwrap();
+                let object = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false);
+                object.map_err(|e| format!("Failed to instantiate {} (id={}) in {}: {}", node._type, node.id, file_path, e))
+            } else {
+                Err(format!("{} (id={}) in {} is not a type in the loaded metadata", node._type, node.id, file_path))
+            };
+
+            match object {
+                Ok(object) => {
+                    ctx.objects.insert(node.id, object);
+                }
+                Err(message) => {
+                    // References to the object resolve to the placeholder, which stops them from being saved
+                    let placeholder: igObject = Arc::new(RwLock::new(igNull));
+                    ctx.current_object = Some(placeholder.clone());
+                    ctx.report_failure(igLoadDiagnosticKind::InstantiationFailed, message);
+                    ctx.current_object = None;
+                    ctx.objects.insert(node.id, placeholder);
+                }
+            }
+        }
+
+        for node in &document.objects {
+            let object = ctx.objects[&node.id].clone();
+            if object.read().unwrap().as_any().is::<igNull>() {
+                // Failed to instantiate, already reported. There are no fields to set
+                continue;
+            }
+            debug!("igObject(id={}, type={}) fields are being set", node.id, node._type);
+            ig_metadata_manager.read_igx_fields(ig_object_stream_manager, &mut ctx, object, &node.fields);
+        }
+
+        dir.all_objects = document.objects.iter().map(|node| ctx.objects[&node.id].clone()).collect();
+
+        let object_list = ctx.objects.get(&document.root).map(|root| root.clone().cast_to());
+        match object_list {
+            Some(Ok(object_list)) => dir.object_list = object_list,
+            // The directory keeps its empty igObjectList
+            Some(Err(_)) => ctx.report_failure(
+                igLoadDiagnosticKind::MalformedFile,
+                format!("Failed to load igx {}. The root object {} is not an igObjectList", file_path, document.root),
+            ),
+            None => ctx.report_failure(
+                igLoadDiagnosticKind::UnresolvedReference,
+                format!("Failed to load igx {}. The root object {} does not exist", file_path, document.root),
+            ),
+        }
+        dir.diagnostics.append(&mut ctx.diagnostics);
+
+        if let Some(names) = document.names.and_then(|id| ctx.objects.get(&id)) {
+            let name_list: Result<Arc<RwLock<igNameList>>, _> = names.clone().cast_to();
+            if let Ok(name_list) = name_list {
+                dir.use_name_list = true;
+                dir.name_list = name_list;
+            }
+        }
+    }
+}
+
+struct IgxDocument {
+    root: u32,
+    names: Option<u32>,
+    /// (name, path) of every dependency
+    dependencies: Vec<(String, String)>,
+    objects: Vec<IgxObjectNode>,
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+fn required_id(e: &BytesStart, name: &[u8]) -> Result<u32, String> {
+    attribute(e, name)
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| format!("<{}> is missing a valid {} attribute", String::from_utf8_lossy(e.name().as_ref()), String::from_utf8_lossy(name)))
+}
+
+fn parse_document(data: &[u8]) -> Result<IgxDocument, String> {
+    let mut reader = Reader::from_reader(data);
+    reader.config_mut().trim_text(true);
+
+    let mut document = IgxDocument {
+        root: 0,
+        names: None,
+        dependencies: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    loop {
+        match reader.read_event() {
+            Err(e) => return Err(format!("at position {}: {:?}", reader.error_position(), e)),
+            Ok(Event::Eof) => break,
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.name().as_ref() == b"igx" => {
+                let version = attribute(&e, b"version").and_then(|version| version.parse::<u32>().ok()).unwrap_or(0);
+                if version != IGX_VERSION {
+                    return Err(format!("igx version {} is not supported, only version {} is", version, IGX_VERSION));
+                }
+                document.root = attribute(&e, b"root").and_then(|id| id.parse().ok()).unwrap_or(0);
+                document.names = attribute(&e, b"names").and_then(|id| id.parse().ok());
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"dependency" => {
+                let name = attribute(&e, b"name").ok_or("<dependency> is missing a name")?;
+                let path = attribute(&e, b"path").ok_or("<dependency> is missing a path")?;
+                document.dependencies.push((name, path));
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"object" => {
+                document.objects.push(IgxObjectNode {
+                    id: required_id(&e, b"id")?,
+                    _type: attribute(&e, b"type").ok_or("<object> is missing a type")?,
+                    fields: Vec::new(),
+                });
+            }
+            Ok(Event::Start(e)) if e.name().as_ref() == b"object" => {
+                document.objects.push(IgxObjectNode {
+                    id: required_id(&e, b"id")?,
+                    _type: attribute(&e, b"type").ok_or("<object> is missing a type")?,
+                    fields: Vec::new(),
+                });
+            }
+            Ok(Event::Start(e)) if e.name().as_ref() == b"field" => {
+                let name = attribute(&e, b"name").ok_or("<field> is missing a name")?;
+                let span = reader.read_to_end(e.name()).map_err(|e| e.to_string())?;
+                let object = document.objects.last_mut().ok_or("<field> found outside of an <object>")?;
+                object.fields.push((Arc::from(name), data[span.start as usize..span.end as usize].to_vec()));
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"field" => {
+                let name = attribute(&e, b"name").ok_or("<field> is missing a name")?;
+                let object = document.objects.last_mut().ok_or("<field> found outside of an <object>")?;
+                object.fields.push((Arc::from(name), Vec::new()));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(document)
+}
+
+/// Reads the remaining contents of `handle` as text. Returns [None] when the value is [IGX_NULL]
+pub fn igx_read_text(handle: &Cursor<Vec<u8>>) -> Option<String> {
+    let raw = String::from_utf8_lossy(&handle.get_ref()[handle.position() as usize..]).to_string();
+    // Checked before unescaping, since text spelling out the null element is escaped
+    if raw.trim() == IGX_NULL {
+        return None;
+    }
+    Some(unescape(&raw).map(|text| text.to_string()).unwrap_or(raw))
+}
+
+/// Reads the remaining contents of `handle` as the hex bytes raw values are written as. Returns [None] when the value is [IGX_NULL], and the text as the error when it isn't valid hex
+pub fn igx_read_hex(handle: &Cursor<Vec<u8>>) -> Option<Result<Vec<u8>, String>> {
+    let text = igx_read_text(handle)?;
+    let trimmed = text.trim();
+    let bytes = (0..trimmed.len())
+        .step_by(2)
+        .map(|i| trimmed.get(i..i + 2).and_then(|byte| u8::from_str_radix(byte, 16).ok()))
+        .collect::<Option<Vec<u8>>>();
+    Some(bytes.ok_or(text))
+}
+
+/// Reads the remaining contents of `handle` as a list of `<element>` tags. Each entry holds the raw inner xml of one element so it can be handed to the inner metafield
+pub fn igx_read_elements(handle: &Cursor<Vec<u8>>) -> Vec<Cursor<Vec<u8>>> {
+    igx_read_children(handle)
+        .into_iter()
+        .filter(|(name, _)| name == "element")
+        .map(|(_, contents)| contents)
+        .collect()
+}
+
+/// Reads the remaining contents of `handle` as named child tags, returning the raw inner xml of each one keyed by tag name. Used for struct-like values
+pub fn igx_read_members(handle: &Cursor<Vec<u8>>) -> HashMap<String, Cursor<Vec<u8>>> {
+    igx_read_children(handle).into_iter().collect()
+}
+
+fn igx_read_children(handle: &Cursor<Vec<u8>>) -> Vec<(String, Cursor<Vec<u8>>)> {
+    let data = &handle.get_ref()[handle.position() as usize..];
+    let mut reader = Reader::from_reader(data);
+    let mut children = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match reader.read_to_end(e.name()) {
+                    Ok(span) => children.push((name, Cursor::new(data[span.start as usize..span.end as usize].to_vec()))),
+                    Err(e) => {
+                        error!("Failed to read igx value: {}", e);
+                        break;
+                    }
+                }
+            }
+            Ok(Event::Empty(e)) => {
+                children.push((String::from_utf8_lossy(e.name().as_ref()).to_string(), Cursor::new(Vec::new())));
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                error!("Failed to read igx value: {}", e);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    children
+}
+
+/// Reads the remaining contents of `handle` and parses it as `T`. Returns [None] when the value is [IGX_NULL] or isn't a valid `T`, the latter being reported to `ctx`
+pub fn igx_parse<T: FromStr>(handle: &Cursor<Vec<u8>>, ctx: &mut IgxLoaderContext) -> Option<T> {
+    let text = igx_read_text(handle)?;
+    let value = text.trim().parse::<T>().ok();
+    if value.is_none() {
+        ctx.report(
+            igLoadDiagnosticKind::FieldDecodeFailed,
+            format!("Failed to parse igx value \"{}\" as {}", text, std::any::type_name::<T>()),
+        );
+    }
+    value
+}
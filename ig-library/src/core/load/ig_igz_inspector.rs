@@ -0,0 +1,349 @@
+use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_file_context::igFileContext;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_fs::Endian::{Big, Little};
+use crate::core::ig_objects::igThumbnail;
+use crate::core::ig_registry::igRegistry;
+use crate::core::load::ig_igz_loader::{
+    igIGZLoader, read_aligned_string, unpack_compressed_ints, Fixup, IgzLoaderContext,
+    IGZ_BIG_ENDIAN_MAGIC, IGZ_LITTLE_ENDIAN_MAGIC,
+};
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::util::byteorder_fixes::{read_ptr, read_string, read_struct_array_u8, read_u32, read_u64};
+use serde::Serialize;
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// The raw structure of an igz, read without involving the object system. Serializable so two files can be dumped to JSON and diffed.
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZInspection {
+    pub header: igIGZHeader,
+    pub sections: Vec<igIGZSection>,
+    pub fixups: Vec<igIGZFixup>,
+    /// Every object in the file, ordered by offset
+    pub objects: Vec<igIGZObjectInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZHeader {
+    pub big_endian: bool,
+    pub version: u32,
+    pub meta_object_version: u32,
+    pub platform: IG_CORE_PLATFORM,
+    pub fixup_count: u32,
+    pub section_count: u32,
+}
+
+/// A memory pool section described by the igz header.
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZSection {
+    pub pool_name: String,
+    pub offset: u32,
+    pub length: u32,
+    pub alignment: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZFixup {
+    /// The four character name for igz versions 7 and above. Older versions use a numeric id which is formatted as hex
+    pub magic: String,
+    /// The name of the fixup as ig-library knows it. [None] when the fixup isn't understood
+    pub kind: Option<String>,
+    /// Absolute position of the fixup header in the file
+    pub offset: u32,
+    pub count: u32,
+    pub length: u32,
+    pub start: u32,
+    pub contents: igIGZFixupContents,
+}
+
+/// The decoded contents of a fixup
+#[derive(Debug, Clone, Serialize)]
+pub enum igIGZFixupContents {
+    /// (name, path) of every dependency
+    Dependencies(Vec<(String, String)>),
+    /// Strings from TMET (vtable names) and TSTR
+    Strings(Vec<String>),
+    /// (name hash, namespace hash) of every EXID reference
+    ExternalsById(Vec<(u32, u32)>),
+    /// Every EXNM reference
+    ExternalsByName(Vec<igIGZNamedExternal>),
+    /// (size, offset) of every thumbnail. Offsets are deserialized
+    Thumbnails(Vec<(u64, u64)>),
+    /// Unpacked runtime offsets
+    Offsets(Vec<u64>),
+    /// Serialized offset of the igNameList
+    NamedList(u64),
+    /// On disk size of every type in TMET
+    MetadataSizes(Vec<u32>),
+    /// Fixups ig-library doesn't understand are left as raw bytes
+    Raw(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZNamedExternal {
+    pub namespace: String,
+    pub name: String,
+    /// True when the reference is to a handle rather than directly to an object
+    pub is_handle: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct igIGZObjectInfo {
+    /// The serialized offset as stored in RVTB
+    pub serialized_offset: u64,
+    /// The position of the object in the file
+    pub offset: u64,
+    pub pool: String,
+    /// The name of the type from TMET. Empty if the vtable index is out of range
+    pub type_name: String,
+}
+
+/// Opens the igz at `file_path` and dumps its structure. See [inspect_igz_bytes]
+pub fn inspect_igz(
+    ig_file_context: &igFileContext,
+    ig_registry: &igRegistry,
+    imm: &igMetadataManager,
+    file_path: &str,
+) -> Result<igIGZInspection, String> {
+    let fd = ig_file_context.open(ig_registry, file_path, 0);
+    match fd._handle {
+        Some(handle) => inspect_igz_bytes(handle.into_inner(), imm),
+        None => Err(format!("Failed to inspect igz {}. File could not be read.", file_path)),
+    }
+}
+
+/// Dumps the structure of an igz held in memory. Nothing is instantiated and no dependencies are loaded, so this works on files the loader can't handle. Malformed files return an error instead of panicking.
+pub fn inspect_igz_bytes(data: Vec<u8>, imm: &igMetadataManager) -> Result<igIGZInspection, String> {
+    let file_length = data.len() as u64;
+    let mut handle = Cursor::new(data);
+    let io_err = |e: std::io::Error| e.to_string();
+
+    let endian = match read_u32(&mut handle, Little).map_err(io_err)? {
+        IGZ_BIG_ENDIAN_MAGIC => Big,
+        IGZ_LITTLE_ENDIAN_MAGIC => Little,
+        magic => return Err(format!("Not an igz. Magic value was wrong. Got: {:#X}", magic)),
+    };
+
+    let version = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+    let meta_object_version = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+    let platform_index = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+    let platform = imm
+        .try_get_enum::<IG_CORE_PLATFORM>(platform_index as usize)
+        .ok_or_else(|| format!("Unknown platform {}", platform_index))?;
+    let fixup_count = if version >= 0x07 {
+        read_u32(&mut handle, endian.clone()).map_err(io_err)?
+    } else {
+        0
+    };
+
+    let mut ctx = IgzLoaderContext::new(version, meta_object_version, platform.clone(), fixup_count, false, true);
+    igIGZLoader::parse_sections(&mut handle, endian.clone(), &mut ctx)?;
+
+    let mut fixups = Vec::new();
+    let mut vtbl_names = Vec::new();
+    let mut vtables = Vec::new();
+    let mut bytes_processed: u32 = if ctx.version > 0x06 { 0 } else { 0x1C };
+    for i in 0..ctx.fixup_count {
+        let fixup_start = ctx
+            .fixup_offset
+            .checked_add(bytes_processed)
+            .ok_or_else(|| format!("Fixup {} starts past the end of the file", i))?;
+        handle.set_position(fixup_start as u64);
+
+        let (magic, fixup) = if ctx.version > 0x06 {
+            let magic = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+            (String::from_utf8_lossy(&magic.to_le_bytes()).to_string(), Fixup::try_from(magic))
+        } else {
+            let magic = read_u32(&mut handle, endian.clone()).map_err(io_err)? as u8;
+            let _padding = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+            let _padding = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+            (format!("{:#04X}", magic), Fixup::try_from(magic))
+        };
+        let count = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+        let length = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+        let start = read_u32(&mut handle, endian.clone()).map_err(io_err)?;
+        if length == 0 || start > length || fixup_start as u64 + length as u64 > file_length {
+            return Err(format!(
+                "Fixup {} ({}) at {:#X} is malformed. Length {:#X}, data starts at {:#X}",
+                i, magic, fixup_start, length, start
+            ));
+        }
+        handle.set_position(fixup_start as u64 + start as u64);
+
+        let contents = match &fixup {
+            Ok(fixup) => decode_fixup(fixup, &mut handle, endian.clone(), &mut ctx, count, length - start)
+                .map_err(|e| format!("Failed to read fixup {} ({}) at {:#X}: {}", i, magic, fixup_start, e))?,
+            Err(_) => igIGZFixupContents::Raw(
+                read_struct_array_u8(&mut handle, endian.clone(), (length - start) as usize).map_err(io_err)?,
+            ),
+        };
+
+        match (&fixup, &contents) {
+            (Ok(Fixup::T_METADATA), igIGZFixupContents::Strings(names)) => vtbl_names = names.clone(),
+            (Ok(Fixup::RUNTIME_V_TABLES), igIGZFixupContents::Offsets(offsets)) => vtables = offsets.clone(),
+            _ => {}
+        }
+
+        fixups.push(igIGZFixup {
+            magic,
+            kind: fixup.ok().map(|fixup| format!("{:?}", fixup)),
+            offset: fixup_start,
+            count,
+            length,
+            start,
+            contents,
+        });
+        bytes_processed += length;
+    }
+
+    let mut objects = Vec::new();
+    for serialized_offset in vtables {
+        let offset = ctx
+            .deserialize_offset(serialized_offset)
+            .ok_or_else(|| format!("Object at {:#X} is in a section that doesn't exist", serialized_offset))?;
+        handle.set_position(offset);
+        let index = read_ptr(&mut handle, platform.clone(), endian.clone())
+            .map_err(|e| format!("Failed to read the object at {:#X}: {}", offset, e))?;
+        objects.push(igIGZObjectInfo {
+            serialized_offset,
+            offset,
+            pool: format!("{:?}", ctx.get_pool_from_serialized_offset(serialized_offset)),
+            type_name: vtbl_names.get(index as usize).cloned().unwrap_or_default(),
+        });
+    }
+    objects.sort_by_key(|object| object.offset);
+
+    Ok(igIGZInspection {
+        header: igIGZHeader {
+            big_endian: matches!(endian, Big),
+            version,
+            meta_object_version,
+            platform,
+            fixup_count: ctx.fixup_count,
+            section_count: ctx.section_count,
+        },
+        sections: ctx.sections,
+        fixups,
+        objects,
+    })
+}
+
+/// Reads every thumbnail out of an igz held in memory without loading it. Much cheaper than loading the file when only a preview is needed
+pub fn read_igz_thumbnails(data: &[u8], imm: &igMetadataManager) -> Result<Vec<igThumbnail>, String> {
+    let inspection = inspect_igz_bytes(data.to_vec(), imm)?;
+
+    let mut thumbnails = Vec::new();
+    for fixup in inspection.fixups {
+        let igIGZFixupContents::Thumbnails(entries) = fixup.contents else {
+            continue;
+        };
+        for (size, offset) in entries {
+            let thumbnail = offset
+                .checked_add(size)
+                .and_then(|end| data.get(offset as usize..end as usize))
+                .ok_or_else(|| format!("Thumbnail at {:#X} with size {:#X} is outside of the file", offset, size))?;
+            thumbnails.push(igThumbnail {
+                offset,
+                data: Arc::from(thumbnail),
+            });
+        }
+    }
+    Ok(thumbnails)
+}
+
+fn decode_fixup(
+    fixup: &Fixup,
+    handle: &mut Cursor<Vec<u8>>,
+    endian: Endian,
+    ctx: &mut IgzLoaderContext,
+    count: u32,
+    data_length: u32,
+) -> Result<igIGZFixupContents, String> {
+    let io_err = |e: std::io::Error| e.to_string();
+
+    let contents = match fixup {
+        Fixup::T_DEPENDENCIES => {
+            let mut dependencies = Vec::new();
+            for _i in 0..count {
+                let name = read_string(handle).map_err(io_err)?;
+                let path = read_string(handle).map_err(io_err)?;
+                dependencies.push((name, path));
+            }
+            igIGZFixupContents::Dependencies(dependencies)
+        }
+        Fixup::T_METADATA | Fixup::T_STRING_LIST => {
+            let mut strings = Vec::new();
+            for _i in 0..count {
+                strings.push(read_aligned_string(handle, ctx.version).map_err(io_err)?);
+            }
+            if let Fixup::T_STRING_LIST = fixup {
+                ctx.string_list = strings.clone();
+            }
+            igIGZFixupContents::Strings(strings)
+        }
+        Fixup::EXTERNAL_DEPENDENCIES_BY_ID => {
+            let mut externals = Vec::new();
+            for _i in 0..count {
+                let name = read_u32(handle, endian.clone()).map_err(io_err)?;
+                let namespace = read_u32(handle, endian.clone()).map_err(io_err)?;
+                externals.push((name, namespace));
+            }
+            igIGZFixupContents::ExternalsById(externals)
+        }
+        Fixup::EXTERNAL_DEPENDENCIES_BY_NAME => {
+            let mut externals = Vec::new();
+            for _i in 0..count {
+                let raw_handle = read_u64(handle, endian.clone()).map_err(io_err)?;
+                let ns_str_index = (raw_handle >> 32) as u32;
+                let name_str_index = raw_handle as u32 & 0x7FFF_FFFF;
+                let lookup = |index: u32| ctx.string_list.get(index as usize).cloned().unwrap_or_default();
+                externals.push(igIGZNamedExternal {
+                    namespace: lookup(ns_str_index & 0x7FFF_FFFF),
+                    name: lookup(name_str_index),
+                    is_handle: (ns_str_index & 0x80000000) != 0,
+                });
+            }
+            igIGZFixupContents::ExternalsByName(externals)
+        }
+        Fixup::THUMBNAIL => {
+            let mut thumbnails = Vec::new();
+            for _i in 0..count {
+                let size = read_ptr(handle, ctx.platform.clone(), endian.clone()).map_err(io_err)?;
+                let raw = read_ptr(handle, ctx.platform.clone(), endian.clone()).map_err(io_err)?;
+                let offset = ctx
+                    .deserialize_offset(raw)
+                    .ok_or_else(|| format!("Thumbnail at {:#X} is in a section that doesn't exist", raw))?;
+                thumbnails.push((size, offset));
+            }
+            igIGZFixupContents::Thumbnails(thumbnails)
+        }
+        Fixup::RUNTIME_V_TABLES | Fixup::RUNTIME_OBJECT_LISTS => {
+            let bytes = read_struct_array_u8(handle, endian, data_length as usize).map_err(io_err)?;
+            igIGZFixupContents::Offsets(unpack_compressed_ints(ctx, &bytes, count, false)?)
+        }
+        Fixup::RUNTIME_OFFSETS
+        | Fixup::RUNTIME_POOL_IDS
+        | Fixup::RUNTIME_STRING_TABLES
+        | Fixup::RUNTIME_STRING_REFERENCES
+        | Fixup::RUNTIME_MEMORY_HANDLES
+        | Fixup::RUNTIME_EXTERNALS
+        | Fixup::RUNTIME_NAMED_EXTERNALS
+        | Fixup::RUNTIME_HANDLES => {
+            let bytes = read_struct_array_u8(handle, endian, data_length as usize).map_err(io_err)?;
+            igIGZFixupContents::Offsets(unpack_compressed_ints(ctx, &bytes, count, true)?)
+        }
+        Fixup::OPTION_NAMED_LIST => {
+            igIGZFixupContents::NamedList(read_u32(handle, endian).map_err(io_err)? as u64)
+        }
+        Fixup::METADATA_SIZES => {
+            let mut sizes = Vec::new();
+            for _i in 0..count {
+                sizes.push(read_u32(handle, endian.clone()).map_err(io_err)?);
+            }
+            igIGZFixupContents::MetadataSizes(sizes)
+        }
+    };
+
+    Ok(contents)
+}
@@ -6,9 +6,11 @@ use crate::core::ig_fs::Endian;
 use crate::core::ig_fs::Endian::{Big, Little};
 use crate::core::ig_handle::{igHandle, igHandleName, igObjectHandleManager};
 use crate::core::ig_memory::igMemoryPool;
-use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
+use crate::core::ig_dependency_graph::igDependencyReason;
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager, igThumbnail};
 use crate::core::ig_registry::igRegistry;
-use crate::core::load::ig_loader::igObjectLoader;
+use crate::core::load::ig_igz_inspector::igIGZSection;
+use crate::core::load::ig_loader::{report_unreadable, igLoadDiagnostic, igLoadDiagnosticKind, igObjectLoader};
 use crate::core::meta::ig_metadata_manager::{__internalObjectBase, igMetaObject};
 use crate::core::meta::ig_metadata_manager::{igMetaInstantiationError, igMetadataManager};
 use crate::util::byteorder_fixes::{
@@ -16,21 +18,22 @@ use crate::util::byteorder_fixes::{
 };
 use crate::util::ig_hash::{hash, hash_lower};
 use crate::util::ig_name::igName;
-use log::{debug, error, info};
-use std::collections::HashMap;
+use log::{debug, error, warn};
+use std::collections::BTreeMap;
+use std::mem;
 use std::io::Cursor;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 
-const IGZ_LITTLE_ENDIAN_MAGIC: u32 = u32::from_be_bytes([b'I', b'G', b'Z', 0x01]);
-const IGZ_BIG_ENDIAN_MAGIC: u32 = u32::from_le_bytes([b'I', b'G', b'Z', 0x01]);
+pub(crate) const IGZ_LITTLE_ENDIAN_MAGIC: u32 = u32::from_be_bytes([b'I', b'G', b'Z', 0x01]);
+pub(crate) const IGZ_BIG_ENDIAN_MAGIC: u32 = u32::from_le_bytes([b'I', b'G', b'Z', 0x01]);
 
 pub struct igIGZObjectLoader;
 
 #[derive(Debug)]
-enum Fixup {
+pub(crate) enum Fixup {
     T_METADATA,
     T_DEPENDENCIES,
     T_STRING_LIST,
@@ -71,6 +74,7 @@ impl Fixup {
         match self {
             Fixup::T_DEPENDENCIES => {
                 if ctx.read_dependencies {
+                    let mut dependencies = Vec::with_capacity(count as usize);
                     for _i in 0..count {
                         let name = read_string(handle).unwrap();
                         let path = read_string(handle).unwrap();
@@ -78,56 +82,59 @@ impl Fixup {
                             // Unsure on why cauldron does this
                             continue;
                         }
-                        let name = igName::new(name);
-                        if let Ok(dependency) = ig_object_stream_manager.load_with_namespace(
+                        dependencies.push((name, path));
+                    }
+
+                    let results = if ig_object_stream_manager.parallel_loading {
+                        let paths = dependencies.iter().map(|(name, path)| (path.clone(), igName::new(name.clone()))).collect();
+                        ig_object_stream_manager.load_many_with_namespace(
                             ig_file_context,
                             ig_registry,
                             imm,
                             ig_ext_ref_system,
                             ig_handle_manager,
-                            path.clone(),
-                            name,
-                        ) {
+                            paths,
+                        )
+                    } else {
+                        dependencies
+                            .iter()
+                            .map(|(name, path)| {
+                                ig_object_stream_manager.load_with_namespace(
+                                    ig_file_context,
+                                    ig_registry,
+                                    imm,
+                                    ig_ext_ref_system,
+                                    ig_handle_manager,
+                                    path.clone(),
+                                    igName::new(name.clone()),
+                                )
+                            })
+                            .collect()
+                    };
+
+                    for ((_, path), result) in dependencies.into_iter().zip(results) {
+                        if let Ok(dependency) = result {
                             dir.dependencies.push(dependency)
                         } else {
                             error!("Failed to find dependency {}", path);
+                            dir.missing_dependencies.push(path);
                         }
                     }
                 }
             }
             Fixup::T_METADATA => {
                 for _i in 0..count {
-                    let base_pos = handle.position();
-                    let vtbl_name = read_string(handle).unwrap();
+                    let vtbl_name = read_aligned_string(handle, ctx.version).unwrap();
                     ctx.vtbl_list
                         .push(imm.get_or_create_meta(&vtbl_name).unwrap());
                     debug!("IGZ contains igObject of type {}", vtbl_name);
-
-                    let bits: i32 = if ctx.version > 7 { 2 } else { 1 };
-                    handle
-                        .seek(SeekFrom::Start(
-                            base_pos
-                                + bits as u64
-                                + ((handle.position() - base_pos - 1) & ((-bits) as u32) as u64),
-                        ))
-                        .unwrap();
                 }
             }
 
             Fixup::T_STRING_LIST => {
                 for _i in 0..count {
-                    let base_pos = handle.position();
-                    let data = read_string(handle).unwrap();
+                    let data = read_aligned_string(handle, ctx.version).unwrap();
                     ctx.string_list.push(data);
-
-                    let bits: i32 = if ctx.version > 7 { 2 } else { 1 };
-                    handle
-                        .seek(SeekFrom::Start(
-                            base_pos
-                                + bits as u64
-                                + ((handle.position() - base_pos - 1) & ((-bits) as u32) as u64),
-                        ))
-                        .unwrap();
                 }
             }
             Fixup::EXTERNAL_DEPENDENCIES_BY_ID => {
@@ -136,84 +143,44 @@ impl Fixup {
                         igName::from_hash(read_u32(handle, endian.clone()).unwrap()), // name
                         igName::from_hash(read_u32(handle, endian.clone()).unwrap()), // namespace
                     );
+                    dir.external_namespaces.push((igDependencyReason::ExternalById, dependency_name.namespace.clone()));
+                    ig_object_stream_manager.finish_deferred_namespace(imm, ig_handle_manager, dependency_name.namespace.hash);
 
-                    let mut obj = None;
-                    if let Some(list) = ig_object_stream_manager
-                        .name_to_directory_lookup
-                        .get(&dependency_name.namespace.hash)
-                    {
-                        for dependant_dir in list.iter() {
-                            if let Ok(dependent_dir) = dependant_dir.try_read() {
-                                if dependent_dir.use_name_list {
-                                    let name_list = dependent_dir.name_list.read().unwrap();
-                                    for i in 0..name_list.len() {
-                                        let name = &name_list.query()[i];
-                                        if name.hash == dependency_name.namespace.hash {
-                                            obj = Some(
-                                                dependent_dir.object_list.read().unwrap().query()
-                                                    [i]
-                                                    .clone(),
-                                            );
-                                            break;
-                                        }
-                                    }
-
-                                    if obj.is_some() {
-                                        break;
-                                    }
-                                }
-                            } else {
-                                error!("Failed to get read lock on igObjectDirectory");
-                                panic!("Alchemy Error! Check the logs.")
-                            }
-                        }
-                    } else {
-                        error!("EXID Fixup load failed: Failed to find namespace {:#01}, referenced in {}", dependency_name.namespace.hash, dir.path);
-                        ctx.external_list.push(ig_handle_manager.lookup_handle_name(&dependency_name))
-                    }
+                    // The handle is shared with the handle manager, so it points at the object as soon as the directory holding it is added.
+                    // Files loaded alongside this one may not be added yet, so unresolved handles are reported once the fields are read
+                    ctx.external_list.push(ig_handle_manager.lookup_handle_name(&dependency_name))
                 }
             }
             Fixup::EXTERNAL_DEPENDENCIES_BY_NAME => {
-                for _i in 0..count {
+                for i in 0..count {
                     let raw_handle = read_u64(handle, endian.clone()).unwrap();
+                    // The top bit of the namespace index marks entries that are handles rather than objects
+                    let is_handle = (raw_handle >> 32) as u32 & 0x8000_0000 != 0;
                     let ns_str_index = (raw_handle >> 32) as u32 & 0x7FFF_FFFF;
                     let name_str_index = raw_handle as u32 & 0x7FFF_FFFF;
-                    let dependency_handle_name = igHandleName::new(
-                        igName::new(ctx.string_list[name_str_index as usize].clone()),
-                        igName::new(ctx.string_list[ns_str_index as usize].clone()),
-                    );
-
-                    let mut obj = None;
-                    if let Some(dependant_dir) = dir.dependencies.iter().find(|dependency| {
-                        let guard = dependency.read().unwrap();
-                        guard.name.hash == dependency_handle_name.namespace.hash
-                    }) {
-                        if let Ok(dependent_dir) = dependant_dir.try_read() {
-                            if dependent_dir.use_name_list {
-                                let name_list = dependent_dir.name_list.read().unwrap();
-                                for i in 0..name_list.len() {
-                                    let name = &name_list.query()[i];
-                                    if name.hash == dependency_handle_name.namespace.hash {
-                                        obj = Some(
-                                            dependent_dir.object_list.read().unwrap().query()[i]
-                                                .clone(),
-                                        );
-                                        break;
-                                    }
-                                }
-
-                                if obj.is_some() {
-                                    break;
-                                }
-                            }
+                    let (Some(namespace), Some(name)) = (
+                        ctx.string_list.get(ns_str_index as usize).cloned(),
+                        ctx.string_list.get(name_str_index as usize).cloned(),
+                    ) else {
+                        ctx.report_failure(
+                            igLoadDiagnosticKind::UnresolvedReference,
+                            format!("EXNM Fixup entry {} points past the end of the string list", i),
+                        );
+                        // Keep the lists lined up with the indices fields use
+                        if is_handle {
+                            let placeholder = igHandleName::new(igName::new(String::new()), igName::new(String::new()));
+                            ctx.named_handle_list.push(igHandle::from_handle_name(&placeholder));
                         } else {
-                            error!("Failed to get read lock on igObjectDirectory");
-                            panic!("Alchemy Error! Check the logs.")
+                            ctx.named_external_list.push(Arc::new(RwLock::new(igNull)));
                         }
-                    }
-
-                    let dependency_handle = igHandle::from_handle_name(&dependency_handle_name);
-                    if (ns_str_index & 0x80000000) != 0 {
+                        continue;
+                    };
+                    let dependency_handle_name = igHandleName::new(igName::new(name.clone()), igName::new(namespace.clone()));
+                    dir.external_namespaces.push((igDependencyReason::ExternalByName, dependency_handle_name.namespace.clone()));
+                    ig_object_stream_manager.finish_deferred_namespace(imm, ig_handle_manager, dependency_handle_name.namespace.hash);
+
+                    let dependency_handle = ig_handle_manager.lookup_handle_name(&dependency_handle_name);
+                    if is_handle {
                         ctx.named_handle_list.push(dependency_handle.clone());
                     } else {
                         let mut ref_ctx = igReferenceResolverContext {
@@ -229,6 +196,12 @@ impl Fixup {
                         if reference.is_none() {
                             reference = dependency_handle.write().unwrap().get_object_alias(ig_object_stream_manager)
                         }
+                        if reference.is_none() {
+                            ctx.report(
+                                igLoadDiagnosticKind::UnresolvedReference,
+                                format!("EXNM Fixup failed to resolve {}::{}", namespace, name),
+                            );
+                        }
                         ctx.named_external_list.push(reference.unwrap_or(Arc::new(RwLock::new(igNull))));
                     }
                 }
@@ -237,78 +210,123 @@ impl Fixup {
                 for _i in 0..count {
                     let size = read_ptr(handle, ctx.platform.clone(), endian.clone()).unwrap();
                     let raw = read_ptr(handle, ctx.platform.clone(), endian.clone()).unwrap();
-                    ctx.thumbnails.push((size, raw))
+                    let Some(offset) = ctx.deserialize_offset(raw) else {
+                        ctx.report(igLoadDiagnosticKind::UnreadableThumbnail, format!("Thumbnail offset {:#X} points into a section that doesn't exist", raw));
+                        continue;
+                    };
+
+                    let return_position = handle.position();
+                    handle.set_position(offset);
+                    match read_struct_array_u8(handle, endian.clone(), size as usize) {
+                        Ok(data) => ctx.thumbnails.push(igThumbnail {
+                            offset,
+                            data: Arc::from(data),
+                        }),
+                        Err(e) => ctx.report(igLoadDiagnosticKind::UnreadableThumbnail, format!("Failed to read thumbnail at {:#X}: {}", offset, e)),
+                    }
+                    handle.set_position(return_position);
                 }
             }
             Fixup::RUNTIME_V_TABLES => {
-                let vec = read_struct_array_u8(handle, endian.clone(), (length - start) as usize).unwrap();
-                ctx.runtime_fields.vtables = unpack_compressed_ints(ctx, &vec, count, false);
+                ctx.runtime_fields.vtables = read_compressed_ints(ctx, handle, endian.clone(), length - start, count, false);
                 instantiate_and_append_objects(ctx, handle, endian.clone());
             }
             Fixup::RUNTIME_OBJECT_LISTS => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.object_lists = unpack_compressed_ints(ctx, &vec, count, false);
-                let ig_object_list_idx = ctx.runtime_fields.object_lists[0];
-                dir.object_list = ctx.offset_object_list[&ig_object_list_idx]
-                    .clone()
-                    .cast_to()
-                    .unwrap()
+                ctx.runtime_fields.object_lists = read_compressed_ints(ctx, handle, endian, length - start, count, false);
+                let root = ctx.runtime_fields.object_lists.first().and_then(|idx| ctx.offset_object_list.get(idx)).cloned();
+                match root.map(|root| root.cast_to()) {
+                    Some(Ok(object_list)) => dir.object_list = object_list,
+                    // The root failed to instantiate or is missing. The directory keeps its empty igObjectList
+                    _ => ctx.report_failure(igLoadDiagnosticKind::MalformedFile, "ROOT Fixup does not point at an igObjectList".to_string()),
+                }
             }
             Fixup::RUNTIME_OFFSETS => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.offsets = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.offsets = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_POOL_IDS => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.pool_ids = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.pool_ids = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_STRING_TABLES => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.string_tables = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.string_tables = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_STRING_REFERENCES => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.string_references =
-                    unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.string_references = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_MEMORY_HANDLES => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.memory_handles = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.memory_handles = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_EXTERNALS => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.externals = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.externals = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_NAMED_EXTERNALS => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.named_externals = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.named_externals = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::RUNTIME_HANDLES => {
-                let vec = read_struct_array_u8(handle, endian, (length - start) as usize).unwrap();
-                ctx.runtime_fields.handles = unpack_compressed_ints(ctx, &vec, count, true);
+                ctx.runtime_fields.handles = read_compressed_ints(ctx, handle, endian, length - start, count, true);
             }
             Fixup::OPTION_NAMED_LIST => {
                 dir.use_name_list = true;
-                let name_list_idx = read_u32(handle, endian).unwrap() as u64;
-                // pull out the generic object (trait-object)
-                let generic_obj: Arc<RwLock<dyn __internalObjectBase>> =
-                    ctx.offset_object_list[&name_list_idx].clone();
-
-                // assign into your field
-                dir.name_list = generic_obj.cast_to().unwrap();
+                let name_list = read_u32(handle, endian)
+                    .ok()
+                    .and_then(|idx| ctx.offset_object_list.get(&(idx as u64)))
+                    .cloned();
+                match name_list.map(|name_list| name_list.cast_to()) {
+                    Some(Ok(name_list)) => dir.name_list = name_list,
+                    // The directory keeps its empty igNameList
+                    _ => ctx.report_failure(igLoadDiagnosticKind::MalformedFile, "ONAM Fixup does not point at an igNameList".to_string()),
+                }
             },
-            Fixup::METADATA_SIZES => {}
+            Fixup::METADATA_SIZES => {
+                for i in 0..count as usize {
+                    let Ok(disk_size) = read_u32(handle, endian.clone()) else {
+                        ctx.report(igLoadDiagnosticKind::LayoutMismatch, format!("METADATA_SIZES ends after {} of its {} entries", i, count));
+                        break;
+                    };
+                    let Some(meta) = ctx.vtbl_list.get(i) else {
+                        warn!("METADATA_SIZES has more entries than there are types in {}", dir.path);
+                        break;
+                    };
+
+                    let meta = meta.read().unwrap();
+                    let computed_size = meta.calculate_size();
+                    if disk_size != computed_size {
+                        let message = format!(
+                            "{} is {:#X} bytes on disk but the loaded metadata describes {:#X} bytes. metafields.xml/metaobjects.xml are likely wrong for {:?}",
+                            meta.name, disk_size, computed_size, ctx.platform
+                        );
+                        drop(meta);
+                        warn!("{}", message);
+                        ctx.report(igLoadDiagnosticKind::LayoutMismatch, message);
+                    }
+                }
+            }
         }
     }
 }
 
+/// Reads a string from the TMET and TSTR fixups. Newer igz versions pad every string to a 2 byte boundary
+pub(crate) fn read_aligned_string(handle: &mut Cursor<Vec<u8>>, version: u32) -> std::io::Result<String> {
+    let base_pos = handle.position();
+    let data = read_string(handle)?;
+
+    let bits: i32 = if version > 7 { 2 } else { 1 };
+    handle
+        .seek(SeekFrom::Start(
+            base_pos
+                + bits as u64
+                + ((handle.position() - base_pos - 1) & ((-bits) as u32) as u64),
+        ))?;
+    Ok(data)
+}
+
 fn instantiate_and_append_objects(
     ctx: &mut IgzLoaderContext,
     handle: &mut Cursor<Vec<u8>>,
     endian: Endian,
 ) {
-    let vtables = ctx.runtime_fields.vtables.clone();
-    
+    let mut vtables = ctx.runtime_fields.vtables.clone();
+    vtables.sort_unstable();
+
     for vtable in vtables {
         let obj = instantiate_object(ctx, handle, endian.clone(), &vtable);
         ctx.offset_object_list
@@ -322,29 +340,28 @@ fn instantiate_object(
     endian: Endian,
     offset: &u64,
 ) -> Arc<RwLock<dyn __internalObjectBase>> {
-    let deserialize_offset = ctx.deserialize_offset(*offset);
-
-    handle.seek(SeekFrom::Start(deserialize_offset)).unwrap();
-    let index = read_ptr(handle, ctx.platform.clone(), endian).unwrap();
-    let return_value = ctx.vtbl_list[index as usize]
-        .clone()
-        .read()
-        .unwrap()
-        .raw_instantiate(get_mem_pool_from_serialized_offset(ctx, *offset), false);
-
-    match return_value {
-        Ok(value) => {
-            value
+    let meta = ctx.deserialize_offset(*offset).and_then(|position| {
+        handle.set_position(position);
+        let index = read_ptr(handle, ctx.platform.clone(), endian).ok()?;
+        ctx.vtbl_list.get(index as usize).cloned()
+    });
+
+    let message = match meta.map(|meta| meta.read().unwrap().raw_instantiate(get_mem_pool_from_serialized_offset(ctx, *offset), false)) {
+        Some(Ok(value)) => return value,
+        Some(Err(igMetaInstantiationError::TypeMismatchError(expected_type))) => {
+            format!("Instantiation when loading IGZ failed the real type returned was {}", expected_type)
         }
-        Err(igMetaInstantiationError::TypeMismatchError(expected_type)) => {
-            error!(
-                "Instantiation when loading IGZ failed the real type returned was {}",
-                expected_type
-            );
-            panic!("Alchemy Error! Check the logs.")
+        Some(Err(igMetaInstantiationError::SetupDefaultFieldsError)) => {
+            "Instantiation when loading IGZ failed to set up the default fields".to_string()
         }
-        Err(igMetaInstantiationError::SetupDefaultFieldsError) => todo!(),
-    }
+        None => format!("The object at {:#X} does not point at a type in the TMET Fixup", offset),
+    };
+
+    let placeholder: igObject = Arc::new(RwLock::new(igNull));
+    ctx.current_object = Some((*offset, placeholder.clone()));
+    ctx.report_failure(igLoadDiagnosticKind::InstantiationFailed, message);
+    ctx.current_object = None;
+    placeholder
 }
 
 fn get_mem_pool_from_serialized_offset(ctx: &IgzLoaderContext, offset: u64) -> igMemoryPool {
@@ -355,54 +372,59 @@ fn get_mem_pool_from_serialized_offset(ctx: &IgzLoaderContext, offset: u64) -> i
     }
 }
 
-fn unpack_compressed_ints(
+/// Reads and unpacks the compressed ints runtime fixups are stored as. When they can't be unpacked the failure is reported and no ints are returned
+fn read_compressed_ints(
     ctx: &mut IgzLoaderContext,
-    bytes: &[u8],
+    handle: &mut Cursor<Vec<u8>>,
+    endian: Endian,
+    length: u32,
     count: u32,
     deserialize: bool,
 ) -> Vec<u64> {
-    let mut output = Vec::with_capacity(count as usize);
+    let values = read_struct_array_u8(handle, endian, length as usize)
+        .map_err(|e| format!("Failed to read compressed ints: {}", e))
+        .and_then(|bytes| unpack_compressed_ints(ctx, &bytes, count, deserialize));
+    match values {
+        Ok(values) => values,
+        Err(e) => {
+            ctx.report_failure(igLoadDiagnosticKind::MalformedFile, e);
+            vec![]
+        }
+    }
+}
+
+/// Unpacks `count` ints from `bytes`. Each int is stored as a delta from the previous one, 3 bits to a nibble with the 4th bit set when another nibble follows
+pub(crate) fn unpack_compressed_ints(
+    ctx: &IgzLoaderContext,
+    bytes: &[u8],
+    count: u32,
+    deserialize: bool,
+) -> Result<Vec<u64>, String> {
+    let mut output = Vec::new();
     let mut prev_int: u32 = 0;
-    let mut shift_move_or_mask = false;
-    let mut idx: usize = 0;
+    // low nibble first
+    let mut nibbles = bytes.iter().flat_map(|byte| [(byte & 0xF) as u32, (byte >> 4) as u32]);
+    let truncated = || format!("Compressed ints end before all {} of them were read", count);
 
     for _ in 0..count {
-        let mut current = if !shift_move_or_mask {
-            let b = bytes[idx];
-            shift_move_or_mask = true;
-            (b & 0xF) as u32
-        } else {
-            let b = bytes[idx];
-            shift_move_or_mask = false;
-            idx += 1;
-            (b >> 4) as u32
-        };
-
+        let mut current = nibbles.next().ok_or_else(truncated)?;
         let mut unpacked = current & 0x7;
         let mut shift_amount = 3;
 
         while (current & 0x8) != 0 {
-            current = if !shift_move_or_mask {
-                let b = bytes[idx];
-                shift_move_or_mask = true;
-                (b & 0xF) as u32
-            } else {
-                let b = bytes[idx];
-                shift_move_or_mask = false;
-                idx += 1;
-                (b >> 4) as u32
-            };
+            current = nibbles.next().ok_or_else(truncated)?;
             unpacked |= (current & 0x7) << (shift_amount & 0x1F);
             shift_amount += 3;
         }
 
         // delta‑and‑scale, plus version‑dependent bias
         prev_int = prev_int
-            .wrapping_add(unpacked * 4)
+            .wrapping_add(unpacked.wrapping_mul(4))
             .wrapping_add(if ctx.version < 9 { 4 } else { 0 });
 
         let final_val = if deserialize {
             ctx.deserialize_offset(prev_int as u64)
+                .ok_or_else(|| format!("Offset {:#X} points into a section that doesn't exist", prev_int))?
         } else {
             prev_int as u64
         };
@@ -410,7 +432,7 @@ fn unpack_compressed_ints(
         output.push(final_val);
     }
 
-    output
+    Ok(output)
 }
 
 /// TryFrom<u32>'s implementation here has a conversion table for names of fixups from any igz versioned 7 or above.
@@ -436,6 +458,7 @@ impl TryFrom<u32> for Fixup {
             b"RNEX" => Ok(Fixup::RUNTIME_NAMED_EXTERNALS),
             b"RHND" => Ok(Fixup::RUNTIME_HANDLES),
             b"ONAM" => Ok(Fixup::OPTION_NAMED_LIST),
+            b"MTSZ" => Ok(Fixup::METADATA_SIZES),
             _ => Err(()),
         }
     }
@@ -451,21 +474,11 @@ impl TryFrom<u8> for Fixup {
             0x01 => Ok(Fixup::T_STRING_LIST),
             0x02 => Ok(Fixup::EXTERNAL_DEPENDENCIES_BY_ID),
             0x03 => Ok(Fixup::EXTERNAL_DEPENDENCIES_BY_NAME),
-            0x04 => todo!("Unknown Fixup 0x04"),
             0x05 => Ok(Fixup::RUNTIME_V_TABLES),
-            0x06 => todo!("Unknown Fixup 0x06"),
-            0x07 => todo!("Unknown Fixup 0x07"),
-            0x08 => todo!("Unknown Fixup 0x08"),
-            0x09 => todo!("Unknown Fixup 0x09"),
             0x0A => Ok(Fixup::THUMBNAIL),
-            0x0B => todo!("Unknown Fixup 0x0B"),
             0x0C => Ok(Fixup::METADATA_SIZES),
-            0x0D => todo!("Unknown Fixup 0x0D"),
             0x0E => Ok(Fixup::RUNTIME_STRING_REFERENCES),
-            0x0F => todo!("Unknown Fixup 0x0F"),
-            0x10 => todo!("Unknown Fixup 0x10"),
-            0x11 => todo!("Unknown Fixup 0x11"),
-            0x12 => todo!("Unknown Fixup 0x12"),
+            // 0x04, 0x06 to 0x09, 0x0B, 0x0D and 0x0F to 0x12 exist but aren't understood yet
             _ => Err(()),
         }
     }
@@ -476,6 +489,12 @@ impl igObjectLoader for igIGZObjectLoader {
         file_name.ends_with(".igz") || file_name.ends_with(".bld") || file_name.ends_with(".lng")
     }
 
+    fn can_read_data(&self, data: &[u8]) -> bool {
+        data.get(0..4)
+            .map(|magic| u32::from_le_bytes(magic.try_into().unwrap()))
+            .is_some_and(|magic| magic == IGZ_LITTLE_ENDIAN_MAGIC || magic == IGZ_BIG_ENDIAN_MAGIC)
+    }
+
     fn get_name(&self) -> &'static str {
         "Alchemy Platform"
     }
@@ -495,6 +514,7 @@ impl igObjectLoader for igIGZObjectLoader {
         dir: &mut igObjectDirectory,
         file_path: &str,
     ) {
+        let lenient = ig_object_stream_manager.lenient_loading;
         igIGZLoader::read(
             ig_file_context,
             ig_registry,
@@ -505,6 +525,7 @@ impl igObjectLoader for igIGZObjectLoader {
             dir,
             file_path,
             true,
+            lenient,
         );
     }
 }
@@ -565,27 +586,81 @@ pub struct IgzLoaderContext {
     /// A list of all strings present inside the igz
     pub string_list: Vec<String>,
     /// A list of all external ig object dependencies needed that don't get names
-    pub external_list: Vec<igHandle>,
+    pub external_list: Vec<Arc<RwLock<igHandle>>>,
     /// A list of all external ig object dependencies needed
     pub named_external_list: Vec<igObject>,
     /// A list of all handles used from dependencies
     pub named_handle_list: Vec<Arc<RwLock<igHandle>>>,
     /// Setting decides if the dependency fixup will try load dependencies
     pub read_dependencies: bool,
-    /// A list of all thumbnails present in the igz.
-    pub thumbnails: Vec<(u64, u64)>,
+    /// A list of all thumbnails present in the igz. Moved into [igObjectDirectory::thumbnails] once loading is finished
+    pub thumbnails: Vec<igThumbnail>,
     /// All runtime lists stored from fixups. Used for various parts of the runtime
     pub runtime_fields: RuntimeFields,
-    /// TODO: comment
-    pub offset_object_list: HashMap<u64, igObject>,
+    /// Every object in the igz keyed by its serialized offset. Ordered so objects are always processed in the order they appear in the file
+    pub offset_object_list: BTreeMap<u64, igObject>,
+    /// Setting decides if problems with the igz abort the load or get recorded into [IgzLoaderContext::diagnostics]. See [igObjectStreamManager::lenient_loading]
+    pub lenient: bool,
+    /// All problems found while loading. Moved into [igObjectDirectory::diagnostics] once loading is finished
+    pub diagnostics: Vec<igLoadDiagnostic>,
+    /// The serialized offset and instance of the object having its fields read. Used to tie diagnostics to objects
+    pub current_object: Option<(u64, igObject)>,
+    /// Every section described by the igz header, in the order they are stored. The first one holds the fixups
+    pub sections: Vec<igIGZSection>,
+    /// The name of the field currently being read. Used to tie diagnostics to fields
+    pub current_field: Option<Arc<str>>,
 }
 
 impl IgzLoaderContext {
-    pub fn deserialize_offset(&self, offset: u64) -> u64 {
+    pub(crate) fn new(
+        version: u32,
+        meta_object_version: u32,
+        platform: IG_CORE_PLATFORM,
+        fixup_count: u32,
+        read_dependencies: bool,
+        lenient: bool,
+    ) -> IgzLoaderContext {
+        IgzLoaderContext {
+            version,
+            meta_object_version,
+            platform,
+            section_count: 0,
+            fixup_count,
+            loaded_pools: Default::default(),
+            loaded_pointers: Default::default(),
+            fixup_offset: 0,
+            vtbl_list: vec![],
+            string_list: vec![],
+            external_list: vec![],
+            named_external_list: vec![],
+            named_handle_list: vec![],
+            read_dependencies,
+            thumbnails: vec![],
+            runtime_fields: RuntimeFields::new(),
+            offset_object_list: BTreeMap::new(),
+            lenient,
+            diagnostics: vec![],
+            current_object: None,
+            sections: vec![],
+            current_field: None,
+        }
+    }
+
+    /// Converts a serialized offset into a position in the file. Returns [None] when the offset points into a section that doesn't exist
+    pub fn deserialize_offset(&self, offset: u64) -> Option<u64> {
+        if self.version <= 6 {
+            Some(*self.loaded_pointers.get((offset >> 0x18) as usize)? as u64 + (offset & 0x00FFFFFF))
+        } else {
+            Some(*self.loaded_pointers.get((offset >> 0x1B) as usize)? as u64 + (offset & 0x07FFFFFF))
+        }
+    }
+
+    /// Returns where a serialized offset points relative to the start of its section
+    pub fn get_offset_in_section(&self, offset: u64) -> u64 {
         if self.version <= 6 {
-            self.loaded_pointers[(offset >> 0x18) as usize] as u64 + (offset & 0x00FFFFFF)
+            offset & 0x00FFFFFF
         } else {
-            self.loaded_pointers[(offset >> 0x1B) as usize] as u64 + (offset & 0x07FFFFFF)
+            offset & 0x07FFFFFF
         }
     }
 
@@ -596,6 +671,33 @@ impl IgzLoaderContext {
             self.loaded_pools[(offset >> 0x1B) as usize]
         }
     }
+
+    /// Records a problem that doesn't stop the igz from loading against the current object and field.
+    pub fn report(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        let (object_offset, object) = match &self.current_object {
+            Some((offset, object)) => (Some(*offset), Some(object.clone())),
+            None => (None, None),
+        };
+
+        self.diagnostics.push(igLoadDiagnostic {
+            kind,
+            object_offset,
+            object,
+            field: self.current_field.clone(),
+            message,
+        });
+    }
+
+    /// Used when the igz contains something we can't understand. Aborts the load unless [IgzLoaderContext::lenient] is set, where the problem is recorded instead and the caller is expected to carry on.
+    pub fn report_failure(&mut self, kind: igLoadDiagnosticKind, message: String) {
+        if !self.lenient {
+            error!("{}", message);
+            panic!("Alchemy Error! Check the logs.")
+        }
+
+        warn!("{}", message);
+        self.report(kind, message);
+    }
 }
 
 impl igIGZLoader {
@@ -609,8 +711,13 @@ impl igIGZLoader {
         dir: &mut igObjectDirectory,
         file_path: &str,
         read_dependencies: bool,
+        lenient: bool,
     ) {
-        let mut fd = ig_file_context.open(ig_registry, file_path, 0);
+        let defer_fields = ig_object_stream_manager.take_defer_current_load();
+        let mut fd = match ig_object_stream_manager.take_prefetched(file_path) {
+            Some(fd) => fd,
+            None => ig_file_context.open(ig_registry, file_path, 0),
+        };
         if let Some(mut handle) = fd._handle {
             // if file_path == "packages/generated/shaders/shaders_cafe_pkg.igz" {
             //     use std::io::Read;
@@ -627,11 +734,8 @@ impl igIGZLoader {
                 IGZ_BIG_ENDIAN_MAGIC => fd.endianness = Big,
                 IGZ_LITTLE_ENDIAN_MAGIC => fd.endianness = Little,
                 _ => {
-                    error!(
-                        "Failed to load igz {}. Magic value was wrong. Got: {}",
-                        file_path, magic
-                    );
-                    panic!("Alchemy Error! Check the logs.")
+                    report_unreadable(dir, lenient, format!("Failed to load igz {}. Magic value was wrong. Got: {}", file_path, magic));
+                    return;
                 }
             }
 
@@ -648,27 +752,19 @@ impl igIGZLoader {
                 fixup_count = read_u32(&mut handle, fd.endianness.clone()).unwrap();
             }
 
-            let mut shared_state = IgzLoaderContext {
+            let mut shared_state = IgzLoaderContext::new(
                 version,
                 meta_object_version,
                 platform,
-                section_count: 0,
                 fixup_count,
-                loaded_pools: Default::default(),
-                loaded_pointers: Default::default(),
-                fixup_offset: 0,
-                vtbl_list: vec![],
-                string_list: vec![],
-                external_list: vec![],
-                named_external_list: vec![],
-                named_handle_list: vec![],
                 read_dependencies,
-                thumbnails: vec![],
-                runtime_fields: RuntimeFields::new(),
-                offset_object_list: HashMap::new(),
-            };
+                lenient,
+            );
 
-            igIGZLoader::parse_sections(&mut handle, fd.endianness.clone(), &mut shared_state);
+            if let Err(e) = igIGZLoader::parse_sections(&mut handle, fd.endianness.clone(), &mut shared_state) {
+                report_unreadable(dir, lenient, format!("Failed to load igz {}. {}", file_path, e));
+                return;
+            }
             if shared_state.version > 0x06 {
                 igIGZLoader::process_modern_fixup_sections(
                     &mut handle,
@@ -697,55 +793,68 @@ impl igIGZLoader {
                 );
             }
 
-            igIGZLoader::read_objects(imm, ig_object_stream_manager, &mut handle, fd.endianness.clone(), &mut shared_state);
+            let fields = igIGZDeferredFields {
+                handle,
+                endian: fd.endianness.clone(),
+                ctx: shared_state,
+            };
+            if defer_fields {
+                ig_object_stream_manager.deferred_fields.insert(hash_lower(file_path), fields);
+            } else {
+                fields.read(imm, ig_object_stream_manager, dir);
+            }
         } else {
-            error!("Failed to load igz {}. File could not be read.", file_path);
-            panic!("Alchemy Error! Check the logs.")
+            report_unreadable(dir, lenient, format!("Failed to load igz {}. File could not be read.", file_path));
         }
     }
 
-    fn parse_sections(
+    /// Reads the section descriptors from the header into `shared_state`
+    pub(crate) fn parse_sections(
         handle: &mut Cursor<Vec<u8>>,
         endian: Endian,
         shared_state: &mut IgzLoaderContext,
-    ) {
-        for i in 0..0x20 {
-            handle.seek(SeekFrom::Start(get_chunk_descriptor_start(shared_state.version) + 0x10 * i)).unwrap();
-            let mem_pool_name_ptr = read_u32(handle, endian.clone()).unwrap();
-            let offset;
+    ) -> Result<(), String> {
+        let io_err = |e: std::io::Error| format!("Failed to read the section descriptors: {}", e);
+        let version = shared_state.version;
+        let (Some(descriptor_start), Some(attribute_location)) = (get_chunk_descriptor_start(version), get_attribute_location(version)) else {
+            return Err(format!("Unsupported igz version {}", version));
+        };
 
-            offset = read_u32(handle, endian.clone()).unwrap();
-            let _length = read_u32(handle, endian.clone()).unwrap();
-            let _alignment = read_u32(handle, endian.clone()).unwrap();
+        for i in 0..0x20 {
+            handle.set_position(descriptor_start + 0x10 * i);
+            let mem_pool_name_ptr = read_u32(handle, endian.clone()).map_err(io_err)?;
+            let offset = read_u32(handle, endian.clone()).map_err(io_err)?;
+            let length = read_u32(handle, endian.clone()).map_err(io_err)?;
+            let alignment = read_u32(handle, endian.clone()).map_err(io_err)?;
 
             if offset == 0 {
                 shared_state.section_count = i as u32;
                 break;
             }
 
-            if i == 0 && shared_state.version <= 0x06 {
+            if i == 0 && version <= 0x06 {
                 // Giants and under don't store the fixup count in the header but in this weird second IGZ header area. TODO: find out if this applies to version 0x07(SSF)
                 handle.set_position((offset + 0x10) as u64); // We don't care about storing the old position because the next code will just seek again anyway
-                shared_state.fixup_count = read_u32(handle, endian.clone()).unwrap()
+                shared_state.fixup_count = read_u32(handle, endian.clone()).map_err(io_err)?
             }
 
-            handle
-                .seek(SeekFrom::Start((get_attribute_location(shared_state.version) + mem_pool_name_ptr) as u64))
-                .unwrap();
-            let memory_pool_name = read_string(handle).unwrap();
-            if memory_pool_name.is_empty() {
-                println!("cooked");
-            }
+            handle.set_position(attribute_location as u64 + mem_pool_name_ptr as u64);
+            let memory_pool_name = read_string(handle).map_err(io_err)?;
+            shared_state.sections.push(igIGZSection {
+                pool_name: memory_pool_name.clone(),
+                offset,
+                length,
+                alignment,
+            });
             if i > 0 {
-                shared_state.loaded_pools[(i - 1) as usize] =
-                    igMemoryPool::from_str(&memory_pool_name).unwrap_or_else(|_| {
-                        panic!("Invalid memory pool name '{}'", memory_pool_name)
-                    });
+                shared_state.loaded_pools[(i - 1) as usize] = igMemoryPool::from_str(&memory_pool_name)
+                    .map_err(|_| format!("Section {} has the invalid memory pool name '{}'", i, memory_pool_name))?;
                 shared_state.loaded_pointers[(i - 1) as usize] = offset;
             } else {
                 shared_state.fixup_offset = offset;
             }
         }
+        Ok(())
     }
 
     /// This function handles the older style of fixup used in IGZ versions 0x06 (Giants/SSA Wii) and below. It is handled quite differently so in the end its just better do keep it separate.
@@ -861,35 +970,65 @@ impl igIGZLoader {
             bytes_processed += length;
         }
     }
+}
 
-    fn read_objects(
-        imm: &mut igMetadataManager,
-        object_stream_manager: &igObjectStreamManager,
-        handle: &mut Cursor<Vec<u8>>,
-        endian: Endian,
-        ctx: &mut IgzLoaderContext,
-    ) {
+/// An igz whose objects have been instantiated and whose fixups have been processed, but whose fields haven't been read. Reading fields only needs shared access to the managers, so [igObjectStreamManager::load_many] does it on worker threads
+pub(crate) struct igIGZDeferredFields {
+    handle: Cursor<Vec<u8>>,
+    endian: Endian,
+    ctx: IgzLoaderContext,
+}
+
+impl igIGZDeferredFields {
+    /// Reads the fields of every object and hands the objects, diagnostics and thumbnails to `dir`
+    pub(crate) fn read(mut self, imm: &igMetadataManager, object_stream_manager: &igObjectStreamManager, dir: &mut igObjectDirectory) {
+        let ctx = &mut self.ctx;
         let offset_object_list = ctx.offset_object_list.clone();
-        
+
+        for handle in ctx.external_list.clone() {
+            if handle.write().unwrap().get_object_alias(object_stream_manager).is_none() {
+                let message = format!("EXID Fixup failed to resolve {}, referenced in {}", handle.read().unwrap(), dir.path);
+                ctx.report(igLoadDiagnosticKind::UnresolvedReference, message);
+            }
+        }
+
         for (offset, object) in offset_object_list {
-            handle.set_position(ctx.deserialize_offset(offset));
-            imm.read_igz_fields(object_stream_manager, handle, endian.clone(), ctx, object.clone())
+            if object.read().unwrap().as_any().is::<igNull>() {
+                // Failed to instantiate, already reported. There are no fields to set
+                continue;
+            }
+
+            // Already checked when the object was instantiated
+            let position = ctx.deserialize_offset(offset).unwrap();
+            self.handle.set_position(position);
+            let index = read_ptr(&mut self.handle, ctx.platform.clone(), self.endian.clone()).unwrap();
+            let meta = ctx.vtbl_list[index as usize].clone();
+            self.handle.set_position(position);
+            ctx.current_object = Some((offset, object.clone()));
+            imm.read_igz_fields(object_stream_manager, &mut self.handle, self.endian.clone(), ctx, object, meta)
         }
+        ctx.current_object = None;
+
+        dir.diagnostics.append(&mut ctx.diagnostics);
+        dir.thumbnails.append(&mut ctx.thumbnails);
+        dir.all_objects = mem::take(&mut ctx.offset_object_list).into_values().collect();
     }
 }
 
-fn get_chunk_descriptor_start(version: u32) -> u64 {
+/// Where the section descriptors start. [None] for unsupported versions
+fn get_chunk_descriptor_start(version: u32) -> Option<u64> {
     match version {
-        0x05 | 0x06 => 0xC,
-        0x07 | 0x08 | 0x09 => 0x14,
-        _ => todo!("Unsupported igz version {}", version)
+        0x05 | 0x06 => Some(0xC),
+        0x07..=0x09 => Some(0x14),
+        _ => None
     }
 }
 
-fn get_attribute_location(version: u32) -> u32 {
+/// Where the memory pool names of sections start. [None] for unsupported versions
+fn get_attribute_location(version: u32) -> Option<u32> {
     match version {
-        0x05 | 0x06 | 0x07 => 0x56C,
-        0x08 | 0x09 => 0x224, // FIXME: this could be wrong...
-        _ => todo!("Unsupported igz version {}", version)
+        0x05..=0x07 => Some(0x56C),
+        0x08 | 0x09 => Some(0x224), // FIXME: this could be wrong...
+        _ => None
     }
 }
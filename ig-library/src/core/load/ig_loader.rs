@@ -1,16 +1,15 @@
 use crate::core::ig_file_context::igFileContext;
-use crate::core::ig_objects::{igObjectDirectory, igObjectStreamManager};
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
 use crate::core::ig_registry::igRegistry;
+use crate::core::load::ig_igb_loader::igIGBObjectLoader;
+use crate::core::load::ig_igx_loader::igIGXObjectLoader;
 use crate::core::load::ig_igz_loader::igIGZObjectLoader;
 use crate::core::meta::ig_metadata_manager::igMetadataManager;
-use once_cell::sync::Lazy;
+use log::{error, warn};
 use std::sync::{Arc, RwLock};
 use crate::core::ig_external_ref::igExternalReferenceSystem;
 use crate::core::ig_handle::igObjectHandleManager;
 
-static LOADERS: Lazy<[Arc<RwLock<dyn igObjectLoader>>; 1]> =
-    Lazy::new(|| [Arc::new(RwLock::new(igIGZObjectLoader))]);
-
 /// The shared base between anything that can load an alchemy binary (igz, igx, igb)
 pub trait igObjectLoader: Send + Sync {
     /// Returns true if the loader can load the specified file
@@ -22,6 +21,11 @@ pub trait igObjectLoader: Send + Sync {
     /// The provider of the loader. For the built-in loaders of alchemy, this will usually be "Alchemy"
     fn get_type(&self) -> &'static str;
 
+    /// Returns true if the loader recognises the contents of the file, usually from its magic bytes. `data` is the whole file. Checked before [igObjectLoader::can_read] so files are loaded by what they are instead of what they are called
+    fn can_read_data(&self, _data: &[u8]) -> bool {
+        false
+    }
+
     fn read_file(
         &self,
         ig_file_context: &igFileContext,
@@ -35,14 +39,126 @@ pub trait igObjectLoader: Send + Sync {
     );
 }
 
-pub fn get_loader(file_path: &str) -> Option<Arc<RwLock<dyn igObjectLoader>>> {
-    for loader in LOADERS.iter() {
-        let loader_guard = loader.read().unwrap();
-        if loader_guard.can_read(file_path) {
-            drop(loader_guard);
-            return Some(loader.clone());
+/// Describes what went wrong in an [igLoadDiagnostic]
+#[derive(Debug, Clone, PartialEq)]
+pub enum igLoadDiagnosticKind {
+    /// An object reference could not be resolved. The field was given an igNull placeholder (or left null) instead.
+    UnresolvedReference,
+    /// A field could not be decoded. When loading leniently the field keeps its raw bytes instead.
+    FieldDecodeFailed,
+    /// A field has no metafield implementation and was read as raw bytes by igPlaceholderMetafield. The bytes are written back unchanged, so this doesn't stop the file from being saved.
+    MissingMetaFieldImpl,
+    /// The decoded value was rejected by the object it belongs to.
+    SetFieldFailed,
+    /// The object could not be constructed and was replaced with an igNull placeholder.
+    InstantiationFailed,
+    /// A thumbnail in the THUMBNAIL fixup could not be read. It is missing from [igObjectDirectory::thumbnails], so saving the directory would drop it
+    UnreadableThumbnail,
+    /// The file itself could not be read past this point, for example a bad header or fixup. Nothing after the problem was loaded.
+    MalformedFile,
+    /// The size of a type stored in the igz does not match the size computed from the loaded metadata. Every object of that type is likely read incorrectly.
+    LayoutMismatch,
+    /// The file sets a property field the loaded metadata has no storage field for. The value is dropped.
+    UnmappedProperty,
+}
+
+impl igLoadDiagnosticKind {
+    /// Returns true when saving the object the problem was found in would lose or change data
+    pub fn blocks_saving(&self) -> bool {
+        !matches!(self, igLoadDiagnosticKind::MissingMetaFieldImpl)
+    }
+}
+
+/// A problem found while loading a file that did not stop the file from loading. Diagnostics whose kind [blocks saving](igLoadDiagnosticKind::blocks_saving) mean the object they point to cannot be safely re-saved. These are stored on [igObjectDirectory::diagnostics] so tools can flag those objects.
+#[derive(Clone)]
+pub struct igLoadDiagnostic {
+    pub kind: igLoadDiagnosticKind,
+    /// The serialized offset of the object the problem was found in. [None] when the problem isn't tied to an object (fixups for example)
+    pub object_offset: Option<u64>,
+    /// The object the problem was found in, if there is one
+    pub object: Option<igObject>,
+    /// The name of the field being read when the problem was found, if there is one
+    pub field: Option<Arc<str>>,
+    pub message: String,
+}
+
+/// Used when a file can't be read at all. Aborts the load unless loading leniently, where the directory is left empty and the problem is recorded on it
+pub(crate) fn report_unreadable(dir: &mut igObjectDirectory, lenient: bool, message: String) {
+    if !lenient {
+        error!("{}", message);
+        panic!("Alchemy Error! Check the logs.")
+    }
+
+    warn!("{}", message);
+    dir.diagnostics.push(igLoadDiagnostic {
+        kind: igLoadDiagnosticKind::MalformedFile,
+        object_offset: None,
+        object: None,
+        field: None,
+        message,
+    });
+}
+
+/// Every [igObjectLoader] an [igObjectStreamManager] can load files with. Custom formats can be added at runtime with [igObjectLoaderRegistry::register].
+pub struct igObjectLoaderRegistry {
+    /// (priority, loader) sorted from highest to lowest priority. Loaders with the same priority keep the order they were registered in
+    loaders: Vec<(i32, Arc<RwLock<dyn igObjectLoader>>)>,
+}
+
+impl igObjectLoaderRegistry {
+    /// Creates a registry with the built-in igz, igx and igb loaders registered at priority 0
+    pub fn new() -> igObjectLoaderRegistry {
+        let mut registry = igObjectLoaderRegistry::empty();
+        registry.register(Arc::new(RwLock::new(igIGZObjectLoader)), 0);
+        registry.register(Arc::new(RwLock::new(igIGXObjectLoader)), 0);
+        registry.register(Arc::new(RwLock::new(igIGBObjectLoader)), 0);
+        registry
+    }
+
+    /// Creates a registry with no loaders
+    pub fn empty() -> igObjectLoaderRegistry {
+        igObjectLoaderRegistry { loaders: Vec::new() }
+    }
+
+    /// Adds a loader. Loaders with a higher priority are asked first, so registering above 0 overrides the built-in loaders
+    pub fn register(&mut self, loader: Arc<RwLock<dyn igObjectLoader>>, priority: i32) {
+        let index = self.loaders.partition_point(|(existing, _)| *existing >= priority);
+        self.loaders.insert(index, (priority, loader));
+    }
+
+    /// Removes every loader with the name passed in. Returns true if any were removed
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let count = self.loaders.len();
+        self.loaders.retain(|(_, loader)| loader.read().unwrap().get_name() != name);
+        count != self.loaders.len()
+    }
+
+    /// Every registered loader from highest to lowest priority
+    pub fn loaders(&self) -> impl Iterator<Item = &Arc<RwLock<dyn igObjectLoader>>> {
+        self.loaders.iter().map(|(_, loader)| loader)
+    }
+
+    /// Picks the loader for a file. Loaders that recognise `data` win over loaders that only recognise the file name. Archives (IGA) are never picked as they have to be mounted. `data` may be empty when the file couldn't be read
+    pub fn get_loader(&self, file_path: &str, data: &[u8]) -> Option<Arc<RwLock<dyn igObjectLoader>>> {
+        if data.starts_with(b"IGA\x1A") || data.starts_with(b"\x1AAGI") {
+            warn!("{} is an igArchive, not an igObjectDirectory. Mount it with the igArchiveManager instead", file_path);
+            return None;
+        }
+
+        if !data.is_empty() {
+            if let Some(loader) = self.loaders().find(|loader| loader.read().unwrap().can_read_data(data)) {
+                return Some(loader.clone());
+            }
         }
+
+        self.loaders()
+            .find(|loader| loader.read().unwrap().can_read(file_path))
+            .cloned()
     }
+}
 
-    None
+impl Default for igObjectLoaderRegistry {
+    fn default() -> Self {
+        igObjectLoaderRegistry::new()
+    }
 }
@@ -1,5 +1,7 @@
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
 use crate::core::ig_memory::igMemoryPool;
+use crate::core::ig_objects::igAny;
+use std::sync::{Arc, RwLock};
 
 #[allow(dead_code)] // TODO: i need to look into this more. it seems cauldron's implementation (what this is based on) isn't completely finished and leaves a few things out... Will check in ghidra later hopefully
 pub struct igMemory<T> where T: 'static + Send + Sync {
@@ -49,4 +51,30 @@ where
             alignment_multiple: 0,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Stored in an [igMemory] in place of an element that is null (a null string or object reference for example), so the elements after it keep their index
+#[derive(Debug, Clone, Copy)]
+pub struct igNullElement;
+
+/// Turns the value a metafield read into an element of an [igMemory], using [igNullElement] when it is null
+pub(crate) fn element_or_null(value: Option<igAny>) -> igAny {
+    value.unwrap_or_else(|| Arc::new(RwLock::new(igNullElement)))
+}
+
+/// Turns an element of an [igMemory] back into the value to hand to its metafield. [None] when the element is an [igNullElement]
+pub(crate) fn element_value(element: &igAny) -> Option<igAny> {
+    if element.read().unwrap().is::<igNullElement>() {
+        None
+    } else {
+        Some(element.clone())
+    }
+}
+
+/// A pointer into the memory of the file it was loaded from that isn't an object or memory block the metadata knows about (igRawRefMetaField). Only the location is kept: the pool it was allocated from and where it points inside the section holding that pool. Keeping it relative to the section rather than the file means the reference still holds when the file is saved with its sections laid out differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct igRawRef {
+    /// Offset from the start of the section holding `pool`
+    pub offset: u64,
+    pub pool: igMemoryPool,
+}
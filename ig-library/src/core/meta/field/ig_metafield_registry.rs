@@ -1,20 +1,51 @@
 use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_array_meta_field::igArrayMetaField;
+use crate::core::meta::field::r#impl::ig_compound_meta_field::igCompoundMetaField;
 use crate::core::meta::field::r#impl::ig_placeholder_meta_field::igPlaceholderMetafield;
 use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
-use crate::core::meta::ig_xml_metadata::RawArkMetaObjectField;
+use crate::core::meta::ig_xml_metadata::ArkMetaObjectField;
 use log::debug;
 use std::any::Any;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
 
 /// Used when you need more complex information in the meta enum
 type ComplexMetaFieldFactory = fn(Arc<igMetaFieldInfo>, &igMetadataManager, &igMetafieldRegistry, IG_CORE_PLATFORM) -> Arc<dyn igMetaField>;
 
+/// Identifies a field whose metafield is built from metadata. The address of `ark_info` stands in for the field, it is kept alive by [BuiltMetaField] so it can't be reused
+#[derive(Hash, PartialEq, Eq)]
+struct BuiltMetaFieldKey {
+    _type: Arc<str>,
+    name: Option<Arc<str>>,
+    size: u32,
+    ark_info: usize,
+    platform: IG_CORE_PLATFORM,
+}
+
+impl BuiltMetaFieldKey {
+    fn new(field: &igMetaFieldInfo, platform: IG_CORE_PLATFORM) -> BuiltMetaFieldKey {
+        BuiltMetaFieldKey {
+            _type: field._type.clone(),
+            name: field.name.clone(),
+            size: field.size,
+            ark_info: Arc::as_ptr(&field.ark_info) as usize,
+            platform,
+        }
+    }
+}
+
+struct BuiltMetaField {
+    _ark_info: ArkMetaObjectField,
+    metafield: Arc<dyn igMetaField>,
+}
+
 /// Deals with registering implementations of MetaField and retrieving these later on
 pub struct igMetafieldRegistry {
     basic: HashMap<Arc<str>, Arc<dyn igMetaField>>,
     complex: HashMap<Arc<str>, ComplexMetaFieldFactory>,
+    /// Array and compound metafields walk their whole tree of elements and sub-fields when they are created, so they are only built once per field
+    built: RwLock<HashMap<BuiltMetaFieldKey, BuiltMetaField>>,
 }
 
 impl igMetafieldRegistry {
@@ -22,8 +53,19 @@ impl igMetafieldRegistry {
         Self {
             basic: HashMap::new(),
             complex: HashMap::new(),
+            built: RwLock::new(HashMap::new()),
         }
     }
+
+    /// Keeps a metafield built from metadata so later lookups of the same field share it
+    fn insert_built(&self, key: BuiltMetaFieldKey, field: &igMetaFieldInfo, metafield: Arc<dyn igMetaField>) -> Arc<dyn igMetaField> {
+        let mut built = self.built.write().unwrap();
+        let built = built.entry(key).or_insert(BuiltMetaField {
+            _ark_info: field.ark_info.clone(),
+            metafield,
+        });
+        built.metafield.clone()
+    }
 }
 
 impl igMetafieldRegistry {
@@ -33,6 +75,7 @@ impl igMetafieldRegistry {
         _impl: Arc<dyn igMetaField>,
     ) {
         self.basic.insert(name, _impl);
+        self.built.get_mut().unwrap().clear();
     }
 
     /// Used on metafields that need more specific information to function correctly.
@@ -42,41 +85,45 @@ impl igMetafieldRegistry {
         _impl: ComplexMetaFieldFactory,
     ) {
         self.complex.insert(name, _impl);
+        self.built.get_mut().unwrap().clear();
+    }
+
+    /// The names of every registered metafield implementation, basic and complex
+    pub fn registered_names(&self) -> impl Iterator<Item = &Arc<str>> {
+        self.basic.keys().chain(self.complex.keys())
     }
 
     pub fn get(&self, field: Arc<igMetaFieldInfo>, imm: &igMetadataManager, platform: IG_CORE_PLATFORM) -> Arc<dyn igMetaField> {
         let type_name = &field._type.clone();
 
-        match self.basic.get(type_name) {
-            Some(v) => v.clone(),
-            None => match self.complex.get(type_name) {
-                Some(v) => v(field.clone(), imm, self, platform).clone(),
-                None => {
-                    debug!(
-                        "instantiated a new igPlaceholderMetafield. No implementation for {}",
-                        field._type
-                    );
-                    Arc::new(igPlaceholderMetafield {
-                        size: field.size,
-                        missing_impl_name: field.name.clone().unwrap(),
-                    })
-                }
-            },
+        if let Some(v) = self.basic.get(type_name) {
+            return v.clone();
         }
-    }
-
-    /// Used to get inner types which are known not to need fancy features.
-    pub(crate) fn get_simple(&self, ark_info: &RawArkMetaObjectField) -> Arc<dyn igMetaField> {
-        let name = &ark_info._type.clone();
-        match self.basic.get(name) {
-            Some(v) => v.clone(),
-            None => {
-                debug!("instantiated a new igPlaceholderMetafield. No SIMPLE implementation for {}. (This could be very bad lots of my code would need to be changed to make this work pls tell me it's not a complex type)", name);
-                Arc::new(igPlaceholderMetafield {
-                    size: ark_info.required_alignment.unwrap_or(4),
-                    missing_impl_name: ark_info.name.clone().unwrap_or(Arc::from("unknown??? (possible child metafield from memoryRef or similar)")),
-                })
-            }
+        if let Some(v) = self.complex.get(type_name) {
+            return v(field.clone(), imm, self, platform).clone();
         }
+        let key = BuiltMetaFieldKey::new(&field, platform.clone());
+        if let Some(built) = self.built.read().unwrap().get(&key) {
+            return built.metafield.clone();
+        }
+        // Array forms extend their element metafield, so they have to be checked before compound metafields
+        if let Some(element_type) = imm.array_element_type(type_name) {
+            let metafield = Arc::new(igArrayMetaField::new(field.clone(), element_type, imm, self, platform));
+            return self.insert_built(key, &field, metafield);
+        }
+        // Compound metafields are described by metaobjects.xml instead of being implemented
+        if let Some(fields) = imm.get_compound_fields(type_name, platform.clone()) {
+            let metafield = Arc::new(igCompoundMetaField::new(field.clone(), fields, imm, self, platform));
+            return self.insert_built(key, &field, metafield);
+        }
+
+        debug!(
+            "instantiated a new igPlaceholderMetafield. No implementation for {}",
+            field._type
+        );
+        Arc::new(igPlaceholderMetafield {
+            size: field.size,
+            missing_impl_name: field.name.clone().unwrap_or(field._type.clone()),
+        })
     }
 }
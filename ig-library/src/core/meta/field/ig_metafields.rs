@@ -24,13 +24,13 @@ pub trait igMetaField: Send + Sync {
         endian: Endian,
         ctx: &mut IgzLoaderContext,
     ) -> Option<igAny>;
-    /// Accepts a value of type <T> and will return [Ok] if successful. If an error occurred, the type [IgzSaverError] will be returned hopefully containing useful information for debugging
+    /// Accepts a value of type <T> ([None] when the value is "null") and will return [Ok] if successful. If an error occurred, the type [IgzSaverError] will be returned hopefully containing useful information for debugging
     fn value_into_igz(
         &self,
-        registry: &igMetafieldRegistry,
         metadata_manager: &igMetadataManager,
         object_stream_manager: &igObjectStreamManager,
         handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         endian: Endian,
         ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError>;
@@ -45,13 +45,13 @@ pub trait igMetaField: Send + Sync {
         endian: Endian,
         ctx: &mut IgxLoaderContext,
     ) -> Option<igAny>;
-    /// Accepts a value of type <T> and will return [Ok] if successful. If an error occurred, the type [IgxSaverError] will be returned hopefully containing useful information for debugging
+    /// Accepts a value of type <T> ([None] when the value is "null") and will return [Ok] if successful. If an error occurred, the type [IgxSaverError] will be returned hopefully containing useful information for debugging
     fn value_into_igx(
         &self,
-        registry: &igMetafieldRegistry,
         metadata_manager: &igMetadataManager,
         object_stream_manager: &igObjectStreamManager,
         handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         endian: Endian,
         ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError>;
@@ -66,13 +66,13 @@ pub trait igMetaField: Send + Sync {
         endian: Endian,
         ctx: &mut IgbLoaderContext,
     ) -> Option<igAny>;
-    /// Accepts a value of type <T> and will return [Ok] if successful. If an error occurred, the type [IgbSaverError] will be returned hopefully containing useful information for debugging
+    /// Accepts a value of type <T> ([None] when the value is "null") and will return [Ok] if successful. If an error occurred, the type [IgbSaverError] will be returned hopefully containing useful information for debugging
     fn value_into_igb(
         &self,
-        registry: &igMetafieldRegistry,
         metadata_manager: &igMetadataManager,
         object_stream_manager: &igObjectStreamManager,
         handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         endian: Endian,
         ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError>;
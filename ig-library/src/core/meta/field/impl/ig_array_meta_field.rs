@@ -0,0 +1,214 @@
+use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_elements, igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_newline, igx_write_null, igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use log::warn;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+/// A fixed amount of elements stored inline, one after the other (igFloatArrayMetaField, igObjectRefArrayMetaField, ColorDataArrayMetaField...). Every array form wraps the metafield of its element, the amount of elements comes from the `num` of the field.
+///
+/// The value is a `Vec<Option<igAny>>` always holding `num` elements, an element is [None] when it is null
+pub(crate) struct igArrayMetaField {
+    element: Arc<dyn igMetaField>,
+    element_size: u32,
+    /// Distance between the start of two elements. The element size rounded up to the element alignment
+    stride: u32,
+    num: u32,
+}
+
+impl igArrayMetaField {
+    pub fn new(field: Arc<igMetaFieldInfo>, element_type: Arc<str>, metadata_manager: &igMetadataManager, registry: &igMetafieldRegistry, platform: IG_CORE_PLATFORM) -> igArrayMetaField {
+        let mut element_info = field.ark_info.read().unwrap().clone();
+        let num = element_info.num.take().unwrap_or_else(|| {
+            warn!("The array {} has no element count. Assuming it holds a single element", field._type);
+            1
+        });
+        element_info._type = element_type.clone();
+
+        let element_size = metadata_manager.calculate_size(&element_info, platform.clone());
+        let alignment = element_info
+            .required_alignment
+            .or_else(|| metadata_manager.platform_alignment(&element_type))
+            .unwrap_or(1)
+            .max(1);
+        let element = igMetaFieldInfo {
+            _type: element_type,
+            name: field.name.clone(),
+            size: element_size,
+            alignment,
+            offset: 0,
+            ark_info: Arc::new(RwLock::new(element_info)),
+        };
+
+        igArrayMetaField {
+            element: registry.get(Arc::new(element), metadata_manager, platform),
+            element_size,
+            stride: element_size.div_ceil(alignment) * alignment,
+            num,
+        }
+    }
+
+    /// Returns the elements to write. A null array is written as `num` null elements. Fails with the type the value should have when it isn't a `Vec<Option<igAny>>` of `num` elements
+    fn elements(&self, value: Option<igAny>) -> Result<Vec<Option<igAny>>, Arc<str>> {
+        let Some(value) = value else {
+            return Ok(vec![None; self.num as usize]);
+        };
+        let guard = value.read().unwrap();
+        match guard.downcast_ref::<Vec<Option<igAny>>>() {
+            Some(elements) if elements.len() == self.num as usize => Ok(elements.clone()),
+            _ => Err(Arc::from(format!("Vec<Option<igAny>> of {} elements", self.num))),
+        }
+    }
+}
+
+impl igMetaField for igArrayMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Vec<Option<igAny>>>()
+    }
+
+    fn value_from_igz(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let start = handle.position();
+        let mut elements = Vec::with_capacity(self.num as usize);
+        for i in 0..self.num as u64 {
+            handle.set_position(start + self.stride as u64 * i);
+            elements.push(self.element.value_from_igz(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx));
+        }
+        handle.set_position(start + self.stride as u64 * self.num as u64);
+
+        Some(Arc::new(RwLock::new(elements)))
+    }
+
+    fn value_into_igz(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let elements = self.elements(value).map_err(IgzSaverError::InvalidValueType)?;
+        let start = handle.position();
+        for (i, element) in elements.into_iter().enumerate() {
+            handle.set_position(start + self.stride as u64 * i as u64);
+            self.element.value_into_igz(metadata_manager, object_stream_manager, handle, element, endian.clone(), ctx)?;
+        }
+
+        // Padding between and after the elements is zeroed
+        let end = start + self.stride as u64 * self.num as u64;
+        if (handle.get_ref().len() as u64) < end {
+            handle.get_mut().resize(end as usize, 0);
+        }
+        handle.set_position(end);
+        Ok(())
+    }
+
+    fn value_from_igx(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        igx_read_text(handle)?;
+
+        let mut elements = Vec::with_capacity(self.num as usize);
+        for mut element in igx_read_elements(handle) {
+            elements.push(self.element.value_from_igx(registry, metadata_manager, object_stream_manager, &mut element, endian.clone(), ctx));
+        }
+        // The field is left null and the directory can't be saved, the same as a value that doesn't parse
+        if elements.len() != self.num as usize {
+            ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("igx has {} elements for an array of {}", elements.len(), self.num));
+            return None;
+        }
+
+        Some(Arc::new(RwLock::new(elements)))
+    }
+
+    fn value_into_igx(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        if value.is_none() {
+            igx_write_null(handle);
+            return Ok(());
+        }
+        let elements = self.elements(value).map_err(IgxSaverError::InvalidValueType)?;
+
+        let indent = ctx.indent;
+        for element in elements {
+            ctx.indent = indent + 1;
+            igx_write_newline(handle, ctx);
+            igx_write_raw(handle, "<element>");
+            ctx.indent = indent + 2;
+            self.element.value_into_igx(metadata_manager, object_stream_manager, handle, element, endian.clone(), ctx)?;
+            igx_write_raw(handle, "</element>");
+        }
+        ctx.indent = indent;
+        igx_write_newline(handle, ctx);
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        // The igb only knows the size of the whole array, elements are read with the size from the metadata
+        let field_size = ctx.field_size;
+        ctx.field_size = self.element_size;
+        let mut elements = Vec::with_capacity(self.num as usize);
+        for _ in 0..self.num {
+            elements.push(self.element.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx));
+        }
+        ctx.field_size = field_size;
+
+        Some(Arc::new(RwLock::new(elements)))
+    }
+
+    fn value_into_igb(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        let elements = self.elements(value).map_err(IgbSaverError::InvalidValueType)?;
+        for element in elements {
+            self.element.value_into_igb(metadata_manager, object_stream_manager, handle, element, endian.clone(), ctx)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::IgxLoaderContext;
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_primitive_meta_field::igPrimitiveMetaField;
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use log::error;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// A value packed into some of the bits of another field (the storage field). The bits are decoded through the inner metafield (bool, enum, int...) so the value looks the same as if it had its own field.
+///
+/// Writing only replaces this field's bits, leaving the bits of every sibling bit field untouched. This means the storage field has to be written before its bit fields, otherwise it would overwrite them
+pub(crate) struct igBitFieldMetaField {
+    shift: u8,
+    bits: u8,
+    /// Reads and writes the whole storage field
+    storage: igPrimitiveMetaField<u64>,
+    /// Decodes the bits once they are extracted from the storage field
+    inner: Arc<dyn igMetaField>,
+    inner_size: u32,
+    /// Signed integers have their top bit copied into the rest of the value, so -1 stored in 8 bits reads back as -1
+    signed: bool,
+}
+
+impl igBitFieldMetaField {
+    pub fn new(field: Arc<igMetaFieldInfo>, metadata_manager: &igMetadataManager, registry: &igMetafieldRegistry, platform: IG_CORE_PLATFORM) -> igBitFieldMetaField {
+        let name = field.name.clone().unwrap_or(Arc::from("(unnamed)"));
+        let Some(bit_shift_info) = field.ark_info.read().unwrap().ig_bit_shift_info.clone() else {
+            error!("The bit field {} has no bit shift info", name);
+            panic!("Alchemy Error! Check the logs.")
+        };
+        let bit_shift_info = bit_shift_info.read().unwrap().clone();
+        let Some(raw_inner) = bit_shift_info._type else {
+            error!("The bit field {} doesn't say what type its bits are", name);
+            panic!("Alchemy Error! Check the logs.")
+        };
+
+        let raw_inner_guard = raw_inner.read().unwrap();
+        let inner_size = metadata_manager.calculate_size(&raw_inner_guard, platform.clone());
+        let inner_field = igMetaFieldInfo {
+            ark_info: raw_inner.clone(),
+            _type: raw_inner_guard._type.clone(),
+            name: field.name.clone(),
+            size: inner_size,
+            alignment: raw_inner_guard.required_alignment.unwrap_or(4),
+            offset: 0,
+        };
+        drop(raw_inner_guard);
+
+        let inner = registry.get(Arc::new(inner_field), metadata_manager, platform);
+        let signed = [TypeId::of::<i8>(), TypeId::of::<i16>(), TypeId::of::<i32>(), TypeId::of::<i64>()].contains(&igMetaField::type_id(inner.as_ref()));
+        igBitFieldMetaField {
+            shift: bit_shift_info.shift,
+            bits: bit_shift_info.bits,
+            storage: igPrimitiveMetaField::with_size(field.size),
+            inner,
+            inner_size,
+            signed,
+        }
+    }
+
+    fn mask(&self) -> u64 {
+        match self.bits {
+            64.. => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Reads the storage field and returns this field's bits laid out the way the inner metafield expects them (little endian)
+    fn extract(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> Result<Cursor<Vec<u8>>, String> {
+        let raw = self.storage.read(handle, endian).map_err(|e| format!("Failed to read the storage of a bit field: {}", e))?;
+        let mut value = (raw >> self.shift) & self.mask();
+        if self.signed && self.bits > 0 && self.bits < 64 && (value >> (self.bits - 1)) & 1 == 1 {
+            value |= !self.mask();
+        }
+        Ok(Cursor::new(value.to_le_bytes()[..self.inner_size.min(8) as usize].to_vec()))
+    }
+
+    /// Replaces this field's bits in the storage field with the value the inner metafield wrote to `encoded`
+    fn merge(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian, encoded: Cursor<Vec<u8>>) -> std::io::Result<()> {
+        let mut bytes = [0u8; 8];
+        let encoded = encoded.into_inner();
+        let len = encoded.len().min(8);
+        bytes[..len].copy_from_slice(&encoded[..len]);
+        let value = u64::from_le_bytes(bytes) & self.mask();
+
+        let start = handle.position();
+        // The storage field may not be written yet when the bit field comes first. It is then treated as empty
+        let existing = self.storage.read(handle, endian.clone()).unwrap_or(0);
+        handle.set_position(start);
+        let merged = (existing & !(self.mask() << self.shift)) | (value << self.shift);
+        self.storage.write(handle, endian, merged)
+    }
+}
+
+impl igMetaField for igBitFieldMetaField {
+    fn type_id(&self) -> TypeId {
+        self.inner.type_id()
+    }
+
+    fn value_from_igz(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let mut bits = self
+            .extract(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()?;
+        self.inner.value_from_igz(registry, metadata_manager, object_stream_manager, &mut bits, Endian::Little, ctx)
+    }
+
+    fn value_into_igz(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        if value.is_none() {
+            return Ok(());
+        }
+        let mut encoded = Cursor::new(Vec::new());
+        self.inner.value_into_igz(metadata_manager, object_stream_manager, &mut encoded, value, Endian::Little, ctx)?;
+        self.merge(handle, endian, encoded).map_err(IgzSaverError::Io)
+    }
+
+    fn value_from_igx(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        // igx stores bit fields as their own fields, no packing is involved
+        self.inner.value_from_igx(registry, metadata_manager, object_stream_manager, handle, endian, ctx)
+    }
+
+    fn value_into_igx(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        self.inner.value_into_igx(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+
+    fn value_from_igb(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        let mut bits = self
+            .extract(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()?;
+        self.inner.value_from_igb(registry, metadata_manager, object_stream_manager, &mut bits, Endian::Little, ctx)
+    }
+
+    fn value_into_igb(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        if value.is_none() {
+            return Ok(());
+        }
+        let mut encoded = Cursor::new(Vec::new());
+        self.inner.value_into_igb(metadata_manager, object_stream_manager, &mut encoded, value, Endian::Little, ctx)?;
+        self.merge(handle, endian, encoded).map_err(IgbSaverError::Io)
+    }
+}
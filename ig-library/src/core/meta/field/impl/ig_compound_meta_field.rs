@@ -0,0 +1,241 @@
+use crate::core::ig_compound::igCompoundValue;
+use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_members, igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager, FieldStorage};
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_newline, igx_write_null, igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+/// A struct described by the `compoundfields` of a metaobject extending igCompoundMetaField (ColorDataMetaField, igVfxRangedCurveMetaField...). Each sub-field is read with its own metafield at its offset from the start of the compound value, so compound metafields can be nested and used as the element of a memory ref.
+///
+/// The value is an [igCompoundValue]. In igx files every non-null sub-field is written as a tag named after it, and igb files store the sub-fields one after the other in offset order like the fields of an object
+pub(crate) struct igCompoundMetaField {
+    type_name: Arc<str>,
+    /// Size of the whole compound value on the platform, padding included
+    size: u32,
+    /// Every sub-field that isn't a bit field, sorted by offset
+    stored: Vec<(Arc<igMetaFieldInfo>, Arc<dyn igMetaField>)>,
+    /// Bit fields are read from and merged into their storage field, so they are handled after it
+    bit_fields: Vec<(Arc<igMetaFieldInfo>, Arc<dyn igMetaField>)>,
+}
+
+impl igCompoundMetaField {
+    pub fn new(field: Arc<igMetaFieldInfo>, fields: FieldStorage, metadata_manager: &igMetadataManager, registry: &igMetafieldRegistry, platform: IG_CORE_PLATFORM) -> igCompoundMetaField {
+        let mut stored = Vec::new();
+        let mut bit_fields = Vec::new();
+        for sub_field in fields.fields() {
+            let metafield = registry.get(sub_field.clone(), metadata_manager, platform.clone());
+            match sub_field.bit_field_storage() {
+                Some(_) => bit_fields.push((sub_field.clone(), metafield)),
+                None => stored.push((sub_field.clone(), metafield)),
+            }
+        }
+        stored.sort_by_key(|(sub_field, _)| sub_field.offset);
+
+        igCompoundMetaField {
+            type_name: field._type.clone(),
+            size: field.size,
+            stored,
+            bit_fields,
+        }
+    }
+
+    fn name(field: &igMetaFieldInfo) -> Arc<str> {
+        field.name.clone().unwrap_or(Arc::from("(unnamed)"))
+    }
+
+    /// Returns a copy of the value to write, or an empty value when it is null. Fails when the value isn't an [igCompoundValue]
+    fn downcast(&self, value: Option<igAny>) -> Option<igCompoundValue> {
+        match value {
+            Some(value) => value.read().unwrap().downcast_ref::<igCompoundValue>().cloned(),
+            None => Some(igCompoundValue::new(self.type_name.clone())),
+        }
+    }
+}
+
+impl igMetaField for igCompoundMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<igCompoundValue>()
+    }
+
+    fn value_from_igz(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let start = handle.position();
+        let mut value = igCompoundValue::new(self.type_name.clone());
+        for (field, metafield) in self.stored.iter().chain(&self.bit_fields) {
+            handle.set_position(start + field.offset as u64);
+            let field_value = metafield.value_from_igz(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx);
+            value.set_field(Self::name(field), field_value);
+        }
+        handle.set_position(start + self.size as u64);
+
+        Some(Arc::new(RwLock::new(value)))
+    }
+
+    fn value_into_igz(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let value = self.downcast(value).ok_or(IgzSaverError::InvalidValueType(Arc::from("igCompoundValue")))?;
+        let start = handle.position();
+        for (field, metafield) in self.stored.iter().chain(&self.bit_fields) {
+            handle.set_position(start + field.offset as u64);
+            let field_value = value.get_field(&Self::name(field));
+            metafield.value_into_igz(metadata_manager, object_stream_manager, handle, field_value, endian.clone(), ctx)?;
+        }
+
+        // Padding at the end of the value is zeroed
+        let end = start + self.size as u64;
+        if (handle.get_ref().len() as u64) < end {
+            handle.get_mut().resize(end as usize, 0);
+        }
+        handle.set_position(end);
+        Ok(())
+    }
+
+    fn value_from_igx(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        igx_read_text(handle)?;
+
+        let mut members = igx_read_members(handle);
+        let mut value = igCompoundValue::new(self.type_name.clone());
+        for (field, metafield) in self.stored.iter().chain(&self.bit_fields) {
+            let name = Self::name(field);
+            let Some(mut member) = members.remove(name.as_ref()) else {
+                continue;
+            };
+            let field_value = metafield.value_from_igx(registry, metadata_manager, object_stream_manager, &mut member, endian.clone(), ctx);
+            value.set_field(name, field_value);
+        }
+
+        Some(Arc::new(RwLock::new(value)))
+    }
+
+    fn value_into_igx(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        if value.is_none() {
+            igx_write_null(handle);
+            return Ok(());
+        }
+        let value = self.downcast(value).ok_or(IgxSaverError::InvalidValueType(Arc::from("igCompoundValue")))?;
+
+        let mut fields: Vec<_> = self.stored.iter().chain(&self.bit_fields).collect();
+        fields.sort_by_key(|(field, _)| field.offset);
+        let indent = ctx.indent;
+        for (field, metafield) in fields {
+            let name = Self::name(field);
+            let Some(field_value) = value.get_field(&name) else {
+                continue;
+            };
+            ctx.indent = indent + 1;
+            igx_write_newline(handle, ctx);
+            igx_write_raw(handle, &format!("<{}>", name));
+            ctx.indent = indent + 2;
+            metafield.value_into_igx(metadata_manager, object_stream_manager, handle, Some(field_value), endian.clone(), ctx)?;
+            igx_write_raw(handle, &format!("</{}>", name));
+        }
+        ctx.indent = indent;
+        igx_write_newline(handle, ctx);
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        let field_size = ctx.field_size;
+        let mut value = igCompoundValue::new(self.type_name.clone());
+        let mut field_positions: HashMap<Arc<str>, u64> = HashMap::new();
+        for (field, metafield) in &self.stored {
+            let name = Self::name(field);
+            field_positions.insert(name.clone(), handle.position());
+            ctx.field_size = field.size;
+            let field_value = metafield.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx);
+            value.set_field(name, field_value);
+        }
+
+        let end = handle.position();
+        for (field, metafield) in &self.bit_fields {
+            let Some(start) = field.bit_field_storage().and_then(|storage| field_positions.get(storage.as_str()).copied()) else {
+                continue;
+            };
+            handle.set_position(start);
+            let field_value = metafield.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx);
+            value.set_field(Self::name(field), field_value);
+        }
+        handle.set_position(end);
+        ctx.field_size = field_size;
+
+        Some(Arc::new(RwLock::new(value)))
+    }
+
+    fn value_into_igb(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        let value = self.downcast(value).ok_or(IgbSaverError::InvalidValueType(Arc::from("igCompoundValue")))?;
+        let mut field_positions: HashMap<Arc<str>, u64> = HashMap::new();
+        for (field, metafield) in &self.stored {
+            let name = Self::name(field);
+            field_positions.insert(name.clone(), handle.position());
+            metafield.value_into_igb(metadata_manager, object_stream_manager, handle, value.get_field(&name), endian.clone(), ctx)?;
+        }
+
+        let end = handle.position();
+        for (field, metafield) in &self.bit_fields {
+            let Some(start) = field.bit_field_storage().and_then(|storage| field_positions.get(storage.as_str()).copied()) else {
+                continue;
+            };
+            handle.set_position(start);
+            metafield.value_into_igb(metadata_manager, object_stream_manager, handle, value.get_field(&Self::name(field)), endian.clone(), ctx)?;
+        }
+        handle.set_position(end);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_primitive_meta_field::igPrimitiveMetaField;
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw, igx_write_text, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use log::warn;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+/// The value of an enum field. Holds the integer stored in the file and, when metaenums.xml knows it, the name of the entry.
+///
+/// When writing, `name` is looked up in the metaenum and `value` is only used when there is no name or the metaenum doesn't know it. Use [igEnumValue::set_name] to change both at once, and [igMetadataManager::get_meta_enum_values] with `meta_enum` to list every possible entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct igEnumValue {
+    /// The metaenum from metaenums.xml this value belongs to. [None] when the field doesn't say
+    pub meta_enum: Option<Arc<str>>,
+    pub value: i32,
+    pub name: Option<Arc<str>>,
+}
+
+impl igEnumValue {
+    /// Sets `name` and the matching `value`. Returns false and changes nothing when `name` isn't an entry of the metaenum
+    pub fn set_name(&mut self, name: &str, metadata_manager: &igMetadataManager) -> bool {
+        let value = self
+            .meta_enum
+            .as_ref()
+            .and_then(|meta_enum| metadata_manager.get_meta_enum_values(meta_enum))
+            .and_then(|values| values.into_iter().find(|(x, _)| x.as_ref() == name));
+        match value {
+            Some((name, value)) => {
+                self.name = Some(name);
+                self.value = value;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub(crate) struct igEnumMetaField {
+    meta_enum: Option<Arc<str>>,
+    /// (name, value) of every entry in the metaenum
+    values: Vec<(Arc<str>, i32)>,
+    storage: igPrimitiveMetaField<i32>,
+}
+
+impl igEnumMetaField {
+    pub fn new(field: Arc<igMetaFieldInfo>, metadata_manager: &igMetadataManager) -> igEnumMetaField {
+        let meta_enum = field.ark_info.read().unwrap().ig_meta_enum.clone();
+        let values = match &meta_enum {
+            Some(name) => metadata_manager.get_meta_enum_values(name).unwrap_or_else(|| {
+                warn!("{} uses the metaenum {} which doesn't exist", field.name.clone().unwrap_or(Arc::from("(unnamed)")), name);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+
+        igEnumMetaField {
+            meta_enum,
+            values,
+            storage: igPrimitiveMetaField::with_size(field.size),
+        }
+    }
+
+    /// Turns a raw integer into a value, resolving the name of the entry
+    fn to_value(&self, value: i32) -> igEnumValue {
+        igEnumValue {
+            meta_enum: self.meta_enum.clone(),
+            value,
+            name: self.values.iter().find(|(_, x)| *x == value).map(|(name, _)| name.clone()),
+        }
+    }
+
+    /// Turns an entry name into its integer
+    fn value_of(&self, name: &str) -> Option<i32> {
+        self.values.iter().find(|(x, _)| x.as_ref() == name).map(|(_, value)| *value)
+    }
+
+    /// Returns the integer to write for a value. Accepts [igEnumValue] or a plain [i32]. When `name` was changed without `value`, the name wins
+    fn to_raw(&self, value: &igAny) -> Option<i32> {
+        let guard = value.read().unwrap();
+        if let Some(value) = guard.downcast_ref::<igEnumValue>() {
+            let Some(name) = &value.name else {
+                return Some(value.value);
+            };
+            return match self.value_of(name) {
+                Some(raw) => Some(raw),
+                None => {
+                    warn!("{} is not an entry of {}. Writing the value {}", name, self.meta_enum.as_deref().unwrap_or("(unknown enum)"), value.value);
+                    Some(value.value)
+                }
+            };
+        }
+        guard.downcast_ref::<i32>().copied()
+    }
+
+    fn read_value(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> Result<igAny, String> {
+        self.storage
+            .read(handle, endian)
+            .map(|value| Arc::new(RwLock::new(self.to_value(value))) as igAny)
+            .map_err(|e| format!("Failed to read {}: {}", self.meta_enum.as_deref().unwrap_or("enum"), e))
+    }
+}
+
+impl igMetaField for igEnumMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<igEnumValue>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let raw = match value {
+            Some(value) => self.to_raw(&value).ok_or(IgzSaverError::InvalidValueType(Arc::from("igEnumValue")))?,
+            None => 0,
+        };
+        self.storage.write(handle, endian, raw).map_err(IgzSaverError::Io)
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let text = igx_read_text(handle)?;
+        let text = text.trim();
+        // Usually the igx was written against other metadata. The field is left null and the directory can't be saved
+        let Some(value) = text.parse::<i32>().ok().or_else(|| self.value_of(text)) else {
+            ctx.report(
+                igLoadDiagnosticKind::FieldDecodeFailed,
+                format!("\"{}\" is not an entry of {}", text, self.meta_enum.as_deref().unwrap_or("(unknown enum)")),
+            );
+            return None;
+        };
+        Some(Arc::new(RwLock::new(self.to_value(value))))
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let raw = self.to_raw(&value).ok_or(IgxSaverError::InvalidValueType(Arc::from("igEnumValue")))?;
+        // Names are written when known so the file stays readable. Values without a name fall back to the integer
+        match self.to_value(raw).name {
+            Some(name) => igx_write_text(handle, &name),
+            None => igx_write_raw(handle, &raw.to_string()),
+        }
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        let raw = match value {
+            Some(value) => self.to_raw(&value).ok_or(IgbSaverError::InvalidValueType(Arc::from("igEnumValue")))?,
+            None => 0,
+        };
+        self.storage.write(handle, endian, raw).map_err(IgbSaverError::Io)
+    }
+}
@@ -0,0 +1,173 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_handle::{igHandle, igHandleName};
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_text, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::util::byteorder_fixes::read_ptr;
+use crate::util::ig_name::igName;
+use log::warn;
+use std::any::TypeId;
+use std::io::{Cursor, Read, Write};
+use std::sync::{Arc, RwLock};
+
+/// A reference to a named object that may live in a directory that isn't loaded yet. Unlike igObjectRefMetaField the object isn't looked up while loading, the value is the shared [igHandle] and resolves once the directory holding the object is added to the [igObjectHandleManager](crate::core::ig_handle::igObjectHandleManager).
+///
+/// The value is an `Arc<RwLock<igHandle>>`. In igx files handles are written as `namespace::name`, with the hash in hex for names whose string isn't known
+pub(crate) struct igHandleMetaField {
+    pub size: u32,
+}
+
+/// Parses one half of a handle written by [igHandle]'s Display implementation
+fn parse_name(text: &str) -> igName {
+    match text.strip_prefix("0x").and_then(|hash| u32::from_str_radix(hash, 16).ok()) {
+        Some(hash) => igName::from_hash(hash),
+        None => igName::new(text.to_string()),
+    }
+}
+
+impl igMetaField for igHandleMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Arc<RwLock<igHandle>>>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let base_offset = handle.position();
+        let raw = match read_ptr(handle, ctx.platform.clone(), endian) {
+            Ok(raw) => raw,
+            Err(e) => {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read a handle: {}", e));
+                return None;
+            }
+        };
+
+        if ctx.runtime_fields.handles.binary_search(&base_offset).is_err() {
+            if raw != 0 {
+                ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Handle at {:#X} has no RHND fixup", base_offset));
+            }
+            return None;
+        }
+
+        // Named handles come from EXNM, the rest from EXID
+        let index = (raw & 0x3FFFFFFF) as usize;
+        let (list, list_name) = if raw & 0x80000000 != 0 {
+            (&ctx.named_handle_list, "named handle")
+        } else {
+            (&ctx.external_list, "external")
+        };
+        match list.get(index).cloned() {
+            Some(handle) => Some(Arc::new(RwLock::new(handle))),
+            None => {
+                ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("The {} index {} of a handle is out of range", list_name, index));
+                None
+            }
+        }
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        Err(IgzSaverError::Unsupported(Arc::from("igHandleMetaField")))
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let text = igx_read_text(handle)?;
+        let text = text.trim();
+        let Some((namespace, name)) = text.split_once("::") else {
+            ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("\"{}\" is not a valid igx handle", text));
+            return None;
+        };
+
+        let handle_name = igHandleName::new(parse_name(name), parse_name(namespace));
+        // Sharing the manager's handle means it resolves as soon as the directory holding the object is added
+        let handle = match ctx.handle_manager.as_deref_mut() {
+            Some(manager) => manager.lookup_handle_name(&handle_name),
+            None => igHandle::from_handle_name(&handle_name),
+        };
+        Some(Arc::new(RwLock::new(handle)))
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let ig_handle = guard.downcast_ref::<Arc<RwLock<igHandle>>>().ok_or(IgxSaverError::InvalidValueType(Arc::from("Arc<RwLock<igHandle>>")))?;
+        igx_write_text(handle, &ig_handle.read().unwrap().to_string());
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        // igb files predate handles and have no way to name an object in another file
+        let mut buffer = vec![0u8; ctx.field_size as usize];
+        if let Err(e) = handle.read_exact(&mut buffer) {
+            ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read a handle: {}", e));
+            return None;
+        }
+        if buffer.iter().any(|byte| *byte != 0) {
+            ctx.report(igLoadDiagnosticKind::UnresolvedReference, "igb files can't store handles. The handle is left null".to_string());
+        }
+        None
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        if value.is_some() {
+            warn!("igb files can't store handles. The handle is written as null");
+        }
+        handle.write_all(&vec![0u8; self.size as usize]).map_err(IgbSaverError::Io)
+    }
+}
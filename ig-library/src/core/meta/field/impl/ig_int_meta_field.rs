@@ -1,4 +1,7 @@
-﻿use crate::core::ig_fs::Endian;
+﻿use crate::util::byteorder_fixes::write_i32;
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw};
+use crate::core::load::ig_igx_loader::igx_parse;
+use crate::core::ig_fs::Endian;
 use crate::core::ig_objects::{igAny, igObjectStreamManager};
 use crate::core::load::ig_igb_loader::IgbLoaderContext;
 use crate::core::load::ig_igx_loader::IgxLoaderContext;
@@ -35,10 +38,10 @@ impl igMetaField for igIntMetaField {
 
     fn value_into_igz(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
         _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError> {
@@ -50,23 +53,30 @@ impl igMetaField for igIntMetaField {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
-        _ctx: &mut IgxLoaderContext,
+        ctx: &mut IgxLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        igx_parse::<i32>(handle, ctx).map(|value| Arc::new(RwLock::new(value)) as igAny)
     }
 
     fn value_into_igx(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError> {
-        todo!()
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<i32>().ok_or(IgxSaverError::InvalidValueType(Arc::from("i32")))?;
+        igx_write_raw(handle, &value.to_string());
+        Ok(())
     }
 
     fn value_from_igb(
@@ -74,22 +84,27 @@ impl igMetaField for igIntMetaField {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
         _ctx: &mut IgbLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        Some(Arc::new(RwLock::new(read_i32(handle, endian).unwrap())))
     }
 
     fn value_into_igb(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
         _ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError> {
-        todo!()
+        let value = match value {
+            Some(value) => *value.read().unwrap().downcast_ref::<i32>().ok_or(IgbSaverError::InvalidValueType(Arc::from("i32")))?,
+            None => 0,
+        };
+        write_i32(handle, endian, value).unwrap();
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,296 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_math::{igMatrix44f, igQuaternionf, igRangedFloat, igRangedVector, igVec2f, igVec2uc, igVec3d, igVec3f, igVec3uc, igVec4f, igVec4i, igVec4uc};
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_primitive_meta_field::{igPrimitive, igPrimitiveMetaField};
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use log::warn;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+/// A math type made of several components of the same primitive type, stored one after the other
+pub(crate) trait igMathValue: Copy + Default + Send + Sync + 'static {
+    type Component: igPrimitive;
+    /// Name of the rust type. Used when reporting a value of the wrong type
+    const TYPE_NAME: &'static str;
+    /// Amount of components stored
+    const COUNT: usize;
+
+    fn components(&self) -> Vec<Self::Component>;
+    /// `components` always holds [igMathValue::COUNT] values
+    fn from_components(components: &[Self::Component]) -> Self;
+}
+
+macro_rules! define_vector_value {
+    ($($type:ty: $component:ty => $($field:ident),+;)*) => {
+        $(
+            impl igMathValue for $type {
+                type Component = $component;
+                const TYPE_NAME: &'static str = stringify!($type);
+                const COUNT: usize = [$(stringify!($field)),+].len();
+
+                fn components(&self) -> Vec<$component> {
+                    vec![$(self.$field),+]
+                }
+
+                fn from_components(components: &[$component]) -> Self {
+                    let mut components = components.iter().copied();
+                    Self {
+                        $($field: components.next().unwrap_or_default()),+
+                    }
+                }
+            }
+        )*
+    };
+}
+
+define_vector_value! {
+    igVec2f: f32 => x, y;
+    igVec3f: f32 => x, y, z;
+    igVec3d: f64 => x, y, z;
+    igVec4f: f32 => x, y, z, w;
+    igVec4i: i32 => x, y, z, w;
+    igVec2uc: u8 => x, y;
+    igVec3uc: u8 => x, y, z;
+    igVec4uc: u8 => x, y, z, w;
+    igQuaternionf: f32 => x, y, z, w;
+    igRangedFloat: f32 => min, max;
+}
+
+impl igMathValue for igMatrix44f {
+    type Component = f32;
+    const TYPE_NAME: &'static str = "igMatrix44f";
+    const COUNT: usize = 16;
+
+    fn components(&self) -> Vec<f32> {
+        self.rows.iter().flatten().copied().collect()
+    }
+
+    fn from_components(components: &[f32]) -> Self {
+        let mut matrix = igMatrix44f::default();
+        for (i, value) in components.iter().enumerate().take(Self::COUNT) {
+            matrix.rows[i / 4][i % 4] = *value;
+        }
+        matrix
+    }
+}
+
+impl igMathValue for igRangedVector {
+    type Component = f32;
+    const TYPE_NAME: &'static str = "igRangedVector";
+    const COUNT: usize = 6;
+
+    fn components(&self) -> Vec<f32> {
+        let mut components = self.min.components();
+        components.extend(self.max.components());
+        components
+    }
+
+    fn from_components(components: &[f32]) -> Self {
+        igRangedVector {
+            min: igVec3f::from_components(&components[..3]),
+            max: igVec3f::from_components(&components[3..]),
+        }
+    }
+}
+
+/// Shared implementation of every vector, quaternion, matrix and ranged value metafield. Components are read one after the other and the rest of the platform size (such as the 4th float of igVec3fAlignedMetaField) is treated as padding.
+///
+/// In igx files the components are written as text separated by spaces
+pub(crate) struct igMathMetaField<T: igMathValue> {
+    /// Size on disk in bytes, padding included
+    pub size: u32,
+    component: igPrimitiveMetaField<T::Component>,
+    _type: PhantomData<T>,
+}
+
+pub(crate) type igVec2fMetaField = igMathMetaField<igVec2f>;
+pub(crate) type igVec3fMetaField = igMathMetaField<igVec3f>;
+pub(crate) type igVec3fAlignedMetaField = igMathMetaField<igVec3f>;
+pub(crate) type igVec3dMetaField = igMathMetaField<igVec3d>;
+pub(crate) type igVec4fMetaField = igMathMetaField<igVec4f>;
+pub(crate) type igVec4fUnalignedMetaField = igMathMetaField<igVec4f>;
+pub(crate) type igVec4iMetaField = igMathMetaField<igVec4i>;
+pub(crate) type igVec2ucMetaField = igMathMetaField<igVec2uc>;
+pub(crate) type igVec3ucMetaField = igMathMetaField<igVec3uc>;
+pub(crate) type igVec4ucMetaField = igMathMetaField<igVec4uc>;
+pub(crate) type igQuaternionfMetaField = igMathMetaField<igQuaternionf>;
+pub(crate) type igMatrix44fMetaField = igMathMetaField<igMatrix44f>;
+pub(crate) type igRangedFloatMetaField = igMathMetaField<igRangedFloat>;
+pub(crate) type igRangedVectorMetaField = igMathMetaField<igRangedVector>;
+
+impl<T: igMathValue> igMathMetaField<T> {
+    /// Creates the metafield for `type_name` using its size on the targeted platform. Falls back to the size of the components when metafields.xml doesn't describe the type
+    pub fn new(type_name: &str, metadata_manager: &igMetadataManager) -> Self {
+        let packed_size = (T::COUNT * size_of::<T::Component>()) as u32;
+        let size = metadata_manager.platform_size(type_name).unwrap_or_else(|| {
+            warn!("{} has no platform info. Assuming it is {} bytes", type_name, packed_size);
+            packed_size
+        });
+        if size < packed_size {
+            warn!("{} is {} bytes on this platform which can't fit {} components", type_name, size, T::COUNT);
+        }
+
+        igMathMetaField {
+            size,
+            component: igPrimitiveMetaField::with_size(size_of::<T::Component>() as u32),
+            _type: PhantomData,
+        }
+    }
+
+    pub fn read(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> std::io::Result<T> {
+        let start = handle.position();
+        let mut components = Vec::with_capacity(T::COUNT);
+        for _ in 0..T::COUNT {
+            components.push(self.component.read(handle, endian.clone())?);
+        }
+        handle.set_position(start + self.size as u64);
+        Ok(T::from_components(&components))
+    }
+
+    pub fn write(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian, value: T) -> std::io::Result<()> {
+        let start = handle.position();
+        for component in value.components() {
+            self.component.write(handle, endian.clone(), component)?;
+        }
+        // Padding is zeroed so the output is the same between saves
+        while handle.position() < start + self.size as u64 {
+            igPrimitiveMetaField::<u8>::with_size(1).write(handle, endian.clone(), 0)?;
+        }
+        Ok(())
+    }
+
+    fn read_value(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> Result<igAny, String> {
+        self.read(handle, endian)
+            .map(|value| Arc::new(RwLock::new(value)) as igAny)
+            .map_err(|e| format!("Failed to read {}: {}", T::TYPE_NAME, e))
+    }
+
+    /// Returns the value as `T`, or [None] when it is stored as another type
+    fn downcast(value: &igAny) -> Option<T> {
+        value.read().unwrap().downcast_ref::<T>().copied()
+    }
+}
+
+impl<T: igMathValue> igMetaField for igMathMetaField<T> {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let value = match value {
+            Some(value) => Self::downcast(&value).ok_or(IgzSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?,
+            None => T::default(),
+        };
+        self.write(handle, endian, value).map_err(IgzSaverError::Io)
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let text = igx_read_text(handle)?;
+        let components: Result<Vec<T::Component>, _> = text
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|x| !x.is_empty())
+            .map(|x| x.parse())
+            .collect();
+        // Hand edited igx files may have the wrong amount of components. Those are reported and the field is left null
+        match components {
+            Ok(components) if components.len() == T::COUNT => Some(Arc::new(RwLock::new(T::from_components(&components)))),
+            _ => {
+                ctx.report(
+                    igLoadDiagnosticKind::FieldDecodeFailed,
+                    format!("Failed to parse igx value \"{}\" as {}. Expected {} components", text, T::TYPE_NAME, T::COUNT),
+                );
+                None
+            }
+        }
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let value = Self::downcast(&value).ok_or(IgxSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?;
+        let text: Vec<String> = value.components().iter().map(|x| x.to_string()).collect();
+        igx_write_raw(handle, &text.join(" "));
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        let value = match value {
+            Some(value) => Self::downcast(&value).ok_or(IgbSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?,
+            None => T::default(),
+        };
+        self.write(handle, endian, value).map_err(IgbSaverError::Io)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::IgxLoaderContext;
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::memory::{element_or_null, igMemory};
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_memory_ref_meta_field::igMemoryRefMetaField;
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::util::byteorder_fixes::read_ptr;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+/// A memory ref that igz files store out of line. Instead of flags and an offset the field holds an index into the blocks of the THUMBNAIL fixup, and the RUNTIME_MEMORY_HANDLES fixup marks which fields are memory handles.
+///
+/// The value is an [igMemory] just like [igMemoryRefMetaField], which igx and igb files are read and written with
+pub(crate) struct igMemoryRefHandleMetaField(pub igMemoryRefMetaField);
+
+impl igMetaField for igMemoryRefHandleMetaField {
+    fn type_id(&self) -> TypeId {
+        igMetaField::type_id(&self.0)
+    }
+
+    fn value_from_igz(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let start = handle.position();
+        let raw = match read_ptr(handle, ctx.platform.clone(), endian.clone()) {
+            Ok(raw) => raw,
+            Err(e) => {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read a memory handle: {}", e));
+                return None;
+            }
+        };
+        if ctx.runtime_fields.memory_handles.binary_search(&start).is_err() {
+            return None;
+        }
+
+        let Some(block) = ctx.thumbnails.get(raw as usize).cloned() else {
+            ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Memory handle {} is out of range", raw));
+            return None;
+        };
+
+        let element = &self.0 .0;
+        let mut memory: igMemory<igAny> = igMemory::new();
+        if element.ark_info.read().unwrap()._type.as_ref() == "igUnsignedCharMetaField" {
+            for byte in block.data.iter() {
+                memory.data.push(Arc::new(RwLock::new(*byte)));
+            }
+        } else {
+            let inner_meta_field = registry.get(element.clone(), metadata_manager, metadata_manager.platform());
+            let count = block.data.len() as u64 / element.size.max(1) as u64;
+            for i in 0..count {
+                handle.set_position(block.offset + element.size as u64 * i);
+                memory.data.push(element_or_null(inner_meta_field.value_from_igz(
+                    registry,
+                    metadata_manager,
+                    object_stream_manager,
+                    handle,
+                    endian.clone(),
+                    ctx,
+                )));
+            }
+        }
+
+        Some(Arc::new(RwLock::new(memory)))
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        Err(IgzSaverError::Unsupported(Arc::from("igMemoryRefHandleMetaField")))
+    }
+
+    fn value_from_igx(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        self.0.value_from_igx(registry, metadata_manager, object_stream_manager, handle, endian, ctx)
+    }
+
+    fn value_into_igx(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        self.0.value_into_igx(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+
+    fn value_from_igb(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        self.0.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian, ctx)
+    }
+
+    fn value_into_igb(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        self.0.value_into_igb(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+}
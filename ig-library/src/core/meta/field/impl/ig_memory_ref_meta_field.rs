@@ -1,9 +1,15 @@
+use std::io::Write;
+use crate::util::byteorder_fixes::write_i32;
+use crate::util::byteorder_fixes::read_i32;
+use crate::core::save::ig_igx_saver::{igx_write_newline, igx_write_null, igx_write_raw};
+use crate::core::load::ig_igx_loader::{igx_parse, igx_read_elements, igx_read_text};
 use crate::core::ig_fs::Endian;
 use crate::core::ig_objects::{igAny, igObjectStreamManager};
 use crate::core::load::ig_igb_loader::IgbLoaderContext;
 use crate::core::load::ig_igx_loader::IgxLoaderContext;
 use crate::core::load::ig_igz_loader::IgzLoaderContext;
-use crate::core::memory::igMemory;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::memory::{element_or_null, element_value, igMemory};
 use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
 use crate::core::meta::field::ig_metafields::igMetaField;
 use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
@@ -38,7 +44,10 @@ impl igMetaField for igMemoryRefMetaField {
         let flags = read_ptr(handle, ctx.platform.clone(), endian.clone()).unwrap();
         let raw = read_ptr(handle, ctx.platform.clone(), endian.clone()).unwrap();
 
-        let offset = ctx.deserialize_offset(raw);
+        let Some(offset) = ctx.deserialize_offset(raw) else {
+            ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Memory at {:#X} points into a section that doesn't exist", raw));
+            return None;
+        };
         let mut memory: igMemory<igAny> = igMemory::new(); // We don't know the type inside the memory, we didn't create it. However, we know the metafield so we know what is supposed to be here, making it safe in the end.
 
         // TODO: make 2 constructors for igMemory: one takes a pool and the other a set of flags. This fits in with rust's structuring where nothing should be used until initialized and guarantees better safety
@@ -58,17 +67,17 @@ impl igMetaField for igMemoryRefMetaField {
                     memory.data.push(Arc::new(RwLock::new(x)));
                 }
             } else {
-                let inner_meta_field = registry.get_simple(&self.0.ark_info.read().unwrap());
+                let inner_meta_field = registry.get(self.0.clone(), metadata_manager, metadata_manager.platform());
                 for i in 0..memory.data.capacity() {
                     handle.set_position(offset + (self.0.size as u64) * (i as u64));
-                    memory.data.push(inner_meta_field.value_from_igz(
+                    memory.data.push(element_or_null(inner_meta_field.value_from_igz(
                         registry,
                         metadata_manager,
                         object_stream_manager,
                         handle,
                         endian.clone(),
                         ctx,
-                    )?)
+                    )))
                 }
             }
         }
@@ -78,61 +87,202 @@ impl igMetaField for igMemoryRefMetaField {
 
     fn value_into_igz(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
         _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError> {
-        todo!()
+        Err(IgzSaverError::Unsupported(Arc::from("igMemoryRefMetaField")))
     }
 
     fn value_from_igx(
         &self,
-        _registry: &igMetafieldRegistry,
-        _metadata_manager: &igMetadataManager,
-        _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgxLoaderContext,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        igx_read_text(handle)?;
+
+        let mut memory: igMemory<igAny> = igMemory::new();
+        let guard = self.0.ark_info.read().unwrap();
+        if guard._type.as_ref() == "igUnsignedCharMetaField" {
+            for element in igx_read_elements(handle) {
+                // A null element keeps its place instead of dropping the block
+                memory.data.push(element_or_null(igx_parse::<u8>(&element, ctx).map(|byte| Arc::new(RwLock::new(byte)) as igAny)));
+            }
+        } else {
+            let inner_meta_field = registry.get(self.0.clone(), metadata_manager, metadata_manager.platform());
+            for mut element in igx_read_elements(handle) {
+                memory.data.push(element_or_null(inner_meta_field.value_from_igx(
+                    registry,
+                    metadata_manager,
+                    object_stream_manager,
+                    &mut element,
+                    endian.clone(),
+                    ctx,
+                )))
+            }
+        }
+
+        Some(Arc::new(RwLock::new(memory)))
     }
 
     fn value_into_igx(
         &self,
-        _registry: &igMetafieldRegistry,
-        _metadata_manager: &igMetadataManager,
-        _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgxSaverContext,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError> {
-        todo!()
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let memory = guard.downcast_ref::<igMemory<igAny>>().ok_or(IgxSaverError::InvalidValueType(Arc::from("igMemory<igAny>")))?;
+        if memory.data.is_empty() {
+            return Ok(());
+        }
+
+        let inner_meta_field = metadata_manager.meta_field_registry.get(self.0.clone(), metadata_manager, metadata_manager.platform());
+        let indent = ctx.indent;
+        ctx.indent = indent + 1;
+        for element in &memory.data {
+            igx_write_newline(handle, ctx);
+            igx_write_raw(handle, "<element>");
+            ctx.indent = indent + 2;
+            if self.0.ark_info.read().unwrap()._type.as_ref() == "igUnsignedCharMetaField" {
+                match element_value(element) {
+                    Some(byte) => {
+                        let byte = byte.read().unwrap();
+                        let byte = byte.downcast_ref::<u8>().ok_or(IgxSaverError::InvalidValueType(Arc::from("u8")))?;
+                        igx_write_raw(handle, &byte.to_string());
+                    }
+                    None => igx_write_null(handle),
+                }
+            } else {
+                inner_meta_field.value_into_igx(
+                    metadata_manager,
+                    object_stream_manager,
+                    handle,
+                    element_value(element),
+                    endian.clone(),
+                    ctx,
+                )?;
+            }
+            ctx.indent = indent + 1;
+            igx_write_raw(handle, "</element>");
+        }
+        ctx.indent = indent;
+        igx_write_newline(handle, ctx);
+        Ok(())
     }
 
     fn value_from_igb(
         &self,
-        _registry: &igMetafieldRegistry,
-        _metadata_manager: &igMetadataManager,
-        _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgbLoaderContext,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        // igb stores memory references as an index into its directory
+        let index = read_i32(handle, endian.clone()).unwrap();
+        if index < 0 {
+            return None;
+        }
+
+        let Some(block) = ctx.memory_blocks.get(&(index as u32)).cloned() else {
+            ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Directory entry {} is not a memory block", index));
+            return None;
+        };
+
+        let mut memory: igMemory<igAny> = igMemory::new();
+        let guard = self.0.ark_info.read().unwrap();
+        if guard._type.as_ref() == "igUnsignedCharMetaField" {
+            for byte in block {
+                memory.data.push(Arc::new(RwLock::new(byte)));
+            }
+        } else {
+            // The element count isn't stored, the block is just read until it runs out
+            let inner_meta_field = registry.get(self.0.clone(), metadata_manager, metadata_manager.platform());
+            let length = block.len() as u64;
+            let mut block_handle = Cursor::new(block);
+            let field_size = ctx.field_size;
+            while block_handle.position() < length {
+                let start = block_handle.position();
+                if length - start < self.0.size as u64 {
+                    ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Memory block {} ends partway through an element", index));
+                    break;
+                }
+                let value = inner_meta_field.value_from_igb(
+                    registry,
+                    metadata_manager,
+                    object_stream_manager,
+                    &mut block_handle,
+                    endian.clone(),
+                    ctx,
+                );
+                if block_handle.position() == start {
+                    ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read an element of memory block {}", index));
+                    break;
+                }
+                memory.data.push(element_or_null(value));
+            }
+            ctx.field_size = field_size;
+        }
+
+        Some(Arc::new(RwLock::new(memory)))
     }
 
     fn value_into_igb(
         &self,
-        _registry: &igMetafieldRegistry,
-        _metadata_manager: &igMetadataManager,
-        _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgbSaverContext,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError> {
-        todo!()
+        let Some(value) = value else {
+            write_i32(handle, endian, -1).unwrap();
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let memory = guard.downcast_ref::<igMemory<igAny>>().ok_or(IgbSaverError::InvalidValueType(Arc::from("igMemory<igAny>")))?;
+
+        let element_type = self.0.ark_info.read().unwrap()._type.clone();
+        let mut block = Cursor::new(Vec::new());
+        if element_type.as_ref() == "igUnsignedCharMetaField" {
+            for element in &memory.data {
+                let byte = element.read().unwrap();
+                let byte = byte.downcast_ref::<u8>().ok_or(IgbSaverError::InvalidValueType(Arc::from("u8")))?;
+                block.write_all(&[*byte]).unwrap();
+            }
+        } else {
+            let inner_meta_field = metadata_manager.meta_field_registry.get(self.0.clone(), metadata_manager, metadata_manager.platform());
+            for element in &memory.data {
+                inner_meta_field.value_into_igb(
+                    metadata_manager,
+                    object_stream_manager,
+                    &mut block,
+                    element_value(element),
+                    endian.clone(),
+                    ctx,
+                )?;
+            }
+        }
+
+        let index = ctx.push_memory(block.get_ref(), &element_type);
+        write_i32(handle, endian, index as i32).unwrap();
+        Ok(())
     }
 }
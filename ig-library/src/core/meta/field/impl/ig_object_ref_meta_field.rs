@@ -1,3 +1,10 @@
+use crate::util::byteorder_fixes::write_i32;
+use crate::util::byteorder_fixes::read_i32;
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw, igx_write_text};
+use log::{error, warn};
+use crate::util::ig_name::igName;
+use crate::core::ig_handle::{igHandle, igHandleName};
+use crate::core::load::ig_igx_loader::igx_read_text;
 use crate::core::ig_fs::Endian;
 use crate::core::ig_objects::{igAny, igObject, igObjectStreamManager};
 use crate::core::load::ig_igb_loader::IgbLoaderContext;
@@ -12,11 +19,17 @@ use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
 use std::any::TypeId;
 use std::io::Cursor;
 use std::sync::{Arc, RwLock};
-use log::error;
+use crate::core::ig_custom::igNull;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
 use crate::core::meta::field::r#impl::ig_size_type_meta_field::igSizeTypeMetaField;
 
 pub struct igObjectRefMetaField;
 
+/// Stands in for a reference that failed to resolve when loading leniently
+fn null_placeholder() -> igObject {
+    Arc::new(RwLock::new(igNull))
+}
+
 impl igMetaField for igObjectRefMetaField {
     fn type_id(&self) -> TypeId {
         TypeId::of::<igObject>()
@@ -44,24 +57,46 @@ impl igMetaField for igObjectRefMetaField {
 
         let is_offset = ctx.runtime_fields.offsets.binary_search(&base_offset).is_ok();
         if is_offset {
-            return Some(Arc::new(RwLock::new(ctx.offset_object_list[&raw].clone())));
+            return if let Some(obj) = ctx.offset_object_list.get(&raw) {
+                Some(Arc::new(RwLock::new(obj.clone())))
+            } else {
+                ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("No object exists at offset {:#X}", raw));
+                Some(Arc::new(RwLock::new(null_placeholder())))
+            };
         }
         let is_named_external = ctx.runtime_fields.named_externals.binary_search(&base_offset).is_ok();
         if is_named_external {
-            return Some(Arc::new(RwLock::new(ctx.named_external_list[(raw & 0x7FFFFFFF) as usize].clone())));
+            return if let Some(obj) = ctx.named_external_list.get((raw & 0x7FFFFFFF) as usize).cloned() {
+                if obj.read().unwrap().as_any().is::<igNull>() {
+                    ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("Named external {} failed to resolve", raw & 0x7FFFFFFF));
+                }
+                Some(Arc::new(RwLock::new(obj)))
+            } else {
+                ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Named external index {} is out of range", raw & 0x7FFFFFFF));
+                Some(Arc::new(RwLock::new(null_placeholder())))
+            };
         }
         let is_exid = ctx.runtime_fields.externals.binary_search(&base_offset).is_ok();
         if is_exid {
-            return if let Some(obj) = ctx.external_list[(raw & 0x7FFFFFFF) as usize].get_object_alias(object_stream_manager) {
+            let obj = ctx
+                .external_list
+                .get((raw & 0x7FFFFFFF) as usize)
+                .and_then(|handle| handle.write().unwrap().get_object_alias(object_stream_manager));
+            return if let Some(obj) = obj {
                 Some(Arc::new(RwLock::new(obj)))
             } else {
-                None
+                ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("External {} failed to resolve", raw & 0x7FFFFFFF));
+                if ctx.lenient {
+                    Some(Arc::new(RwLock::new(null_placeholder())))
+                } else {
+                    None
+                }
             };
         }
         if raw != 0 {
             // the value should not be null, but we couldn't determine what it actually was.
-            error!("Failed to read igObjectRefMetaField properly");
-            panic!("Alchemy Error! Check the logs.");
+            ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, "Failed to read igObjectRefMetaField properly".to_string());
+            return Some(Arc::new(RwLock::new(null_placeholder())));
         }
 
         None
@@ -69,10 +104,10 @@ impl igMetaField for igObjectRefMetaField {
 
     fn value_into_igz(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
         _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError> {
@@ -83,24 +118,85 @@ impl igMetaField for igObjectRefMetaField {
         &self,
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
-        _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
-        _ctx: &mut IgxLoaderContext,
+        ctx: &mut IgxLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        let text = igx_read_text(handle)?;
+        let text = text.trim();
+
+        if let Some(id) = text.strip_prefix('@') {
+            return match id.parse::<u32>().ok().and_then(|id| ctx.objects.get(&id)) {
+                Some(obj) => Some(Arc::new(RwLock::new(obj.clone()))),
+                None => {
+                    ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("igx references the object {} which doesn't exist", text));
+                    None
+                }
+            };
+        }
+
+        if let Some((namespace, name)) = text.split_once("::") {
+            let dependency_handle_name = igHandleName::new(igName::new(name.to_string()), igName::new(namespace.to_string()));
+            let resolved = ctx
+                .handle_manager
+                .as_deref_mut()
+                .and_then(|manager| manager.resolve(dependency_handle_name.namespace.clone(), dependency_handle_name.name.clone()));
+            if let Some(obj) = resolved {
+                return Some(Arc::new(RwLock::new(obj)));
+            }
+
+            // Directories loaded without going through the handle manager can still be searched by name
+            let obj = igHandle::from_handle_name(&dependency_handle_name)
+                .write()
+                .unwrap()
+                .get_object_alias(object_stream_manager);
+            return match obj {
+                Some(obj) => {
+                    warn!("{} has no handle. It was found by searching the directories loaded into {}", text, namespace);
+                    Some(Arc::new(RwLock::new(obj)))
+                }
+                None => {
+                    ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("igx failed to resolve the external reference {}", text));
+                    None
+                }
+            };
+        }
+
+        ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("\"{}\" is not a valid igx object reference", text));
+        None
     }
 
     fn value_into_igx(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
-        _ctx: &mut IgxSaverContext,
+        ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError> {
-        todo!()
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let obj = guard.downcast_ref::<igObject>().ok_or(IgxSaverError::InvalidValueType(Arc::from("igObject")))?;
+
+        if let Some(name) = ctx.external_name(obj) {
+            igx_write_text(handle, &name);
+            return Ok(());
+        }
+
+        let object_name = obj.read().unwrap().object_name();
+        if obj.read().unwrap().as_any().is::<igNull>() {
+            error!("Cannot write a reference to an object that failed to load");
+            return Err(IgxSaverError::UnresolvedReference(object_name));
+        }
+
+        let id = ctx.object_id(obj);
+        igx_write_raw(handle, &format!("@{}", id));
+        Ok(())
     }
 
     fn value_from_igb(
@@ -108,22 +204,51 @@ impl igMetaField for igObjectRefMetaField {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgbLoaderContext,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        // igb stores object references as an index into its directory
+        let index = read_i32(handle, endian).unwrap();
+        if index < 0 {
+            return None;
+        }
+
+        if let Some(obj) = ctx.objects.get(&(index as u32)).cloned() {
+            if obj.read().unwrap().as_any().is::<igNull>() {
+                ctx.report(igLoadDiagnosticKind::UnresolvedReference, format!("Directory entry {} failed to load", index));
+            }
+            return Some(Arc::new(RwLock::new(obj)));
+        }
+
+        ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Directory entry {} is not an object", index));
+        Some(Arc::new(RwLock::new(null_placeholder())))
     }
 
     fn value_into_igb(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgbSaverContext,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError> {
-        todo!()
+        let Some(value) = value else {
+            write_i32(handle, endian, -1).unwrap();
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let obj = guard.downcast_ref::<igObject>().ok_or(IgbSaverError::InvalidValueType(Arc::from("igObject")))?;
+
+        let object_name = obj.read().unwrap().object_name();
+        if obj.read().unwrap().as_any().is::<igNull>() {
+            error!("Cannot write a reference to an object that failed to load");
+            return Err(IgbSaverError::UnresolvedReference(object_name));
+        }
+
+        let index = ctx.object_index(obj);
+        write_i32(handle, endian, index as i32).unwrap();
+        Ok(())
     }
 }
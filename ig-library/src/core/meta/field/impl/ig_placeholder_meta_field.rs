@@ -1,13 +1,17 @@
+use std::io::Write;
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw};
+use crate::core::load::ig_igx_loader::igx_read_hex;
 use crate::core::ig_fs::Endian;
 use crate::core::ig_objects::{igAny, igObjectStreamManager};
 use crate::core::load::ig_igb_loader::IgbLoaderContext;
 use crate::core::load::ig_igx_loader::IgxLoaderContext;
 use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
 use crate::core::meta::field::ig_metafields::igMetaField;
 use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
 use crate::core::save::ig_igx_saver::{IgxSaverContext, IgxSaverError};
 use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
-use log::{error, warn};
+use log::warn;
 use std::any::TypeId;
 use std::io::{Cursor, Read};
 use std::sync::{Arc, RwLock};
@@ -32,29 +36,31 @@ impl igMetaField for igPlaceholderMetafield {
         _object_stream_manager: &igObjectStreamManager,
         handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
-        _ctx: &mut IgzLoaderContext,
+        ctx: &mut IgzLoaderContext,
     ) -> Option<igAny> {
         warn!("{} has no implementation. Using igPlaceholderMetafield. Harass hydos to implement this or make a PR!", self.missing_impl_name);
-        let mut fake_buffer = Vec::with_capacity(self.size as usize);
+        ctx.report(igLoadDiagnosticKind::MissingMetaFieldImpl, format!("{} has no implementation", self.missing_impl_name));
+        let mut fake_buffer = vec![0u8; self.size as usize];
         handle.read_exact(&mut fake_buffer).unwrap();
         Some(Arc::new(RwLock::new(fake_buffer)))
     }
 
     fn value_into_igz(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError> {
-        todo!()
-        // let fake_buffer = Vec::with_capacity(self.platform_size(ctx.platform.clone()) as usize);
-        // handle
-        //     .write(fake_buffer.as_slice())
-        //     .map_err(|_e| IgzSaverError::Unknown)
-        //     .map(|_t| {})
+        // The bytes were read without knowing what they are, so they are written back exactly as they were
+        let Some(value) = value else {
+            return handle.write_all(&vec![0u8; self.size as usize]).map_err(IgzSaverError::Io);
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Vec<u8>>().ok_or(IgzSaverError::InvalidValueType(Arc::from("Vec<u8>")))?;
+        handle.write_all(value).map_err(IgzSaverError::Io)
     }
 
     fn value_from_igx(
@@ -62,26 +68,39 @@ impl igMetaField for igPlaceholderMetafield {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
-        _ctx: &mut IgxLoaderContext,
+        ctx: &mut IgxLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        warn!("{} has no implementation. Using igPlaceholderMetafield. Harass hydos to implement this or make a PR!", self.missing_impl_name);
+        match igx_read_hex(handle)? {
+            Ok(buffer) => Some(Arc::new(RwLock::new(buffer))),
+            Err(_) => {
+                ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("igx value for {} is not valid hex", self.missing_impl_name));
+                None
+            }
+        }
     }
 
     fn value_into_igx(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError> {
-        error!(
-            "Using igPlaceholderMetafield for saving is not supported. Implement the metafield!"
-        );
-        panic!("Alchemy Error! Check the logs.")
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Vec<u8>>().ok_or(IgxSaverError::InvalidValueType(Arc::from("Vec<u8>")))?;
+        for byte in value {
+            igx_write_raw(handle, &format!("{:02X}", byte));
+        }
+        Ok(())
     }
 
     fn value_from_igb(
@@ -89,26 +108,34 @@ impl igMetaField for igPlaceholderMetafield {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
-        _ctx: &mut IgbLoaderContext,
+        ctx: &mut IgbLoaderContext,
     ) -> Option<igAny> {
-        error!("Using igPlaceholderMetafield not supported. Implement the metafield!");
-        panic!("Alchemy Error! Check the logs.")
+        warn!("{} has no implementation. Using igPlaceholderMetafield. Harass hydos to implement this or make a PR!", self.missing_impl_name);
+        ctx.report(igLoadDiagnosticKind::MissingMetaFieldImpl, format!("{} has no implementation", self.missing_impl_name));
+        // the igb knows the size of the field better than our metadata does
+        let mut fake_buffer = vec![0u8; ctx.field_size as usize];
+        handle.read_exact(&mut fake_buffer).unwrap();
+        Some(Arc::new(RwLock::new(fake_buffer)))
     }
 
     fn value_into_igb(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError> {
-        error!(
-            "Using igPlaceholderMetafield for saving is not supported. Implement the metafield!"
-        );
-        panic!("Alchemy Error! Check the logs.")
+        let Some(value) = value else {
+            handle.write_all(&vec![0u8; self.size as usize]).unwrap();
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Vec<u8>>().ok_or(IgbSaverError::InvalidValueType(Arc::from("Vec<u8>")))?;
+        handle.write_all(value).unwrap();
+        Ok(())
     }
 }
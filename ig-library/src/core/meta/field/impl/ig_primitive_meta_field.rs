@@ -0,0 +1,297 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::util::byteorder_fixes::{read_u16, read_u32, read_u64, read_u8, write_u16, write_u32, write_u64, write_u8};
+use log::warn;
+use std::any::TypeId;
+use std::fmt::Display;
+use std::io::{Cursor, ErrorKind};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// A rust type a primitive metafield stores its value as. Values are converted to and from the raw bits stored in the file so the size on disk can differ from the size of the rust type
+pub(crate) trait igPrimitive: Copy + Default + Display + FromStr + Send + Sync + 'static {
+    /// Name of the rust type. Used when reporting a value of the wrong type
+    const TYPE_NAME: &'static str;
+
+    fn from_raw(raw: u64, size: u32) -> Self;
+    fn into_raw(self, size: u32) -> u64;
+}
+
+/// Sign extends the low `size` bytes of `raw`
+fn sign_extend(raw: u64, size: u32) -> i64 {
+    let shift = 64 - size * 8;
+    ((raw << shift) as i64) >> shift
+}
+
+macro_rules! define_unsigned_primitive {
+    ($($type:ty),*) => {
+        $(
+            impl igPrimitive for $type {
+                const TYPE_NAME: &'static str = stringify!($type);
+
+                fn from_raw(raw: u64, _size: u32) -> Self {
+                    raw as $type
+                }
+
+                fn into_raw(self, _size: u32) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! define_signed_primitive {
+    ($($type:ty),*) => {
+        $(
+            impl igPrimitive for $type {
+                const TYPE_NAME: &'static str = stringify!($type);
+
+                fn from_raw(raw: u64, size: u32) -> Self {
+                    sign_extend(raw, size) as $type
+                }
+
+                fn into_raw(self, _size: u32) -> u64 {
+                    self as i64 as u64
+                }
+            }
+        )*
+    };
+}
+
+define_unsigned_primitive!(u8, u16, u32, u64);
+define_signed_primitive!(i8, i16, i32, i64);
+
+impl igPrimitive for bool {
+    const TYPE_NAME: &'static str = "bool";
+
+    fn from_raw(raw: u64, _size: u32) -> Self {
+        raw != 0
+    }
+
+    fn into_raw(self, _size: u32) -> u64 {
+        self as u64
+    }
+}
+
+impl igPrimitive for f32 {
+    const TYPE_NAME: &'static str = "f32";
+
+    fn from_raw(raw: u64, size: u32) -> Self {
+        match size {
+            8 => f64::from_bits(raw) as f32,
+            _ => f32::from_bits(raw as u32),
+        }
+    }
+
+    fn into_raw(self, size: u32) -> u64 {
+        match size {
+            8 => (self as f64).to_bits(),
+            _ => self.to_bits() as u64,
+        }
+    }
+}
+
+impl igPrimitive for f64 {
+    const TYPE_NAME: &'static str = "f64";
+
+    fn from_raw(raw: u64, size: u32) -> Self {
+        match size {
+            4 => f32::from_bits(raw as u32) as f64,
+            _ => f64::from_bits(raw),
+        }
+    }
+
+    fn into_raw(self, size: u32) -> u64 {
+        match size {
+            4 => (self as f32).to_bits() as u64,
+            _ => self.to_bits(),
+        }
+    }
+}
+
+/// Shared implementation of every metafield that stores a single number or bool. The size is taken from metafields.xml for the platform being loaded, so a platform storing a type wider or narrower than usual still reads correctly
+pub(crate) struct igPrimitiveMetaField<T: igPrimitive> {
+    /// Size on disk in bytes
+    pub size: u32,
+    _type: PhantomData<T>,
+}
+
+pub(crate) type igBoolMetaField = igPrimitiveMetaField<bool>;
+pub(crate) type igCharMetaField = igPrimitiveMetaField<i8>;
+pub(crate) type igUnsignedCharMetaField = igPrimitiveMetaField<u8>;
+pub(crate) type igShortMetaField = igPrimitiveMetaField<i16>;
+pub(crate) type igUnsignedShortMetaField = igPrimitiveMetaField<u16>;
+pub(crate) type igUnsignedIntMetaField = igPrimitiveMetaField<u32>;
+pub(crate) type igLongMetaField = igPrimitiveMetaField<i64>;
+pub(crate) type igUnsignedLongMetaField = igPrimitiveMetaField<u64>;
+pub(crate) type igFloatMetaField = igPrimitiveMetaField<f32>;
+pub(crate) type igDoubleMetaField = igPrimitiveMetaField<f64>;
+
+impl<T: igPrimitive> igPrimitiveMetaField<T> {
+    /// Creates the metafield for `type_name` using its size on the targeted platform. Falls back to the size of `T` when metafields.xml doesn't describe the type
+    pub fn new(type_name: &str, metadata_manager: &igMetadataManager) -> Self {
+        let size = metadata_manager.platform_size(type_name).unwrap_or_else(|| {
+            warn!("{} has no platform info. Assuming it is {} bytes", type_name, size_of::<T>());
+            size_of::<T>() as u32
+        });
+
+        igPrimitiveMetaField::with_size(size)
+    }
+
+    /// Creates the metafield with a size that is already known
+    pub fn with_size(size: u32) -> Self {
+        igPrimitiveMetaField {
+            size,
+            _type: PhantomData,
+        }
+    }
+
+    pub fn read(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> std::io::Result<T> {
+        let raw = match self.size {
+            1 => read_u8(handle, endian)? as u64,
+            2 => read_u16(handle, endian)? as u64,
+            4 => read_u32(handle, endian)? as u64,
+            8 => read_u64(handle, endian)?,
+            size => return Err(std::io::Error::new(ErrorKind::InvalidData, format!("{} can't be stored in {} bytes", T::TYPE_NAME, size))),
+        };
+        Ok(T::from_raw(raw, self.size))
+    }
+
+    pub fn write(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian, value: T) -> std::io::Result<()> {
+        let raw = value.into_raw(self.size);
+        match self.size {
+            1 => write_u8(handle, endian, raw as u8),
+            2 => write_u16(handle, endian, raw as u16),
+            4 => write_u32(handle, endian, raw as u32),
+            8 => write_u64(handle, endian, raw),
+            size => Err(std::io::Error::new(ErrorKind::InvalidData, format!("{} can't be stored in {} bytes", T::TYPE_NAME, size))),
+        }
+    }
+
+    /// Reads the value as an [igAny]. The error is the message to report as [igLoadDiagnosticKind::FieldDecodeFailed]
+    fn read_value(&self, handle: &mut Cursor<Vec<u8>>, endian: Endian) -> Result<igAny, String> {
+        self.read(handle, endian)
+            .map(|value| Arc::new(RwLock::new(value)) as igAny)
+            .map_err(|e| format!("Failed to read {}: {}", T::TYPE_NAME, e))
+    }
+
+    /// Returns the value as `T`, or [None] when it is stored as another type
+    fn downcast(value: &igAny) -> Option<T> {
+        value.read().unwrap().downcast_ref::<T>().copied()
+    }
+}
+
+impl<T: igPrimitive> igMetaField for igPrimitiveMetaField<T> {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let value = match value {
+            Some(value) => Self::downcast(&value).ok_or(IgzSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?,
+            None => T::default(),
+        };
+        self.write(handle, endian, value).map_err(IgzSaverError::Io)
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let text = igx_read_text(handle)?;
+        match text.trim().parse::<T>() {
+            Ok(value) => Some(Arc::new(RwLock::new(value))),
+            Err(_) => {
+                ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to parse igx value \"{}\" as {}", text, T::TYPE_NAME));
+                None
+            }
+        }
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let value = Self::downcast(&value).ok_or(IgxSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?;
+        igx_write_raw(handle, &value.to_string());
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        self.read_value(handle, endian)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        let value = match value {
+            Some(value) => Self::downcast(&value).ok_or(IgbSaverError::InvalidValueType(Arc::from(T::TYPE_NAME)))?,
+            None => T::default(),
+        };
+        self.write(handle, endian, value).map_err(IgbSaverError::Io)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_memory::igMemoryPool;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_text, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::memory::igRawRef;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_text, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::util::byteorder_fixes::{read_ptr, write_ptr};
+use log::warn;
+use std::any::TypeId;
+use std::io::{Cursor, Read, Write};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+/// A pointer to data the metadata doesn't describe, such as buffers handed straight to the GPU. The pointer is kept as a location in the file rather than followed.
+///
+/// The value is an [igRawRef]. In igx files raw refs are written as `Pool:0xOFFSET`, with the offset relative to the pool's section
+pub(crate) struct igRawRefMetaField {
+    pub size: u32,
+}
+
+impl igMetaField for igRawRefMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<igRawRef>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let raw = match read_ptr(handle, ctx.platform.clone(), endian) {
+            Ok(raw) => raw,
+            Err(e) => {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read a raw reference: {}", e));
+                return None;
+            }
+        };
+        if raw == 0 {
+            return None;
+        }
+
+        if ctx.deserialize_offset(raw).is_none() {
+            ctx.report_failure(igLoadDiagnosticKind::UnresolvedReference, format!("Raw reference {:#X} points into a section that doesn't exist", raw));
+            return None;
+        }
+
+        Some(Arc::new(RwLock::new(igRawRef {
+            offset: ctx.get_offset_in_section(raw),
+            pool: ctx.get_pool_from_serialized_offset(raw),
+        })))
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        let Some(value) = value else {
+            return write_ptr(handle, ctx.platform.clone(), endian, 0).map_err(IgzSaverError::Io);
+        };
+        let guard = value.read().unwrap();
+        let raw_ref = guard.downcast_ref::<igRawRef>().ok_or(IgzSaverError::InvalidValueType(Arc::from("igRawRef")))?;
+        let serialized = ctx.serialize_offset(raw_ref.pool, raw_ref.offset).ok_or(IgzSaverError::MissingSection(raw_ref.pool))?;
+
+        ctx.offsets.push(handle.position());
+        write_ptr(handle, ctx.platform.clone(), endian, serialized).map_err(IgzSaverError::Io)
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let text = igx_read_text(handle)?;
+        let text = text.trim();
+        let parsed = text.split_once(":0x").and_then(|(pool, offset)| {
+            Some(igRawRef {
+                offset: u64::from_str_radix(offset, 16).ok()?,
+                pool: igMemoryPool::from_str(pool).ok()?,
+            })
+        });
+        let Some(raw_ref) = parsed else {
+            ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("\"{}\" is not a valid igx raw reference", text));
+            return None;
+        };
+
+        Some(Arc::new(RwLock::new(raw_ref)))
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let raw_ref = guard.downcast_ref::<igRawRef>().ok_or(IgxSaverError::InvalidValueType(Arc::from("igRawRef")))?;
+        igx_write_text(handle, &format!("{:?}:0x{:X}", raw_ref.pool, raw_ref.offset));
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        // igb files are written without fixups, so there is nothing to tell a raw pointer apart from any other value
+        let mut buffer = vec![0u8; ctx.field_size as usize];
+        if let Err(e) = handle.read_exact(&mut buffer) {
+            ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read a raw reference: {}", e));
+            return None;
+        }
+        if buffer.iter().any(|byte| *byte != 0) {
+            ctx.report(igLoadDiagnosticKind::UnresolvedReference, "igb files can't store raw references. The reference is left null".to_string());
+        }
+        None
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        if value.is_some() {
+            warn!("igb files can't store raw references. The reference is written as null");
+        }
+        handle.write_all(&vec![0u8; self.size as usize]).map_err(IgbSaverError::Io)
+    }
+}
@@ -1,8 +1,14 @@
+use std::io::Write;
+use crate::util::byteorder_fixes::write_u32;
+use crate::util::byteorder_fixes::{read_struct_array_u8, read_u32};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_text};
+use crate::core::load::ig_igx_loader::igx_read_text;
 use crate::core::ig_fs::Endian;
 use crate::core::ig_objects::{igAny, igObjectStreamManager};
 use crate::core::load::ig_igb_loader::IgbLoaderContext;
 use crate::core::load::ig_igx_loader::IgxLoaderContext;
 use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
 use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
 use crate::core::meta::field::ig_metafields::igMetaField;
 use crate::core::meta::ig_metadata_manager::igMetadataManager;
@@ -42,11 +48,21 @@ impl igMetaField for igStringMetaField {
         let mut result: Option<String> = None;
 
         if is_ref {
-            let offset = ctx.deserialize_offset(raw);
-            handle.set_position(offset);
-            result = Some(read_string(handle).unwrap());
+            if let Some(offset) = ctx.deserialize_offset(raw) {
+                handle.set_position(offset);
+                match read_string(handle) {
+                    Ok(string) => result = Some(string),
+                    Err(e) => ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read string reference: {}", e)),
+                }
+            } else {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("String reference {:#X} points into a section that doesn't exist", raw));
+            }
         } else if is_table {
-            result = Some(ctx.string_list[raw as usize].clone());
+            if let Some(string) = ctx.string_list.get(raw as usize) {
+                result = Some(string.clone());
+            } else {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("String table index {} is out of range", raw));
+            }
         }
 
         handle.set_position(base_pos + ctx.platform.get_pointer_size() as u64);
@@ -64,10 +80,10 @@ impl igMetaField for igStringMetaField {
 
     fn value_into_igz(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
         _handle: &mut Cursor<Vec<u8>>,
+        _value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgzSaverContext,
     ) -> Result<(), IgzSaverError> {
@@ -79,23 +95,30 @@ impl igMetaField for igStringMetaField {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
         _endian: Endian,
         _ctx: &mut IgxLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        igx_read_text(handle).map(|text| Arc::new(RwLock::new(Arc::<str>::from(text))) as igAny)
     }
 
     fn value_into_igx(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
         _endian: Endian,
         _ctx: &mut IgxSaverContext,
     ) -> Result<(), IgxSaverError> {
-        todo!()
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Arc<str>>().ok_or(IgxSaverError::InvalidValueType(Arc::from("Arc<str>")))?;
+        igx_write_text(handle, value);
+        Ok(())
     }
 
     fn value_from_igb(
@@ -103,22 +126,47 @@ impl igMetaField for igStringMetaField {
         _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
-        _ctx: &mut IgbLoaderContext,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
     ) -> Option<igAny> {
-        todo!()
+        // igb stores strings inline as a length (including the null terminator) followed by the characters
+        let length = read_u32(handle, endian.clone()).unwrap();
+        if length == 0 {
+            return None;
+        }
+
+        match read_struct_array_u8(handle, endian, length as usize) {
+            Ok(bytes) => {
+                let end = bytes.iter().position(|byte| *byte == 0).unwrap_or(bytes.len());
+                let string: Arc<str> = Arc::from(String::from_utf8_lossy(&bytes[..end]).as_ref());
+                Some(Arc::new(RwLock::new(string)))
+            }
+            Err(e) => {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read igb string: {}", e));
+                None
+            }
+        }
     }
 
     fn value_into_igb(
         &self,
-        _registry: &igMetafieldRegistry,
         _metadata_manager: &igMetadataManager,
         _object_stream_manager: &igObjectStreamManager,
-        _handle: &mut Cursor<Vec<u8>>,
-        _endian: Endian,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
         _ctx: &mut IgbSaverContext,
     ) -> Result<(), IgbSaverError> {
-        todo!()
+        let Some(value) = value else {
+            write_u32(handle, endian, 0).unwrap();
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Arc<str>>().ok_or(IgbSaverError::InvalidValueType(Arc::from("Arc<str>")))?;
+        write_u32(handle, endian, value.len() as u32 + 1).unwrap();
+        handle.write_all(value.as_bytes()).unwrap();
+        handle.write_all(&[0]).unwrap();
+        Ok(())
     }
 }
@@ -0,0 +1,145 @@
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::{igx_read_hex, IgxLoaderContext};
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_null, igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use log::warn;
+use std::any::TypeId;
+use std::io::{Cursor, Read, Write};
+use std::sync::{Arc, RwLock};
+
+/// A native struct embedded in an object. The metadata only describes its size (the `typeSize` of the field) and not what it contains, so the value is the raw bytes of the struct.
+///
+/// In igx files the bytes are written as hex
+pub(crate) struct igStructMetaField {
+    /// Size of the struct in bytes
+    pub size: u32,
+}
+
+impl igStructMetaField {
+    fn read(&self, handle: &mut Cursor<Vec<u8>>) -> Result<igAny, String> {
+        let mut buffer = vec![0u8; self.size as usize];
+        handle.read_exact(&mut buffer).map_err(|e| format!("Failed to read a struct of {} bytes: {}", self.size, e))?;
+        Ok(Arc::new(RwLock::new(buffer)))
+    }
+
+    /// Writes the bytes of the struct, padding or cutting them to the size of the struct so the fields after it stay in place
+    fn write(&self, handle: &mut Cursor<Vec<u8>>, value: Option<igAny>) -> Result<(), Arc<str>> {
+        let mut buffer = vec![0u8; self.size as usize];
+        if let Some(value) = value {
+            let guard = value.read().unwrap();
+            let bytes = guard.downcast_ref::<Vec<u8>>().ok_or(Arc::from("Vec<u8>"))?;
+            if bytes.len() != buffer.len() {
+                warn!("Writing {} bytes to a struct of {} bytes", bytes.len(), self.size);
+            }
+            let len = bytes.len().min(buffer.len());
+            buffer[..len].copy_from_slice(&bytes[..len]);
+        }
+        handle.write_all(&buffer).unwrap();
+        Ok(())
+    }
+}
+
+impl igMetaField for igStructMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<Vec<u8>>()
+    }
+
+    fn value_from_igz(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        self.read(handle)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igz(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        self.write(handle, value).map_err(IgzSaverError::InvalidValueType)
+    }
+
+    fn value_from_igx(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        match igx_read_hex(handle)? {
+            Ok(buffer) => Some(Arc::new(RwLock::new(buffer))),
+            Err(text) => {
+                ctx.report(igLoadDiagnosticKind::FieldDecodeFailed, format!("igx value \"{}\" of an igStructMetaField is not valid hex", text));
+                None
+            }
+        }
+    }
+
+    fn value_into_igx(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        let Some(value) = value else {
+            igx_write_null(handle);
+            return Ok(());
+        };
+        let guard = value.read().unwrap();
+        let value = guard.downcast_ref::<Vec<u8>>().ok_or(IgxSaverError::InvalidValueType(Arc::from("Vec<u8>")))?;
+        for byte in value {
+            igx_write_raw(handle, &format!("{:02X}", byte));
+        }
+        Ok(())
+    }
+
+    fn value_from_igb(
+        &self,
+        _registry: &igMetafieldRegistry,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        _endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        self.read(handle)
+            .map_err(|message| ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message))
+            .ok()
+    }
+
+    fn value_into_igb(
+        &self,
+        _metadata_manager: &igMetadataManager,
+        _object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        _endian: Endian,
+        _ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        self.write(handle, value).map_err(IgbSaverError::InvalidValueType)
+    }
+}
@@ -0,0 +1,176 @@
+use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igAny, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::IgbLoaderContext;
+use crate::core::load::ig_igx_loader::IgxLoaderContext;
+use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
+use crate::core::memory::igMemory;
+use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::meta::field::r#impl::ig_memory_ref_meta_field::igMemoryRefMetaField;
+use crate::core::meta::ig_metadata_manager::{igMetaFieldInfo, igMetadataManager};
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{IgxSaverContext, IgxSaverError};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::util::byteorder_fixes::{read_ptr, write_ptr};
+use log::error;
+use std::any::TypeId;
+use std::io::Cursor;
+use std::sync::Arc;
+
+/// An igVector is a count followed by an igMemory holding the elements. The memory can be larger than the count (the capacity), only the first `count` elements are alive.
+///
+/// The value is an [igMemory] holding only the alive elements, so it can be used the same way as the value of an igMemoryRefMetaField. igx and igb don't store the count, it is the length of the memory
+pub(crate) struct igVectorMetaField {
+    /// Reads and writes the memory. The element type comes from the vector's [VectorInfo](crate::core::meta::ig_xml_metadata::VectorInfo)
+    memory: igMemoryRefMetaField,
+    /// Multiple applied to the alignment of the elements. [None] when metaobjects.xml doesn't specify one
+    mem_type_alignment_multiple: Option<u8>,
+}
+
+impl igVectorMetaField {
+    pub fn new(field: Arc<igMetaFieldInfo>, metadata_manager: &igMetadataManager, platform: IG_CORE_PLATFORM) -> igVectorMetaField {
+        let name = field.name.clone().unwrap_or(Arc::from("(unnamed)"));
+        let Some(vector_info) = field.ark_info.read().unwrap().ig_vector_info.clone() else {
+            error!("The vector {} has no vector info", name);
+            panic!("Alchemy Error! Check the logs.")
+        };
+        let Some(element) = vector_info.field else {
+            error!("The vector {} doesn't say what type its elements are", name);
+            panic!("Alchemy Error! Check the logs.")
+        };
+
+        igVectorMetaField {
+            memory: igMemoryRefMetaField(Arc::new(metadata_manager.inner_field_info(&element, platform))),
+            mem_type_alignment_multiple: Some(vector_info.mem_type_alignment_multiple).filter(|x| *x != u8::MAX),
+        }
+    }
+
+    /// Drops the elements past `count`. Fails when the memory doesn't hold `count` elements
+    fn trim(&self, value: &igAny, count: u64) -> Result<(), String> {
+        let mut guard = value.write().unwrap();
+        let Some(memory) = guard.downcast_mut::<igMemory<igAny>>() else {
+            return Ok(());
+        };
+        // Memory living in a runtime pool isn't read, there is nothing to compare against
+        if memory.data.is_empty() {
+            return Ok(());
+        }
+
+        if count > memory.data.len() as u64 {
+            return Err(format!("igVector has a count of {} but its memory only holds {} elements", count, memory.data.len()));
+        }
+        memory.data.truncate(count as usize);
+        Ok(())
+    }
+
+    /// Applies the alignment multiple from metaobjects.xml to memory that was read without flags
+    fn apply_alignment(&self, value: Option<igAny>) -> Option<igAny> {
+        if let (Some(value), Some(multiple)) = (&value, self.mem_type_alignment_multiple) {
+            if let Some(memory) = value.write().unwrap().downcast_mut::<igMemory<igAny>>() {
+                memory.alignment_multiple = multiple as u32;
+            }
+        }
+        value
+    }
+
+    fn count(value: &Option<igAny>) -> u64 {
+        value
+            .as_ref()
+            .and_then(|value| value.read().unwrap().downcast_ref::<igMemory<igAny>>().map(|memory| memory.data.len() as u64))
+            .unwrap_or(0)
+    }
+}
+
+impl igMetaField for igVectorMetaField {
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<igMemory<igAny>>()
+    }
+
+    fn value_from_igz(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgzLoaderContext,
+    ) -> Option<igAny> {
+        let count = match read_ptr(handle, ctx.platform.clone(), endian.clone()) {
+            Ok(count) => count,
+            Err(e) => {
+                ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read the count of an igVector: {}", e));
+                return None;
+            }
+        };
+        let value = self.memory.value_from_igz(registry, metadata_manager, object_stream_manager, handle, endian, ctx)?;
+        if let Err(message) = self.trim(&value, count) {
+            ctx.report_failure(igLoadDiagnosticKind::FieldDecodeFailed, message);
+        }
+        Some(value)
+    }
+
+    fn value_into_igz(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgzSaverContext,
+    ) -> Result<(), IgzSaverError> {
+        write_ptr(handle, ctx.platform.clone(), endian.clone(), Self::count(&value)).map_err(IgzSaverError::Io)?;
+        self.memory.value_into_igz(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+
+    fn value_from_igx(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgxLoaderContext,
+    ) -> Option<igAny> {
+        let value = self.memory.value_from_igx(registry, metadata_manager, object_stream_manager, handle, endian, ctx);
+        self.apply_alignment(value)
+    }
+
+    fn value_into_igx(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgxSaverContext,
+    ) -> Result<(), IgxSaverError> {
+        self.memory.value_into_igx(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+
+    fn value_from_igb(
+        &self,
+        registry: &igMetafieldRegistry,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+    ) -> Option<igAny> {
+        let value = self.memory.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian, ctx);
+        self.apply_alignment(value)
+    }
+
+    fn value_into_igb(
+        &self,
+        metadata_manager: &igMetadataManager,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        value: Option<igAny>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+    ) -> Result<(), IgbSaverError> {
+        self.memory.value_into_igb(metadata_manager, object_stream_manager, handle, value, endian, ctx)
+    }
+}
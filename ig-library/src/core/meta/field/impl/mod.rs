@@ -3,4 +3,15 @@ pub(crate) mod ig_string_meta_field;
 pub(crate) mod ig_memory_ref_meta_field;
 pub(crate) mod ig_object_ref_meta_field;
 pub(crate) mod ig_size_type_meta_field;
-pub(crate) mod ig_int_meta_field;
\ No newline at end of file
+pub(crate) mod ig_int_meta_field;
+pub(crate) mod ig_primitive_meta_field;
+pub mod ig_enum_meta_field;
+pub(crate) mod ig_bit_field_meta_field;
+pub(crate) mod ig_math_meta_field;
+pub(crate) mod ig_vector_meta_field;
+pub(crate) mod ig_struct_meta_field;
+pub(crate) mod ig_compound_meta_field;
+pub(crate) mod ig_array_meta_field;
+pub(crate) mod ig_handle_meta_field;
+pub(crate) mod ig_memory_ref_handle_meta_field;
+pub(crate) mod ig_raw_ref_meta_field;
\ No newline at end of file
@@ -1,12 +1,19 @@
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
-use crate::core::ig_custom::{igNameList, igObjectList, igStringRefList};
+use crate::core::ig_custom::{data_list_constructor, hash_table_constructor, igNameList, igObjectList, igStringRefList};
 use crate::core::ig_fs::Endian;
 use crate::core::ig_memory::igMemoryPool;
-use crate::core::ig_objects::{igAny, igObjectStreamManager, ObjectExt};
+use crate::core::ig_objects::{igAny, igObject, igObjectStreamManager, ObjectExt};
+use crate::core::load::ig_igb_loader::{igIGBMetaObjectField, IgbLoaderContext};
+use crate::core::load::ig_igx_loader::IgxLoaderContext;
 use crate::core::load::ig_igz_loader::IgzLoaderContext;
+use crate::core::load::ig_loader::igLoadDiagnosticKind;
 use crate::core::meta::field::ig_metafield_registry::igMetafieldRegistry;
-use crate::core::meta::ig_xml_metadata::{ArcMetaEnum, ArcMetaField, ArkMetaObjectField, MetaObject, RawArkMetaObjectField};
-use log::{debug, error, info};
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::save::ig_igb_saver::{IgbSaverContext, IgbSaverError};
+use crate::core::save::ig_igx_saver::{igx_write_raw, IgxSaverContext, IgxSaverError};
+use crate::util::byteorder_fixes::read_struct_array_u8;
+use crate::core::meta::ig_xml_metadata::{ArcMetaEnum, ArcMetaField, ArkMetaObjectField, HashTableInfo, MetaObject, RawArkMetaObjectField};
+use log::{debug, error, info, warn};
 use phf::phf_map;
 use std::any::Any;
 use std::collections::HashMap;
@@ -18,7 +25,7 @@ use std::time::Instant;
 use std::todo;
 use strum_macros::Display;
 
-type MetaObjectConstructor = fn(
+pub(crate) type MetaObjectConstructor = fn(
     ig_meta_object: &igMetaObject,
     pool: igMemoryPool,
 ) -> Result<Arc<RwLock<dyn __internalObjectBase>>, igMetaInstantiationError>;
@@ -33,6 +40,9 @@ pub static TYPE_TO_METAOBJECT_LOOKUP: phf::Map<
     "igNameList"            => igNameList::construct,
 };
 
+/// The fields of a type in the order they are read from igz files, each with the metafield reading it
+type IgzFieldPlan = Arc<[(Arc<igMetaFieldInfo>, Arc<dyn igMetaField>)]>;
+
 /// Fast structure used to manage and create new instances of metaobjects, metafields, and metaenums
 pub struct igMetadataManager {
     meta_fields: HashMap<Arc<str>, ArcMetaField>,
@@ -42,59 +52,260 @@ pub struct igMetadataManager {
     /// The platform the metadata system is targeting. Can be stored here because we know this is not used between different loaded games.
     platform: IG_CORE_PLATFORM,
     pub meta_field_registry: igMetafieldRegistry,
+    /// Built by [igMetadataManager::igz_field_plan] the first time an object of the type is read, then shared by every object of that type
+    igz_field_plans: RwLock<HashMap<Arc<str>, IgzFieldPlan>>,
 }
 
 impl igMetadataManager {
-    /// Takes in igz context and sets the fields of the passed in ig_object. called from ig_igz_loader.
+    /// Takes in igz context and sets the fields of the passed in ig_object, laid out as `meta` (the type it was instantiated from). called from ig_igz_loader.
     pub(crate) fn read_igz_fields(
-        &mut self,
+        &self,
         object_stream_manager: &igObjectStreamManager,
         handle: &mut Cursor<Vec<u8>>,
         endian: Endian,
         ctx: &mut IgzLoaderContext,
         ig_object: Arc<RwLock<dyn __internalObjectBase>>,
+        meta: Arc<RwLock<igMetaObject>>,
     ) {
         let object_offset = handle.position();
-        let meta = ig_object.read().unwrap().meta_type(self);
         let meta = meta.read().unwrap();
         debug!("igObject(name={}) fields are being set", meta.name);
-        let fields = &meta.field_storage.name_lookup;
+        for (field, metafield) in self.igz_field_plan(&meta).iter() {
+            let name = field.name.clone().unwrap();
+            #[cfg(debug_assertions)]
+            debug!("Setting up igz field(name={}, type={})", name, field._type);
+            handle.set_position(object_offset + field.offset as u64);
+            ctx.current_field = Some(name.clone());
+            let diagnostic_count = ctx.diagnostics.len();
+            let mut value = metafield.value_from_igz(&self.meta_field_registry, self, object_stream_manager, handle, endian.clone(), ctx);
+
+            let decode_failed = ctx.diagnostics[diagnostic_count..]
+                .iter()
+                .any(|x| x.kind == igLoadDiagnosticKind::FieldDecodeFailed);
+            if decode_failed {
+                // Keep the original bytes around so the field isn't lost entirely
+                handle.set_position(object_offset + field.offset as u64);
+                let raw = read_struct_array_u8(handle, endian.clone(), field.size as usize).unwrap_or_default();
+                value = Some(Arc::new(RwLock::new(raw)));
+            }
 
-        for (name, field) in fields {
-            match name.as_ref() {
-                "igStaticMetaField" | "igPropertyFieldMetaField" => {
-                    // ignored, not important on a per-object basis.
+            if let Ok(mut guard) = ig_object.write() {
+                if let Err(e) = guard.set_field(name.as_ref(), value) {
+                    ctx.report_failure(
+                        igLoadDiagnosticKind::SetFieldFailed,
+                        format!("When reading the igz value for the field {}, got SetObjectFieldError::{:?}", name, e),
+                    );
                 }
-                &_ => {
-                    #[cfg(debug_assertions)]
-                    debug!("Setting up igz field(name={}, type={})", name, field._type);
-                    handle.set_position(object_offset + field.offset as u64);
-                    let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
-                    let value = metafield.value_from_igz(&self.meta_field_registry, &self, object_stream_manager, handle, endian.clone(), ctx);
-                    if let Ok(mut guard) = ig_object.write() {
-                        match guard.set_field(name.as_ref(), value) {
-                            Ok(_) => {}
-                            Err(SetObjectFieldError::FieldDoesntExist) => {
-                                error!("When reading the igz value for the field {}, got SetObjectFieldError::FieldDoesntExist", name);
-                                panic!("Alchemy Error! Check the logs.")
-                            }
-                            Err(SetObjectFieldError::InvalidValueType) => {
-                                error!("When reading the igz value for the field {}, got SetObjectFieldError::InvalidValueType", name);
-                                panic!("Alchemy Error! Check the logs.")
-                            }
-                            Err(SetObjectFieldError::InvalidValue) => {
-                                error!("When reading the igz value for the field {}, got SetObjectFieldError::InvalidValue", name);
-                                panic!("Alchemy Error! Check the logs.")
-                            }
-                            Err(SetObjectFieldError::Unknown) => {
-                                error!("When reading the igz value for the field {}, got SetObjectFieldError::Unknown", name);
-                                panic!("Alchemy Error! Check the logs.")
-                            }
-                        }
-                    }
+            }
+        }
+        ctx.current_field = None;
+    }
+
+    /// Returns the fields of `meta` in igz read order with their metafields, building them the first time the type is read
+    fn igz_field_plan(&self, meta: &igMetaObject) -> IgzFieldPlan {
+        if let Some(plan) = self.igz_field_plans.read().unwrap().get(&meta.name) {
+            return plan.clone();
+        }
+
+        // Fields are read by offset and bit fields last, so logs and diagnostics come out in the same order on every load
+        let plan: IgzFieldPlan = meta
+            .fields_by_offset()
+            .into_iter()
+            .chain(meta.bit_fields())
+            .map(|field| {
+                let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+                (field, metafield)
+            })
+            .collect();
+        self.igz_field_plans.write().unwrap().entry(meta.name.clone()).or_insert(plan).clone()
+    }
+
+    /// Takes in igx context and sets the fields of the passed in ig_object. `fields` holds the name and raw inner xml of every field written in the igx. called from ig_igx_loader.
+    pub(crate) fn read_igx_fields(
+        &mut self,
+        object_stream_manager: &igObjectStreamManager,
+        ctx: &mut IgxLoaderContext,
+        ig_object: Arc<RwLock<dyn __internalObjectBase>>,
+        fields: &[(Arc<str>, Vec<u8>)],
+    ) {
+        let meta = ig_object.read().unwrap().meta_type(self);
+        let meta = meta.read().unwrap();
+
+        ctx.current_object = Some(ig_object.clone());
+        for (name, contents) in fields {
+            ctx.current_field = Some(name.clone());
+            let storage = meta.field_storage.properties.get(name).unwrap_or(name);
+            let Some(field) = meta.field_storage.name_lookup.get(storage) else {
+                if meta.field_storage.unmapped_properties.contains(name) {
+                    ctx.report(
+                        igLoadDiagnosticKind::UnmappedProperty,
+                        format!("igx sets the property {} on {}, which has no field storing its value", name, meta.name),
+                    );
+                } else {
+                    warn!("igx sets the field {} which doesn't exist on {}", name, meta.name);
+                }
+                continue;
+            };
+
+            #[cfg(debug_assertions)]
+            debug!("Setting up igx field(name={}, type={})", name, field._type);
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            let mut handle = Cursor::new(contents.clone());
+            let value = metafield.value_from_igx(&self.meta_field_registry, self, object_stream_manager, &mut handle, Endian::Little, ctx);
+
+            if let Ok(mut guard) = ig_object.write() {
+                if let Err(e) = guard.set_field(name.as_ref(), value) {
+                    ctx.report_failure(
+                        igLoadDiagnosticKind::SetFieldFailed,
+                        format!("When reading the igx value for the field {}, got SetObjectFieldError::{:?}", name, e),
+                    );
                 }
             }
         }
+        ctx.current_field = None;
+        ctx.current_object = None;
+    }
+
+    /// Takes in igb context and sets the fields of the passed in ig_object. `fields` are the fields the igb describes for the object in slot order, and are matched against the loaded metadata by that order. called from ig_igb_loader.
+    pub(crate) fn read_igb_fields(
+        &mut self,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbLoaderContext,
+        ig_object: igObject,
+        fields: &[igIGBMetaObjectField],
+    ) {
+        let meta = ig_object.read().unwrap().meta_type(self);
+        let meta = meta.read().unwrap();
+        let known_fields = meta.fields_by_offset();
+        let mut field_positions: HashMap<Arc<str>, u64> = HashMap::new();
+
+        for (i, igb_field) in fields.iter().enumerate() {
+            let start = handle.position();
+            let type_name = ctx.meta_fields.get(igb_field.type_index as usize).cloned().unwrap_or_else(|| Arc::from("unknown"));
+            let Some(field) = known_fields.get(i).filter(|field| field._type == type_name) else {
+                ctx.current_field = None;
+                ctx.report(
+                    igLoadDiagnosticKind::LayoutMismatch,
+                    format!("{} slot {} ({}) doesn't match the loaded metadata", meta.name, igb_field.slot, type_name),
+                );
+                handle.set_position(start + igb_field.size as u64);
+                continue;
+            };
+            let name = field.name.clone().unwrap();
+            field_positions.insert(name.clone(), start);
+
+            #[cfg(debug_assertions)]
+            debug!("Setting up igb field(name={}, type={})", name, field._type);
+            ctx.current_field = Some(name.clone());
+            ctx.field_size = igb_field.size as u32;
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            let value = metafield.value_from_igb(&self.meta_field_registry, self, object_stream_manager, handle, endian.clone(), ctx);
+
+            if let Ok(mut guard) = ig_object.write() {
+                if let Err(e) = guard.set_field(name.as_ref(), value) {
+                    ctx.report_failure(
+                        igLoadDiagnosticKind::SetFieldFailed,
+                        format!("When reading the igb value for the field {}, got SetObjectFieldError::{:?}", name, e),
+                    );
+                }
+            }
+        }
+
+        // Bit fields aren't stored on their own in igb files. They are decoded from their storage field once every field was read
+        let end = handle.position();
+        for field in meta.field_storage.fields() {
+            let Some(start) = field.bit_field_storage().and_then(|storage| field_positions.get(storage.as_str()).copied()) else {
+                continue;
+            };
+            let name = field.name.clone().unwrap();
+            ctx.current_field = Some(name.clone());
+            handle.set_position(start);
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            let value = metafield.value_from_igb(&self.meta_field_registry, self, object_stream_manager, handle, endian.clone(), ctx);
+
+            if let Ok(mut guard) = ig_object.write() {
+                if let Err(e) = guard.set_field(name.as_ref(), value) {
+                    ctx.report_failure(
+                        igLoadDiagnosticKind::SetFieldFailed,
+                        format!("When reading the igb value for the field {}, got SetObjectFieldError::{:?}", name, e),
+                    );
+                }
+            }
+        }
+        handle.set_position(end);
+        ctx.current_field = None;
+    }
+
+    /// Writes every field of the passed in ig_object in the order given by [igMetaObject::fields_by_offset] and returns how many bytes each of them took, which isn't always the size of the field in memory (refs are always 4 bytes in igb for example). called from ig_igb_saver.
+    pub(crate) fn write_igb_fields(
+        &mut self,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        endian: Endian,
+        ctx: &mut IgbSaverContext,
+        ig_object: igObject,
+    ) -> Result<Vec<u16>, IgbSaverError> {
+        let meta = ig_object.read().unwrap().meta_type(self);
+        let meta = meta.read().unwrap();
+
+        let mut field_positions: HashMap<Arc<str>, u64> = HashMap::new();
+        let mut sizes = Vec::new();
+        for field in meta.fields_by_offset() {
+            let name = field.name.clone().unwrap();
+            let start = handle.position();
+            field_positions.insert(name.clone(), start);
+            let value = ig_object.read().unwrap().get_field(&name).ok().flatten();
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            metafield.value_into_igb(self, object_stream_manager, handle, value, endian.clone(), ctx)?;
+            sizes.push((handle.position() - start) as u16);
+        }
+
+        // Bit fields are merged into their storage field after it was written so they aren't overwritten by it
+        let end = handle.position();
+        for field in meta.field_storage.fields() {
+            let Some(start) = field.bit_field_storage().and_then(|storage| field_positions.get(storage.as_str()).copied()) else {
+                continue;
+            };
+            let name = field.name.clone().unwrap();
+            let value = ig_object.read().unwrap().get_field(&name).ok().flatten();
+            handle.set_position(start);
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            metafield.value_into_igb(self, object_stream_manager, handle, value, endian.clone(), ctx)?;
+        }
+        handle.set_position(end);
+
+        Ok(sizes)
+    }
+
+    /// Writes every non-null field of the passed in ig_object as `<field>` tags. Fields are written in offset order so the output is the same between saves. called from ig_igx_saver.
+    pub(crate) fn write_igx_fields(
+        &mut self,
+        object_stream_manager: &igObjectStreamManager,
+        handle: &mut Cursor<Vec<u8>>,
+        ctx: &mut IgxSaverContext,
+        ig_object: igObject,
+    ) -> Result<(), IgxSaverError> {
+        let meta = ig_object.read().unwrap().meta_type(self);
+        let meta = meta.read().unwrap();
+        let mut fields: Vec<_> = meta.field_storage.name_lookup.iter().collect();
+        fields.sort_by_key(|(name, field)| (field.offset, (*name).clone()));
+
+        for (name, field) in fields {
+            // Fields that don't exist on a programmer-made type (such as igDataList's _count) are derived from other fields
+            let Ok(Some(value)) = ig_object.read().unwrap().get_field(name) else {
+                continue;
+            };
+
+            let metafield = self.meta_field_registry.get(field.clone(), self, self.platform.clone());
+            igx_write_raw(handle, &format!("        <field name=\"{}\">", name));
+            ctx.indent = 2;
+            metafield.value_into_igx(self, object_stream_manager, handle, Some(value), Endian::Little, ctx)?;
+            igx_write_raw(handle, "</field>\n");
+        }
+
+        Ok(())
     }
 }
 
@@ -166,6 +377,8 @@ pub trait __internalObjectBase: Any + Sync + Send {
 pub struct igGenericObject {
     object_name: Arc<str>,
     constructed_field_storage: Vec<RwLock<igConstructedField>>,
+    /// Property fields are read and written through the field storing their value
+    property_storage: HashMap<Arc<str>, Arc<str>>,
     internal_pool: igMemoryPool,
 }
 
@@ -185,9 +398,15 @@ impl igGenericObject {
         Ok(Arc::new(RwLock::new(igGenericObject {
             object_name: meta.name.clone(),
             constructed_field_storage,
+            property_storage: meta.field_storage.properties.clone(),
             internal_pool: _pool,
         })))
     }
+
+    /// Returns the name of the field holding the value of `name`
+    fn storage_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.property_storage.get(name).map(|storage| storage.as_ref()).unwrap_or(name)
+    }
 }
 
 impl __internalObjectBase for igGenericObject {
@@ -212,6 +431,7 @@ impl __internalObjectBase for igGenericObject {
         name: &str,
         value: Option<igAny>,
     ) -> Result<(), SetObjectFieldError> {
+        let name = self.storage_name(name);
         for field in &self.constructed_field_storage {
             if let Ok(mut guard) = field.write() {
                 if guard.name.as_ref() == name {
@@ -237,6 +457,7 @@ impl __internalObjectBase for igGenericObject {
         &self,
         name: &str,
     ) -> Result<Option<Arc<RwLock<(dyn Any + Send + Sync + 'static)>>>, FieldDoesntExist> {
+        let name = self.storage_name(name);
         for field in &self.constructed_field_storage {
             if field.read().unwrap().name.as_ref() == name {
                 return Ok(field.read().unwrap().value.clone());
@@ -274,32 +495,103 @@ pub struct igMetaFieldInfo {
     pub offset: u16,
 }
 
+impl igMetaFieldInfo {
+    /// Returns the name of the field a bit field stores its bits in, or [None] when this isn't a bit field
+    pub fn bit_field_storage(&self) -> Option<String> {
+        let ark_info = self.ark_info.read().unwrap();
+        ark_info.ig_bit_shift_info.as_ref().map(|info| info.read().unwrap().storage_field.clone())
+    }
+}
+
 /// Type designed for ergonomics and to keep speed up
 #[derive(Clone, Debug)]
 pub struct FieldStorage {
-    /// All field will be present in this map. Use the offset of a field to look it up
-    offset_lookup: HashMap<u16, Arc<igMetaFieldInfo>>,
-    /// NOT all field will be present in this map. Any field not using a name will not be present
+    /// All field will be present in this list, in the order they were declared. Several fields can share an offset (bit fields share the offset of their storage field)
+    fields: Vec<Arc<igMetaFieldInfo>>,
+    /// NOT all field will be present in this map. Any field not using a name will not be present. Static and property fields aren't stored in objects, so they aren't present either
     pub name_lookup: HashMap<Arc<str>, Arc<igMetaFieldInfo>>,
+    /// The name of the field storing the value of each property field
+    pub properties: HashMap<Arc<str>, Arc<str>>,
+    /// Property fields with no field storing their value. The game computes these in code, so they can't be read or written
+    pub unmapped_properties: Vec<Arc<str>>,
 }
 
 impl FieldStorage {
     pub fn new(fields: Vec<Arc<igMetaFieldInfo>>) -> FieldStorage {
-        let mut offset_lookup = HashMap::new();
-        let mut name_lookup = HashMap::new();
+        // metafields.xml describes bit fields as 0 bytes. They read their storage field, so they take its size and alignment
+        let fields: Vec<Arc<igMetaFieldInfo>> = fields
+            .iter()
+            .map(|field| {
+                let storage = field.bit_field_storage().and_then(|storage| {
+                    fields.iter().find(|x| x.name.as_deref() == Some(storage.as_str()))
+                });
+                match storage {
+                    Some(storage) => Arc::new(igMetaFieldInfo {
+                        size: storage.size,
+                        alignment: storage.alignment,
+                        ..(**field).clone()
+                    }),
+                    None => field.clone(),
+                }
+            })
+            .collect();
 
-        for x in fields {
-            offset_lookup.insert(x.offset, x.clone());
-            if let Some(name) = &x.name {
-                name_lookup.insert(name.clone(), x.clone());
+        let mut name_lookup = HashMap::new();
+        let mut properties = HashMap::new();
+        let mut unmapped_properties = Vec::new();
+        for x in &fields {
+            let Some(name) = &x.name else {
+                continue;
+            };
+            match x._type.as_ref() {
+                "igStaticMetaField" => {}
+                "igPropertyFieldMetaField" => match FieldStorage::find_property_storage(&fields, x) {
+                    Some(storage) => {
+                        properties.insert(name.clone(), storage);
+                    }
+                    None => unmapped_properties.push(name.clone()),
+                },
+                _ => {
+                    name_lookup.insert(name.clone(), x.clone());
+                }
             }
         }
 
         FieldStorage {
-            offset_lookup,
+            fields,
             name_lookup,
+            properties,
+            unmapped_properties,
         }
     }
+
+    /// Properties are accessors the game implements in code, metadata only says what type they are. The value is kept in a field named after the property (_rotationAxisInternal for _rotationAxis), which is only accepted when its type matches the one ig_property_info gives the property. Properties without one end up in [FieldStorage::unmapped_properties]
+    fn find_property_storage(fields: &[Arc<igMetaFieldInfo>], property: &igMetaFieldInfo) -> Option<Arc<str>> {
+        let name = property.name.as_ref()?;
+        let ark_info = property.ark_info.read().unwrap();
+        let value_type = ark_info.ig_property_info.as_ref()?.read().unwrap()._type.clone();
+
+        ["Internal", "Storage", "Cache"].iter().find_map(|suffix| {
+            let storage_name = format!("{}{}", name, suffix);
+            fields
+                .iter()
+                .find(|x| x.name.as_deref() == Some(storage_name.as_str()) && x._type == value_type)
+                .and_then(|x| x.name.clone())
+        })
+    }
+
+    /// Every field in the order they were declared
+    pub fn fields(&self) -> &[Arc<igMetaFieldInfo>] {
+        &self.fields
+    }
+
+    /// Returns the metafield type of the elements of the memory ref field `name`
+    pub(crate) fn memory_element_type(&self, name: &str) -> Option<Arc<str>> {
+        let field = self.name_lookup.get(name)?;
+        let element = field.ark_info.read().unwrap().ig_memory_ref_info.clone()?;
+        let element_type = element.read().unwrap()._type.clone();
+        Some(element_type)
+    }
 }
 
 type InternalMetaObjectConstructor = fn(
@@ -318,6 +610,11 @@ pub struct igMetaObject {
     /// The (optional if we are the root metaobject __internalObjectBase) name of the parent igMetaObject we inherit from
     pub parent: Option<Arc<str>>,
     pub field_storage: FieldStorage,
+    /// Present when the type extends igHashTable. Holds the keys and values stored in empty slots
+    pub hash_table_info: Option<HashTableInfo>,
+    /// The static fields declared by this type, described by the field storing them. Statics belong to the type rather than its objects, so they are read and written with [igMetaObject::get_static] and [igMetaObject::set_static]
+    pub static_fields: HashMap<Arc<str>, Arc<igMetaFieldInfo>>,
+    static_values: HashMap<Arc<str>, Option<igAny>>,
 }
 
 /// Describes all possible errors returned from the function [igMetaObject::instantiate]
@@ -359,6 +656,59 @@ impl igMetaObject {
         Ok(_type)
     }
 
+    /// Every named field sorted by offset. This is the order fields are stored in igb files
+    pub fn fields_by_offset(&self) -> Vec<Arc<igMetaFieldInfo>> {
+        let mut fields: Vec<_> = self
+            .stored_fields()
+            .filter(|field| field.bit_field_storage().is_none())
+            .cloned()
+            .collect();
+        // Stable, so fields sharing an offset stay in declaration order
+        fields.sort_by_key(|field| field.offset);
+        fields
+    }
+
+    /// Every bit field stored in objects of this type, in declaration order
+    pub fn bit_fields(&self) -> Vec<Arc<igMetaFieldInfo>> {
+        self.stored_fields().filter(|field| field.bit_field_storage().is_some()).cloned().collect()
+    }
+
+    /// The fields in [FieldStorage::name_lookup], in declaration order
+    fn stored_fields(&self) -> impl Iterator<Item = &Arc<igMetaFieldInfo>> {
+        self.field_storage
+            .fields()
+            .iter()
+            .filter(|field| field.name.as_ref().is_some_and(|name| self.field_storage.name_lookup.contains_key(name)))
+    }
+
+    /// Computes the size of an instance of this type from the end of the furthest field, padded to the largest field alignment. Used to check the loaded metadata against the sizes stored in igz files
+    pub fn calculate_size(&self) -> u32 {
+        let mut size = 0;
+        let mut alignment = 1;
+        for field in &self.field_storage.fields {
+            size = size.max(field.offset as u32 + field.size);
+            alignment = alignment.max(field.alignment);
+        }
+
+        (size + alignment - 1) & !(alignment - 1)
+    }
+
+    /// Returns the value of the static field `name`. Only statics declared by this type are found, inherited ones live on the parent
+    pub fn get_static(&self, name: &str) -> Result<Option<igAny>, FieldDoesntExist> {
+        self.static_values.get(name).cloned().ok_or(FieldDoesntExist)
+    }
+
+    /// Sets the value of the static field `name`, which is shared by every object of this type
+    pub fn set_static(&mut self, name: &str, value: Option<igAny>) -> Result<(), SetObjectFieldError> {
+        match self.static_values.get_mut(name) {
+            Some(stored) => {
+                *stored = value;
+                Ok(())
+            }
+            None => Err(SetObjectFieldError::FieldDoesntExist),
+        }
+    }
+
     pub fn raw_instantiate(
         &self,
         _source_pool: igMemoryPool,
@@ -413,6 +763,29 @@ impl __internalObjectBase for igMetaObject {
 pub enum MetaInitializationFailedException {}
 
 impl igMetadataManager {
+    /// Returns true when the type exists in the loaded metadata or was added by [igMetadataManager::create_generic_meta]. [igMetadataManager::get_or_create_meta] will panic for any type where this is false
+    pub fn contains_meta(&self, type_name: &str) -> bool {
+        self.meta_objects.contains_key(type_name) || self.object_meta_lookup.contains_key(type_name)
+    }
+
+    /// Adds a type the loaded metadata doesn't have, laid out as `fields`. Used for types a file describes itself, such as the metaobject table of an igb. Instances are [igGenericObject]s. If the type was already added, the existing one is returned
+    pub(crate) fn create_generic_meta(&mut self, type_name: Arc<str>, parent: Option<Arc<str>>, fields: Vec<Arc<igMetaFieldInfo>>) -> Arc<RwLock<igMetaObject>> {
+        self.object_meta_lookup
+            .entry(type_name.clone())
+            .or_insert_with(|| {
+                Arc::new(RwLock::new(igMetaObject {
+                    name: type_name,
+                    constructor: igGenericObject::new,
+                    parent,
+                    field_storage: FieldStorage::new(fields),
+                    hash_table_info: None,
+                    static_fields: HashMap::new(),
+                    static_values: HashMap::new(),
+                }))
+            })
+            .clone()
+    }
+
     /// Will search the cache for the type from the given name, if there is no match, It will load the type now and cache it for later use
     pub fn get_or_create_meta(&mut self, type_name: &str) -> Result<Arc<RwLock<igMetaObject>>, MetaInitializationFailedException> {
         if self.object_meta_lookup.contains_key(type_name) {
@@ -434,26 +807,185 @@ impl igMetadataManager {
         }
 
         let field_storage = self.get_current_fields(self.platform.clone(), parent_meta.clone(), &current_meta);
+        let hash_table_info = self.hash_table_info(type_name).map(|info| self.resolve_enum_invalid_key(info, &field_storage));
+        let static_fields = self.static_fields(&current_meta);
+        let static_values = static_fields.keys().map(|name| (name.clone(), None)).collect();
+
+        let constructor = if let Some(constructor) = TYPE_TO_METAOBJECT_LOOKUP.get(type_name) {
+            *constructor
+        } else if let Some(element_type) = self.data_list_element_type(type_name, &field_storage) {
+            data_list_constructor(&element_type)
+        } else if let Some(constructor) = self.hash_table_key_type(type_name, &field_storage).and_then(|key_type| hash_table_constructor(&key_type)) {
+            constructor
+        } else {
+            igGenericObject::new
+        };
+
+        igMetaObject {
+            name: Arc::from(type_name),
+            constructor,
+            parent: parent_meta,
+            field_storage,
+            hash_table_info,
+            static_fields,
+            static_values,
+        }
+    }
 
-        if let Some(constructor) = TYPE_TO_METAOBJECT_LOOKUP.get(type_name) {
-            igMetaObject {
-                name: Arc::from(type_name),
-                constructor: *constructor,
-                parent: parent_meta,
-                field_storage,
+    /// Returns the static fields declared by `meta_object`. metafields.xml describes statics as 0 bytes, the type of the value is the one of the field they wrap
+    fn static_fields(&self, meta_object: &MetaObject) -> HashMap<Arc<str>, Arc<igMetaFieldInfo>> {
+        let mut static_fields = HashMap::new();
+        for field in &meta_object.new_fields {
+            let field = field.read().unwrap();
+            let (Some(name), Some(storage)) = (&field.name, &field.ig_static_info) else {
+                continue;
+            };
+            let mut info = self.inner_field_info(storage, self.platform.clone());
+            info.name = Some(name.clone());
+            info.offset = 0;
+            static_fields.insert(name.clone(), Arc::new(info));
+        }
+        static_fields
+    }
+
+    pub(crate) fn calculate_size(&self, object: &RawArkMetaObjectField, platform: IG_CORE_PLATFORM) -> u32 {
+        // metafields.xml only has a placeholder size for igStructMetaField, every struct field stores its own
+        let element_size = match object.type_size {
+            Some(type_size) => type_size,
+            None => self.meta_fields[&object._type].platform_info[&platform].size as u32,
+        };
+        // Array forms are described with the size of a single element
+        element_size * object.num.unwrap_or(1)
+    }
+
+    /// Returns the metafield type of the elements of the fixed-size array metafield `type_name` (igFloatMetaField for igFloatArrayMetaField). Returns [None] when `type_name` isn't an array form
+    pub(crate) fn array_element_type(&self, type_name: &str) -> Option<Arc<str>> {
+        let prefix = type_name.strip_suffix("ArrayMetaField")?;
+        // Array forms extend their element metafield. The few without a metaobject are named after it
+        let element_type = match self.meta_objects.get(type_name).and_then(|meta| meta.base_type.clone()) {
+            Some(base_type) => base_type,
+            None => format!("{}MetaField", prefix),
+        };
+        self.meta_fields.contains_key(element_type.as_str()).then(|| Arc::from(element_type))
+    }
+
+    /// Returns the fields laid out by the compound metafield `type_name`, including the ones of the compound metafields it extends. Offsets are relative to the start of the compound value. Returns [None] when `type_name` isn't a compound metafield
+    pub(crate) fn get_compound_fields(&self, type_name: &str, platform: IG_CORE_PLATFORM) -> Option<FieldStorage> {
+        let mut chain: Vec<&MetaObject> = Vec::new();
+        let mut current = Some(type_name.to_string());
+        while let Some(name) = current {
+            if name == "igCompoundMetaField" {
+                let fields = chain
+                    .iter()
+                    .rev()
+                    .flat_map(|meta| meta.compound_fields.iter())
+                    .map(|field| Arc::new(self.inner_field_info(field, platform.clone())))
+                    .collect();
+                return Some(FieldStorage::new(fields));
             }
-        } else {
-            igMetaObject {
-                name: Arc::from(type_name),
-                constructor: igGenericObject::new,
-                parent: parent_meta,
-                field_storage,
+            let meta = self.meta_objects.get(name.as_str())?;
+            chain.push(meta);
+            current = meta.base_type.clone();
+        }
+        None
+    }
+
+    /// Returns true when `type_name` is `base` or extends it
+    fn extends(&self, type_name: &str, base: &str) -> bool {
+        let mut current = Some(type_name.to_string());
+        while let Some(name) = current {
+            if name == base {
+                return true;
             }
+            current = self.meta_objects.get(name.as_str()).and_then(|meta| meta.base_type.clone());
         }
+        false
     }
 
-    pub(crate) fn calculate_size(&self, object: &RawArkMetaObjectField, platform: IG_CORE_PLATFORM) -> u32 {
-        self.meta_fields[&object._type].platform_info[&platform].size as u32
+    /// Returns the metafield type of the elements of `type_name` when it extends igDataList
+    fn data_list_element_type(&self, type_name: &str, field_storage: &FieldStorage) -> Option<Arc<str>> {
+        if !self.extends(type_name, "igDataList") {
+            return None;
+        }
+        field_storage.memory_element_type("_data")
+    }
+
+    /// Returns the metafield type of the keys of `type_name` when it extends igHashTable
+    fn hash_table_key_type(&self, type_name: &str, field_storage: &FieldStorage) -> Option<Arc<str>> {
+        if !self.extends(type_name, "igHashTable") {
+            return None;
+        }
+        field_storage.memory_element_type("_keys")
+    }
+
+    /// The invalid key of enum keyed hash tables is written as the name of an entry. It is replaced with the value of the entry so the table can parse it without the metaenum
+    fn resolve_enum_invalid_key(&self, mut info: HashTableInfo, field_storage: &FieldStorage) -> HashTableInfo {
+        let element = field_storage.name_lookup.get("_keys").and_then(|field| field.ark_info.read().unwrap().ig_memory_ref_info.clone());
+        let Some(element) = element else {
+            return info;
+        };
+        let element = element.read().unwrap();
+        if element._type.as_ref() != "igEnumMetaField" {
+            return info;
+        }
+
+        let value = element
+            .ig_meta_enum
+            .as_ref()
+            .and_then(|meta_enum| self.get_meta_enum_values(meta_enum))
+            .and_then(|values| values.into_iter().find(|(name, _)| name.as_ref() == info.invalid_key));
+        match value {
+            Some((_, value)) => info.invalid_key = value.to_string(),
+            None => warn!("The invalid key {} is not an entry of {:?}", info.invalid_key, element.ig_meta_enum),
+        }
+        info
+    }
+
+    /// Returns the hash table info of `type_name`, inherited from the closest type declaring one
+    fn hash_table_info(&self, type_name: &str) -> Option<HashTableInfo> {
+        let mut current = Some(type_name.to_string());
+        while let Some(name) = current {
+            let meta = self.meta_objects.get(name.as_str())?;
+            if let Some(info) = &meta.hash_table_info {
+                return Some(info.clone());
+            }
+            current = meta.base_type.clone();
+        }
+        None
+    }
+
+    /// Builds the info of a metafield nested inside another one (the element of a memory ref or vector, or a field of a compound metafield for example). The size comes from the platform and the offset, when there is one, is relative to the parent
+    pub(crate) fn inner_field_info(&self, inner: &ArkMetaObjectField, platform: IG_CORE_PLATFORM) -> igMetaFieldInfo {
+        let guard = inner.read().unwrap();
+        igMetaFieldInfo {
+            ark_info: inner.clone(),
+            _type: guard._type.clone(),
+            name: guard.name.clone(),
+            size: self.calculate_size(&guard, platform),
+            alignment: guard.required_alignment.unwrap_or(4),
+            offset: guard.offset,
+        }
+    }
+
+    /// The platform metafields are being read and written for
+    pub(crate) fn platform(&self) -> IG_CORE_PLATFORM {
+        self.platform.clone()
+    }
+
+    /// Returns the alignment of a metafield type on the platform being targeted, as described by metafields.xml
+    pub(crate) fn platform_alignment(&self, type_name: &str) -> Option<u32> {
+        self.meta_fields
+            .get(type_name)
+            .and_then(|meta_field| meta_field.platform_info.get(&self.platform))
+            .map(|info| info.align as u32)
+    }
+
+    /// Returns the size of a metafield type on the platform being targeted, as described by metafields.xml
+    pub(crate) fn platform_size(&self, type_name: &str) -> Option<u32> {
+        self.meta_fields
+            .get(type_name)
+            .and_then(|meta_field| meta_field.platform_info.get(&self.platform))
+            .map(|info| info.size as u32)
     }
 
     /// Loops through all available field and builds up a list of field for the current meta object taking into account overridden field.
@@ -463,10 +995,10 @@ impl igMetadataManager {
         parent_ref: Option<Arc<str>>,
         current_object: &MetaObject,
     ) -> FieldStorage {
-        // TODO: handle however compound fields work.
+        // Compound fields belong to compound metafields rather than objects, they are laid out by get_compound_fields
         if let Some(parent) = &parent_ref {
             let parent = self.get_or_create_meta(parent.as_ref()).unwrap();
-            let parent_fields = &parent.read().unwrap().field_storage.offset_lookup;
+            let parent_fields = &parent.read().unwrap().field_storage.fields;
             let mut new_fields: Vec<Arc<igMetaFieldInfo>> = Vec::new();
 
             for parent_field in parent_fields {
@@ -474,7 +1006,9 @@ impl igMetadataManager {
 
                 for override_field in &current_object.overriden_fields {
                     let override_field = override_field.read().unwrap();
-                    if *parent_field.0 == override_field.offset {
+                    // Several fields can share an offset, so the name has to match too when there is one
+                    let same_name = override_field.name.is_none() || override_field.name == parent_field.name;
+                    if parent_field.offset == override_field.offset && same_name {
                         new_fields.push(Arc::new(igMetaFieldInfo {
                             ark_info: Arc::new(RwLock::new(override_field.clone())),
                             _type: override_field.clone()._type,
@@ -484,7 +1018,7 @@ impl igMetadataManager {
                                 &override_field,
                                 platform.clone(),
                             ),
-                            alignment: override_field.required_alignment.unwrap(),
+                            alignment: override_field.required_alignment.unwrap_or(4),
                             offset: override_field.offset,
                         }));
                         overriden = true;
@@ -493,7 +1027,7 @@ impl igMetadataManager {
                 }
 
                 if !overriden {
-                    new_fields.push(parent_field.1.clone())
+                    new_fields.push(parent_field.clone())
                 }
             }
 
@@ -513,31 +1047,22 @@ impl igMetadataManager {
 
             FieldStorage::new(new_fields)
         } else {
-            let mut offset_lookup: HashMap<u16, Arc<igMetaFieldInfo>> = HashMap::new();
-            let mut name_lookup: HashMap<Arc<str>, Arc<igMetaFieldInfo>> = HashMap::new();
+            let mut new_fields: Vec<Arc<igMetaFieldInfo>> = Vec::new();
 
             for field in &current_object.new_fields {
                 let lock = field.read().unwrap();
 
-                let new_field = Arc::new(igMetaFieldInfo {
+                new_fields.push(Arc::new(igMetaFieldInfo {
                     ark_info: Arc::new(RwLock::new(lock.clone())),
                     _type: lock._type.clone(),
                     name: lock.name.clone(),
                     size: self.calculate_size(&lock, platform.clone()),
                     alignment: lock.required_alignment.unwrap(),
                     offset: lock.offset,
-                });
-
-                offset_lookup.insert(new_field.offset, new_field.clone());
-                if let Some(name) = &lock.name {
-                    name_lookup.insert(name.clone(), new_field.clone());
-                }
+                }));
             }
 
-            FieldStorage {
-                offset_lookup,
-                name_lookup,
-            }
+            FieldStorage::new(new_fields)
         }
     }
 
@@ -570,9 +1095,23 @@ impl igMetadataManager {
             meta_objects,
             platform,
             meta_field_registry: igMetafieldRegistry::new(),
+            igz_field_plans: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Returns the (name, value) of every entry in a metaenum from metaenums.xml. Used to list the choices for an enum field
+    pub fn get_meta_enum_values(&self, meta_enum: &str) -> Option<Vec<(Arc<str>, i32)>> {
+        self.meta_enums
+            .get(meta_enum)
+            .map(|meta_enum| meta_enum.values.iter().map(|value| (value.name.clone(), value.value)).collect())
+    }
+
+    /// Same as [igMetadataManager::get_enum], but returns [None] when the index or value isn't in the loaded metadata
+    pub fn try_get_enum<T: MetaEnumImpl>(&self, value_index: usize) -> Option<T> {
+        let value = self.meta_enums.get(T::META_KEY)?.values.get(value_index)?;
+        T::from_str(&value.name).ok()
+    }
+
     pub fn get_enum<T: MetaEnumImpl>(&self, value_index: usize) -> T {
         let value = &self.meta_enums[T::META_KEY].values[value_index];
         if let Ok(return_value) = T::from_str(&value.name) {
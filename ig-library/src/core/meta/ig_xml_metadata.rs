@@ -1,7 +1,7 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
-use log::info;
+use log::{debug, info, warn};
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use std::path::PathBuf;
@@ -89,7 +89,7 @@ pub struct RawArkMetaObjectField {
     pub meta_object: Option<Arc<str>>,
     /// Some field will require a specific alignment otherwise they won't work. These types will specify it. I am unsure specifically which ones do this.
     pub required_alignment: Option<u32>,
-    /// Present when _type is equal to "igVectorMetaField"
+    /// Present when _type is equal to "igVectorMetaField" or "igVectorArrayMetaField"
     pub ig_vector_info: Option<VectorInfo>,
     /// Present when _type is equal to "igMemoryRefMetaField"
     pub ig_memory_ref_info: Option<ArkMetaObjectField>,
@@ -101,6 +101,10 @@ pub struct RawArkMetaObjectField {
     pub ig_meta_enum: Option<Arc<str>>,
     /// Present when _type is equal to "igStaticMetaField"
     pub ig_static_info: Option<ArkMetaObjectField>,
+    /// Present when _type is equal to "igStructMetaField". Size in bytes of the struct, metafields.xml doesn't know it
+    pub type_size: Option<u32>,
+    /// Present when _type is a fixed-size array (igFloatArrayMetaField, igStructArrayMetaField...). Amount of elements in the array
+    pub num: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -307,7 +311,7 @@ fn load_meta_enums(path: &PathBuf) -> Result<Vec<ArcMetaEnum>, String> {
 }
 
 // I got to talk to jasleen about simplifying this format because damn this is hard
-fn load_meta_objects(path: &PathBuf) -> Result<Vec<MetaObject>, String> {
+pub(crate) fn load_meta_objects(path: &PathBuf) -> Result<Vec<MetaObject>, String> {
     let start = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
     let mut buf = Vec::new();
     let mut reader = Reader::from_file(path).map_err(|e| e.to_string())?;
@@ -315,7 +319,8 @@ fn load_meta_objects(path: &PathBuf) -> Result<Vec<MetaObject>, String> {
 
     let mut meta_objects = Vec::new();
     let mut current_meta_object: Option<Arc<RefCell<MetaObject>>> = None;
-    let mut current_meta_field: Option<ArkMetaObjectField> = None;
+    // Every <metafield> that was opened and not closed yet. Nested metafields describe the type stored by the last one
+    let mut open_meta_fields: Vec<ArkMetaObjectField> = Vec::new();
     // when reading "overriddenmetafields" this should be false but when reading "metafields" it should be true
     let mut field_type = FieldType::NewField;
     loop {
@@ -324,25 +329,27 @@ fn load_meta_objects(path: &PathBuf) -> Result<Vec<MetaObject>, String> {
                 panic!("at position {}: {:?}", reader.error_position(), e)
             }
             Ok(Event::Eof) => break,
-            Ok(Event::End(e)) => {
-                if e.local_name().as_ref() == b"metaobject" {
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"metaobject" => {
                     if let Some(old_meta_obj) = current_meta_object.clone() {
                         meta_objects.push(old_meta_obj.borrow().to_owned());
                     }
-                    
+
                     current_meta_object = None;
-                    current_meta_field = None;
+                    open_meta_fields.clear();
                 }
-            }
+                b"metafield" => {
+                    open_meta_fields.pop();
+                }
+                _ => {}
+            },
             Ok(Event::Empty(e)) => match e.local_name().as_ref() {
-                b"overriddenmetafields" => field_type = FieldType::OverridenField,
-                b"metafields" => field_type = FieldType::NewField,
-                b"compoundfields" => field_type = FieldType::CompoundField,
-                b"metafield" => on_metafield_tag(
+                b"overriddenmetafields" | b"metafields" | b"compoundfields" | b"metafield" | b"objectlist" | b"hashtable" => on_metafield_tag(
                     &mut current_meta_object,
-                    &mut current_meta_field,
+                    &mut open_meta_fields,
                     &mut field_type,
                     &e,
+                    false,
                 )?,
                 b"binding" => on_tfbscript_binding(
                     &mut current_meta_object,
@@ -352,9 +359,10 @@ fn load_meta_objects(path: &PathBuf) -> Result<Vec<MetaObject>, String> {
             },
             Ok(Event::Start(e)) => on_metafield_tag(
                 &mut current_meta_object,
-                &mut current_meta_field,
+                &mut open_meta_fields,
                 &mut field_type,
                 &e,
+                true,
             )?,
 
             _ => {}
@@ -384,11 +392,15 @@ fn on_tfbscript_binding(
 
 fn on_metafield_tag(
     current_meta_object: &mut Option<Arc<RefCell<MetaObject>>>,
-    current_meta_field: &mut Option<ArkMetaObjectField>,
+    open_meta_fields: &mut Vec<ArkMetaObjectField>,
     field_type: &mut FieldType,
     e: &BytesStart,
+    has_children: bool,
 ) -> Result<(), String> {
     match e.local_name().as_ref() {
+        b"overriddenmetafields" => *field_type = FieldType::OverridenField,
+        b"metafields" => *field_type = FieldType::NewField,
+        b"compoundfields" => *field_type = FieldType::CompoundField,
         b"metaobject" => {
             let mut _type: Option<String> = None;
             let mut ref_name: Option<Arc<str>> = None;
@@ -454,10 +466,10 @@ fn on_metafield_tag(
             for result in e.attributes() {
                 let attrib = result.unwrap();
                 match attrib.key.local_name().as_ref() {
-                    b"invalid_value" => {
+                    b"invalidvalue" | b"invalid_value" => {
                         invalid_value = Some(String::from(attrib.unescape_value().unwrap()));
                     }
-                    b"invalid_key" => {
+                    b"invalidkey" | b"invalid_key" => {
                         invalid_key = Some(String::from(attrib.unescape_value().unwrap()));
                     }
                     _ => {
@@ -480,191 +492,25 @@ fn on_metafield_tag(
         }
 
         b"metafield" => {
-            // Let's find out if we are a child first before we do anything.
-            if current_meta_field.is_some() {
-                let current_meta_field_ref = current_meta_field.clone().unwrap();
-                let current_type = current_meta_field_ref.read().unwrap().clone()._type;
-
-                match current_type.as_ref() {
-                    "igPropertyFieldMetaField" => {
-                        let child_metafield = process_new_metafield(&e);
-                        current_meta_field_ref.write().unwrap().ig_property_info = Some(
-                            child_metafield
-                                .clone()
-                                .expect("Failed to process child field of igPropertyFieldMetaField"),
-                        );
-
-                        // TODO: de-duplicate this
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = child_metafield;
-                    }
-
-                    "igStaticMetaField" => {
-                        let child_metafield = process_new_metafield(&e);
-                        current_meta_field_ref.write().unwrap().ig_static_info = Some(
-                            child_metafield
-                                .clone()
-                                .expect("Failed to process child field of igStaticMetaField"),
-                        );
-
-                        // TODO: de-duplicate this
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = child_metafield;
-                    }
-
-                    "igVectorMetaField" => {
-                        let child_metafield = process_new_metafield(&e);
-                        current_meta_field_ref
-                            .write()
-                            .unwrap()
-                            .ig_vector_info
-                            .as_mut()
-                            .unwrap()
-                            .field = Some(
-                            child_metafield
-                                .clone()
-                                .expect("Failed to process child field of igVectorMetaField"),
-                        );
-
-                        // TODO: de-duplicate this
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = child_metafield;
-                    }
-                    "igBitFieldMetaField" => {
-                        let child_metafield = process_new_metafield(&e);
-                        current_meta_field_ref
-                            .read()
-                            .unwrap()
-                            .ig_bit_shift_info
-                            .as_ref()
-                            .unwrap()
-                            .write()
-                            .unwrap()
-                            ._type = Some(
-                            child_metafield
-                                .clone()
-                                .expect("Failed to process child field of igVectorMetaField"),
-                        );
-
-                        // TODO: de-duplicate this
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = child_metafield;
-                    }
-                    "igMemoryRefMetaField" => {
-                        // This ONE type is driving me insane.
-                        // We ARE a child, but we are still burdened with the responsibility
-                        // to process an entire new metafield which itself has a chance of having its own metafields...
-
-                        let child_metafield = process_new_metafield(&e);
-                        current_meta_field_ref.write().unwrap().ig_memory_ref_info = Some(
-                            child_metafield
-                                .clone()
-                                .expect("Failed to process child field of igMemoryRefMetaField"),
-                        );
-
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = child_metafield;
-                    }
-                    _ => {
-                        // Not a child. Let's push it into the list
-                        if current_meta_field.is_some() {
-                            let field = current_meta_field.clone().unwrap();
-                            let raw_meta_object = current_meta_object.clone().unwrap();
-                            let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                            let field_vector = match field_type {
-                                FieldType::NewField => &mut meta_object_borrow.new_fields,
-                                FieldType::OverridenField => {
-                                    &mut meta_object_borrow.overriden_fields
-                                }
-                                FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                            };
-                            field_vector.push(field);
-                        }
-
-                        *current_meta_field = process_new_metafield(&e);
-                    }
+            let field = process_new_metafield(e).expect("Failed to process metafield");
+            match open_meta_fields.last() {
+                Some(parent) => attach_child_metafield(parent, field.clone()),
+                None => {
+                    let raw_meta_object = current_meta_object.clone().unwrap();
+                    let mut meta_object_borrow = raw_meta_object.borrow_mut();
+                    let field_vector = match field_type {
+                        FieldType::NewField => &mut meta_object_borrow.new_fields,
+                        FieldType::OverridenField => &mut meta_object_borrow.overriden_fields,
+                        FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
+                    };
+                    field_vector.push(field.clone());
                 }
             }
 
-            // We aren't a child. we must be new. Save and get rid of the old type
-            if current_meta_field.is_some() {
-                let field = current_meta_field.clone().unwrap();
-                let raw_meta_object = current_meta_object.clone().unwrap();
-                let mut meta_object_borrow = raw_meta_object.borrow_mut();
-                let field_vector = match field_type {
-                    FieldType::NewField => &mut meta_object_borrow.new_fields,
-                    FieldType::OverridenField => &mut meta_object_borrow.overriden_fields,
-                    FieldType::CompoundField => &mut meta_object_borrow.compound_fields,
-                };
-                field_vector.push(field);
+            // Only fields that were opened can have children. Self-closing ones are already complete
+            if has_children {
+                open_meta_fields.push(field);
             }
-
-            *current_meta_field = process_new_metafield(&e);
         }
         _ => {}
     }
@@ -672,6 +518,32 @@ fn on_metafield_tag(
     Ok(())
 }
 
+/// Stores a nested metafield on its parent. Nested metafields have no name and describe the type the parent stores (the element of a vector, the value of a bit field, ...)
+fn attach_child_metafield(parent: &ArkMetaObjectField, child: ArkMetaObjectField) {
+    let mut parent = parent.write().unwrap();
+    match parent._type.as_ref() {
+        "igPropertyFieldMetaField" => parent.ig_property_info = Some(child),
+        "igStaticMetaField" => parent.ig_static_info = Some(child),
+        "igVectorMetaField" | "igVectorArrayMetaField" => {
+            parent
+                .ig_vector_info
+                .get_or_insert(VectorInfo {
+                    field: None,
+                    mem_type_alignment_multiple: u8::MAX,
+                })
+                .field = Some(child)
+        }
+        "igBitFieldMetaField" => match &parent.ig_bit_shift_info {
+            Some(bit_shift_info) => bit_shift_info.write().unwrap()._type = Some(child),
+            None => warn!("igBitFieldMetaField {:?} has no storage field", parent.name),
+        },
+        "igMemoryRefMetaField" | "igMemoryRefHandleMetaField" | "igMemoryRefArrayMetaField" => {
+            parent.ig_memory_ref_info = Some(child)
+        }
+        _ => debug!("{} has a nested {} which isn't used", parent._type, child.read().unwrap()._type),
+    }
+}
+
 fn process_new_metafield(e: &BytesStart) -> Option<ArkMetaObjectField> {
     let mut _type: Option<Arc<str>> = None;
     let mut offset: Option<u16> = None;
@@ -685,6 +557,8 @@ fn process_new_metafield(e: &BytesStart) -> Option<ArkMetaObjectField> {
         _type: None,
     };
     let mut ig_meta_enum: Option<Arc<str>> = None;
+    let mut type_size: Option<u32> = None;
+    let mut num: Option<u32> = None;
     let mut ig_vector_info = VectorInfo {
         field: None,
         mem_type_alignment_multiple: u8::MAX,
@@ -730,6 +604,17 @@ fn process_new_metafield(e: &BytesStart) -> Option<ArkMetaObjectField> {
                 ig_vector_info.mem_type_alignment_multiple =
                     u8::from_str_radix(&without_prefix, 16).unwrap();
             }
+            // igStructMetaField
+            b"typeSize" => {
+                let raw = String::from(attrib.unescape_value().unwrap());
+                let without_prefix = raw.trim_start_matches("0x");
+                type_size = Some(u32::from_str_radix(without_prefix, 16).unwrap());
+            }
+            // Fixed-size arrays
+            b"num" => {
+                let raw = String::from(attrib.unescape_value().unwrap());
+                num = Some(u32::from_str(&raw).unwrap());
+            }
             // igMemoryRefMetaField
             // ...
             // igPropertyFieldMetaField
@@ -748,7 +633,7 @@ fn process_new_metafield(e: &BytesStart) -> Option<ArkMetaObjectField> {
 
     // Don't store vector info when it's not a igVectorMetaField to not confuse users of metadata
     let mut optional_ig_vector = None;
-    if _type.clone().unwrap().as_ref() == "igVectorMetaField" {
+    if matches!(_type.clone().unwrap().as_ref(), "igVectorMetaField" | "igVectorArrayMetaField") {
         optional_ig_vector = Some(ig_vector_info)
     }
 
@@ -764,5 +649,7 @@ fn process_new_metafield(e: &BytesStart) -> Option<ArkMetaObjectField> {
         ig_property_info: None, // Requires child metafield to get more information
         ig_meta_enum,
         ig_static_info: None, // Requires child metafield to get more information
+        type_size,
+        num,
     })))
 }
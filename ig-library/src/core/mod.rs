@@ -1,6 +1,7 @@
 pub mod ig_core_platform;
 pub mod ig_ark_core;
 pub mod ig_file_context;
+pub mod ig_directory_cache;
 pub mod ig_registry;
 pub mod ig_archive;
 pub mod ig_fs;
@@ -10,9 +11,12 @@ pub mod ig_std_lib_storage_device;
 pub mod ig_custom;
 pub mod meta;
 pub mod ig_memory;
+pub mod ig_math;
+pub mod ig_compound;
 pub mod ig_objects;
 pub mod load;
 pub mod ig_handle;
+pub mod ig_dependency_graph;
 pub mod ig_external_ref;
 pub mod save;
-mod memory;
+pub(crate) mod memory;
@@ -1,2 +1,301 @@
-pub struct IgbSaverContext;
-pub enum IgbSaverError {}
\ No newline at end of file
+use crate::core::ig_fs::Endian;
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
+use crate::core::load::ig_igb_loader::{igIGBDirectoryEntry, igIGBMetaObject, igIGBMetaObjectField, IGB_MAGIC_COOKIE};
+use crate::core::meta::ig_metadata_manager::{igMetaObject, igMetadataManager};
+use crate::util::byteorder_fixes::{write_u16, write_u32};
+use log::error;
+use std::collections::{HashMap, VecDeque};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+
+/// Version written into the header of every igb
+const IGB_VERSION: u32 = 5;
+
+/// Internal type to store while jumping around to other methods. Also shared with saving metafields
+pub struct IgbSaverContext {
+    /// The type name of every metafield written so far. Indices into this are stored in the igb
+    meta_fields: Vec<Arc<str>>,
+    /// Every metaobject written so far
+    meta_objects: Vec<igIGBMetaObject>,
+    /// Index in [IgbSaverContext::meta_objects] of every metaobject keyed by name
+    meta_object_lookup: HashMap<Arc<str>, u32>,
+    /// The igb directory. Object and memory ref fields store an index into this
+    directory: Vec<igIGBDirectoryEntry>,
+    /// Directory index of every object keyed by the address of the object
+    object_ids: HashMap<usize, u32>,
+    /// Objects that have been given a directory index but haven't been written yet, in directory order
+    pending: VecDeque<igObject>,
+    /// Contents of every memory block, in directory order
+    memory_blocks: Cursor<Vec<u8>>,
+    /// `(namespace, name)` of every object that lives in another loaded directory, keyed by the address of the object
+    external_names: HashMap<usize, (Arc<str>, Arc<str>)>,
+}
+
+/// Describes everything that can stop a directory from being written as igb
+#[derive(Debug)]
+pub enum IgbSaverError {
+    /// The directory ran into problems while loading, so writing it would lose data. See [igObjectDirectory::diagnostics]
+    UnsafeToSave,
+    /// An object reference points at something that was never loaded
+    UnresolvedReference(Arc<str>),
+    /// The value stored in a field doesn't match the type its metafield writes
+    InvalidValueType(Arc<str>),
+    /// Writing the value failed, for example because the metafield's size can't hold it
+    Io(std::io::Error),
+}
+
+fn address(object: &igObject) -> usize {
+    Arc::as_ptr(object) as *const () as usize
+}
+
+impl IgbSaverContext {
+    pub(crate) fn new(dir: &igObjectDirectory, object_stream_manager: &igObjectStreamManager) -> IgbSaverContext {
+        let mut ctx = IgbSaverContext {
+            meta_fields: Vec::new(),
+            meta_object_lookup: HashMap::new(),
+            meta_objects: Vec::new(),
+            directory: Vec::new(),
+            object_ids: HashMap::new(),
+            pending: VecDeque::new(),
+            memory_blocks: Cursor::new(Vec::new()),
+            external_names: HashMap::new(),
+        };
+
+        // Same search order as igx, so an object loaded in several places is referenced from the directory the file asked for
+        let mut others: Vec<_> = object_stream_manager.path_to_directory_lookup.values().cloned().collect();
+        others.sort_by_key(|other| other.read().unwrap().path.clone());
+        for other in dir.dependencies.iter().chain(others) {
+            let other = other.read().unwrap();
+            let Some(namespace) = other.name.string.clone() else {
+                continue;
+            };
+            if other.path == dir.path {
+                continue;
+            }
+
+            let objects = other.object_list.read().unwrap();
+            let names = other.name_list.read().unwrap();
+            for (object, name) in objects.iter().zip(names.iter()) {
+                if let Some(name) = &name.string {
+                    ctx.external_names
+                        .entry(address(&object))
+                        .or_insert_with(|| (Arc::from(namespace.as_str()), Arc::from(name.as_str())));
+                }
+            }
+        }
+
+        // The directory entries are objects themselves, so their types have to be described too
+        let int_type = ctx.meta_field_index("igIntMetaField");
+        let int_field = |slot| igIGBMetaObjectField {
+            type_index: int_type,
+            slot,
+            size: 4,
+        };
+        ctx.push_meta_object(Arc::from("igObjectDirEntry"), vec![int_field(0)]);
+        ctx.push_meta_object(Arc::from("igMemoryDirEntry"), vec![int_field(0), int_field(1)]);
+        ctx.push_meta_object(Arc::from("igExternalObjectDirEntry"), vec![int_field(0), int_field(1)]);
+        ctx
+    }
+
+    fn push_meta_object(&mut self, name: Arc<str>, fields: Vec<igIGBMetaObjectField>) -> u32 {
+        let index = self.meta_objects.len() as u32;
+        self.meta_object_lookup.insert(name.clone(), index);
+        self.meta_objects.push(igIGBMetaObject { name, parent: None, fields });
+        index
+    }
+
+    /// Returns the index of a metafield type in the igb, adding it the first time it is seen
+    pub fn meta_field_index(&mut self, type_name: &str) -> u16 {
+        match self.meta_fields.iter().position(|name| name.as_ref() == type_name) {
+            Some(index) => index as u16,
+            None => {
+                self.meta_fields.push(Arc::from(type_name));
+                (self.meta_fields.len() - 1) as u16
+            }
+        }
+    }
+
+    /// Returns the index of a metaobject in the igb, adding it the first time it is seen. Inherited fields are flattened into the metaobject so it never needs a parent.
+    ///
+    /// `sizes` are the bytes each field took when the first object of the type was written. Only fixed size fields are skipped by their size when loading, so strings (which are written inline) taking a different size in other objects doesn't matter
+    fn meta_object_index(&mut self, meta: &igMetaObject, sizes: &[u16]) -> u32 {
+        if let Some(index) = self.meta_object_lookup.get(&meta.name) {
+            return *index;
+        }
+
+        let fields: Vec<_> = meta
+            .fields_by_offset()
+            .into_iter()
+            .zip(sizes)
+            .enumerate()
+            .map(|(slot, (field, size))| igIGBMetaObjectField {
+                type_index: self.meta_field_index(&field._type),
+                slot: slot as u16,
+                size: *size,
+            })
+            .collect();
+        self.push_meta_object(meta.name.clone(), fields)
+    }
+
+    /// Returns the directory index of an object, handing out the next index (and queueing the object to be written) the first time it is seen. Objects of other loaded directories get an external entry instead
+    pub fn object_index(&mut self, object: &igObject) -> u32 {
+        let next_index = self.directory.len() as u32;
+        if let Some(index) = self.object_ids.get(&address(object)) {
+            return *index;
+        }
+
+        if let Some((namespace, name)) = self.external_names.get(&address(object)) {
+            self.directory.push(igIGBDirectoryEntry::External {
+                namespace: namespace.clone(),
+                name: name.clone(),
+            });
+            self.object_ids.insert(address(object), next_index);
+            return next_index;
+        }
+
+        // The type is filled in once the object is written. Metafields don't have mutable access to the metadata to look it up
+        self.directory.push(igIGBDirectoryEntry::Object { type_index: u32::MAX });
+        self.object_ids.insert(address(object), next_index);
+        self.pending.push_back(object.clone());
+        next_index
+    }
+
+    /// Adds a block of memory to the directory and returns its index. `element_type` is the metafield type of every element in the block
+    pub fn push_memory(&mut self, data: &[u8], element_type: &str) -> u32 {
+        let type_index = self.meta_field_index(element_type) as u32;
+        self.memory_blocks.write_all(data).expect("writing to a Vec can't fail");
+        self.directory.push(igIGBDirectoryEntry::Memory {
+            size: data.len() as u32,
+            type_index,
+        });
+        (self.directory.len() - 1) as u32
+    }
+}
+
+/// Writes `dir` as igb, the binary format read by [crate::core::load::ig_igb_loader::igIGBObjectLoader]. Every object reachable from the directory is written into the file, except for named objects of other loaded directories which are written as `namespace::name` references.
+pub fn save_igb(
+    dir: &igObjectDirectory,
+    object_stream_manager: &igObjectStreamManager,
+    metadata_manager: &mut igMetadataManager,
+    endian: Endian,
+) -> Result<Vec<u8>, IgbSaverError> {
+    if !dir.can_safely_save() {
+        error!("Refusing to save {} as igb. It has {} load diagnostics that would lose data", dir.path, dir.blocking_diagnostic_count());
+        return Err(IgbSaverError::UnsafeToSave);
+    }
+
+    let mut ctx = IgbSaverContext::new(dir, object_stream_manager);
+    let info_list_index = ctx.object_index(&(dir.object_list.clone() as igObject));
+    for object in &dir.all_objects {
+        ctx.object_index(object);
+    }
+
+    let mut objects = Cursor::new(Vec::new());
+    while let Some(object) = ctx.pending.pop_front() {
+        let index = ctx.object_ids[&address(&object)];
+        let meta = object.read().unwrap().meta_type(metadata_manager);
+        let sizes = metadata_manager.write_igb_fields(object_stream_manager, &mut objects, endian.clone(), &mut ctx, object)?;
+        let type_index = ctx.meta_object_index(&meta.read().unwrap(), &sizes);
+        ctx.directory[index as usize] = igIGBDirectoryEntry::Object { type_index };
+    }
+
+    // Meta fields
+    let mut meta_fields = Cursor::new(Vec::new());
+    for name in &ctx.meta_fields {
+        write_u32(&mut meta_fields, endian.clone(), name.len() as u32 + 1).unwrap();
+        write_u32(&mut meta_fields, endian.clone(), 0).unwrap(); // major version
+        write_u32(&mut meta_fields, endian.clone(), 0).unwrap(); // minor version
+    }
+    for name in &ctx.meta_fields {
+        write_name(&mut meta_fields, name);
+    }
+
+    // Meta objects
+    let mut meta_objects = Cursor::new(Vec::new());
+    for meta_object in &ctx.meta_objects {
+        write_u32(&mut meta_objects, endian.clone(), meta_object.name.len() as u32 + 1).unwrap();
+        write_u32(&mut meta_objects, endian.clone(), 0).unwrap(); // major version
+        write_u32(&mut meta_objects, endian.clone(), 0).unwrap(); // minor version
+        write_u32(&mut meta_objects, endian.clone(), meta_object.fields.len() as u32).unwrap();
+        write_u32(&mut meta_objects, endian.clone(), u32::MAX).unwrap(); // parent
+        write_u32(&mut meta_objects, endian.clone(), meta_object.fields.len() as u32).unwrap(); // slot count
+        for field in &meta_object.fields {
+            write_u16(&mut meta_objects, endian.clone(), field.type_index).unwrap();
+            write_u16(&mut meta_objects, endian.clone(), field.slot).unwrap();
+            write_u16(&mut meta_objects, endian.clone(), field.size).unwrap();
+        }
+        write_name(&mut meta_objects, &meta_object.name);
+    }
+
+    // Entries. Every directory index has its own entry so the directory index list is just 0..n
+    let object_entry_type = ctx.meta_object_lookup["igObjectDirEntry"];
+    let memory_entry_type = ctx.meta_object_lookup["igMemoryDirEntry"];
+    let external_entry_type = ctx.meta_object_lookup["igExternalObjectDirEntry"];
+    let mut entries = Cursor::new(Vec::new());
+    let mut object_count = 0;
+    let mut memory_count = 0;
+    for entry in &ctx.directory {
+        match entry {
+            igIGBDirectoryEntry::Object { type_index } => {
+                write_u32(&mut entries, endian.clone(), object_entry_type).unwrap();
+                write_u32(&mut entries, endian.clone(), 12).unwrap();
+                write_u32(&mut entries, endian.clone(), *type_index).unwrap();
+                object_count += 1;
+            }
+            igIGBDirectoryEntry::Memory { size, type_index } => {
+                write_u32(&mut entries, endian.clone(), memory_entry_type).unwrap();
+                write_u32(&mut entries, endian.clone(), 16).unwrap();
+                write_u32(&mut entries, endian.clone(), *size).unwrap();
+                write_u32(&mut entries, endian.clone(), *type_index).unwrap();
+                memory_count += 1;
+            }
+            igIGBDirectoryEntry::External { namespace, name } => {
+                write_u32(&mut entries, endian.clone(), external_entry_type).unwrap();
+                write_u32(&mut entries, endian.clone(), 16 + namespace.len() as u32 + name.len() as u32 + 2).unwrap();
+                write_u32(&mut entries, endian.clone(), namespace.len() as u32 + 1).unwrap();
+                write_u32(&mut entries, endian.clone(), name.len() as u32 + 1).unwrap();
+                write_name(&mut entries, namespace);
+                write_name(&mut entries, name);
+            }
+            igIGBDirectoryEntry::Unsupported(_) => unreachable!("the saver never creates unsupported entries"),
+        }
+    }
+
+    let mut handle = Cursor::new(Vec::new());
+    let header = [
+        entries.get_ref().len() as u32,
+        ctx.directory.len() as u32,
+        meta_objects.get_ref().len() as u32,
+        ctx.meta_objects.len() as u32,
+        objects.get_ref().len() as u32,
+        object_count,
+        ctx.memory_blocks.get_ref().len() as u32,
+        memory_count,
+        meta_fields.get_ref().len() as u32,
+        ctx.meta_fields.len() as u32,
+        IGB_MAGIC_COOKIE,
+        IGB_VERSION,
+    ];
+    for value in header {
+        write_u32(&mut handle, endian.clone(), value).unwrap();
+    }
+
+    handle.write_all(meta_fields.get_ref()).unwrap();
+    handle.write_all(meta_objects.get_ref()).unwrap();
+    handle.write_all(entries.get_ref()).unwrap();
+    write_u32(&mut handle, endian.clone(), ctx.directory.len() as u32).unwrap();
+    for index in 0..ctx.directory.len() as u32 {
+        write_u32(&mut handle, endian.clone(), index).unwrap();
+    }
+    write_u32(&mut handle, endian.clone(), info_list_index).unwrap();
+    handle.write_all(objects.get_ref()).unwrap();
+    handle.write_all(ctx.memory_blocks.get_ref()).unwrap();
+
+    Ok(handle.into_inner())
+}
+
+/// Names are written with a null terminator. Their length is stored separately
+fn write_name(handle: &mut Cursor<Vec<u8>>, name: &str) {
+    handle.write_all(name.as_bytes()).unwrap();
+    handle.write_all(&[0]).unwrap();
+}
@@ -1,2 +1,173 @@
-pub struct IgxSaverContext;
-pub enum IgxSaverError {}
\ No newline at end of file
+use crate::core::ig_objects::{igObject, igObjectDirectory, igObjectStreamManager};
+use crate::core::load::ig_igx_loader::{IGX_NULL, IGX_VERSION};
+use crate::core::meta::ig_metadata_manager::igMetadataManager;
+use log::error;
+use quick_xml::escape::escape;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+
+/// Internal type to store while jumping around to other methods. Also shared with saving metafields
+pub struct IgxSaverContext {
+    /// Id of every object written to this igx keyed by the address of the object
+    object_ids: HashMap<usize, u32>,
+    /// Objects that have been given an id but haven't been written yet, in id order
+    pending: VecDeque<igObject>,
+    /// (namespace, path) of every other loaded directory that objects can be referenced from
+    directories: Vec<(String, String)>,
+    /// `namespace::name` and the index in [IgxSaverContext::directories] of every object that lives in another directory
+    external_names: HashMap<usize, (String, usize)>,
+    /// Indices in [IgxSaverContext::directories] of every directory actually referenced
+    referenced_directories: BTreeSet<usize>,
+    /// How deeply nested the value being written is. Used to indent lists
+    pub indent: usize,
+}
+
+/// Describes everything that can stop a directory from being written as igx
+#[derive(Debug)]
+pub enum IgxSaverError {
+    /// The directory ran into problems while loading, so writing it would lose data. See [igObjectDirectory::diagnostics]
+    UnsafeToSave,
+    /// An object reference points at something that was never loaded
+    UnresolvedReference(Arc<str>),
+    /// The value stored in a field doesn't match the type its metafield writes
+    InvalidValueType(Arc<str>),
+}
+
+fn address(object: &igObject) -> usize {
+    Arc::as_ptr(object) as *const () as usize
+}
+
+impl IgxSaverContext {
+    pub(crate) fn new(dir: &igObjectDirectory, object_stream_manager: &igObjectStreamManager) -> IgxSaverContext {
+        let mut ctx = IgxSaverContext {
+            object_ids: HashMap::new(),
+            pending: VecDeque::new(),
+            directories: Vec::new(),
+            external_names: HashMap::new(),
+            referenced_directories: BTreeSet::new(),
+            indent: 0,
+        };
+
+        // Dependencies are searched first so an object loaded in several places resolves to the directory the file actually asked for
+        let mut others: Vec<_> = object_stream_manager.path_to_directory_lookup.values().cloned().collect();
+        others.sort_by_key(|other| other.read().unwrap().path.clone());
+        for other in dir.dependencies.iter().chain(others) {
+            let other = other.read().unwrap();
+            let Some(namespace) = other.name.string.clone() else {
+                continue;
+            };
+            if other.path == dir.path || ctx.directories.iter().any(|(_, path)| *path == other.path) {
+                continue;
+            }
+
+            let index = ctx.directories.len();
+            ctx.directories.push((namespace.clone(), other.path.clone()));
+
+            let objects = other.object_list.read().unwrap();
+            let names = other.name_list.read().unwrap();
+            for (object, name) in objects.iter().zip(names.iter()) {
+                if let Some(name) = &name.string {
+                    ctx.external_names
+                        .entry(address(&object))
+                        .or_insert_with(|| (format!("{}::{}", namespace, name), index));
+                }
+            }
+        }
+
+        ctx
+    }
+
+    /// Returns the id of an object in this igx, handing out the next id (and queueing the object to be written) the first time it is seen
+    pub fn object_id(&mut self, object: &igObject) -> u32 {
+        let next_id = self.object_ids.len() as u32;
+        *self.object_ids.entry(address(object)).or_insert_with(|| {
+            self.pending.push_back(object.clone());
+            next_id
+        })
+    }
+
+    /// Returns `namespace::name` when the object belongs to another loaded directory. That directory is written as a dependency of the igx
+    pub fn external_name(&mut self, object: &igObject) -> Option<String> {
+        let (name, index) = self.external_names.get(&address(object))?;
+        self.referenced_directories.insert(*index);
+        Some(name.clone())
+    }
+}
+
+/// Writes `text` to the handle as-is. Used for tags and values that never need escaping
+pub fn igx_write_raw(handle: &mut Cursor<Vec<u8>>, text: &str) {
+    handle.write_all(text.as_bytes()).expect("writing to a Vec can't fail");
+}
+
+/// Escapes and writes `text` to the handle
+pub fn igx_write_text(handle: &mut Cursor<Vec<u8>>, text: &str) {
+    igx_write_raw(handle, &escape(text));
+}
+
+/// Writes [IGX_NULL] to the handle
+pub fn igx_write_null(handle: &mut Cursor<Vec<u8>>) {
+    igx_write_raw(handle, IGX_NULL);
+}
+
+/// Writes the start of a new line at the current indentation of the context
+pub fn igx_write_newline(handle: &mut Cursor<Vec<u8>>, ctx: &IgxSaverContext) {
+    igx_write_raw(handle, "\n");
+    igx_write_raw(handle, &"    ".repeat(ctx.indent));
+}
+
+/// Writes `dir` as igx, the XML format read by [crate::core::load::ig_igx_loader::igIGXObjectLoader]. The output is stable: objects keep the order they were loaded in and are numbered in that order, fields are written in offset order, and saving the same directory twice gives the same bytes. This makes the files suitable for checking into version control.
+pub fn save_igx(
+    dir: &igObjectDirectory,
+    object_stream_manager: &igObjectStreamManager,
+    metadata_manager: &mut igMetadataManager,
+) -> Result<Vec<u8>, IgxSaverError> {
+    if !dir.can_safely_save() {
+        error!("Refusing to save {} as igx. It has {} load diagnostics that would lose data", dir.path, dir.blocking_diagnostic_count());
+        return Err(IgxSaverError::UnsafeToSave);
+    }
+
+    let mut ctx = IgxSaverContext::new(dir, object_stream_manager);
+    let root = ctx.object_id(&(dir.object_list.clone() as igObject));
+    let names = dir.use_name_list.then(|| ctx.object_id(&(dir.name_list.clone() as igObject)));
+    for object in &dir.all_objects {
+        ctx.object_id(object);
+    }
+
+    let mut body = Cursor::new(Vec::new());
+    while let Some(object) = ctx.pending.pop_front() {
+        let id = ctx.object_ids[&address(&object)];
+        let type_name = object.read().unwrap().object_name();
+        igx_write_raw(&mut body, &format!("    <object type=\"{}\" id=\"{}\">\n", escape(type_name.as_ref()), id));
+        metadata_manager.write_igx_fields(object_stream_manager, &mut body, &mut ctx, object)?;
+        igx_write_raw(&mut body, "    </object>\n");
+    }
+
+    let mut dependencies: Vec<(String, String)> = Vec::new();
+    for dependency in dir.dependencies.iter() {
+        let dependency = dependency.read().unwrap();
+        if let Some(namespace) = &dependency.name.string {
+            dependencies.push((namespace.clone(), dependency.path.clone()));
+        }
+    }
+    for index in &ctx.referenced_directories {
+        let (namespace, path) = &ctx.directories[*index];
+        if !dependencies.iter().any(|(_, dependency_path)| dependency_path == path) {
+            dependencies.push((namespace.clone(), path.clone()));
+        }
+    }
+
+    let mut handle = Cursor::new(Vec::new());
+    igx_write_raw(&mut handle, "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    match names {
+        Some(names) => igx_write_raw(&mut handle, &format!("<igx version=\"{}\" root=\"{}\" names=\"{}\">\n", IGX_VERSION, root, names)),
+        None => igx_write_raw(&mut handle, &format!("<igx version=\"{}\" root=\"{}\">\n", IGX_VERSION, root)),
+    }
+    for (namespace, path) in dependencies {
+        igx_write_raw(&mut handle, &format!("    <dependency name=\"{}\" path=\"{}\"/>\n", escape(&namespace), escape(&path)));
+    }
+    handle.write_all(body.get_ref()).expect("writing to a Vec can't fail");
+    igx_write_raw(&mut handle, "</igx>\n");
+
+    Ok(handle.into_inner())
+}
@@ -1,8 +1,40 @@
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
+use crate::core::ig_memory::igMemoryPool;
+use std::sync::Arc;
 
 pub struct IgzSaverContext {
-    pub platform: IG_CORE_PLATFORM
+    pub platform: IG_CORE_PLATFORM,
+    /// The pool of every data section being written, in the order they are stored. Serialized offsets are built from an index into this list
+    pub sections: Vec<igMemoryPool>,
+    /// Where each serialized offset written so far was placed. These are listed in the ROFS fixup so the game relocates them
+    pub offsets: Vec<u64>,
 }
+
+impl IgzSaverContext {
+    pub fn new(platform: IG_CORE_PLATFORM) -> IgzSaverContext {
+        IgzSaverContext {
+            platform,
+            sections: vec![],
+            offsets: vec![],
+        }
+    }
+
+    /// Builds the serialized offset for `offset` in the section holding `pool`. Sections are indexed the way version 7 and later igz files index them. Returns [None] when no section holds `pool` or the offset doesn't fit
+    pub fn serialize_offset(&self, pool: igMemoryPool, offset: u64) -> Option<u64> {
+        let section = self.sections.iter().position(|x| *x == pool)?;
+        (offset <= 0x07FFFFFF).then_some(((section as u64) << 0x1B) | offset)
+    }
+}
+
+#[derive(Debug)]
 pub enum IgzSaverError {
     Unknown,
-}
\ No newline at end of file
+    /// The value stored in a field doesn't match the type its metafield writes
+    InvalidValueType(Arc<str>),
+    /// Writing the value failed, for example because the metafield's size can't hold it
+    Io(std::io::Error),
+    /// The metafield needs fixups (handles, memory handles...) that can't be written to igz yet
+    Unsupported(Arc<str>),
+    /// A reference points into a pool none of the written sections hold
+    MissingSection(igMemoryPool),
+}
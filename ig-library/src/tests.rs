@@ -4,16 +4,44 @@ use crate::core::ig_ark_core::{igArkCore, EGame};
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
 use crate::core::ig_file_context::igFileContext;
 use crate::core::ig_memory::igMemoryPool;
-use crate::core::ig_objects::{igAny, ObjectExt};
+use crate::core::ig_objects::{igAny, igCachedLoad, igObject, igObjectDirectory, igObjectStreamManager, ObjectExt};
 use crate::core::ig_registry::igRegistry;
 use crate::core::meta::ig_metadata_manager::{
-    __internalObjectBase, igMetaObject, FieldDoesntExist, SetObjectFieldError,
+    __internalObjectBase, igGenericObject, igMetaFieldInfo, igMetaObject, igMetadataManager, FieldDoesntExist, SetObjectFieldError,
 };
 use crate::util::ig_common::igAlchemy;
-use std::any::Any;
+use crate::core::ig_custom::{igDataList, igHashTable, igNull, igObjectDirectoryList, CastTo};
+use crate::core::ig_dependency_graph::igDependencyReason;
+use crate::core::ig_archive::FileInfo;
+use crate::core::ig_directory_cache::{igDirectoryCache, FNV_OFFSET};
+use crate::core::load::ig_igz_loader::{igIGZObjectLoader, IgzLoaderContext};
+use crate::core::save::ig_igz_saver::{IgzSaverContext, IgzSaverError};
+use crate::core::meta::field::r#impl::ig_enum_meta_field::igEnumValue;
+use crate::core::load::ig_igz_inspector::{inspect_igz_bytes, igIGZFixupContents, read_igz_thumbnails};
+use crate::util::ig_name::igName;
+use crate::core::ig_compound::igCompoundValue;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait};
+use crate::core::load::ig_loader::{igLoadDiagnosticKind, igObjectLoader, igObjectLoaderRegistry};
+use crate::core::ig_external_ref::igExternalReferenceSystem;
+use crate::core::ig_handle::{igHandle, igHandleName, igObjectHandleManager};
+use crate::core::save::ig_igx_saver::{save_igx, IgxSaverContext};
+use crate::core::load::ig_igx_loader::IgxLoaderContext;
+use crate::core::load::ig_igb_loader::{igIGBObjectLoader, IgbLoaderContext};
+use crate::core::meta::field::ig_metafields::igMetaField;
+use crate::core::save::ig_igb_saver::{save_igb, IgbSaverContext, IgbSaverError};
+use crate::core::meta::field::r#impl::ig_primitive_meta_field::igPrimitiveMetaField;
+use crate::core::ig_fs::Endian;
+use crate::core::memory::{igMemory, igNullElement, igRawRef};
+use std::io::Cursor;
+use crate::core::ig_math::{igMatrix44f, igRangedFloat, igRangedVector, igVec3f};
+use crate::core::meta::field::r#impl::ig_math_meta_field::{igMatrix44fMetaField, igVec3fAlignedMetaField, igVec3fMetaField};
+use crate::util::ig_hash::{hash_int, hash_lower};
+use std::collections::BTreeMap;
+use std::any::{Any, TypeId};
 use std::ops::Sub;
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
+use crate::core::meta::ig_xml_metadata::{load_meta_objects, ArkMetaObjectField};
 
 fn load_alchemy() -> igAlchemy {
     let start_time = Instant::now();
@@ -54,7 +82,11 @@ struct igModelData {
 }
 
 impl __internalObjectBase for igModelData {
-    fn meta_type(&self) -> Arc<RwLock<igMetaObject>> {
+    fn object_name(&self) -> Arc<str> {
+        Arc::from("igModelData")
+    }
+
+    fn meta_type(&self, _metadata_manager: &mut igMetadataManager) -> Arc<RwLock<igMetaObject>> {
         todo!()
     }
 
@@ -99,7 +131,11 @@ struct igModelInfo {
 }
 
 impl __internalObjectBase for igModelInfo {
-    fn meta_type(&self) -> Arc<RwLock<igMetaObject>> {
+    fn object_name(&self) -> Arc<str> {
+        Arc::from("igModelInfo")
+    }
+
+    fn meta_type(&self, _metadata_manager: &mut igMetadataManager) -> Arc<RwLock<igMetaObject>> {
         todo!()
     }
 
@@ -146,10 +182,11 @@ fn test_type_usability() {
     let file_driver_moneybone = ig_alchemy
         .object_stream_manager
         .load(
-            &mut ig_alchemy.file_context,
+            &ig_alchemy.file_context,
             &ig_alchemy.registry,
             &mut ig_alchemy.ark_core.metadata_manager,
             &mut ig_alchemy.ig_ext_ref_system,
+            &mut ig_alchemy.ig_object_handle_manager,
             "DriverMoneybone".to_string(),
         )
         .unwrap();
@@ -171,3 +208,2789 @@ fn test_type_usability() {
         }
     };
 }
+
+fn handle(namespace: &str, alias: &str) -> Arc<RwLock<igHandle>> {
+    igHandle::from_handle_name(&igHandleName::new(igName::new(alias.to_string()), igName::new(namespace.to_string())))
+}
+
+/// Verifies handles skip directories without a name list instead of giving up on the namespace
+#[test]
+fn test_handle_searches_every_directory() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    synthetic_directory(&mut object_stream_manager, "a.igz", "shared", &["foo"], false);
+    let (_, objects) = synthetic_directory(&mut object_stream_manager, "b.igz", "shared", &["bar", "foo"], true);
+
+    let object = handle("shared", "foo").write().unwrap().get_object_alias(&object_stream_manager);
+    assert!(object.is_some_and(|object| Arc::ptr_eq(&object, &objects[1])));
+}
+
+/// Verifies the alias is compared against names and not the namespace
+#[test]
+fn test_handle_compares_alias() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    synthetic_directory(&mut object_stream_manager, "a.igz", "foo", &["foo"], true);
+
+    assert!(handle("foo", "bar").write().unwrap().get_object_alias(&object_stream_manager).is_none());
+    assert!(handle("foo", "foo").write().unwrap().get_object_alias(&object_stream_manager).is_some());
+}
+
+/// Verifies a resolved handle keeps its object even once the directory is gone
+#[test]
+fn test_handle_caches_result() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let (_, objects) = synthetic_directory(&mut object_stream_manager, "a.igz", "ns", &["foo"], true);
+
+    let handle = handle("ns", "foo");
+    handle.write().unwrap().get_object_alias(&object_stream_manager);
+    object_stream_manager.name_to_directory_lookup.clear();
+
+    let object = handle.write().unwrap().get_object_alias(&object_stream_manager);
+    assert!(object.is_some_and(|object| Arc::ptr_eq(&object, &objects[0])));
+}
+
+/// Verifies every match is reported when several directories share a namespace and alias, and that the first directory loaded wins
+#[test]
+fn test_handle_reports_ambiguous_matches() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let (_, first) = synthetic_directory(&mut object_stream_manager, "a.igz", "ns", &["foo"], true);
+    let (_, second) = synthetic_directory(&mut object_stream_manager, "b.igz", "ns", &["foo"], true);
+
+    let handle = handle("ns", "foo");
+    let matches = handle.read().unwrap().find_matches(&object_stream_manager);
+    assert_eq!(matches.len(), 2);
+    assert!(Arc::ptr_eq(&matches[0].0, &first[0]));
+    assert!(Arc::ptr_eq(&matches[1].0, &second[0]));
+    assert_eq!(matches[1].1, "b.igz");
+
+    let object = handle.write().unwrap().get_object_alias(&object_stream_manager);
+    assert!(object.is_some_and(|object| Arc::ptr_eq(&object, &first[0])));
+}
+
+/// Verifies directories added to the handle manager resolve both ways
+#[test]
+fn test_handle_manager_add_directory() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let mut handle_manager = igObjectHandleManager::new();
+    let (dir, objects) = synthetic_directory(&mut object_stream_manager, "a.igz", "ns", &["foo", "bar"], true);
+    handle_manager.add_directory(&dir.read().unwrap());
+
+    let object = handle_manager.resolve(igName::new("ns".to_string()), igName::new("bar".to_string()));
+    assert!(object.is_some_and(|object| Arc::ptr_eq(&object, &objects[1])));
+
+    let handle = handle_manager.get_handle(&objects[0]).unwrap();
+    assert_eq!(handle.read().unwrap().alias.hash, igName::new("foo".to_string()).hash);
+    assert_eq!(handle_manager.handles().len(), 2);
+}
+
+/// Verifies handles the manager didn't create resolve through it once the directory is added
+#[test]
+fn test_handle_manager_resolves_foreign_handle() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let mut handle_manager = igObjectHandleManager::new();
+    let handle = handle("ns", "foo");
+    handle_manager.lookup_handle(igName::new("ns".to_string()), igName::new("foo".to_string()));
+    assert!(handle_manager.resolve_handle(&handle).is_none());
+
+    let (dir, objects) = synthetic_directory(&mut object_stream_manager, "a.igz", "ns", &["foo"], true);
+    handle_manager.add_directory(&dir.read().unwrap());
+    let object = handle_manager.resolve_handle(&handle);
+    assert!(object.is_some_and(|object| Arc::ptr_eq(&object, &objects[0])));
+    assert!(handle.read().unwrap().object.is_some());
+}
+
+/// Handle fields are read from igx as `namespace::name`, with names whose string is unknown written as a hash
+#[test]
+fn test_handle_meta_field_igx() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igVfxPrimitiveData").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_deathFx").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    assert_eq!(igMetaField::type_id(metafield.as_ref()), TypeId::of::<Arc<RwLock<igHandle>>>());
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut text = Cursor::new(b"0x0000ABCD::fire".to_vec());
+    let value = metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut text, Endian::Big, &mut ctx).unwrap();
+    let value = value.read().unwrap();
+    let handle = value.downcast_ref::<Arc<RwLock<igHandle>>>().unwrap().read().unwrap();
+    assert_eq!(handle.namespace.hash, 0xABCD);
+    assert_eq!(handle.alias.hash, igName::new("fire".to_string()).hash);
+    assert_eq!(handle.to_string(), "0x0000ABCD::fire");
+    drop(handle);
+    drop(value);
+
+    // Handles that don't name a namespace are reported instead of aborting the load
+    let mut text = Cursor::new(b"fire".to_vec());
+    assert!(metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut text, Endian::Big, &mut ctx).is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+}
+
+/// Nested metafields in metaobjects.xml describe the type stored by their parent and must not be added as fields of their own
+#[test]
+fn test_nested_metafields_attach_to_parent() {
+    let xml = r#"<metaobjects>
+	<metaobject type="igMetaObject" refname="igNestedTest" basetype="igObject">
+		<metafields>
+			<metafield type="igMemoryRefMetaField" offset="0x0008" name="_data">
+				<metafield type="igUnsignedCharMetaField" offset="0x0000"/>
+			</metafield>
+			<metafield type="igStaticMetaField" offset="0x0000" name="_instances" storageMetaField="f0">
+				<metafield type="igVectorMetaField" offset="0x0000" memTypeAlignmentMultiple="0x01">
+					<templateargs>
+						<metafield type="igObjectRefMetaField" offset="0x0000" metaobject="igObject"/>
+					</templateargs>
+				</metafield>
+			</metafield>
+			<metafield type="igUnsignedCharMetaField" offset="0x0010" name="_flags"/>
+			<metafield type="igBitFieldMetaField" offset="0x0010" name="_enabled" shift="0x01" bits="0x01" storageField="_flags">
+				<metafield type="igBoolMetaField" offset="0x0000"/>
+			</metafield>
+		</metafields>
+		<overriddenmetafields>
+			<metafield type="igIntMetaField" offset="0x0004" name="_count"/>
+		</overriddenmetafields>
+	</metaobject>
+</metaobjects>"#;
+    let path = std::env::temp_dir().join(format!("ig_library_nested_metafields_{}.xml", std::process::id()));
+    std::fs::write(&path, xml).unwrap();
+    let meta_objects = load_meta_objects(&path);
+    std::fs::remove_file(&path).unwrap();
+
+    let meta_objects = meta_objects.unwrap();
+    assert_eq!(meta_objects.len(), 1);
+    let meta_object = &meta_objects[0];
+    let names: Vec<_> = meta_object.new_fields.iter().map(|x| x.read().unwrap().name.clone()).collect();
+    assert_eq!(names, vec![Some(Arc::from("_data")), Some(Arc::from("_instances")), Some(Arc::from("_flags")), Some(Arc::from("_enabled"))]);
+    let overridden: Vec<_> = meta_object.overriden_fields.iter().map(|x| x.read().unwrap().name.clone()).collect();
+    assert_eq!(overridden, vec![Some(Arc::from("_count"))]);
+
+    let field_type = |field: &ArkMetaObjectField| field.read().unwrap()._type.clone();
+    let data = meta_object.new_fields[0].read().unwrap();
+    assert_eq!(field_type(data.ig_memory_ref_info.as_ref().unwrap()).as_ref(), "igUnsignedCharMetaField");
+
+    // Metafields nest more than one level deep, the vector inside the static keeps its own element type
+    let instances = meta_object.new_fields[1].read().unwrap();
+    let vector = instances.ig_static_info.clone().unwrap();
+    assert_eq!(field_type(&vector).as_ref(), "igVectorMetaField");
+    let element = vector.read().unwrap().ig_vector_info.as_ref().unwrap().field.clone().unwrap();
+    assert_eq!(field_type(&element).as_ref(), "igObjectRefMetaField");
+
+    let enabled = meta_object.new_fields[3].read().unwrap();
+    let bit_shift_info = enabled.ig_bit_shift_info.as_ref().unwrap().read().unwrap();
+    assert_eq!(field_type(bit_shift_info._type.as_ref().unwrap()).as_ref(), "igBoolMetaField");
+}
+
+/// Bit fields share the offset of their storage field and must not replace it or be parsed twice
+#[test]
+fn test_bit_fields_are_loaded() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igMetaImage").unwrap();
+    let meta = meta.read().unwrap();
+    let fields = meta.field_storage.fields();
+
+    let storage = meta.field_storage.name_lookup.get("_properties").unwrap();
+    let is_tile = meta.field_storage.name_lookup.get("_isTile").unwrap();
+    assert_eq!(storage.offset, is_tile.offset);
+    assert_eq!(is_tile.bit_field_storage().as_deref(), Some("_properties"));
+    assert_eq!(is_tile.size, storage.size);
+    assert_eq!(fields.iter().filter(|x| x.name.as_deref() == Some("_isTile")).count(), 1);
+    assert!(fields.iter().all(|x| x.name.is_some()));
+    assert!(meta.fields_by_offset().iter().all(|x| x.bit_field_storage().is_none()));
+}
+
+/// Writing a bit field only replaces its own bits in the storage field
+#[test]
+fn test_bit_field_merges_into_storage() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igMetaImage").unwrap();
+    let meta = meta.read().unwrap();
+    let field = |name: &str| {
+        let info = meta.field_storage.name_lookup.get(name).unwrap().clone();
+        imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE)
+    };
+    let is_tile = field("_isTile");
+    let is_canonical = field("_isCanonical");
+
+    let mut handle = Cursor::new(vec![0xFFu8]);
+    let dir = igObjectDirectory::with_loader("bit_field.igb", igName::new("bit_field".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    let mut saver_ctx = IgbSaverContext::new(&dir, &object_stream_manager);
+    let value: igAny = Arc::new(RwLock::new(false));
+    is_canonical.value_into_igb(imm, &object_stream_manager, &mut handle, Some(value), Endian::Big, &mut saver_ctx).unwrap();
+    assert_eq!(handle.get_ref(), &vec![0xFD]);
+
+    // A missing value leaves the storage field alone
+    handle.set_position(0);
+    is_tile.value_into_igb(imm, &object_stream_manager, &mut handle, None, Endian::Big, &mut saver_ctx).unwrap();
+    assert_eq!(handle.get_ref(), &vec![0xFD]);
+
+    let mut loader_ctx = IgbLoaderContext::new(5, false);
+    let read = |metafield: &Arc<dyn igMetaField>, loader_ctx: &mut IgbLoaderContext| {
+        let mut handle = Cursor::new(vec![0xFDu8]);
+        let value = metafield.value_from_igb(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, loader_ctx).unwrap();
+        let value = *value.read().unwrap().downcast_ref::<bool>().unwrap();
+        value
+    };
+    assert!(read(&is_tile, &mut loader_ctx));
+    assert!(!read(&is_canonical, &mut loader_ctx));
+}
+
+/// Signed bit fields are sign extended from their top bit and write back into their own bits only
+#[test]
+fn test_bit_field_sign_extends() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("tfbSoundRuntime").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_controllerID").unwrap().clone();
+    let controller_id = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let mut loader_ctx = IgbLoaderContext::new(5, false);
+    let read = |bytes: [u8; 4], loader_ctx: &mut IgbLoaderContext| {
+        let mut handle = Cursor::new(bytes.to_vec());
+        let value = controller_id.value_from_igb(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, loader_ctx).unwrap();
+        let value = *value.read().unwrap().downcast_ref::<i32>().unwrap();
+        value
+    };
+    assert_eq!(read([0x00, 0x00, 0x01, 0xFF], &mut loader_ctx), -1);
+    assert_eq!(read([0x00, 0x00, 0x01, 0x80], &mut loader_ctx), -128);
+    assert_eq!(read([0x00, 0x00, 0x01, 0x7F], &mut loader_ctx), 127);
+
+    let mut handle = Cursor::new(vec![0x00, 0x00, 0x01, 0x00]);
+    let value: igAny = Arc::new(RwLock::new(-2i32));
+    let dir = igObjectDirectory::with_loader("bit_field.igb", igName::new("bit_field".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    controller_id.value_into_igb(imm, &object_stream_manager, &mut handle, Some(value), Endian::Big, &mut IgbSaverContext::new(&dir, &object_stream_manager)).unwrap();
+    assert_eq!(handle.get_ref(), &vec![0x00, 0x00, 0x01, 0xFE]);
+}
+
+/// Aligned vectors are padded to their platform size and components follow the file endian
+#[test]
+fn test_math_meta_field_padding() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let metafield = igVec3fAlignedMetaField::new("igVec3fAlignedMetaField", &ark_core.metadata_manager);
+    assert_eq!(metafield.size, 16);
+
+    let mut handle = Cursor::new(Vec::new());
+    metafield.write(&mut handle, Endian::Big, igVec3f::new(1.0, 2.0, 3.0)).unwrap();
+    assert_eq!(handle.get_ref().len(), 16);
+    assert_eq!(&handle.get_ref()[..4], &1.0f32.to_be_bytes());
+    assert_eq!(&handle.get_ref()[12..], &[0, 0, 0, 0]);
+
+    handle.get_mut()[12..].copy_from_slice(&[0xFF; 4]);
+    handle.set_position(0);
+    assert_eq!(metafield.read(&mut handle, Endian::Big).unwrap(), igVec3f::new(1.0, 2.0, 3.0));
+    assert_eq!(handle.position(), 16);
+}
+
+/// Matrices are stored row by row
+#[test]
+fn test_math_meta_field_matrix() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let metafield = igMatrix44fMetaField::new("igMatrix44fMetaField", &ark_core.metadata_manager);
+    let mut matrix = igMatrix44f::identity();
+    matrix.rows[3] = [4.0, 5.0, 6.0, 1.0];
+
+    let mut handle = Cursor::new(Vec::new());
+    metafield.write(&mut handle, Endian::Little, matrix).unwrap();
+    assert_eq!(&handle.get_ref()[48..52], &4.0f32.to_le_bytes());
+    handle.set_position(0);
+    assert_eq!(metafield.read(&mut handle, Endian::Little).unwrap(), matrix);
+}
+
+/// igx values with the wrong amount of components, or components that don't parse, are reported instead of aborting the load
+#[test]
+fn test_math_meta_field_igx_reports_bad_values() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &ark_core.metadata_manager;
+    let object_stream_manager = igObjectStreamManager::new();
+    let metafield = igVec3fMetaField::new("igVec3fMetaField", imm);
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut read = |text: &str| {
+        let mut handle = Cursor::new(text.as_bytes().to_vec());
+        metafield
+            .value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx)
+            .map(|value| *value.read().unwrap().downcast_ref::<igVec3f>().unwrap())
+    };
+    assert_eq!(read("1 2.5, 3"), Some(igVec3f::new(1.0, 2.5, 3.0)));
+    assert_eq!(read("1 2"), None);
+    assert_eq!(read("1 two 3"), None);
+    assert_eq!(ctx.diagnostics.len(), 2);
+    assert!(ctx.diagnostics.iter().all(|x| x.kind == igLoadDiagnosticKind::FieldDecodeFailed));
+    assert!(ctx.diagnostics[1].message.contains("1 two 3"));
+}
+
+fn int_memory(values: &[i32]) -> igAny {
+    let mut memory: igMemory<igAny> = igMemory::new();
+    for value in values {
+        memory.data.push(Arc::new(RwLock::new(*value)));
+    }
+    Arc::new(RwLock::new(memory))
+}
+
+/// Lists with no registered constructor are typed from the element metafield of _data
+#[test]
+fn test_data_list_typed_from_element() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igIntList").unwrap();
+    let object = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    assert!(object.downcast::<igDataList<i32>>().is_some());
+}
+
+/// _count drops the unused capacity of _data no matter which field is set first
+#[test]
+fn test_data_list_count() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igIntList").unwrap();
+    let count: igAny = Arc::new(RwLock::new(2i32));
+
+    let list = igDataList::<i32>::construct(&meta.read().unwrap(), igMemoryPool::Default).unwrap();
+    let mut guard = list.write().unwrap();
+    guard.set_field("_count", Some(count.clone())).unwrap();
+    guard.set_field("_data", Some(int_memory(&[1, 2, 3]))).unwrap();
+    drop(guard);
+    assert_eq!(list.downcast::<igDataList<i32>>().unwrap().read().unwrap().iter().collect::<Vec<_>>(), vec![1, 2]);
+
+    let list = igDataList::<i32>::construct(&meta.read().unwrap(), igMemoryPool::Default).unwrap();
+    let mut guard = list.write().unwrap();
+    guard.set_field("_data", Some(int_memory(&[1]))).unwrap();
+    assert!(matches!(guard.set_field("_count", Some(count)), Err(SetObjectFieldError::InvalidValue)));
+}
+
+/// igStructMetaField has no real size in metafields.xml, every field stores the size of its struct
+#[test]
+fn test_struct_meta_field_uses_type_size() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igMetaField").unwrap();
+    let properties = meta.read().unwrap().field_storage.name_lookup.get("_properties").unwrap().clone();
+    assert_eq!(properties.size, 4);
+}
+
+/// Struct bytes that aren't valid hex in an igx file are reported instead of aborting the load
+#[test]
+fn test_struct_meta_field_igx_reports_bad_hex() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igMetaField").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_properties").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut read = |text: &str| {
+        let mut handle = Cursor::new(text.as_bytes().to_vec());
+        metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx)
+    };
+    let value = read("0102A0FF").unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<Vec<u8>>().unwrap(), vec![0x01, 0x02, 0xA0, 0xFF]);
+    assert!(read("0102A0F").is_none());
+    assert!(read("01 2A0FF").is_none());
+    assert_eq!(ctx.diagnostics.len(), 2);
+    assert!(ctx.diagnostics.iter().all(|x| x.kind == igLoadDiagnosticKind::FieldDecodeFailed));
+}
+
+/// Compound fields are inherited from the compound metafields a type extends
+#[test]
+fn test_compound_fields_are_inherited() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &ark_core.metadata_manager;
+    let fields = imm.get_compound_fields("igDebugTriangleArrayMetaField", IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE).unwrap();
+    let color = fields.name_lookup.get("_color0").unwrap();
+    assert_eq!(color.offset, 0x30);
+    assert_eq!(color.size, 16);
+    assert_eq!(fields.fields().len(), 6);
+    assert!(imm.get_compound_fields("igFloatMetaField", IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE).is_none());
+}
+
+/// Compound values are written field by field in igb files and read back into an igCompoundValue
+#[test]
+fn test_compound_meta_field_igb_round_trip() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igVfxPlacedPrimitiveData").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_velocityScale").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info.clone(), imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    assert_eq!(igMetaField::type_id(metafield.as_ref()), TypeId::of::<igCompoundValue>());
+    // The sub-field tree is only built the first time the field is looked up
+    assert!(Arc::ptr_eq(&metafield, &imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE)));
+
+    let mut value = igCompoundValue::new(Arc::from("igVfxRangedCurveMetaField"));
+    value.set_field(Arc::from("_valueOrScale"), Some(Arc::new(RwLock::new(2.5f32))));
+    let mut handle = Cursor::new(Vec::new());
+    let dir = igObjectDirectory::with_loader("compound.igb", igName::new("compound".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    let mut saver_ctx = IgbSaverContext::new(&dir, &object_stream_manager);
+    metafield.value_into_igb(imm, &object_stream_manager, &mut handle, Some(Arc::new(RwLock::new(value))), Endian::Big, &mut saver_ctx).unwrap();
+    // The float, then a null memory ref
+    assert_eq!(handle.get_ref(), &[0x40, 0x20, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF]);
+
+    handle.set_position(0);
+    let mut loader_ctx = IgbLoaderContext::new(5, false);
+    let read = metafield.value_from_igb(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut loader_ctx).unwrap();
+    let read = read.read().unwrap();
+    let read = read.downcast_ref::<igCompoundValue>().unwrap();
+    assert_eq!(*read.get_field("_valueOrScale").unwrap().read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    assert!(read.get_field("_data").is_none());
+    assert_eq!(handle.position(), 8);
+}
+
+/// Array forms take the size of their element times the element count
+#[test]
+fn test_array_meta_field_size() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igMorphTargetWeightAttr").unwrap();
+    assert_eq!(meta.read().unwrap().field_storage.name_lookup.get("_weights").unwrap().size, 4 * 32);
+    let meta = imm.get_or_create_meta("igPS3EdgeManager").unwrap();
+    assert_eq!(meta.read().unwrap().field_storage.name_lookup.get("_queue").unwrap().size, 0x60 * 5);
+    assert_eq!(imm.array_element_type("igFloatArrayMetaField").as_deref(), Some("igFloatMetaField"));
+    assert!(imm.array_element_type("igFloatMetaField").is_none());
+}
+
+/// Null elements are written as the default value of the element, and values that don't hold `num` elements can't be saved
+#[test]
+fn test_array_meta_field_igb_round_trip() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igCommonTraversal").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_renderTypeEnabled").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info.clone(), imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    assert!(Arc::ptr_eq(&metafield, &imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE)));
+
+    let value: Vec<Option<igAny>> = vec![Some(Arc::new(RwLock::new(true))), None, Some(Arc::new(RwLock::new(false)))];
+    let mut handle = Cursor::new(Vec::new());
+    let dir = igObjectDirectory::with_loader("array.igb", igName::new("array".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    let mut saver_ctx = IgbSaverContext::new(&dir, &object_stream_manager);
+    metafield.value_into_igb(imm, &object_stream_manager, &mut handle, Some(Arc::new(RwLock::new(value))), Endian::Big, &mut saver_ctx).unwrap();
+    assert_eq!(handle.get_ref(), &[1, 0, 0]);
+    let short: Vec<Option<igAny>> = vec![None, None];
+    let result = metafield.value_into_igb(imm, &object_stream_manager, &mut Cursor::new(Vec::new()), Some(Arc::new(RwLock::new(short))), Endian::Big, &mut saver_ctx);
+    assert!(matches!(result, Err(IgbSaverError::InvalidValueType(_))));
+
+    handle.get_mut()[2] = 1;
+    handle.set_position(0);
+    let mut loader_ctx = IgbLoaderContext::new(5, false);
+    let read = metafield.value_from_igb(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut loader_ctx).unwrap();
+    let read = read.read().unwrap();
+    let read: Vec<bool> = read
+        .downcast_ref::<Vec<Option<igAny>>>()
+        .unwrap()
+        .iter()
+        .map(|element| *element.as_ref().unwrap().read().unwrap().downcast_ref::<bool>().unwrap())
+        .collect();
+    assert_eq!(read, vec![true, false, true]);
+}
+
+/// igx arrays that don't hold `num` elements are reported instead of being padded or cut
+#[test]
+fn test_array_meta_field_igx_reports_wrong_count() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igCommonTraversal").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_renderTypeEnabled").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut read = |igx: &str| {
+        let mut handle = Cursor::new(igx.as_bytes().to_vec());
+        metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx)
+    };
+    let value = read("<element>true</element><element>false</element><element>true</element>").unwrap();
+    assert_eq!(value.read().unwrap().downcast_ref::<Vec<Option<igAny>>>().unwrap().len(), 3);
+    assert!(read("<element>true</element><element>false</element>").is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+}
+
+/// Hash tables are typed from the element metafield of _keys and take their invalid key and value from the metadata
+#[test]
+fn test_hash_table_typed_from_keys() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igStringIntHashTable").unwrap();
+    assert_eq!(meta.read().unwrap().hash_table_info.as_ref().unwrap().invalid_value, "-84215046");
+    let object = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    assert!(object.downcast::<igHashTable<Arc<str>>>().is_some());
+
+    let meta = ark_core.metadata_manager.get_or_create_meta("igObjectLoaderTable").unwrap();
+    assert!(meta.read().unwrap().hash_table_info.is_some());
+
+    // Enum keys can't be hashed yet
+    let meta = ark_core.metadata_manager.get_or_create_meta("igPlatformRenderPassMap").unwrap();
+    let object = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    assert!(object.downcast::<igHashTable<i32>>().is_none());
+}
+
+/// Slots holding the invalid key are not entries of the table
+#[test]
+fn test_hash_table_skips_invalid_slots() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igIntIntHashTable").unwrap();
+    let table = igHashTable::<i32>::construct(&meta.read().unwrap(), igMemoryPool::Default).unwrap();
+    let mut guard = table.write().unwrap();
+    guard.set_field("_hashItemCount", Some(Arc::new(RwLock::new(2i32)))).unwrap();
+    guard.set_field("_keys", Some(int_memory(&[-84215046, 5, -84215046, 7]))).unwrap();
+    guard.set_field("_values", Some(int_memory(&[-84215046, 50, -84215046, 70]))).unwrap();
+    drop(guard);
+
+    let table = table.downcast::<igHashTable<i32>>().unwrap();
+    let table = table.read().unwrap();
+    assert_eq!(table.len(), 2);
+    assert!(!table.contains_key(&-84215046));
+    assert_eq!(*table.get(&7).unwrap().read().unwrap().downcast_ref::<i32>().unwrap(), 70);
+}
+
+/// Known answers for [hash_int], worked out by hand from Bob Jenkins' published hash rather than with the code under test. There are no game files to check them against, so this only guards the implementation against drifting from the published hash
+#[test]
+fn test_hash_int_known_values() {
+    for (key, hash) in [(0u32, 0x6B4ED927u32), (1, 0xB48681B6), (2, 0xE267B84C), (3, 0x4F6E0E9C), (4, 0x3868201D), (0xDEADBEEF, 0x7FF0EADA), (u32::MAX, 0xFE64C182)] {
+        assert_eq!(hash_int(key), hash, "hash_int({:#x})", key);
+    }
+}
+
+fn hash_table_memory(elements: Vec<igAny>) -> Option<igAny> {
+    let mut memory: igMemory<igAny> = igMemory::new();
+    memory.data = elements;
+    Some(Arc::new(RwLock::new(memory)))
+}
+
+fn hash_table_slots<T: Copy + 'static>(table: &dyn __internalObjectBase, name: &str) -> Vec<T> {
+    let memory = table.get_field(name).ok().flatten().unwrap();
+    let memory = memory.read().unwrap();
+    memory.downcast_ref::<igMemory<igAny>>().unwrap().data.iter().map(|x| *x.read().unwrap().downcast_ref::<T>().unwrap()).collect()
+}
+
+/// A pass through table laid out by hand the way open addressing places keys: each key at `key % 8`, pushed to the next free slot on collisions. The layout doesn't depend on any hash function, so loading and saving it checks the probing on its own
+#[test]
+fn test_pass_through_hash_table_fixture() {
+    const INVALID: i32 = -84215046;
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igIntIntPassThroughHashTable").unwrap();
+    let table = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).ok().unwrap();
+    let mut guard = table.write().unwrap();
+
+    // 3 and 11 both want slot 3, 7 wants slot 7 and 15 wraps around from 7 to 0
+    let keys = [15, INVALID, INVALID, 3, 11, INVALID, INVALID, 7];
+    let values = [150, INVALID, INVALID, 30, 110, INVALID, INVALID, 70];
+    let wrap = |slots: [i32; 8]| hash_table_memory(slots.iter().map(|x| Arc::new(RwLock::new(*x)) as igAny).collect());
+    guard.set_field("_values", wrap(values)).unwrap();
+    guard.set_field("_keys", wrap(keys)).unwrap();
+    guard.set_field("_hashItemCount", Some(Arc::new(RwLock::new(4i32)))).unwrap();
+
+    let table = guard.as_any().downcast_ref::<igHashTable<i32>>().unwrap();
+    assert_eq!(table.len(), 4);
+    for key in [3, 7, 11, 15] {
+        assert_eq!(*table.get(&key).unwrap().read().unwrap().downcast_ref::<i32>().unwrap(), key * 10);
+    }
+    assert_eq!(hash_table_slots::<i32>(&*guard, "_keys"), keys);
+    assert_eq!(hash_table_slots::<i32>(&*guard, "_values"), values);
+}
+
+/// Saving places each key at its hash modulo the slot count, probing forward on collisions, and fills the other slots with the invalid key. The expected slots use the hashes from [test_hash_int_known_values]: 1 is in slot 6, 2 and 3 both want slot 4, and 4 wants slot 5 but is pushed to 7
+#[test]
+fn test_hash_table_layout() {
+    const INVALID: u32 = 4210752250;
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igUnsignedIntIntHashTable").unwrap();
+    let table = igHashTable::<u32>::construct(&meta.read().unwrap(), igMemoryPool::Default).unwrap();
+    let table = table.downcast::<igHashTable<u32>>().unwrap();
+    let mut guard = table.write().unwrap();
+    for key in [1u32, 2, 3, 5] {
+        guard.insert(key, Some(Arc::new(RwLock::new(key as i32 * 10))));
+    }
+    assert_eq!(*guard.remove(&5).unwrap().read().unwrap().downcast_ref::<i32>().unwrap(), 50);
+    guard.insert(4, None);
+
+    // 4 entries at a load factor of 0.5 need 8 slots
+    let keys = hash_table_slots::<u32>(&*guard, "_keys");
+    assert_eq!(keys, vec![INVALID, INVALID, INVALID, INVALID, 2, 3, 1, 4]);
+
+    let values = guard.get_field("_values").ok().flatten().unwrap();
+    let values = values.read().unwrap();
+    let values = &values.downcast_ref::<igMemory<igAny>>().unwrap().data;
+    assert!(values[7].read().unwrap().is::<igNullElement>());
+    assert_eq!(*values[0].read().unwrap().downcast_ref::<i32>().unwrap(), -84215046);
+    assert_eq!(*values[5].read().unwrap().downcast_ref::<i32>().unwrap(), 30);
+}
+
+/// Enum keyed tables are typed, with the invalid key resolved from the name of its entry
+#[test]
+fn test_enum_hash_table() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("tfbNetFeedFrequencyMap").unwrap();
+    assert_eq!(meta.read().unwrap().hash_table_info.as_ref().unwrap().invalid_key, "0");
+    let table = meta.read().unwrap().raw_instantiate(igMemoryPool::Default, false).ok().unwrap();
+    let mut guard = table.write().unwrap();
+    let event_codes = imm.get_meta_enum_values("EventCode").unwrap();
+    let (name, value) = event_codes.iter().find(|(_, value)| *value != 0).unwrap().clone();
+
+    let key = |value: i32, name: Option<Arc<str>>| Arc::new(RwLock::new(igEnumValue { meta_enum: Some(Arc::from("EventCode")), value, name })) as igAny;
+    guard.set_field("_keys", hash_table_memory(vec![key(0, Some(Arc::from("ec_none"))), key(value, Some(name.clone()))])).unwrap();
+    guard.set_field("_values", hash_table_memory(vec![Arc::new(RwLock::new(0f32)), Arc::new(RwLock::new(1.5f32))])).unwrap();
+
+    let table = guard.as_any().downcast_ref::<igHashTable<igEnumValue>>().unwrap();
+    assert_eq!(table.len(), 1);
+    let (loaded, frequency) = table.iter().next().unwrap();
+    assert_eq!(loaded.name, Some(name));
+    assert_eq!(*frequency.as_ref().unwrap().read().unwrap().downcast_ref::<f32>().unwrap(), 1.5);
+
+    let keys = guard.get_field("_keys").ok().flatten().unwrap();
+    let keys = keys.read().unwrap();
+    let keys: Vec<i32> = keys.downcast_ref::<igMemory<igAny>>().unwrap().data.iter().map(|x| x.read().unwrap().downcast_ref::<igEnumValue>().unwrap().value).collect();
+    assert_eq!(keys.iter().filter(|key| **key == 0).count(), keys.len() - 1);
+    assert!(keys.contains(&value));
+}
+
+/// Static fields belong to the type and aren't constructed on objects
+#[test]
+fn test_static_fields_are_on_the_meta_object() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igAttr").unwrap();
+    let mut meta = meta.write().unwrap();
+    assert!(!meta.field_storage.name_lookup.contains_key("_nextAttrIndex"));
+    assert_eq!(meta.static_fields.get("_nextAttrIndex").unwrap()._type.as_ref(), "igIntMetaField");
+
+    assert!(meta.get_static("_nextAttrIndex").ok().flatten().is_none());
+    meta.set_static("_nextAttrIndex", Some(Arc::new(RwLock::new(12i32)))).unwrap();
+    let value = meta.get_static("_nextAttrIndex").ok().flatten().unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<i32>().unwrap(), 12);
+    assert!(meta.get_static("_cachedUnitID").is_err());
+
+    let object = meta.raw_instantiate(igMemoryPool::Default, false).ok().unwrap();
+    assert!(object.read().unwrap().get_field("_nextAttrIndex").is_err());
+}
+
+/// Property fields read and write the field storing their value
+#[test]
+fn test_property_fields_use_their_storage() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let meta = ark_core.metadata_manager.get_or_create_meta("igVfxPlacedPrimitiveData").unwrap();
+    let meta = meta.read().unwrap();
+    assert!(!meta.field_storage.name_lookup.contains_key("_rotationAxis"));
+    assert_eq!(meta.field_storage.properties.get("_rotationAxis").map(|x| x.as_ref()), Some("_rotationAxisInternal"));
+    assert!(!meta.field_storage.properties.contains_key("_red"));
+
+    let object = meta.raw_instantiate(igMemoryPool::Default, false).ok().unwrap();
+    let axis = igVec3f::new(0.0, 1.0, 0.0);
+    object.write().unwrap().set_field("_rotationAxis", Some(Arc::new(RwLock::new(axis)))).unwrap();
+    let value = object.read().unwrap().get_field("_rotationAxisInternal").ok().flatten().unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<igVec3f>().unwrap(), axis);
+    // _red is a curve the game builds in code, so setting it from igx is reported instead of silently dropped
+    assert!(meta.field_storage.unmapped_properties.iter().any(|x| x.as_ref() == "_red"));
+    drop(meta);
+
+    let object_stream_manager = igObjectStreamManager::new();
+    let mut ctx = IgxLoaderContext::new();
+    let fields = [(Arc::from("_rotationAxis"), b"1.0 0.0 0.0".to_vec()), (Arc::from("_red"), b"0.5".to_vec())];
+    ark_core.metadata_manager.read_igx_fields(&object_stream_manager, &mut ctx, object.clone(), &fields);
+    let value = object.read().unwrap().get_field("_rotationAxisInternal").ok().flatten().unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<igVec3f>().unwrap(), igVec3f::new(1.0, 0.0, 0.0));
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::UnmappedProperty);
+    assert_eq!(ctx.diagnostics[0].field.as_deref(), Some("_red"));
+}
+
+/// Builds a big endian version 9 igz targeting CAFE so the igz loader can be tested without game files. Every object is stored in a single Default section, so offsets into that section are also the serialized offsets the fixups use
+struct SyntheticIgz {
+    types: Vec<&'static str>,
+    strings: Vec<String>,
+    objects: Vec<u32>,
+    data: Vec<u8>,
+    /// Fixups written after TMET, TSTR and RVTB, in the order they were added
+    fixups: Vec<([u8; 4], u32, Vec<u8>)>,
+    /// Offset lists written after every other fixup, keyed by their magic
+    runtime_fixups: BTreeMap<[u8; 4], Vec<u32>>,
+    root: Option<u32>,
+}
+
+impl SyntheticIgz {
+    fn new(types: &[&'static str]) -> SyntheticIgz {
+        SyntheticIgz { types: types.to_vec(), strings: vec![], objects: vec![], data: vec![], fixups: vec![], runtime_fixups: BTreeMap::new(), root: None }
+    }
+
+    /// Appends an object and returns its offset. The first 4 bytes of `body` are replaced with the index of its type
+    fn object(&mut self, type_index: u32, body: &[u8]) -> u32 {
+        let offset = self.data(body, 4);
+        self.data[offset as usize..offset as usize + 4].copy_from_slice(&type_index.to_be_bytes());
+        self.objects.push(offset);
+        offset
+    }
+
+    /// Appends raw bytes that aren't an object and returns their offset
+    fn data(&mut self, bytes: &[u8], alignment: usize) -> u32 {
+        self.data.resize(self.data.len().next_multiple_of(alignment), 0);
+        let offset = self.data.len() as u32;
+        self.data.extend_from_slice(bytes);
+        offset
+    }
+
+    fn fixup(&mut self, magic: &[u8; 4], count: u32, data: Vec<u8>) {
+        self.fixups.push((*magic, count, data));
+    }
+
+    /// Adds offsets to a fixup holding a compressed list of offsets, the way ROFS, RHND and the other runtime fixups are stored
+    fn runtime_fixup(&mut self, magic: &[u8; 4], offsets: &[u32]) {
+        self.runtime_fixups.entry(*magic).or_default().extend_from_slice(offsets);
+    }
+
+    /// Adds the igObjectList the directory's object list is read from
+    fn root(&mut self, objects: &[u32]) {
+        let elements = self.data(&objects.iter().flat_map(|x| x.to_be_bytes()).collect::<Vec<u8>>(), 4);
+        self.runtime_fixup(b"ROFS", &(0..objects.len() as u32).map(|i| elements + i * 4).collect::<Vec<u32>>());
+
+        self.types.push("igObjectList");
+        let mut body = vec![0u8; 0x18];
+        body[0x8..0xC].copy_from_slice(&(objects.len() as u32).to_be_bytes());
+        body[0xC..0x10].copy_from_slice(&(objects.len() as u32).to_be_bytes());
+        body[0x10..0x14].copy_from_slice(&(objects.len() as u32 * 4).to_be_bytes());
+        body[0x14..0x18].copy_from_slice(&elements.to_be_bytes());
+        let list = self.object(self.types.len() as u32 - 1, &body);
+        self.runtime_fixup(b"ROFS", &[list + 0x14]);
+        self.root = Some(list);
+    }
+
+    fn build(&self) -> Vec<u8> {
+        let mut fixups = vec![(*b"TMET", self.types.len() as u32, aligned_strings(self.types.iter().copied()))];
+        if !self.strings.is_empty() {
+            fixups.push((*b"TSTR", self.strings.len() as u32, aligned_strings(self.strings.iter().map(|x| x.as_str()))));
+        }
+        fixups.push((*b"RVTB", self.objects.len() as u32, pack_compressed_ints(&self.objects)));
+        fixups.extend(self.fixups.iter().cloned());
+        for (magic, offsets) in &self.runtime_fixups {
+            let mut offsets = offsets.clone();
+            offsets.sort_unstable();
+            fixups.push((*magic, offsets.len() as u32, pack_compressed_ints(&offsets)));
+        }
+        if let Some(root) = self.root {
+            fixups.push((*b"ROOT", 1, pack_compressed_ints(&[root])));
+        }
+
+        let mut fixup_section = vec![];
+        for (magic, count, data) in &fixups {
+            let length = (0x10 + data.len()).next_multiple_of(4) as u32;
+            fixup_section.extend_from_slice(&u32::from_le_bytes(*magic).to_be_bytes());
+            fixup_section.extend_from_slice(&count.to_be_bytes());
+            fixup_section.extend_from_slice(&length.to_be_bytes());
+            fixup_section.extend_from_slice(&0x10u32.to_be_bytes());
+            fixup_section.extend_from_slice(data);
+            fixup_section.resize(fixup_section.len().next_multiple_of(4), 0);
+        }
+
+        let fixup_offset = 0x800u32;
+        let data_offset = (fixup_offset as usize + fixup_section.len()).next_multiple_of(0x10) as u32;
+        let mut file = vec![0u8; data_offset as usize];
+        file[0..4].copy_from_slice(b"IGZ\x01");
+        for (i, value) in [9u32, 0, 9, fixups.len() as u32].iter().enumerate() {
+            file[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_be_bytes());
+        }
+        for (i, section) in [[0, fixup_offset, fixup_section.len() as u32, 0x10], [0, data_offset, self.data.len() as u32, 0x10]].iter().enumerate() {
+            for (j, value) in section.iter().enumerate() {
+                let position = 0x14 + 0x10 * i + 4 * j;
+                file[position..position + 4].copy_from_slice(&value.to_be_bytes());
+            }
+        }
+        file[0x224..0x22C].copy_from_slice(b"Default\0");
+        file[fixup_offset as usize..fixup_offset as usize + fixup_section.len()].copy_from_slice(&fixup_section);
+        file.extend_from_slice(&self.data);
+        file
+    }
+
+    /// Adds the igNameList naming the objects in the root list, in the same order. The strings are stored in TSTR
+    fn names(&mut self, names: &[&str]) {
+        let mut elements = vec![];
+        for name in names {
+            elements.extend_from_slice(&(self.strings.len() as u32).to_be_bytes());
+            elements.extend_from_slice(&hash_lower(name).to_be_bytes());
+            self.strings.push(name.to_string());
+        }
+        let elements = self.data(&elements, 4);
+        self.runtime_fixup(b"RSTT", &(0..names.len() as u32).map(|i| elements + i * 8).collect::<Vec<u32>>());
+
+        self.types.push("igNameList");
+        let mut body = vec![0u8; 0x18];
+        body[0x8..0xC].copy_from_slice(&(names.len() as u32).to_be_bytes());
+        body[0xC..0x10].copy_from_slice(&(names.len() as u32).to_be_bytes());
+        body[0x10..0x14].copy_from_slice(&(names.len() as u32 * 8).to_be_bytes());
+        body[0x14..0x18].copy_from_slice(&elements.to_be_bytes());
+        let list = self.object(self.types.len() as u32 - 1, &body);
+        self.runtime_fixup(b"ROFS", &[list + 0x14]);
+        self.fixup(b"ONAM", 1, list.to_be_bytes().to_vec());
+    }
+
+    /// Writes the igz to a temporary file and returns its path. The caller removes it
+    fn write(&self, name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("ig_library_{}_{}.igz", name, std::process::id()));
+        std::fs::write(&path, self.build()).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    /// Writes the igz to a temporary file and loads it
+    fn load(&self, ig_alchemy: &mut igAlchemy, name: &str) -> Arc<RwLock<igObjectDirectory>> {
+        let path = self.write(name);
+        let dir = ig_alchemy.object_stream_manager.load(
+            &ig_alchemy.file_context,
+            &ig_alchemy.registry,
+            &mut ig_alchemy.ark_core.metadata_manager,
+            &mut ig_alchemy.ig_ext_ref_system,
+            &mut ig_alchemy.ig_object_handle_manager,
+            path.clone(),
+        );
+        std::fs::remove_file(&path).unwrap();
+        dir.unwrap()
+    }
+}
+
+/// Strings as TMET and TSTR store them, null terminated and padded to 2 bytes
+fn aligned_strings<'a>(strings: impl Iterator<Item = &'a str>) -> Vec<u8> {
+    let mut data = vec![];
+    for string in strings {
+        data.extend_from_slice(string.as_bytes());
+        data.push(0);
+        data.resize(data.len().next_multiple_of(2), 0);
+    }
+    data
+}
+
+/// The inverse of [crate::core::load::ig_igz_loader::unpack_compressed_ints] for version 9 files. `values` must be sorted and 4 byte aligned
+fn pack_compressed_ints(values: &[u32]) -> Vec<u8> {
+    let mut nibbles = vec![];
+    let mut previous = 0;
+    for value in values {
+        let mut delta = (value - previous) / 4;
+        previous = *value;
+        loop {
+            let nibble = (delta & 0x7) as u8;
+            delta >>= 3;
+            if delta == 0 {
+                nibbles.push(nibble);
+                break;
+            }
+            nibbles.push(nibble | 0x8);
+        }
+    }
+    nibbles.chunks(2).map(|pair| pair[0] | (pair.get(1).unwrap_or(&0) << 4)).collect()
+}
+
+fn load_trap_team_alchemy(lenient: bool) -> igAlchemy {
+    let mut ig_alchemy = igAlchemy::new(
+        igFileContext::new("".to_string(), None),
+        igRegistry::new(IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE),
+        igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE),
+    );
+    ig_alchemy.object_stream_manager.lenient_loading = lenient;
+    ig_alchemy
+}
+
+/// An igTimer: the vtable and ref count, then _active, _startTime (an igTimeMetaField, which has no implementation) and _elapsedSeconds
+fn timer_body() -> Vec<u8> {
+    let mut body = vec![0u8; 0x14];
+    body[0x8] = 1;
+    body[0xC..0x10].copy_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+    body[0x10..0x14].copy_from_slice(&2.5f32.to_be_bytes());
+    body
+}
+
+/// Fields without a metafield implementation keep their bytes and are reported, but don't stop the file from being saved
+#[test]
+fn test_placeholder_fields_dont_block_saving() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    let dir = igz.load(&mut ig_alchemy, "placeholder");
+    let dir = dir.read().unwrap();
+
+    assert_eq!(dir.object_list.read().unwrap().len(), 1);
+    assert_eq!(dir.diagnostics.len(), 1);
+    assert_eq!(dir.diagnostics[0].kind, igLoadDiagnosticKind::MissingMetaFieldImpl);
+    assert_eq!(dir.diagnostics[0].field.as_deref(), Some("_startTime"));
+    assert!(dir.can_safely_save());
+
+    let timer = dir.all_objects[0].read().unwrap();
+    assert!(timer.as_any().is::<igGenericObject>());
+    let start_time = timer.get_field("_startTime").ok().flatten().unwrap();
+    assert_eq!(*start_time.read().unwrap().downcast_ref::<Vec<u8>>().unwrap(), vec![0xDE, 0xAD, 0xBE, 0xEF]);
+    let elapsed = timer.get_field("_elapsedSeconds").ok().flatten().unwrap();
+    assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    assert!(save_igb(&dir, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager, Endian::Big).is_ok());
+}
+
+/// Every object in an igz ends up in all_objects in the order they are stored, including the ones the root list doesn't hold
+#[test]
+fn test_all_objects_in_file_order() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timers: Vec<u32> = [1.0f32, 2.0, 3.0]
+        .iter()
+        .map(|elapsed| {
+            let mut body = timer_body();
+            body[0x10..0x14].copy_from_slice(&elapsed.to_be_bytes());
+            igz.object(0, &body)
+        })
+        .collect();
+    igz.root(&[timers[2], timers[0]]);
+    let dir = igz.load(&mut ig_alchemy, "order");
+    let dir = dir.read().unwrap();
+
+    let elapsed = |object: &igObject| *object.read().unwrap().get_field("_elapsedSeconds").ok().flatten().unwrap().read().unwrap().downcast_ref::<f32>().unwrap();
+    assert_eq!(dir.all_objects.len(), 4);
+    assert_eq!(dir.all_objects[..3].iter().map(elapsed).collect::<Vec<f32>>(), vec![1.0, 2.0, 3.0]);
+    // The root list is stored after the timers
+    assert_eq!(dir.all_objects[3].read().unwrap().object_name().as_ref(), "igObjectList");
+    assert_eq!(dir.object_list.read().unwrap().iter().map(|object| elapsed(&object)).collect::<Vec<f32>>(), vec![3.0, 1.0]);
+}
+
+/// An igProperty whose _key points at an offset no object starts at
+fn dangling_property() -> SyntheticIgz {
+    let mut igz = SyntheticIgz::new(&["igProperty"]);
+    let mut body = vec![0u8; 0x10];
+    body[0x8..0xC].copy_from_slice(&0x40u32.to_be_bytes());
+    let property = igz.object(0, &body);
+    igz.runtime_fixup(b"ROFS", &[property + 0x8]);
+    igz.root(&[property]);
+    igz
+}
+
+/// Lenient loading replaces references it can't resolve with igNull and records why, which stops the file from being saved
+#[test]
+fn test_lenient_loading_reports_unresolved_references() {
+    let mut ig_alchemy = load_trap_team_alchemy(true);
+    let dir = dangling_property().load(&mut ig_alchemy, "lenient");
+    let dir = dir.read().unwrap();
+
+    assert_eq!(dir.diagnostics.len(), 1);
+    let diagnostic = &dir.diagnostics[0];
+    assert_eq!(diagnostic.kind, igLoadDiagnosticKind::UnresolvedReference);
+    assert_eq!(diagnostic.field.as_deref(), Some("_key"));
+    assert!(diagnostic.object.as_ref().is_some_and(|object| Arc::ptr_eq(object, &dir.all_objects[0])));
+    assert_eq!(dir.object_diagnostics(&dir.all_objects[0]).len(), 1);
+    assert!(!dir.can_safely_save());
+
+    let key = dir.all_objects[0].read().unwrap().get_field("_key").ok().flatten().unwrap();
+    let key = key.read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+    assert!(key.read().unwrap().as_any().is::<igNull>());
+    assert!(save_igb(&dir, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager, Endian::Big).is_err());
+}
+
+/// Without lenient loading the same file aborts the load
+#[test]
+#[should_panic(expected = "Alchemy Error!")]
+fn test_strict_loading_aborts_on_unresolved_references() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    dangling_property().load(&mut ig_alchemy, "strict");
+}
+
+/// Broken fixups are recorded when loading leniently, and the directory keeps an empty root and name list
+#[test]
+fn test_lenient_loading_reports_broken_fixups() {
+    let mut ig_alchemy = load_trap_team_alchemy(true);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    // ROOT points at the timer instead of an igObjectList and ONAM points at nothing
+    igz.root = Some(timer);
+    igz.fixup(b"ONAM", 1, 0x400u32.to_be_bytes().to_vec());
+    // Neither string index exists
+    igz.fixup(b"EXNM", 1, ((5u64 << 32) | 6).to_be_bytes().to_vec());
+    let dir = igz.load(&mut ig_alchemy, "broken_fixups");
+    let dir = dir.read().unwrap();
+
+    // _startTime has no metafield implementation, which isn't what's being tested
+    let kinds: Vec<_> = dir.diagnostics.iter().map(|x| x.kind.clone()).filter(|x| *x != igLoadDiagnosticKind::MissingMetaFieldImpl).collect();
+    assert_eq!(kinds, vec![igLoadDiagnosticKind::MalformedFile, igLoadDiagnosticKind::UnresolvedReference, igLoadDiagnosticKind::MalformedFile]);
+    assert!(dir.diagnostics[0].message.contains("ONAM"));
+    assert!(dir.diagnostics[2].message.contains("ROOT"));
+    assert!(dir.object_list.read().unwrap().iter().next().is_none());
+    assert_eq!(dir.all_objects.len(), 1);
+    assert!(!dir.can_safely_save());
+}
+
+/// EXID entries whose namespace never gets loaded are reported against the file as well as the field using them
+#[test]
+fn test_unresolved_exid_is_reported() {
+    let mut ig_alchemy = load_trap_team_alchemy(true);
+    let dir = external_property("missing", "timer").load(&mut ig_alchemy, "unresolved_exid");
+    let dir = dir.read().unwrap();
+
+    let unresolved: Vec<_> = dir.diagnostics.iter().filter(|x| x.kind == igLoadDiagnosticKind::UnresolvedReference).collect();
+    assert_eq!(unresolved.len(), 2);
+    assert!(unresolved[0].object.is_none());
+    assert!(unresolved[0].message.contains("EXID"));
+    assert_eq!(unresolved[1].field.as_deref(), Some("_key"));
+}
+
+/// A file that isn't an igz at all leaves the directory empty when loading leniently
+#[test]
+fn test_lenient_loading_reports_unreadable_files() {
+    let mut ig_alchemy = load_trap_team_alchemy(true);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    igz.object(0, &timer_body());
+    let mut file = igz.build();
+    file[0..4].copy_from_slice(b"NOPE");
+    let path = std::env::temp_dir().join(format!("ig_library_bad_magic_{}.igz", std::process::id()));
+    std::fs::write(&path, file).unwrap();
+    let dir = ig_alchemy.object_stream_manager.load(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        path.to_str().unwrap().to_string(),
+    );
+    std::fs::remove_file(&path).unwrap();
+    let dir = dir.unwrap();
+    let dir = dir.read().unwrap();
+
+    assert_eq!(dir.diagnostics.len(), 1);
+    assert_eq!(dir.diagnostics[0].kind, igLoadDiagnosticKind::MalformedFile);
+    assert!(dir.all_objects.is_empty());
+    assert!(!dir.can_safely_save());
+}
+
+/// An igProperty whose _key is the object named `name` in the namespace `namespace`, looked up through EXID
+fn external_property(namespace: &str, name: &str) -> SyntheticIgz {
+    let mut igz = SyntheticIgz::new(&["igProperty"]);
+    let property = igz.object(0, &[0u8; 0x10]);
+    igz.fixup(b"EXID", 1, [hash_lower(name), hash_lower(namespace)].iter().flat_map(|x| x.to_be_bytes()).collect());
+    igz.runtime_fixup(b"REXT", &[property + 0x8]);
+    igz.root(&[property]);
+    igz
+}
+
+fn load_many(ig_alchemy: &mut igAlchemy, paths: &[&String]) -> Vec<Arc<RwLock<igObjectDirectory>>> {
+    ig_alchemy
+        .object_stream_manager
+        .load_many(
+            &ig_alchemy.file_context,
+            &ig_alchemy.registry,
+            &mut ig_alchemy.ark_core.metadata_manager,
+            &mut ig_alchemy.ig_ext_ref_system,
+            &mut ig_alchemy.ig_object_handle_manager,
+            paths.iter().map(|path| path.to_string()).collect(),
+        )
+        .into_iter()
+        .map(|dir| dir.unwrap())
+        .collect()
+}
+
+/// Files loaded together have their fields read on worker threads, after any file in the batch they reference
+#[test]
+fn test_load_many_reads_fields_in_parallel() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut timers = SyntheticIgz::new(&["igTimer"]);
+    let timer = timers.object(0, &timer_body());
+    timers.root(&[timer]);
+    timers.names(&["timer"]);
+    let timers_path = timers.write("load_many_timers");
+    let property_path = external_property(&timers_path, "timer").write("load_many_property");
+    let other_timers_path = timers.write("load_many_other_timers");
+
+    // The property comes first, but can only be read once the timer's names are
+    let dirs = load_many(&mut ig_alchemy, &[&property_path, &timers_path, &other_timers_path]);
+    for path in [&property_path, &timers_path, &other_timers_path] {
+        std::fs::remove_file(path).unwrap();
+    }
+    assert!(ig_alchemy.object_stream_manager.deferred_fields.is_empty());
+
+    for dir in &dirs[1..] {
+        let dir = dir.read().unwrap();
+        assert!(dir.use_name_list);
+        assert_eq!(dir.name_list.read().unwrap().len(), 1);
+        let elapsed = dir.all_objects[0].read().unwrap().get_field("_elapsedSeconds").ok().flatten().unwrap();
+        assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    }
+
+    let property = dirs[0].read().unwrap();
+    assert!(property.diagnostics.is_empty());
+    let key = property.all_objects[0].read().unwrap().get_field("_key").ok().flatten().unwrap();
+    let key = key.read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+    assert!(Arc::ptr_eq(&key, &dirs[1].read().unwrap().all_objects[0]));
+}
+
+/// Dependencies of a file in the batch are read in full before the file's own fixups need them
+#[test]
+fn test_load_many_reads_dependencies_in_full() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    ig_alchemy.object_stream_manager.parallel_loading = true;
+    let mut timers = SyntheticIgz::new(&["igTimer"]);
+    let timer = timers.object(0, &timer_body());
+    timers.root(&[timer]);
+    timers.names(&["timer"]);
+    let timers_path = timers.write("dependency_timers");
+
+    let mut property = external_property("timers", "timer");
+    let mut dependency = b"timers\0".to_vec();
+    dependency.extend_from_slice(timers_path.as_bytes());
+    dependency.push(0);
+    property.fixup(b"TDEP", 1, dependency);
+    let property_path = property.write("dependency_property");
+
+    let dirs = load_many(&mut ig_alchemy, &[&property_path]);
+    std::fs::remove_file(&property_path).unwrap();
+    std::fs::remove_file(&timers_path).unwrap();
+
+    let property = dirs[0].read().unwrap();
+    assert_eq!(property.dependencies.len(), 1);
+    assert!(property.diagnostics.is_empty());
+    let timers = property.dependencies.iter().next().unwrap();
+    let timers = timers.read().unwrap();
+    assert_eq!(timers.all_objects.len(), 3);
+    let key = property.all_objects[0].read().unwrap().get_field("_key").ok().flatten().unwrap();
+    let key = key.read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+    assert!(Arc::ptr_eq(&key, &timers.all_objects[0]));
+}
+
+/// Loads two files into the `timers` namespace followed by one looking up `timers::timers`, either as one [igObjectStreamManager::load_many] batch or one file at a time. Returns the paths registered under `timers` in order, the path of the directory the lookup resolved into and the diagnostics of the file doing the lookup
+fn load_timers_package(parallel: bool, paths: &[String; 3]) -> (Vec<String>, Option<String>, Vec<String>) {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let namespaces = ["timers", "timers", "user"];
+    let batch: Vec<(String, igName)> = paths.iter().zip(namespaces).map(|(path, namespace)| (path.clone(), igName::new(namespace.to_string()))).collect();
+    let dirs: Vec<_> = if parallel {
+        ig_alchemy.object_stream_manager.load_many_with_namespace(
+            &ig_alchemy.file_context,
+            &ig_alchemy.registry,
+            &mut ig_alchemy.ark_core.metadata_manager,
+            &mut ig_alchemy.ig_ext_ref_system,
+            &mut ig_alchemy.ig_object_handle_manager,
+            batch,
+        )
+    } else {
+        batch
+            .into_iter()
+            .map(|(path, namespace)| {
+                ig_alchemy.object_stream_manager.load_with_namespace(
+                    &ig_alchemy.file_context,
+                    &ig_alchemy.registry,
+                    &mut ig_alchemy.ark_core.metadata_manager,
+                    &mut ig_alchemy.ig_ext_ref_system,
+                    &mut ig_alchemy.ig_object_handle_manager,
+                    path,
+                    namespace,
+                )
+            })
+            .collect()
+    };
+    let dirs: Vec<_> = dirs.into_iter().map(|dir| dir.unwrap()).collect();
+
+    let registered = ig_alchemy.object_stream_manager.name_to_directory_lookup[&igName::new("timers".to_string()).hash]
+        .iter()
+        .map(|dir| dir.read().unwrap().path.clone())
+        .collect();
+    let user = dirs[2].read().unwrap();
+    let key = user.all_objects[0].read().unwrap().get_field("_key").ok().flatten();
+    let key = key.map(|key| key.read().unwrap().downcast_ref::<igObject>().unwrap().clone());
+    let owner = key.and_then(|key| dirs[..2].iter().find(|dir| dir.read().unwrap().all_objects.iter().any(|object| Arc::ptr_eq(object, &key))).map(|dir| dir.read().unwrap().path.clone()));
+    let diagnostics = user.diagnostics.iter().map(|diagnostic| diagnostic.message.clone()).collect();
+    (registered, owner, diagnostics)
+}
+
+/// Loading a batch in parallel registers directories and resolves externals exactly like loading the files one at a time
+#[test]
+fn test_parallel_loading_matches_serial_loading() {
+    let mut timers = SyntheticIgz::new(&["igTimer"]);
+    let timer = timers.object(0, &timer_body());
+    timers.root(&[timer]);
+    timers.names(&["timers"]);
+    let paths = [
+        timers.write("package_first_timers"),
+        timers.write("package_second_timers"),
+        external_property("timers", "timers").write("package_user"),
+    ];
+
+    let serial = load_timers_package(false, &paths);
+    let parallel = load_timers_package(true, &paths);
+    for path in &paths {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    assert_eq!(serial.0, vec![paths[0].clone(), paths[1].clone()]);
+    // Both files define timers::timers, so the lookup resolves into the one registered first
+    assert_eq!(serial.1, Some(paths[0].clone()));
+    assert_eq!(parallel, serial);
+}
+
+/// An igz with one thumbnail. `size` is what the THUMBNAIL fixup claims the thumbnail's size is
+fn igz_with_thumbnail(thumbnail: &[u8], size: u32) -> SyntheticIgz {
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    let offset = igz.data(thumbnail, 0x10);
+    igz.root(&[timer]);
+    igz.fixup(b"TMHN", 1, [size, offset].iter().flat_map(|x| x.to_be_bytes()).collect());
+    igz
+}
+
+/// Thumbnails are read both when loading and straight out of the file, without the objects being touched
+#[test]
+fn test_thumbnails_are_extracted() {
+    let thumbnail = b"\x89PNG not really a png";
+    let igz = igz_with_thumbnail(thumbnail, thumbnail.len() as u32);
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let data = igz.build();
+
+    let thumbnails = read_igz_thumbnails(&data, &ig_alchemy.ark_core.metadata_manager).unwrap();
+    assert_eq!(thumbnails.len(), 1);
+    assert_eq!(thumbnails[0].data.as_ref(), thumbnail);
+    assert_eq!(&data[thumbnails[0].offset as usize..][..thumbnail.len()], thumbnail);
+
+    let dir = igz.load(&mut ig_alchemy, "thumbnail");
+    let dir = dir.read().unwrap();
+    assert_eq!(dir.thumbnails.len(), 1);
+    assert_eq!(dir.thumbnails[0].offset, thumbnails[0].offset);
+    assert_eq!(dir.thumbnails[0].data.as_ref(), thumbnail);
+
+    let igz = igz_with_thumbnail(thumbnail, 0x1000);
+    assert!(read_igz_thumbnails(&igz.build(), &ig_alchemy.ark_core.metadata_manager).is_err());
+    let igz = SyntheticIgz::new(&["igTimer"]);
+    assert!(read_igz_thumbnails(&igz.build(), &ig_alchemy.ark_core.metadata_manager).unwrap().is_empty());
+}
+
+/// A thumbnail that runs past the end of the file is left out and reported on the directory
+#[test]
+fn test_unreadable_thumbnail_is_reported() {
+    let igz = igz_with_thumbnail(b"short", 0x10000);
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+
+    let dir = igz.load(&mut ig_alchemy, "unreadable_thumbnail");
+    let dir = dir.read().unwrap();
+    assert!(dir.thumbnails.is_empty());
+    let unreadable: Vec<_> = dir.diagnostics.iter().filter(|x| x.kind == igLoadDiagnosticKind::UnreadableThumbnail).collect();
+    assert_eq!(unreadable.len(), 1);
+    assert!(unreadable[0].object.is_none());
+    assert!(!dir.can_safely_save());
+}
+
+/// Instance sizes run to the end of the furthest field and are padded to the largest field alignment
+#[test]
+fn test_calculate_size() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &mut ark_core.metadata_manager;
+    // igTimer's last field, _elapsedSeconds, ends at 0x14
+    assert_eq!(imm.get_or_create_meta("igTimer").unwrap().read().unwrap().calculate_size(), 0x14);
+    // igAttr's last field ends at 0xD, which is padded up to its 4 byte alignment
+    assert_eq!(imm.get_or_create_meta("igAttr").unwrap().read().unwrap().calculate_size(), 0x10);
+}
+
+/// A METADATA_SIZES entry that doesn't match the loaded metadata is reported against the type
+#[test]
+fn test_metadata_sizes_mismatch() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    igz.fixup(b"MTSZ", 1, 0x18u32.to_be_bytes().to_vec());
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    let dir = igz.load(&mut ig_alchemy, "sizes");
+    let dir = dir.read().unwrap();
+
+    let mismatches: Vec<_> = dir.diagnostics.iter().filter(|x| x.kind == igLoadDiagnosticKind::LayoutMismatch).collect();
+    assert_eq!(mismatches.len(), 1);
+    assert!(mismatches[0].message.contains("igTimer"));
+    assert!(!dir.can_safely_save());
+
+    // The right size isn't reported
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    igz.fixup(b"MTSZ", 1, 0x14u32.to_be_bytes().to_vec());
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    let dir = igz.load(&mut ig_alchemy, "sizes_match");
+    assert!(dir.read().unwrap().diagnostics.iter().all(|x| x.kind != igLoadDiagnosticKind::LayoutMismatch));
+}
+
+/// Creates a directory holding one [igNull] per name and registers it with the stream manager the same way loading would
+fn synthetic_directory(
+    object_stream_manager: &mut igObjectStreamManager,
+    path: &str,
+    namespace: &str,
+    names: &[&str],
+    use_name_list: bool,
+) -> (Arc<RwLock<igObjectDirectory>>, Vec<igObject>) {
+    let mut dir = igObjectDirectory::with_loader(path, igName::new(namespace.to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    dir.use_name_list = use_name_list;
+    let objects: Vec<igObject> = names.iter().map(|_| Arc::new(RwLock::new(igNull)) as igObject).collect();
+    for (object, name) in objects.iter().zip(names) {
+        dir.object_list.read().unwrap().push(object.clone());
+        dir.name_list.read().unwrap().push(igName::new(name.to_string()));
+    }
+
+    let dir = Arc::new(RwLock::new(dir));
+    object_stream_manager.path_to_directory_lookup.insert(hash_lower(path), dir.clone());
+    object_stream_manager
+        .name_to_directory_lookup
+        .entry(igName::new(namespace.to_string()).hash)
+        .or_insert_with(igObjectDirectoryList::new)
+        .push(dir.clone());
+    (dir, objects)
+}
+
+/// Declared dependencies that failed to load are reported by path, externals by namespace, and both resolve once the target is loaded
+#[test]
+fn test_dependency_graph_reports_missing_targets() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let (a, _) = synthetic_directory(&mut object_stream_manager, "a.igz", "a", &[], false);
+    let (b, _) = synthetic_directory(&mut object_stream_manager, "b.igz", "b", &[], false);
+    {
+        let mut a = a.write().unwrap();
+        a.dependencies.push(b.clone());
+        a.missing_dependencies.push("c.igz".to_string());
+        a.external_namespaces.push((igDependencyReason::ExternalById, igName::new("b".to_string())));
+        a.external_namespaces.push((igDependencyReason::ExternalByName, igName::new("d".to_string())));
+    }
+
+    let graph = object_stream_manager.build_dependency_graph();
+    assert_eq!(graph.nodes.len(), 2);
+    assert_eq!(graph.edges.len(), 4);
+    let missing: Vec<(&str, igDependencyReason)> = graph.missing_targets().iter().map(|edge| (edge.to.as_ref(), edge.reason)).collect();
+    assert_eq!(missing, vec![("c.igz", igDependencyReason::Declared), ("d", igDependencyReason::ExternalByName)]);
+
+    let json: sonic_rs::Value = sonic_rs::from_str(&graph.to_json()).unwrap();
+    assert_eq!(json["nodes"].as_array().unwrap().len(), 2);
+    assert_eq!(json["edges"].as_array().unwrap().len(), 4);
+    assert_eq!(json["edges"][1]["to"].as_str(), Some("c.igz"));
+    assert_eq!(json["edges"][1]["resolved"].as_bool(), Some(false));
+
+    synthetic_directory(&mut object_stream_manager, "c.igz", "c", &[], false);
+    synthetic_directory(&mut object_stream_manager, "d.igz", "d", &[], false);
+    let graph = object_stream_manager.build_dependency_graph();
+    assert!(graph.missing_targets().is_empty());
+    assert!(graph.edges.iter().any(|edge| edge.from.as_ref() == "a.igz" && edge.to.as_ref() == "c.igz"));
+    assert!(graph.edges.iter().any(|edge| edge.from.as_ref() == "a.igz" && edge.to.as_ref() == "d.igz"));
+}
+
+/// Directories depending on each other in a loop are grouped together, and anything depending on the loop isn't part of it
+#[test]
+fn test_dependency_graph_finds_cycles() {
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let (a, _) = synthetic_directory(&mut object_stream_manager, "a.igz", "a", &[], false);
+    let (b, _) = synthetic_directory(&mut object_stream_manager, "b.igz", "b", &[], false);
+    let (c, _) = synthetic_directory(&mut object_stream_manager, "c.igz", "c", &[], false);
+    let (d, _) = synthetic_directory(&mut object_stream_manager, "d.igz", "d", &[], false);
+    a.write().unwrap().dependencies.push(b.clone());
+    b.write().unwrap().dependencies.push(c.clone());
+    c.write().unwrap().external_namespaces.push((igDependencyReason::ExternalByName, igName::new("a".to_string())));
+    d.write().unwrap().dependencies.push(a.clone());
+    // Unresolved edges can't be part of a cycle
+    d.write().unwrap().external_namespaces.push((igDependencyReason::ExternalById, igName::new("missing".to_string())));
+
+    let graph = object_stream_manager.build_dependency_graph();
+    let mut cycles = graph.find_cycles();
+    assert_eq!(cycles.len(), 1);
+    cycles[0].sort();
+    assert_eq!(cycles[0], vec![Arc::from("a.igz"), Arc::from("b.igz"), Arc::from("c.igz")]);
+
+    let mut dependents = graph.dependents_of("a.igz");
+    dependents.sort();
+    assert_eq!(dependents, vec![Arc::from("b.igz"), Arc::from("c.igz"), Arc::from("d.igz")]);
+    assert!(graph.dependents_of("d.igz").is_empty());
+}
+
+/// A temporary cache folder. The caller removes it
+fn directory_cache(name: &str, metadata_version: u64) -> (igDirectoryCache, std::path::PathBuf) {
+    let root = std::env::temp_dir().join(format!("ig_library_cache_{}_{}", name, std::process::id()));
+    (igDirectoryCache::with_metadata_version(&root, metadata_version), root)
+}
+
+/// Editing an uncompressed file in place changes its stamp even when its length and modification time stay the same
+#[test]
+fn test_archive_file_stamp_covers_stored_bytes() {
+    let file = |bytes: &[u8]| FileInfo {
+        _offset: 0x800,
+        _ordinal: 0,
+        _length: bytes.len() as u32,
+        _block_index: 0xFFFFFFFF,
+        _name: "cached.igz".to_string(),
+        _logical_name: "cached.igz".to_string(),
+        _modification_time: 1234,
+        _blocks: None,
+        _compressed_data: bytes.to_vec(),
+        _hash: 0xCAFE,
+    };
+    let original = file(b"IGZ\x01 original").stamp(FNV_OFFSET);
+    assert_eq!(file(b"IGZ\x01 original").stamp(FNV_OFFSET), original);
+    assert_ne!(file(b"IGZ\x01 modified").stamp(FNV_OFFSET), original);
+}
+
+/// A cached directory is rebuilt with its objects, names and thumbnails without the igz being read
+#[test]
+fn test_directory_cache_restores_directory() {
+    let thumbnail = b"\x89PNG not really a png";
+    let mut igz = igz_with_thumbnail(thumbnail, thumbnail.len() as u32);
+    igz.names(&["timer"]);
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let dir = igz.load(&mut ig_alchemy, "cached");
+    let dir = dir.read().unwrap();
+    let (cache, root) = directory_cache("restore", 1);
+    cache.put(&dir, 7, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager);
+
+    let entry = cache.get(&dir.path, 7);
+    std::fs::remove_dir_all(&root).unwrap();
+    let entry = entry.unwrap();
+    assert_eq!(entry.thumbnails.len(), 1);
+
+    // The igz was removed after loading, so everything has to come from the entry
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let restored = ig_alchemy.object_stream_manager.restore_cached(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        igCachedLoad { file_path: &dir.path, namespace: &igName::new("cached".to_string()), entry },
+    );
+    let restored = restored.read().unwrap();
+    assert!(restored.diagnostics.is_empty());
+    assert_eq!(restored.all_objects.len(), dir.all_objects.len());
+    assert_eq!(restored.object_list.read().unwrap().len(), 1);
+    assert!(restored.use_name_list);
+    assert_eq!(restored.name_list.read().unwrap().iter().next().unwrap().string.as_deref(), Some("timer"));
+    assert_eq!(restored.thumbnails[0].offset, dir.thumbnails[0].offset);
+    assert_eq!(restored.thumbnails[0].data.as_ref(), thumbnail);
+
+    let timer = restored.object_list.read().unwrap().iter().next().unwrap().clone();
+    assert_eq!(timer.read().unwrap().object_name().as_ref(), "igTimer");
+    let start_time = timer.read().unwrap().get_field("_startTime").ok().flatten().unwrap();
+    assert_eq!(*start_time.read().unwrap().downcast_ref::<Vec<u8>>().unwrap(), vec![0xDE, 0xAD, 0xBE, 0xEF]);
+    let elapsed = timer.read().unwrap().get_field("_elapsedSeconds").ok().flatten().unwrap();
+    assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    assert!(ig_alchemy.object_stream_manager.path_to_directory_lookup.contains_key(&hash_lower(&dir.path)));
+}
+
+/// References to objects of other directories are stored by name and point at the loaded directory's objects once restored
+#[test]
+fn test_directory_cache_restores_external_references() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let imm = &mut ig_alchemy.ark_core.metadata_manager;
+    let (_, timers) = synthetic_directory(&mut ig_alchemy.object_stream_manager, "timers.igz", "timers", &["timer"], true);
+    let property = imm.get_or_create_meta("igProperty").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let key: igAny = Arc::new(RwLock::new(timers[0].clone()));
+    property.write().unwrap().set_field("_key", Some(key)).unwrap();
+    let list = imm.get_or_create_meta("igObjectList").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let mut dir = igObjectDirectory::with_loader("user.igz", igName::new("user".to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    dir.object_list = list.clone().cast_to().unwrap();
+    dir.object_list.read().unwrap().push(property.clone());
+    dir.all_objects = vec![list, property];
+    dir.external_namespaces.push((igDependencyReason::ExternalByName, igName::new("timers".to_string())));
+    let (cache, root) = directory_cache("external", 1);
+    cache.put(&dir, 7, &ig_alchemy.object_stream_manager, imm);
+
+    let entry = cache.get(&dir.path, 7);
+    std::fs::remove_dir_all(&root).unwrap();
+    let entry = entry.unwrap();
+    assert_eq!(entry.referenced_directories.len(), 1);
+    assert_eq!(entry.referenced_directories[0].1, "timers.igz");
+
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let (_, timers) = synthetic_directory(&mut ig_alchemy.object_stream_manager, "timers.igz", "timers", &["timer"], true);
+    let restored = ig_alchemy.object_stream_manager.restore_cached(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        igCachedLoad { file_path: "user.igz", namespace: &igName::new("user".to_string()), entry },
+    );
+    let restored = restored.read().unwrap();
+    let property = restored.object_list.read().unwrap().iter().next().unwrap().clone();
+    let key = property.read().unwrap().get_field("_key").ok().flatten().unwrap();
+    assert!(Arc::ptr_eq(key.read().unwrap().downcast_ref::<igObject>().unwrap(), &timers[0]));
+}
+
+/// Caches a directory holding only `object` and returns the object rebuilt from the entry
+fn cache_round_trip(
+    name: &str,
+    object: igObject,
+    object_stream_manager: &igObjectStreamManager,
+    imm: &mut igMetadataManager,
+    handle_manager: &mut igObjectHandleManager,
+) -> igObject {
+    let path = format!("{}.igz", name);
+    let mut dir = igObjectDirectory::with_loader(&path, igName::new(name.to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    dir.all_objects = vec![object];
+    let (cache, root) = directory_cache(name, 1);
+    cache.put(&dir, 7, object_stream_manager, imm);
+    let entry = cache.get(&path, 7);
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let mut restored = igObjectDirectory::with_loader(&path, igName::new(name.to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    assert!(entry.unwrap().restore(&mut restored, imm, handle_manager, vec![], &[]));
+    restored.all_objects[0].clone()
+}
+
+/// Values of every metafield are stored in the cache, so directories using them are cached instead of skipped
+#[test]
+fn test_directory_cache_restores_metafield_values() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let mut handle_manager = igObjectHandleManager::new();
+    let imm = &mut ark_core.metadata_manager;
+
+    let input = imm.get_or_create_meta("igRenderTargetInputData").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let repeat = igEnumValue { meta_enum: Some(Arc::from("IG_GFX_TEXTURE_WRAP")), value: 1, name: Some(Arc::from("IG_GFX_TEXTURE_WRAP_REPEAT")) };
+    let unknown = igEnumValue { meta_enum: Some(Arc::from("IG_GFX_TEXTURE_WRAP")), value: 99, name: None };
+    input.write().unwrap().set_field("_wrapS", Some(Arc::new(RwLock::new(repeat.clone())))).unwrap();
+    input.write().unwrap().set_field("_wrapT", Some(Arc::new(RwLock::new(unknown.clone())))).unwrap();
+    let input = cache_round_trip("enum_values", input, &object_stream_manager, imm, &mut handle_manager);
+    let input = input.read().unwrap();
+    assert_eq!(enum_value(&input.get_field("_wrapS").ok().flatten().unwrap()), repeat);
+    assert_eq!(enum_value(&input.get_field("_wrapT").ok().flatten().unwrap()), unknown);
+
+    let primitive = imm.get_or_create_meta("igVfxPlacedPrimitiveData").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let velocity = igRangedVector { min: igVec3f::new(0.1, -2.0, 3.5), max: igVec3f::new(1.0, 2.0, 3.0) };
+    let drag = igRangedFloat { min: 0.25, max: 0.75 };
+    primitive.write().unwrap().set_field("_velocity", Some(Arc::new(RwLock::new(velocity)))).unwrap();
+    primitive.write().unwrap().set_field("_drag", Some(Arc::new(RwLock::new(drag)))).unwrap();
+    primitive.write().unwrap().set_field("_rotationAxisInternal", Some(Arc::new(RwLock::new(igVec3f::new(0.0, 1.0, 0.0))))).unwrap();
+    let mut scale = igCompoundValue::new(Arc::from("igVfxRangedCurveMetaField"));
+    scale.set_field(Arc::from("_valueOrScale"), Some(Arc::new(RwLock::new(2.5f32))));
+    primitive.write().unwrap().set_field("_velocityScale", Some(Arc::new(RwLock::new(scale)))).unwrap();
+    let death_fx = igHandle::from_handle_name(&igHandleName::new(igName::new("fire".to_string()), igName::from_hash(0xABCD)));
+    primitive.write().unwrap().set_field("_deathFx", Some(Arc::new(RwLock::new(death_fx)))).unwrap();
+    let primitive = cache_round_trip("math_values", primitive, &object_stream_manager, imm, &mut handle_manager);
+    let primitive = primitive.read().unwrap();
+    let field = |name: &str| primitive.get_field(name).ok().flatten().unwrap();
+    assert_eq!(*field("_velocity").read().unwrap().downcast_ref::<igRangedVector>().unwrap(), velocity);
+    assert_eq!(*field("_drag").read().unwrap().downcast_ref::<igRangedFloat>().unwrap(), drag);
+    assert_eq!(*field("_rotationAxisInternal").read().unwrap().downcast_ref::<igVec3f>().unwrap(), igVec3f::new(0.0, 1.0, 0.0));
+    let scale = field("_velocityScale");
+    let scale = scale.read().unwrap();
+    let scale = scale.downcast_ref::<igCompoundValue>().unwrap();
+    assert_eq!(scale.type_name.as_ref(), "igVfxRangedCurveMetaField");
+    assert_eq!(*scale.get_field("_valueOrScale").unwrap().read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    assert!(scale.get_field("_data").is_none());
+    // The handle is the handle manager's, so it resolves once the directory holding the object is added
+    let death_fx = field("_deathFx");
+    let death_fx = death_fx.read().unwrap();
+    let death_fx = death_fx.downcast_ref::<Arc<RwLock<igHandle>>>().unwrap();
+    assert_eq!(death_fx.read().unwrap().to_string(), "0x0000ABCD::fire");
+    let shared = handle_manager.find_handle(&igName::from_hash(0xABCD), &igName::new("fire".to_string())).unwrap();
+    assert!(Arc::ptr_eq(death_fx, &shared));
+
+    let shadow = imm.get_or_create_meta("igCascadeShadowParametersAttr").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let mut matrix = igMatrix44f::identity();
+    matrix.rows[3] = [0.1, -2.0, 3.5, 1.0];
+    shadow.write().unwrap().set_field("_worldToLightMatrix", Some(Arc::new(RwLock::new(matrix)))).unwrap();
+    let shadow = cache_round_trip("matrix_values", shadow, &object_stream_manager, imm, &mut handle_manager);
+    let value = shadow.read().unwrap().get_field("_worldToLightMatrix").ok().flatten().unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<igMatrix44f>().unwrap(), matrix);
+
+    let traversal = imm.get_or_create_meta("igCommonTraversal").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let enabled: Vec<Option<igAny>> = vec![Some(Arc::new(RwLock::new(true))), None, Some(Arc::new(RwLock::new(false)))];
+    traversal.write().unwrap().set_field("_renderTypeEnabled", Some(Arc::new(RwLock::new(enabled)))).unwrap();
+    let traversal = cache_round_trip("array_values", traversal, &object_stream_manager, imm, &mut handle_manager);
+    let value = traversal.read().unwrap().get_field("_renderTypeEnabled").ok().flatten().unwrap();
+    let value = value.read().unwrap();
+    let enabled: Vec<Option<bool>> = value
+        .downcast_ref::<Vec<Option<igAny>>>()
+        .unwrap()
+        .iter()
+        .map(|element| element.as_ref().map(|element| *element.read().unwrap().downcast_ref::<bool>().unwrap()))
+        .collect();
+    assert_eq!(enabled, vec![Some(true), None, Some(false)]);
+
+    let archive_manager = imm.get_or_create_meta("igArchiveManager").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let last_file = igRawRef { offset: 0x1F40, pool: igMemoryPool::VRAM };
+    archive_manager.write().unwrap().set_field("_lastFile", Some(Arc::new(RwLock::new(last_file)))).unwrap();
+    let archive_manager = cache_round_trip("raw_ref_values", archive_manager, &object_stream_manager, imm, &mut handle_manager);
+    let value = archive_manager.read().unwrap().get_field("_lastFile").ok().flatten().unwrap();
+    assert_eq!(*value.read().unwrap().downcast_ref::<igRawRef>().unwrap(), last_file);
+}
+
+/// Entries are thrown away once the file they were made from or the metadata they were decoded with changes
+#[test]
+fn test_directory_cache_invalidates_entries() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    let dir = igz.load(&mut ig_alchemy, "invalidated");
+    let dir = dir.read().unwrap();
+    let (cache, root) = directory_cache("invalidate", 1);
+
+    cache.put(&dir, 7, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager);
+    assert!(cache.contains(&dir.path, 7));
+    let (newer_metadata, _) = directory_cache("invalidate", 2);
+    assert!(!newer_metadata.contains(&dir.path, 7));
+    assert!(cache.get(&dir.path, 8).is_none());
+    // The stale entry was removed, so it is gone for the original stamp too
+    assert!(!cache.contains(&dir.path, 7));
+
+    cache.put(&dir, 7, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager);
+    assert!(newer_metadata.get(&dir.path, 7).is_none());
+    assert!(cache.get(&dir.path, 7).is_none());
+
+    cache.put(&dir, 7, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager);
+    cache.clear();
+    assert!(cache.get(&dir.path, 7).is_none());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+/// Broken files are reported as errors by the inspector instead of panicking
+#[test]
+fn test_inspector_rejects_corrupt_igz() {
+    let ig_alchemy = load_trap_team_alchemy(false);
+    let imm = &ig_alchemy.ark_core.metadata_manager;
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    let data = igz.build();
+    assert!(inspect_igz_bytes(data.clone(), imm).is_ok());
+
+    assert!(inspect_igz_bytes(data[..0x10].to_vec(), imm).is_err());
+    assert!(inspect_igz_bytes(data[..0x810].to_vec(), imm).is_err());
+
+    let mut bad_pool = data.clone();
+    bad_pool[0x224..0x22B].copy_from_slice(b"Nowhere");
+    assert!(inspect_igz_bytes(bad_pool, imm).is_err());
+
+    let mut bad_version = data.clone();
+    bad_version[4..8].copy_from_slice(&12u32.to_be_bytes());
+    assert!(inspect_igz_bytes(bad_version, imm).is_err());
+
+    // The start of the fixup's data is after its end
+    let mut bad_fixup = data.clone();
+    bad_fixup[0x80C..0x810].copy_from_slice(&0x100u32.to_be_bytes());
+    assert!(inspect_igz_bytes(bad_fixup, imm).is_err());
+
+    // Fixup data is padded to 4 bytes, which can't hold more than 8 offsets
+    let mut truncated = SyntheticIgz::new(&["igTimer"]);
+    truncated.fixup(b"ROFS", 9, vec![0x11]);
+    assert!(inspect_igz_bytes(truncated.build(), imm).is_err());
+}
+
+/// Every part of the file is dumped with its offsets resolved, and the dump survives a trip through JSON
+#[test]
+fn test_inspector_dumps_igz_structure() {
+    let ig_alchemy = load_trap_team_alchemy(false);
+    let thumbnail = b"\x89PNG not really a png";
+    let igz = igz_with_thumbnail(thumbnail, thumbnail.len() as u32);
+    let data = igz.build();
+    let data_offset = (data.len() - igz.data.len()) as u64;
+    let list = igz.root.unwrap() as u64;
+    let inspection = inspect_igz_bytes(data.clone(), &ig_alchemy.ark_core.metadata_manager).unwrap();
+
+    assert!(inspection.header.big_endian);
+    assert_eq!(inspection.header.version, 9);
+    assert_eq!(inspection.header.meta_object_version, 0);
+    assert_eq!(inspection.header.fixup_count, 5);
+    assert_eq!(inspection.header.section_count, 2);
+
+    // The first section holds the fixups, the second the objects
+    let fixup_length: u32 = inspection.fixups.iter().map(|x| x.length).sum();
+    let sections: Vec<_> = inspection.sections.iter().map(|x| (x.pool_name.as_str(), x.offset as u64, x.length, x.alignment)).collect();
+    assert_eq!(sections, vec![("Default", 0x800, fixup_length, 0x10), ("Default", data_offset, igz.data.len() as u32, 0x10)]);
+
+    let fixups: Vec<_> = inspection.fixups.iter().map(|x| (x.magic.as_str(), x.kind.as_deref(), x.count, x.start)).collect();
+    assert_eq!(
+        fixups,
+        vec![
+            ("TMET", Some("T_METADATA"), 2, 0x10),
+            ("RVTB", Some("RUNTIME_V_TABLES"), 2, 0x10),
+            ("TMHN", Some("THUMBNAIL"), 1, 0x10),
+            ("ROFS", Some("RUNTIME_OFFSETS"), 2, 0x10),
+            ("ROOT", Some("RUNTIME_OBJECT_LISTS"), 1, 0x10),
+        ]
+    );
+    // Fixups are stored back to back from the start of the first section
+    assert_eq!(inspection.fixups[0].offset, 0x800);
+    for pair in inspection.fixups.windows(2) {
+        assert_eq!(pair[1].offset, pair[0].offset + pair[0].length);
+    }
+    assert!(matches!(&inspection.fixups[0].contents, igIGZFixupContents::Strings(names) if names == &["igTimer", "igObjectList"]));
+    assert!(matches!(&inspection.fixups[1].contents, igIGZFixupContents::Offsets(offsets) if offsets == &[0, list]));
+    let thumbnail_offset = data_offset + igz.data.windows(thumbnail.len()).position(|x| x == thumbnail).unwrap() as u64;
+    assert!(matches!(&inspection.fixups[2].contents, igIGZFixupContents::Thumbnails(entries) if entries == &[(thumbnail.len() as u64, thumbnail_offset)]));
+    // Runtime offsets other than RVTB and ROOT are resolved to positions in the file
+    assert!(matches!(&inspection.fixups[3].contents, igIGZFixupContents::Offsets(offsets) if offsets == &[data_offset + list - 4, data_offset + list + 0x14]));
+    assert!(matches!(&inspection.fixups[4].contents, igIGZFixupContents::Offsets(offsets) if offsets == &[list]));
+
+    let objects: Vec<_> = inspection.objects.iter().map(|x| (x.serialized_offset, x.offset, x.pool.as_str(), x.type_name.as_str())).collect();
+    assert_eq!(objects, vec![(0, data_offset, "Default", "igTimer"), (list, data_offset + list, "Default", "igObjectList")]);
+
+    let json: sonic_rs::Value = sonic_rs::from_str(&sonic_rs::to_string(&inspection).unwrap()).unwrap();
+    assert_eq!(json["header"]["version"].as_u64(), Some(9));
+    assert_eq!(json["sections"][1]["offset"].as_u64(), Some(data_offset));
+    assert_eq!(json["fixups"][2]["magic"].as_str(), Some("TMHN"));
+    assert_eq!(json["fixups"][2]["contents"]["Thumbnails"][0][1].as_u64(), Some(thumbnail_offset));
+    assert_eq!(json["objects"][1]["type_name"].as_str(), Some("igObjectList"));
+}
+
+/// A version 6 igz: a TMET fixup followed by a fixup the loader doesn't understand
+fn legacy_igz() -> Vec<u8> {
+    let mut fixups = vec![];
+    for (magic, count, data) in [(0x00u32, 1u32, aligned_strings(["igTimer"].into_iter())), (0x06, 2, vec![1, 2, 3, 4])] {
+        for value in [magic, 0, 0, count, (0x18 + data.len()).next_multiple_of(4) as u32, 0x18] {
+            fixups.extend_from_slice(&value.to_be_bytes());
+        }
+        fixups.extend_from_slice(&data);
+        fixups.resize(fixups.len().next_multiple_of(4), 0);
+    }
+
+    let fixup_offset = 0x800usize;
+    let mut file = vec![0u8; fixup_offset + 0x1C];
+    file[0..4].copy_from_slice(b"IGZ\x01");
+    for (i, value) in [6u32, 0, 9].iter().enumerate() {
+        file[4 + i * 4..8 + i * 4].copy_from_slice(&value.to_be_bytes());
+    }
+    let data_offset = (fixup_offset + 0x1C + fixups.len()).next_multiple_of(0x10);
+    for (i, section) in [[0, fixup_offset as u32, (data_offset - fixup_offset) as u32, 0x10], [0, data_offset as u32, 0x10, 0x10]].iter().enumerate() {
+        for (j, value) in section.iter().enumerate() {
+            let position = 0xC + 0x10 * i + 4 * j;
+            file[position..position + 4].copy_from_slice(&value.to_be_bytes());
+        }
+    }
+    file[0x56C..0x574].copy_from_slice(b"Default\0");
+    file[fixup_offset + 0x10..fixup_offset + 0x14].copy_from_slice(&2u32.to_be_bytes());
+    file.extend_from_slice(&fixups);
+    file.resize(data_offset + 0x10, 0);
+    file
+}
+
+/// Old files are inspected too, and fixups nobody understands yet are kept as raw bytes
+#[test]
+fn test_inspector_reads_legacy_igz() {
+    let ig_alchemy = load_trap_team_alchemy(false);
+    let inspection = inspect_igz_bytes(legacy_igz(), &ig_alchemy.ark_core.metadata_manager).unwrap();
+    assert_eq!(inspection.header.version, 6);
+    assert_eq!(inspection.header.fixup_count, 2);
+    assert_eq!(inspection.sections.len(), 2);
+    assert_eq!(inspection.sections[0].offset, 0x800);
+    assert_eq!(inspection.fixups.len(), 2);
+    // Version 6 fixups start after a second header in the first section
+    assert_eq!(inspection.fixups[0].offset, 0x81C);
+
+    assert!(matches!(&inspection.fixups[0].contents, igIGZFixupContents::Strings(names) if names == &["igTimer".to_string()]));
+    assert_eq!(inspection.fixups[1].magic, "0x06");
+    assert_eq!(inspection.fixups[1].kind, None);
+    assert!(matches!(&inspection.fixups[1].contents, igIGZFixupContents::Raw(bytes) if bytes == &[1, 2, 3, 4]));
+}
+
+/// Hand edited igx with bad hex or a reference to an object that isn't in the file is reported instead of aborting the load
+#[test]
+fn test_igx_bad_values_are_reported() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let mut read = |type_name: &str, field: &str, text: &str, ctx: &mut IgxLoaderContext| {
+        let meta = imm.get_or_create_meta(type_name).unwrap();
+        let info = meta.read().unwrap().field_storage.name_lookup.get(field).unwrap().clone();
+        let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+        let mut handle = Cursor::new(text.as_bytes().to_vec());
+        metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Little, ctx)
+    };
+
+    let mut ctx = IgxLoaderContext::new();
+    // _startTime has no metafield implementation, so it is stored as hex
+    assert!(read("igTimer", "_startTime", "DEADBEEF", &mut ctx).is_some());
+    assert!(read("igTimer", "_startTime", "DEADBXEF", &mut ctx).is_none());
+    assert!(read("igVfxPrimitiveData", "_spawnRate", "@42", &mut ctx).is_none());
+    assert!(read("igVfxPrimitiveData", "_spawnRate", "not a reference", &mut ctx).is_none());
+    assert!(read("igVfxPrimitiveData", "_spawnRate", "missing::nothing", &mut ctx).is_none());
+    let kinds: Vec<_> = ctx.diagnostics.iter().map(|x| x.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            igLoadDiagnosticKind::FieldDecodeFailed,
+            igLoadDiagnosticKind::UnresolvedReference,
+            igLoadDiagnosticKind::FieldDecodeFailed,
+            igLoadDiagnosticKind::UnresolvedReference,
+        ]
+    );
+    assert!(ctx.diagnostics[1].message.contains("@42"));
+    assert!(ctx.diagnostics[3].message.contains("missing::nothing"));
+}
+
+/// A null element of an igx memory block keeps its place instead of dropping the block
+#[test]
+fn test_memory_ref_meta_field_igx_null_element() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igDataList").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_data").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let igx = "<element>1</element><element><null/></element><element>3</element>";
+    let mut text = Cursor::new(igx.as_bytes().to_vec());
+    let mut ctx = IgxLoaderContext::new();
+    let value = metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut text, Endian::Big, &mut ctx).unwrap();
+    let guard = value.read().unwrap();
+    let memory = guard.downcast_ref::<igMemory<igAny>>().unwrap();
+    let elements: Vec<Option<u8>> = memory
+        .data
+        .iter()
+        .map(|element| element.read().unwrap().downcast_ref::<u8>().copied())
+        .collect();
+    assert_eq!(elements, vec![Some(1), None, Some(3)]);
+    assert!(memory.data[1].read().unwrap().downcast_ref::<igNullElement>().is_some());
+    assert!(ctx.diagnostics.is_empty());
+
+    // The null element is written back in its place
+    let dir = igObjectDirectory::with_loader("null_element.igx", igName::new("null_element".to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    let mut saver_ctx = IgxSaverContext::new(&dir, &object_stream_manager);
+    let mut handle = Cursor::new(Vec::new());
+    metafield.value_into_igx(imm, &object_stream_manager, &mut handle, Some(value.clone()), Endian::Big, &mut saver_ctx).unwrap();
+    let written: String = String::from_utf8(handle.into_inner()).unwrap().lines().map(str::trim).collect();
+    assert_eq!(written, igx);
+}
+
+/// Raw references keep their offset and pool, and every array form of them works through the element
+#[test]
+fn test_raw_ref_array_meta_field() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igPS3EdgeManager").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_outputBuffers").unwrap().clone();
+    assert_eq!(info.size, 4 * 3);
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    assert_eq!(igMetaField::type_id(metafield.as_ref()), TypeId::of::<Vec<Option<igAny>>>());
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut text = Cursor::new(b"<element>VRAM:0x1F40</element><element><null/></element><element>Default:0x0</element>".to_vec());
+    let value = metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut text, Endian::Big, &mut ctx).unwrap();
+    let value = value.read().unwrap();
+    let elements = value.downcast_ref::<Vec<Option<igAny>>>().unwrap();
+    assert_eq!(elements.len(), 3);
+    let first = elements[0].as_ref().unwrap().read().unwrap();
+    assert_eq!(*first.downcast_ref::<igRawRef>().unwrap(), igRawRef { offset: 0x1F40, pool: igMemoryPool::VRAM });
+    assert!(elements[1].is_none());
+    assert!(ctx.diagnostics.is_empty());
+
+    // A malformed reference is reported instead of aborting the load
+    let mut text = Cursor::new(b"<element>VRAM:1F40</element><element><null/></element><element><null/></element>".to_vec());
+    metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut text, Endian::Big, &mut ctx);
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+}
+
+/// Raw references are kept relative to their section in igz files, so they're written back against wherever the saver places that section
+#[test]
+fn test_raw_ref_meta_field_igz() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igPS3EdgeManager").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_outputBuffers").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let mut load_ctx = IgzLoaderContext::new(9, 0, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE, 0, false, false);
+    load_ctx.loaded_pools[0] = igMemoryPool::Default;
+    load_ctx.loaded_pools[1] = igMemoryPool::VRAM;
+    load_ctx.loaded_pointers[0] = 0x800;
+    load_ctx.loaded_pointers[1] = 0x4000;
+    let mut bytes = Vec::new();
+    for serialized in [(1u32 << 0x1B) | 0x40, 0, 0x10] {
+        bytes.extend_from_slice(&serialized.to_be_bytes());
+    }
+    let mut handle = Cursor::new(bytes);
+    let value = metafield.value_from_igz(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut load_ctx).unwrap();
+    {
+        let value = value.read().unwrap();
+        let elements = value.downcast_ref::<Vec<Option<igAny>>>().unwrap();
+        assert_eq!(*elements[0].as_ref().unwrap().read().unwrap().downcast_ref::<igRawRef>().unwrap(), igRawRef { offset: 0x40, pool: igMemoryPool::VRAM });
+        assert!(elements[1].is_none());
+        assert_eq!(*elements[2].as_ref().unwrap().read().unwrap().downcast_ref::<igRawRef>().unwrap(), igRawRef { offset: 0x10, pool: igMemoryPool::Default });
+    }
+
+    // The saver stores VRAM first this time
+    let mut save_ctx = IgzSaverContext::new(IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    save_ctx.sections = vec![igMemoryPool::VRAM, igMemoryPool::Default];
+    let mut handle = Cursor::new(Vec::new());
+    metafield.value_into_igz(imm, &object_stream_manager, &mut handle, Some(value.clone()), Endian::Big, &mut save_ctx).unwrap();
+    let expected: Vec<u8> = [0x40u32, 0, (1 << 0x1B) | 0x10].iter().flat_map(|x| x.to_be_bytes()).collect();
+    assert_eq!(handle.into_inner(), expected);
+    assert_eq!(save_ctx.offsets, vec![0, 8]);
+
+    let mut save_ctx = IgzSaverContext::new(IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    save_ctx.sections = vec![igMemoryPool::Default];
+    let result = metafield.value_into_igz(imm, &object_stream_manager, &mut Cursor::new(Vec::new()), Some(value), Endian::Big, &mut save_ctx);
+    assert!(matches!(result, Err(IgzSaverError::MissingSection(igMemoryPool::VRAM))));
+}
+
+/// Lenient loading reports unknown types, unparsable values and a missing root in an igx instead of panicking
+#[test]
+fn test_lenient_loading_reports_broken_igx() {
+    let path = std::env::temp_dir().join(format!("ig_library_broken_{}.igx", std::process::id()));
+    std::fs::write(
+        &path,
+        "<igx version=\"1\" root=\"5\">\n    <object type=\"igNotAType\" id=\"0\"/>\n    <object type=\"igRenderTargetInputData\" id=\"1\">\n        <field name=\"_unitID\">twelve</field>\n    </object>\n</igx>\n",
+    )
+    .unwrap();
+    let mut ig_alchemy = load_trap_team_alchemy(true);
+    let dir = ig_alchemy.object_stream_manager.load(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        path.to_str().unwrap().to_string(),
+    );
+    std::fs::remove_file(&path).unwrap();
+
+    let dir = dir.unwrap();
+    let dir = dir.read().unwrap();
+    let kinds: Vec<igLoadDiagnosticKind> = dir.diagnostics.iter().map(|diagnostic| diagnostic.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![igLoadDiagnosticKind::InstantiationFailed, igLoadDiagnosticKind::FieldDecodeFailed, igLoadDiagnosticKind::UnresolvedReference]
+    );
+    assert_eq!(dir.diagnostics[1].field.as_deref(), Some("_unitID"));
+    assert!(dir.all_objects[0].read().unwrap().as_any().is::<igNull>());
+    assert!(!dir.can_safely_save());
+}
+
+/// Directories saved as igx have to load back unchanged, so every registered metafield has to read back the igx it writes without losing anything
+#[test]
+fn test_igx_round_trips_every_metafield() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    // (metafield, a field of that type, igx written for a value of it). Types the metadata doesn't use borrow the layout of another field
+    let samples: [(&str, (&str, &str), &str); 39] = [
+        ("igIntMetaField", ("igRenderTargetInputData", "_unitID"), "-12"),
+        ("igStringMetaField", ("igMetaImage", "_name"), "a &lt;name&gt;"),
+        ("igNameMetaField", ("igObjectDirectory", "_name"), "<string>timer</string><hash>1550380322</hash>"),
+        ("igSizeTypeMetaField", ("igMemoryPool", "_size"), "4294967295"),
+        ("igObjectRefMetaField", ("igFileWorkItemProcessor", "_workList"), "@0"),
+        ("igBoolMetaField", ("igArchiveManager", "_enableReadAhead"), "true"),
+        ("igCharMetaField", ("igVfxSpriteData", "_spriteFlags"), "-3"),
+        ("igUnsignedCharMetaField", ("igMetaImage", "_bitsPerPixel"), "200"),
+        ("igShortMetaField", ("igAttr", "_cachedUnitID"), "-300"),
+        ("igUnsignedShortMetaField", ("igVfxPrimitiveData", "_spawnLayers"), "65000"),
+        ("igUnsignedIntMetaField", ("igArchiveManager", "_lastBlockIndex"), "4000000000"),
+        ("igLongMetaField", ("igStatistic", "_total"), "-5000000000"),
+        ("igUnsignedLongMetaField", ("igArchiveManager", "_lastConsumedOffset"), "18000000000000000000"),
+        ("igFloatMetaField", ("igVfxPrimitiveData", "_lifeSpan"), "0.1"),
+        ("igDoubleMetaField", ("igStatistic", "_total"), "0.1"),
+        ("igVec2fMetaField", ("igFullScreenRenderPass", "_texelOffset"), "0.1 -2"),
+        ("igVec3fMetaField", ("igVfxPlacedPrimitiveData", "_rotationAxisInternal"), "0.1 -2 3.5"),
+        ("igVec3fAlignedMetaField", ("igVfxBolt", "_velocity"), "0.1 -2 3.5"),
+        ("igVec3dMetaField", ("igVfxBolt", "_velocity"), "0.1 -2 3.5"),
+        ("igVec4fMetaField", ("igCascadeShadowParametersAttr", "_shiftX"), "0.1 -2 3.5 4"),
+        ("igVec4fUnalignedMetaField", ("igCascadeShadowParametersAttr", "_shiftX"), "0.1 -2 3.5 4"),
+        ("igVec4iMetaField", ("igShaderConstantInteger", "_data"), "1 -2 3 4"),
+        ("igVec2ucMetaField", ("SegmentGenerator", "_tintColor"), "1 255"),
+        ("igVec3ucMetaField", ("SegmentGenerator", "_tintColor"), "1 2 255"),
+        ("igVec4ucMetaField", ("SegmentGenerator", "_tintColor"), "1 2 3 255"),
+        ("igQuaternionfMetaField", ("igVfxBolt", "_orientation"), "0.1 -2 3.5 1"),
+        ("igMatrix44fMetaField", ("igCascadeShadowParametersAttr", "_worldToLightMatrix"), "0.1 0 0 0 0 1 0 0 0 0 1 0 0 0 -2 1"),
+        ("igRangedFloatMetaField", ("igVfxPrimitiveData", "_instanceLifeSpan"), "0.1 2"),
+        ("igRangedVectorMetaField", ("igVfxPlacedPrimitiveData", "_velocity"), "0.1 -2 3.5 1 2 3"),
+        ("igMemoryRefMetaField", ("igDataList", "_data"), "<element>1</element><element><null/></element><element>3</element>"),
+        ("igMemoryRefHandleMetaField", ("igImage2", "_data"), "<element>1</element><element>2</element>"),
+        ("igHandleMetaField", ("igVfxPrimitiveData", "_deathFx"), "0x0000ABCD::fire"),
+        ("igRawRefMetaField", ("igArchiveManager", "_lastFile"), "VRAM:0x1F40"),
+        ("igVectorMetaField", ("igMorphTarget", "_indexList"), "<element>1</element><element>2</element>"),
+        ("igEnumMetaField", ("igRenderTargetInputData", "_wrapS"), "IG_GFX_TEXTURE_WRAP_CLAMP"),
+        ("igBitFieldMetaField", ("igMetaImage", "_isTile"), "true"),
+        ("igStructMetaField", ("igMetaField", "_properties"), "0102A0FF"),
+        // Array and compound metafields are built from the metadata instead of being registered
+        ("igBoolArrayMetaField", ("igCommonTraversal", "_renderTypeEnabled"), "<element>true</element><element>false</element><element>true</element>"),
+        ("igVfxRangedCurveMetaField", ("igVfxPlacedPrimitiveData", "_velocityScale"), "<_valueOrScale>2.5</_valueOrScale>"),
+    ];
+
+    for name in imm.meta_field_registry.registered_names() {
+        assert!(samples.iter().any(|(sample, _, _)| sample == &name.as_ref()), "{} has no igx round trip sample", name);
+    }
+
+    let referenced = imm.get_or_create_meta("igTimer").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let dir = igObjectDirectory::with_loader("round_trip.igx", igName::new("round_trip".to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    for (_type, (object, field), igx) in samples {
+        let meta = imm.get_or_create_meta(object).unwrap();
+        let info = meta.read().unwrap().field_storage.name_lookup.get(field).unwrap().clone();
+        let info = if info._type.as_ref() == _type {
+            info
+        } else {
+            Arc::new(igMetaFieldInfo { _type: Arc::from(_type), ..(*info).clone() })
+        };
+        let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+        let mut loader_ctx = IgxLoaderContext::new();
+        loader_ctx.objects.insert(0, referenced.clone());
+        let mut handle = Cursor::new(igx.as_bytes().to_vec());
+        let value = metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut loader_ctx);
+        assert!(value.is_some() && loader_ctx.diagnostics.is_empty(), "{} failed to read {}", _type, igx);
+
+        let mut saver_ctx = IgxSaverContext::new(&dir, &object_stream_manager);
+        let mut handle = Cursor::new(Vec::new());
+        metafield.value_into_igx(imm, &object_stream_manager, &mut handle, value, Endian::Big, &mut saver_ctx).unwrap();
+        // Elements are written on their own lines, which reading ignores
+        let written: String = String::from_utf8(handle.into_inner()).unwrap().lines().map(str::trim).collect();
+        assert_eq!(written, igx, "{} didn't write back what it read", _type);
+    }
+}
+
+/// Saves `dir` as igx, then loads the igx into a fresh instance. Returns the igx and the loaded directory
+fn igx_round_trip(ig_alchemy: &mut igAlchemy, dir: &igObjectDirectory, name: &str) -> (Vec<u8>, Arc<RwLock<igObjectDirectory>>) {
+    let igx = save_igx(dir, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager).unwrap();
+    let path = std::env::temp_dir().join(format!("ig_library_{}_{}.igx", name, std::process::id()));
+    std::fs::write(&path, &igx).unwrap();
+
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let loaded = ig_alchemy.object_stream_manager.load(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        path.to_str().unwrap().to_string(),
+    );
+    std::fs::remove_file(&path).unwrap();
+    (igx, loaded.unwrap())
+}
+
+/// A directory saved as igx loads back with the same objects, field values and names. A name that reads "null" stays a name
+#[test]
+fn test_igx_round_trip() {
+    let mut igz = SyntheticIgz::new(&["igTimer"]);
+    let timer = igz.object(0, &timer_body());
+    igz.root(&[timer]);
+    igz.names(&["null"]);
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let dir = igz.load(&mut ig_alchemy, "igx_round_trip");
+    let dir = dir.read().unwrap();
+
+    let (igx, loaded) = igx_round_trip(&mut ig_alchemy, &dir, "igx_round_trip");
+    let loaded = loaded.read().unwrap();
+    assert_eq!(loaded.loader.read().unwrap().get_name(), "Alchemy XML");
+    assert_eq!(loaded.all_objects.len(), dir.all_objects.len());
+    assert!(loaded.use_name_list);
+    assert_eq!(loaded.name_list.read().unwrap().iter().next().unwrap().string.as_deref(), Some("null"));
+
+    let timer = loaded.object_list.read().unwrap().iter().next().unwrap().clone();
+    let timer = timer.read().unwrap();
+    assert_eq!(timer.object_name().as_ref(), "igTimer");
+    let active = timer.get_field("_active").ok().flatten().unwrap();
+    assert!(*active.read().unwrap().downcast_ref::<bool>().unwrap());
+    let elapsed = timer.get_field("_elapsedSeconds").ok().flatten().unwrap();
+    assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+
+    // Saving what was loaded gives the same file back
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let (resaved, _) = igx_round_trip(&mut ig_alchemy, &loaded, "igx_round_trip_again");
+    assert_eq!(String::from_utf8(resaved).unwrap(), String::from_utf8(igx).unwrap());
+}
+
+/// (name, index of the parent, [(metafield index, slot, size)]) of a metaobject in a [SyntheticIgb]
+type SyntheticIgbMetaObject = (&'static str, Option<u32>, &'static [(u16, u16, u16)]);
+
+/// Builds an igb laid out the way [igIGBObjectLoader] reads it, so the loader can be tested without game files
+struct SyntheticIgb {
+    endian: Endian,
+    meta_fields: &'static [&'static str],
+    meta_objects: &'static [SyntheticIgbMetaObject],
+    /// (metaobject index, contents) of every directory entry
+    entries: Vec<(u32, Vec<u32>)>,
+    /// Entry index of every object and memory block, in directory order
+    directory: Vec<u32>,
+    /// Directory index of the info list
+    info_list: u32,
+    objects: Vec<u8>,
+    memory: Vec<u8>,
+}
+
+impl SyntheticIgb {
+    fn words(&self, values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| match self.endian {
+                Endian::Big => value.to_be_bytes(),
+                _ => value.to_le_bytes(),
+            })
+            .collect()
+    }
+
+    fn halves(&self, values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| match self.endian {
+                Endian::Big => value.to_be_bytes(),
+                _ => value.to_le_bytes(),
+            })
+            .collect()
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        let mut meta_fields = vec![];
+        for name in self.meta_fields {
+            meta_fields.extend(self.words(&[name.len() as u32 + 1, 0, 0]));
+        }
+        for name in self.meta_fields {
+            meta_fields.extend(name.bytes().chain([0]));
+        }
+
+        let mut meta_objects = vec![];
+        for (name, parent, fields) in self.meta_objects {
+            meta_objects.extend(self.words(&[name.len() as u32 + 1, 0, 0, fields.len() as u32, parent.unwrap_or(u32::MAX), fields.len() as u32]));
+            for (type_index, slot, size) in fields.iter() {
+                meta_objects.extend(self.halves(&[*type_index, *slot, *size]));
+            }
+            meta_objects.extend(name.bytes().chain([0]));
+        }
+
+        let mut entries = vec![];
+        for (type_index, contents) in &self.entries {
+            entries.extend(self.words(&[*type_index, 8 + contents.len() as u32 * 4]));
+            entries.extend(self.words(contents));
+        }
+
+        let mut file = self.words(&[
+            entries.len() as u32,
+            self.entries.len() as u32,
+            meta_objects.len() as u32,
+            self.meta_objects.len() as u32,
+            self.objects.len() as u32,
+            0,
+            self.memory.len() as u32,
+            0,
+            meta_fields.len() as u32,
+            self.meta_fields.len() as u32,
+            0xFADA,
+            6,
+        ]);
+        file.extend(meta_fields);
+        file.extend(meta_objects);
+        file.extend(entries);
+        file.extend(self.words(&[self.directory.len() as u32]));
+        file.extend(self.words(&self.directory));
+        file.extend(self.words(&[self.info_list]));
+        file.extend(&self.objects);
+        file.extend(&self.memory);
+        file
+    }
+}
+
+/// Little endian igb with an igTimer (the info list) and an igIntList whose data is the memory block [7, 8]
+fn synthetic_igb() -> Vec<u8> {
+    let mut objects = vec![1];
+    objects.extend([0xDE, 0xAD, 0xBE, 0xEF]);
+    objects.extend(2.5f32.to_le_bytes());
+    objects.extend([2u32, 2, 2].iter().flat_map(|value| value.to_le_bytes()));
+
+    SyntheticIgb {
+        endian: Endian::Little,
+        meta_fields: &["igBoolMetaField", "igTimeMetaField", "igFloatMetaField", "igIntMetaField", "igMemoryRefMetaField"],
+        meta_objects: &[
+            ("igObjectDirEntry", None, &[]),
+            ("igMemoryDirEntry", None, &[]),
+            ("igTimer", None, &[(0, 0, 1), (1, 1, 4), (2, 2, 4)]),
+            ("igIntList", None, &[(3, 0, 4), (3, 1, 4), (4, 2, 4)]),
+        ],
+        entries: vec![(0, vec![2]), (0, vec![3]), (1, vec![8, 3])],
+        directory: vec![0, 1, 2],
+        info_list: 0,
+        objects,
+        memory: [7u32, 8].iter().flat_map(|value| value.to_le_bytes()).collect(),
+    }
+    .bytes()
+}
+
+/// Objects, primitive fields and memory blocks are read from a hand-built igb
+#[test]
+fn test_igb_loader_reads_fixture() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let path = std::env::temp_dir().join(format!("ig_library_fixture_{}.igb", std::process::id()));
+    std::fs::write(&path, synthetic_igb()).unwrap();
+    let dir = ig_alchemy.object_stream_manager.load(
+        &ig_alchemy.file_context,
+        &ig_alchemy.registry,
+        &mut ig_alchemy.ark_core.metadata_manager,
+        &mut ig_alchemy.ig_ext_ref_system,
+        &mut ig_alchemy.ig_object_handle_manager,
+        path.to_str().unwrap().to_string(),
+    );
+    std::fs::remove_file(&path).unwrap();
+    let dir = dir.unwrap();
+    let dir = dir.read().unwrap();
+
+    assert_eq!(dir.loader.read().unwrap().get_name(), "Alchemy Binary");
+    assert_eq!(dir.all_objects.len(), 2);
+    let timer = dir.object_list.read().unwrap().iter().next().unwrap().clone();
+    assert!(Arc::ptr_eq(&timer, &dir.all_objects[0]));
+    let timer = timer.read().unwrap();
+    assert_eq!(timer.object_name().as_ref(), "igTimer");
+    let active = timer.get_field("_active").ok().flatten().unwrap();
+    assert!(*active.read().unwrap().downcast_ref::<bool>().unwrap());
+    let elapsed = timer.get_field("_elapsedSeconds").ok().flatten().unwrap();
+    assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+
+    let list = dir.all_objects[1].read().unwrap();
+    assert_eq!(list.object_name().as_ref(), "igIntList");
+    let data = list.get_field("_data").ok().flatten().unwrap();
+    let data = data.read().unwrap();
+    let values: Vec<i32> = data
+        .downcast_ref::<igMemory<igAny>>()
+        .unwrap()
+        .data
+        .iter()
+        .map(|element| *element.read().unwrap().downcast_ref::<i32>().unwrap())
+        .collect();
+    assert_eq!(values, vec![7, 8]);
+    // Only the igTimeMetaField has no implementation
+    assert!(dir.diagnostics.iter().all(|diagnostic| diagnostic.kind == igLoadDiagnosticKind::MissingMetaFieldImpl));
+}
+
+/// Games from before igz have no ArkCore metadata, so their types come from the igb's own tables. Uses the types of a Marvel Ultimate Alliance actor file, big endian like the console releases: igActorInfo inherits a field from igInfo and points at an igSkeleton holding a memory block
+#[test]
+fn test_igb_loader_reads_legacy_types() {
+    fn words(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_be_bytes()).collect()
+    }
+    let mut objects = vec![1];
+    objects.extend(words(&[1, u32::MAX, 2, 2]));
+    let igb = SyntheticIgb {
+        endian: Endian::Big,
+        meta_fields: &["igObjectRefMetaField", "igIntMetaField", "igMemoryRefMetaField", "igBoolMetaField"],
+        meta_objects: &[
+            ("igObjectDirEntry", None, &[]),
+            ("igMemoryDirEntry", None, &[]),
+            ("igObject", None, &[]),
+            ("igInfo", Some(2), &[(3, 0, 1)]),
+            ("igActorInfo", Some(3), &[(0, 1, 4), (0, 2, 4)]),
+            ("igSkeleton", Some(2), &[(1, 0, 4), (2, 1, 4)]),
+        ],
+        entries: vec![(0, vec![4]), (0, vec![5]), (1, vec![8, 1])],
+        directory: vec![0, 1, 2],
+        info_list: 0,
+        objects,
+        memory: words(&[3, 4]),
+    };
+
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let imm = &mut ig_alchemy.ark_core.metadata_manager;
+    assert!(!imm.contains_meta("igActorInfo") && !imm.contains_meta("igSkeleton"));
+    let mut dir = igObjectDirectory::with_loader("actor.igb", igName::new("actor".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    igIGBObjectLoader.read_bytes(igb.bytes(), &ig_alchemy.object_stream_manager, imm, &mut dir).unwrap();
+    assert!(imm.contains_meta("igActorInfo") && imm.contains_meta("igSkeleton"));
+    assert!(dir.diagnostics.is_empty());
+
+    let actor = dir.object_list.read().unwrap().iter().next().unwrap().clone();
+    let actor = actor.read().unwrap();
+    assert_eq!(actor.object_name().as_ref(), "igActorInfo");
+    // Fields are named after their slot, inherited ones included
+    let resolved = actor.get_field("_slot0").ok().flatten().unwrap();
+    assert!(*resolved.read().unwrap().downcast_ref::<bool>().unwrap());
+    let skeleton = actor.get_field("_slot1").ok().flatten().unwrap();
+    let skeleton = skeleton.read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+    assert!(Arc::ptr_eq(&skeleton, &dir.all_objects[1]));
+    assert!(matches!(actor.get_field("_slot2"), Ok(None)));
+
+    let skeleton = skeleton.read().unwrap();
+    assert_eq!(skeleton.object_name().as_ref(), "igSkeleton");
+    let count = skeleton.get_field("_slot0").ok().flatten().unwrap();
+    assert_eq!(*count.read().unwrap().downcast_ref::<i32>().unwrap(), 2);
+    let joints = skeleton.get_field("_slot1").ok().flatten().unwrap();
+    let joints = joints.read().unwrap();
+    let bytes: Vec<u8> = joints
+        .downcast_ref::<igMemory<igAny>>()
+        .unwrap()
+        .data
+        .iter()
+        .map(|element| *element.read().unwrap().downcast_ref::<u8>().unwrap())
+        .collect();
+    assert_eq!(bytes, words(&[3, 4]));
+}
+
+/// An igb cut short anywhere is an error rather than a panic, and leaves the directory empty
+#[test]
+fn test_igb_loader_rejects_truncated_files() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let data = synthetic_igb();
+    for length in 0..data.len() {
+        let mut dir = igObjectDirectory::with_loader("truncated.igb", igName::new("truncated".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+        let result = igIGBObjectLoader.read_bytes(
+            data[..length].to_vec(),
+            &ig_alchemy.object_stream_manager,
+            &mut ig_alchemy.ark_core.metadata_manager,
+            &mut dir,
+        );
+        assert!(result.is_err(), "{} bytes loaded", length);
+        assert!(dir.all_objects.is_empty());
+    }
+
+    let mut wrong_magic = data.clone();
+    wrong_magic[0x28] = 0;
+    let mut dir = igObjectDirectory::with_loader("magic.igb", igName::new("magic".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    let result = igIGBObjectLoader.read_bytes(wrong_magic, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager, &mut dir);
+    assert!(result.unwrap_err().starts_with("Not an igb"));
+}
+
+/// An igProperty whose _key is an igTimer in the same file
+fn property_with_timer() -> SyntheticIgz {
+    let mut igz = SyntheticIgz::new(&["igProperty", "igTimer"]);
+    let timer = igz.object(1, &timer_body());
+    let mut body = vec![0u8; 0x10];
+    body[0x8..0xC].copy_from_slice(&timer.to_be_bytes());
+    let property = igz.object(0, &body);
+    igz.runtime_fixup(b"ROFS", &[property + 0x8]);
+    igz.root(&[property]);
+    igz
+}
+
+fn find_object(dir: &igObjectDirectory, name: &str) -> igObject {
+    dir.all_objects.iter().find(|object| object.read().unwrap().object_name().as_ref() == name).unwrap().clone()
+}
+
+/// A saved igb loads back with its references, primitives and placeholder bytes intact
+#[test]
+fn test_igb_round_trip() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let dir = property_with_timer().load(&mut ig_alchemy, "igb_round_trip");
+    let dir = dir.read().unwrap();
+
+    for endian in [Endian::Big, Endian::Little] {
+        let data = save_igb(&dir, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager, endian).unwrap();
+        let mut loaded = igObjectDirectory::with_loader("round_trip.igb", igName::new("round_trip".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+        igIGBObjectLoader
+            .read_bytes(data, &ig_alchemy.object_stream_manager, &mut ig_alchemy.ark_core.metadata_manager, &mut loaded)
+            .unwrap();
+
+        // The saver stores the root list as an object of its own
+        assert_eq!(loaded.all_objects.len(), 3);
+        let property = find_object(&loaded, "igProperty");
+        let timer = find_object(&loaded, "igTimer");
+        let root = loaded.object_list.read().unwrap().iter().next().unwrap().clone();
+        assert!(Arc::ptr_eq(&root, &property));
+
+        let key = property.read().unwrap().get_field("_key").ok().flatten().unwrap();
+        let key = key.read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+        assert!(Arc::ptr_eq(&key, &timer));
+
+        let timer = timer.read().unwrap();
+        let start_time = timer.get_field("_startTime").ok().flatten().unwrap();
+        assert_eq!(*start_time.read().unwrap().downcast_ref::<Vec<u8>>().unwrap(), vec![0xDE, 0xAD, 0xBE, 0xEF]);
+        let elapsed = timer.get_field("_elapsedSeconds").ok().flatten().unwrap();
+        assert_eq!(*elapsed.read().unwrap().downcast_ref::<f32>().unwrap(), 2.5);
+    }
+}
+
+/// Objects of other loaded directories are written as references to them rather than copied into the igb
+#[test]
+fn test_igb_saves_external_references() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let imm = &mut ig_alchemy.ark_core.metadata_manager;
+    let timer = imm.get_or_create_meta("igTimer").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let (timers, _) = synthetic_directory(&mut ig_alchemy.object_stream_manager, "timers.igz", "timers", &[], true);
+    timers.read().unwrap().object_list.read().unwrap().push(timer.clone());
+    timers.read().unwrap().name_list.read().unwrap().push(igName::new("timer".to_string()));
+
+    let property = imm.get_or_create_meta("igProperty").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let key: igAny = Arc::new(RwLock::new(timer.clone()));
+    property.write().unwrap().set_field("_key", Some(key)).unwrap();
+    let list = imm.get_or_create_meta("igObjectList").unwrap().read().unwrap().raw_instantiate(igMemoryPool::Default, false).unwrap();
+    let mut dir = igObjectDirectory::with_loader("user.igb", igName::new("user".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    dir.object_list = list.clone().cast_to().unwrap();
+    dir.object_list.read().unwrap().push(property.clone());
+    dir.all_objects = vec![list, property];
+
+    let data = save_igb(&dir, &ig_alchemy.object_stream_manager, imm, Endian::Little).unwrap();
+    let mut loaded = igObjectDirectory::with_loader("user.igb", igName::new("user".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+    igIGBObjectLoader.read_bytes(data, &ig_alchemy.object_stream_manager, imm, &mut loaded).unwrap();
+
+    assert!(loaded.diagnostics.iter().all(|x| x.kind != igLoadDiagnosticKind::UnresolvedReference));
+    assert_eq!(loaded.all_objects.len(), 2);
+    let property = find_object(&loaded, "igProperty");
+    let key = property.read().unwrap().get_field("_key").ok().flatten().unwrap();
+    assert!(Arc::ptr_eq(key.read().unwrap().downcast_ref::<igObject>().unwrap(), &timer));
+}
+
+/// Refs are 8 bytes in memory on 64-bit platforms but always written as 4 bytes in igb, and the metaobject table has to say so
+#[test]
+fn test_igb_meta_object_sizes_match_written_fields() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let dir = property_with_timer().load(&mut ig_alchemy, "igb_sizes");
+    let dir = dir.read().unwrap();
+
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_WIN64);
+    let imm = &mut ark_core.metadata_manager;
+    let property_meta = imm.get_or_create_meta("igProperty").unwrap();
+    let key_size = property_meta.read().unwrap().field_storage.name_lookup.get("_key").unwrap().size;
+    assert_eq!(key_size, 8);
+
+    let data = save_igb(&dir, &ig_alchemy.object_stream_manager, imm, Endian::Little).unwrap();
+    let word = |offset: usize| u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+    let half = |offset: usize| u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap());
+    let meta_object_count = word(0xC);
+    let meta_field_count = word(0x24);
+
+    let mut offset = 0x30;
+    let mut meta_field_lengths = vec![];
+    for _ in 0..meta_field_count {
+        meta_field_lengths.push(word(offset) as usize);
+        offset += 12;
+    }
+    let mut meta_field_names = vec![];
+    for length in meta_field_lengths {
+        meta_field_names.push(String::from_utf8(data[offset..offset + length - 1].to_vec()).unwrap());
+        offset += length;
+    }
+
+    let mut property_fields = None;
+    for _ in 0..meta_object_count {
+        let name_length = word(offset) as usize;
+        let field_count = word(offset + 12) as usize;
+        offset += 24;
+        let fields: Vec<(u16, u16)> = (0..field_count).map(|field| (half(offset + field * 6), half(offset + field * 6 + 4))).collect();
+        offset += field_count * 6;
+        if &data[offset..offset + name_length - 1] == b"igProperty" {
+            property_fields = Some(fields);
+        }
+        offset += name_length;
+    }
+
+    let property_fields = property_fields.unwrap();
+    assert!(!property_fields.is_empty());
+    for (type_index, size) in property_fields {
+        assert_eq!(meta_field_names[type_index as usize], "igObjectRefMetaField");
+        assert_eq!(size, 4);
+    }
+}
+
+/// A loader that claims every igz by name and never reads anything
+struct NamedTestLoader(&'static str);
+
+impl igObjectLoader for NamedTestLoader {
+    fn can_read(&self, file_name: &str) -> bool {
+        file_name.ends_with(".igz")
+    }
+
+    fn get_name(&self) -> &'static str {
+        self.0
+    }
+
+    fn get_type(&self) -> &'static str {
+        "Test"
+    }
+
+    fn read_file(
+        &self,
+        _ig_file_context: &igFileContext,
+        _ig_registry: &igRegistry,
+        _ig_object_stream_manager: &mut igObjectStreamManager,
+        _ig_ext_ref_system: &mut igExternalReferenceSystem,
+        _ig_object_handle_manager: &mut igObjectHandleManager,
+        _ig_metadata_manager: &mut igMetadataManager,
+        _dir: &mut igObjectDirectory,
+        _file_path: &str,
+    ) {
+        unreachable!()
+    }
+}
+
+fn loader_name(registry: &igObjectLoaderRegistry, file_path: &str, data: &[u8]) -> Option<&'static str> {
+    registry.get_loader(file_path, data).map(|loader| loader.read().unwrap().get_name())
+}
+
+/// Higher priorities are asked first and loaders with the same priority keep their registration order
+#[test]
+fn test_loader_registry_priority() {
+    let mut registry = igObjectLoaderRegistry::new();
+    assert_eq!(loader_name(&registry, "file.igz", &[]), Some("Alchemy Platform"));
+
+    registry.register(Arc::new(RwLock::new(NamedTestLoader("Low"))), -1);
+    assert_eq!(loader_name(&registry, "file.igz", &[]), Some("Alchemy Platform"));
+
+    registry.register(Arc::new(RwLock::new(NamedTestLoader("First"))), 1);
+    registry.register(Arc::new(RwLock::new(NamedTestLoader("Second"))), 1);
+    assert_eq!(loader_name(&registry, "file.igz", &[]), Some("First"));
+    let names: Vec<_> = registry.loaders().map(|loader| loader.read().unwrap().get_name()).collect();
+    assert_eq!(names, vec!["First", "Second", "Alchemy Platform", "Alchemy XML", "Alchemy Binary", "Low"]);
+
+    assert!(registry.unregister("First"));
+    assert!(!registry.unregister("First"));
+    assert_eq!(loader_name(&registry, "file.igz", &[]), Some("Second"));
+}
+
+/// Files are loaded by their magic before their extension, and archives are never loaded as directories
+#[test]
+fn test_loader_registry_sniffs_magic() {
+    let mut registry = igObjectLoaderRegistry::new();
+    // Even a higher priority loader that wants the file by name loses to the loader that recognises its contents
+    registry.register(Arc::new(RwLock::new(NamedTestLoader("Named"))), 1);
+
+    let mut igb = vec![0u8; 0x30];
+    igb[0x28..0x2C].copy_from_slice(&0xFADAu32.to_be_bytes());
+    for (data, expected) in [
+        (b"IGZ\x01rest".to_vec(), "Alchemy Platform"),
+        (b"\x01ZGIrest".to_vec(), "Alchemy Platform"),
+        (b"\xEF\xBB\xBF  <?xml version=\"1.0\"?>".to_vec(), "Alchemy XML"),
+        (b"<igx version=\"2\">".to_vec(), "Alchemy XML"),
+        (igb, "Alchemy Binary"),
+    ] {
+        assert_eq!(loader_name(&registry, "file.igz", &data), Some(expected));
+    }
+
+    // Unknown contents fall back to the file name
+    assert_eq!(loader_name(&registry, "file.igz", b"????"), Some("Named"));
+    assert_eq!(loader_name(&registry, "file.unknown", b"????"), None);
+
+    for archive in [b"IGA\x1Arest".as_slice(), b"\x1AAGIrest".as_slice()] {
+        assert_eq!(loader_name(&registry, "file.igz", archive), None);
+        assert_eq!(loader_name(&registry, "file.iga", archive), None);
+    }
+}
+
+/// Primitive metafields take their size from the platform being loaded, falling back to the size of the rust type
+#[test]
+fn test_primitive_meta_field_platform_sizes() {
+    let cafe = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let win64 = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_WIN64);
+    assert_eq!(igPrimitiveMetaField::<u64>::new("igUnsignedIntPtrMetaField", &cafe.metadata_manager).size, 4);
+    assert_eq!(igPrimitiveMetaField::<u64>::new("igUnsignedIntPtrMetaField", &win64.metadata_manager).size, 8);
+    assert_eq!(igPrimitiveMetaField::<i16>::new("igNotARealMetaField", &cafe.metadata_manager).size, 2);
+
+    // A 4 byte value is read as 4 bytes even though the rust type is wider
+    let field = igPrimitiveMetaField::<u64>::new("igUnsignedIntPtrMetaField", &cafe.metadata_manager);
+    let mut handle = Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFF, 0x12]);
+    assert_eq!(field.read(&mut handle, Endian::Big).unwrap(), 0xFFFF_FFFF);
+    assert_eq!(handle.position(), 4);
+}
+
+/// Signed values stored in fewer bytes than their rust type are sign extended, unsigned ones aren't
+#[test]
+fn test_primitive_meta_field_sign_extension() {
+    let mut handle = Cursor::new(vec![0xFF, 0xFF, 0xFF, 0xFE]);
+    assert_eq!(igPrimitiveMetaField::<i64>::with_size(4).read(&mut handle, Endian::Big).unwrap(), -2);
+    let mut handle = Cursor::new(vec![0xFF, 0xFF]);
+    assert_eq!(igPrimitiveMetaField::<i32>::with_size(2).read(&mut handle, Endian::Little).unwrap(), -1);
+    let mut handle = Cursor::new(vec![0x80]);
+    assert_eq!(igPrimitiveMetaField::<i8>::with_size(1).read(&mut handle, Endian::Big).unwrap(), -128);
+    let mut handle = Cursor::new(vec![0x7F, 0xFF]);
+    assert_eq!(igPrimitiveMetaField::<i64>::with_size(2).read(&mut handle, Endian::Big).unwrap(), 0x7FFF);
+    let mut handle = Cursor::new(vec![0xFF, 0xFF]);
+    assert_eq!(igPrimitiveMetaField::<u64>::with_size(2).read(&mut handle, Endian::Big).unwrap(), 0xFFFF);
+
+    // Writing keeps only the low bytes, which read back as the same value
+    let field = igPrimitiveMetaField::<i64>::with_size(4);
+    let mut handle = Cursor::new(Vec::new());
+    field.write(&mut handle, Endian::Little, -2).unwrap();
+    assert_eq!(handle.get_ref(), &vec![0xFE, 0xFF, 0xFF, 0xFF]);
+    handle.set_position(0);
+    assert_eq!(field.read(&mut handle, Endian::Little).unwrap(), -2);
+}
+
+/// Floats stored as doubles (and doubles stored as floats) are converted rather than reinterpreted
+#[test]
+fn test_primitive_meta_field_float_widening() {
+    let field = igPrimitiveMetaField::<f32>::with_size(8);
+    let mut handle = Cursor::new(Vec::new());
+    field.write(&mut handle, Endian::Big, 2.5).unwrap();
+    assert_eq!(handle.get_ref(), &2.5f64.to_be_bytes().to_vec());
+    handle.set_position(0);
+    assert_eq!(field.read(&mut handle, Endian::Big).unwrap(), 2.5);
+
+    let field = igPrimitiveMetaField::<f64>::with_size(4);
+    let mut handle = Cursor::new(Vec::new());
+    field.write(&mut handle, Endian::Little, 0.1).unwrap();
+    assert_eq!(handle.get_ref(), &0.1f32.to_le_bytes().to_vec());
+    handle.set_position(0);
+    assert_eq!(field.read(&mut handle, Endian::Little).unwrap(), 0.1f32 as f64);
+}
+
+/// A size no primitive can be stored in is an I/O error when saving, not a type mismatch
+#[test]
+fn test_primitive_meta_field_write_errors() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &ark_core.metadata_manager;
+    let object_stream_manager = igObjectStreamManager::new();
+    let field = igPrimitiveMetaField::<i32>::with_size(3);
+    let value: igAny = Arc::new(RwLock::new(5i32));
+    let dir = igObjectDirectory::with_loader("primitive.igb", igName::new("primitive".to_string()), Arc::new(RwLock::new(igIGBObjectLoader)));
+
+    let mut handle = Cursor::new(Vec::new());
+    let result = field.value_into_igb(imm, &object_stream_manager, &mut handle, Some(value.clone()), Endian::Big, &mut IgbSaverContext::new(&dir, &object_stream_manager));
+    assert!(matches!(result, Err(IgbSaverError::Io(_))));
+
+    let wrong_type: igAny = Arc::new(RwLock::new(5u8));
+    let result = field.value_into_igb(imm, &object_stream_manager, &mut handle, Some(wrong_type), Endian::Big, &mut IgbSaverContext::new(&dir, &object_stream_manager));
+    assert!(matches!(result, Err(IgbSaverError::InvalidValueType(_))));
+
+    let mut handle = Cursor::new(vec![0x00, 0x01]);
+    assert!(igPrimitiveMetaField::<i32>::with_size(4).read(&mut handle, Endian::Big).is_err());
+}
+
+/// A primitive that can't be read is reported as a decode failure, so the loaders keep its bytes and the file isn't saved with a default in its place
+#[test]
+fn test_primitive_meta_field_read_errors() {
+    let ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let imm = &ark_core.metadata_manager;
+    let object_stream_manager = igObjectStreamManager::new();
+    let field = igPrimitiveMetaField::<i32>::with_size(4);
+
+    let mut ctx = IgzLoaderContext::new(9, 0, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE, 0, false, true);
+    let mut handle = Cursor::new(vec![0x12, 0x34]);
+    assert!(field.value_from_igz(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx).is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+
+    let mut ctx = IgbLoaderContext::new(5, true);
+    let mut handle = Cursor::new(vec![0x12]);
+    assert!(field.value_from_igb(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx).is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut handle = Cursor::new(b"twelve".to_vec());
+    assert!(field.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx).is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+    assert!(ctx.diagnostics[0].message.contains("twelve"));
+}
+
+fn enum_value(value: &igAny) -> igEnumValue {
+    value.read().unwrap().downcast_ref::<igEnumValue>().unwrap().clone()
+}
+
+/// Enums are read from igz with the name of their entry, and are written back from the name when there is one or the value otherwise
+#[test]
+fn test_enum_meta_field_igz() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igRenderTargetInputData").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_wrapS").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    assert_eq!(igMetaField::type_id(metafield.as_ref()), TypeId::of::<igEnumValue>());
+
+    let mut load_ctx = IgzLoaderContext::new(9, 0, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE, 0, false, false);
+    let mut save_ctx = IgzSaverContext::new(IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let mut read = |bytes: [u8; 4]| {
+        let mut handle = Cursor::new(bytes.to_vec());
+        enum_value(&metafield.value_from_igz(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut load_ctx).unwrap())
+    };
+    let repeat = read([0, 0, 0, 1]);
+    assert_eq!(repeat.meta_enum.as_deref(), Some("IG_GFX_TEXTURE_WRAP"));
+    assert_eq!(repeat.name.as_deref(), Some("IG_GFX_TEXTURE_WRAP_REPEAT"));
+    assert_eq!(repeat.value, 1);
+    // Values metaenums.xml doesn't know still load, just without a name
+    let unknown = read([0, 0, 0, 99]);
+    assert_eq!(unknown.name, None);
+    assert_eq!(unknown.value, 99);
+
+    let mut write = |value: igEnumValue| {
+        let mut handle = Cursor::new(Vec::new());
+        metafield
+            .value_into_igz(imm, &object_stream_manager, &mut handle, Some(Arc::new(RwLock::new(value))), Endian::Big, &mut save_ctx)
+            .unwrap();
+        handle.into_inner()
+    };
+    assert_eq!(write(repeat.clone()), vec![0, 0, 0, 1]);
+    assert_eq!(write(unknown), vec![0, 0, 0, 99]);
+    // A changed name is looked up in the metaenum, so the stale value is ignored
+    let mut changed = repeat.clone();
+    changed.name = Some(Arc::from("IG_GFX_TEXTURE_WRAP_REGION_REPEAT"));
+    assert_eq!(write(changed.clone()), vec![0, 0, 0, 3]);
+    // Without a name the value is written as is
+    changed.name = None;
+    changed.value = 2;
+    assert_eq!(write(changed), vec![0, 0, 0, 2]);
+
+    let mut renamed = repeat;
+    assert!(renamed.set_name("IG_GFX_TEXTURE_WRAP_BORDER", imm));
+    assert_eq!(renamed.value, 4);
+    assert!(!renamed.set_name("IG_GFX_TEXTURE_WRAP_MISSING", imm));
+    assert_eq!(renamed.name.as_deref(), Some("IG_GFX_TEXTURE_WRAP_BORDER"));
+    assert_eq!(write(renamed), vec![0, 0, 0, 4]);
+}
+
+/// Enums are written to igx by name when they have one, and names igx files use that metaenums.xml doesn't know are reported instead of aborting the load
+#[test]
+fn test_enum_meta_field_igx() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let object_stream_manager = igObjectStreamManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let meta = imm.get_or_create_meta("igRenderTargetInputData").unwrap();
+    let info = meta.read().unwrap().field_storage.name_lookup.get("_wrapS").unwrap().clone();
+    let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+
+    let mut ctx = IgxLoaderContext::new();
+    let mut read = |text: &str| {
+        let mut handle = Cursor::new(text.as_bytes().to_vec());
+        metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, &mut ctx).map(|value| enum_value(&value))
+    };
+    assert_eq!(read("IG_GFX_TEXTURE_WRAP_CLAMP").unwrap().value, 0);
+    let by_number = read("2").unwrap();
+    assert_eq!(by_number.name.as_deref(), Some("IG_GFX_TEXTURE_WRAP_REGION_CLAMP"));
+    assert_eq!(read("99").unwrap().name, None);
+    assert!(read("IG_GFX_TEXTURE_WRAP_MISSING").is_none());
+    assert_eq!(ctx.diagnostics.len(), 1);
+    assert_eq!(ctx.diagnostics[0].kind, igLoadDiagnosticKind::FieldDecodeFailed);
+    assert!(ctx.diagnostics[0].message.contains("IG_GFX_TEXTURE_WRAP_MISSING"));
+
+    let dir = igObjectDirectory::with_loader("enum.igx", igName::new("enum".to_string()), Arc::new(RwLock::new(igIGZObjectLoader)));
+    let mut save_ctx = IgxSaverContext::new(&dir, &object_stream_manager);
+    let mut write = |value: igAny| {
+        let mut handle = Cursor::new(Vec::new());
+        metafield.value_into_igx(imm, &object_stream_manager, &mut handle, Some(value), Endian::Big, &mut save_ctx).unwrap();
+        String::from_utf8(handle.into_inner()).unwrap()
+    };
+    let mut changed = by_number;
+    changed.name = None;
+    changed.value = 1;
+    assert_eq!(write(Arc::new(RwLock::new(changed))), "IG_GFX_TEXTURE_WRAP_REPEAT");
+    assert_eq!(write(Arc::new(RwLock::new(99i32))), "99");
+}
+
+/// EXNM entries with the top bit of their namespace index set are handles, which handle fields index separately from the named externals
+#[test]
+fn test_handle_meta_field_igz() {
+    let mut ig_alchemy = load_trap_team_alchemy(false);
+    let mut igz = SyntheticIgz::new(&["igVfxPrimitiveData"]);
+    igz.strings = ["effects", "smoke", "fire"].iter().map(|x| x.to_string()).collect();
+    // A named external object the loader can't find, then the handle effects::fire
+    let entries = [(0u32, 1u32), (0x8000_0000, 2)];
+    igz.fixup(b"EXNM", 2, entries.iter().flat_map(|(namespace, name)| [namespace.to_be_bytes(), name.to_be_bytes()]).flatten().collect());
+    let mut body = vec![0u8; 0x2C];
+    // The first named handle
+    body[0x8..0xC].copy_from_slice(&0x8000_0000u32.to_be_bytes());
+    let data = igz.object(0, &body);
+    igz.runtime_fixup(b"RHND", &[data + 0x8]);
+    igz.root(&[data]);
+    let dir = igz.load(&mut ig_alchemy, "handle");
+    let dir = dir.read().unwrap();
+
+    assert!(dir.diagnostics.iter().any(|diagnostic| diagnostic.kind == igLoadDiagnosticKind::UnresolvedReference && diagnostic.message.contains("effects::smoke")));
+    let death_fx = dir.all_objects[0].read().unwrap().get_field("_deathFx").ok().flatten().unwrap();
+    let death_fx = death_fx.read().unwrap().downcast_ref::<Arc<RwLock<igHandle>>>().unwrap().clone();
+    assert_eq!(death_fx.read().unwrap().to_string(), "effects::fire");
+    // The handle is shared with the handle manager so it resolves once effects is loaded
+    let shared = ig_alchemy.ig_object_handle_manager.lookup_handle(igName::new("effects".to_string()), igName::new("fire".to_string()));
+    assert!(Arc::ptr_eq(&death_fx, &shared));
+}
+
+/// igx references to other directories resolve through the handle manager first, falling back to searching the loaded directories
+#[test]
+fn test_igx_external_references_use_handle_manager() {
+    let mut ark_core = igArkCore::new(EGame::EV_SkylandersTrapTeam, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+    let mut object_stream_manager = igObjectStreamManager::new();
+    let mut handle_manager = igObjectHandleManager::new();
+    let imm = &mut ark_core.metadata_manager;
+    let (managed, managed_objects) = synthetic_directory(&mut igObjectStreamManager::new(), "a.igz", "managed", &["foo"], true);
+    handle_manager.add_directory(&managed.read().unwrap());
+    let (_, loose_objects) = synthetic_directory(&mut object_stream_manager, "b.igz", "loose", &["bar"], true);
+
+    let mut read = |type_name: &str, field: &str, text: &str, ctx: &mut IgxLoaderContext| {
+        let meta = imm.get_or_create_meta(type_name).unwrap();
+        let info = meta.read().unwrap().field_storage.name_lookup.get(field).unwrap().clone();
+        let metafield = imm.meta_field_registry.get(info, imm, IG_CORE_PLATFORM::IG_CORE_PLATFORM_CAFE);
+        let mut handle = Cursor::new(text.as_bytes().to_vec());
+        metafield.value_from_igx(&imm.meta_field_registry, imm, &object_stream_manager, &mut handle, Endian::Big, ctx)
+    };
+    let object = |value: Option<igAny>| value.unwrap().read().unwrap().downcast_ref::<igObject>().unwrap().clone();
+
+    let mut ctx = IgxLoaderContext::with_handle_manager(&mut handle_manager);
+    // Only the handle manager knows about the managed directory
+    assert!(Arc::ptr_eq(&object(read("igVfxPrimitiveData", "_spawnRate", "managed::foo", &mut ctx)), &managed_objects[0]));
+    assert!(Arc::ptr_eq(&object(read("igVfxPrimitiveData", "_spawnRate", "loose::bar", &mut ctx)), &loose_objects[0]));
+    assert!(read("igVfxPrimitiveData", "_spawnRate", "managed::missing", &mut ctx).is_none());
+    let kinds: Vec<_> = ctx.diagnostics.iter().map(|x| x.kind.clone()).collect();
+    assert_eq!(kinds, vec![igLoadDiagnosticKind::UnresolvedReference]);
+
+    let value = read("igVfxPrimitiveData", "_deathFx", "managed::foo", &mut ctx).unwrap();
+    let value = value.read().unwrap();
+    let handle = value.downcast_ref::<Arc<RwLock<igHandle>>>().unwrap();
+    assert!(handle_manager.handles().iter().any(|x| Arc::ptr_eq(x, handle)));
+}
@@ -2,7 +2,7 @@
 
 use crate::core::ig_core_platform::IG_CORE_PLATFORM;
 use crate::core::ig_fs::Endian;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use paste::paste;
 use std::io::{Cursor, ErrorKind, Read};
 use std::slice::from_raw_parts;
@@ -13,6 +13,12 @@ pub fn read_u8(cursor: &mut Cursor<Vec<u8>>, _endian: Endian) -> std::io::Result
     cursor.read_u8()
 }
 
+// Endian is ignored here so it needs a custom implementation
+#[inline]
+pub fn write_u8(cursor: &mut Cursor<Vec<u8>>, _endian: Endian, value: u8) -> std::io::Result<()> {
+    cursor.write_u8(value)
+}
+
 macro_rules! define_read {
     ($type:ty) => {
         paste! {
@@ -31,6 +37,24 @@ macro_rules! define_read {
     };
 }
 
+macro_rules! define_write {
+    ($type:ty) => {
+        paste! {
+            #[inline]
+            pub fn [<write_ $type>](cursor: &mut Cursor<Vec<u8>>, endian: Endian, value: $type) -> std::io::Result<()> {
+                match endian {
+                    Endian::Little => cursor.[<write_ $type>]::<LittleEndian>(value),
+                    Endian::Big => cursor.[<write_ $type>]::<BigEndian>(value),
+                    Endian::Unknown => Err(std::io::Error::new(
+                        ErrorKind::InvalidInput,
+                        "Endianness not set",
+                    )),
+                }
+            }
+        }
+    };
+}
+
 pub fn read_ptr(
     cursor: &mut Cursor<Vec<u8>>,
     platform: IG_CORE_PLATFORM,
@@ -43,6 +67,19 @@ pub fn read_ptr(
     }
 }
 
+pub fn write_ptr(
+    cursor: &mut Cursor<Vec<u8>>,
+    platform: IG_CORE_PLATFORM,
+    endian: Endian,
+    value: u64,
+) -> std::io::Result<()> {
+    if platform.is_64bit() {
+        write_u64(cursor, endian, value)
+    } else {
+        write_u32(cursor, endian, value as u32)
+    }
+}
+
 pub fn read_string(cursor: &mut Cursor<Vec<u8>>) -> std::io::Result<String> {
     let mut buf = Vec::with_capacity(0x20); // guess a good starting point for a string. usually names are pretty long so lets go with 0x20
 
@@ -122,4 +159,14 @@ define_read!(u32);
 define_read!(i32);
 define_read!(u64);
 define_read!(i64);
+define_read!(f32);
+define_read!(f64);
 define_read_struct_array!(u16, u32, u64);
+define_write!(u16);
+define_write!(i16);
+define_write!(u32);
+define_write!(i32);
+define_write!(u64);
+define_write!(i64);
+define_write!(f32);
+define_write!(f64);
@@ -13,3 +13,17 @@ pub fn hash_lower(str: &str) -> u32 {
     let str = str.to_lowercase();
     hash(&str)
 }
+
+/// Bob Jenkins' 32bit integer hash. Used by igHashTable to spread integer keys over its slots.
+///
+/// This matches the published hash, but it hasn't been checked against a hash table from a game file yet. If the game hashes integers differently, integer keyed tables still load and save but their slots won't be laid out the way the game would
+pub fn hash_int(key: u32) -> u32 {
+    let mut a = key;
+    a = a.wrapping_add(0x7ed55d16).wrapping_add(a << 12);
+    a = (a ^ 0xc761c23c) ^ (a >> 19);
+    a = a.wrapping_add(0x165667b1).wrapping_add(a << 5);
+    a = a.wrapping_add(0xd3a2646c) ^ (a << 9);
+    a = a.wrapping_add(0xfd7046c5).wrapping_add(a << 3);
+    a = (a ^ 0xb55a4f09) ^ (a >> 16);
+    a
+}
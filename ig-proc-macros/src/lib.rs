@@ -33,33 +33,116 @@ pub fn igStruct(_attr: TokenStream, item: TokenStream) -> TokenStream {
         _ => panic!("#[igStruct] only supports structs"),
     };
 
+    let mut field_kinds = Vec::new();
+    for field in fields {
+        match StructFieldKind::of(&field.ty) {
+            Some(kind) => field_kinds.push(kind),
+            None => {
+                return syn::Error::new_spanned(&field.ty, "#[igStruct] only supports Option<String> and u32 fields")
+                    .to_compile_error()
+                    .into()
+            }
+        }
+    }
+
     // Generate reading code for each field (simplified)
-    let read_fields = fields.iter().map(|field| {
+    let read_fields = fields.iter().zip(&field_kinds).map(|(field, kind)| {
         let name = field.ident.as_ref().expect("internal igStruct error #1");
-        let ty = &field.ty;
-        if quote!(#ty).to_string().contains("Option < String") {
-            quote! {
+        let name_lit = name.to_string();
+        match kind {
+            StructFieldKind::String => quote! {
                 let string_meta_field = igStringMetaField;
 
                 let #name = string_meta_field.value_from_igz(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx)
                     .map(|s| Some(s.read().unwrap().downcast_ref::<Arc<str>>().expect("igStruct string downcast failed.").to_string()))
                     .unwrap_or(None);
-            }
-        } else if quote!(#ty).to_string() == "u32" {
-            quote! {
-                let #name = read_u32(handle, endian.clone()).expect("igStruct impl u32 decoding failed");
-            }
-        } else {
-            quote! {
-                let #name = todo!("Unsupported field type");
-            }
+            },
+            StructFieldKind::U32 => quote! {
+                let #name = match read_u32(handle, endian.clone()) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        ctx.report(crate::core::load::ig_loader::igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read {}::{}: {}", stringify!(#struct_name), #name_lit, e));
+                        return None;
+                    }
+                };
+            },
+        }
+    });
+
+    let read_igx_fields = fields.iter().zip(&field_kinds).map(|(field, kind)| {
+        let name = field.ident.as_ref().expect("internal igStruct error #1");
+        let name_lit = name.to_string();
+        match kind {
+            StructFieldKind::String => quote! {
+                let #name = members.get(#name_lit).and_then(crate::core::load::ig_igx_loader::igx_read_text);
+            },
+            StructFieldKind::U32 => quote! {
+                let #name = members.get(#name_lit).and_then(|member| crate::core::load::ig_igx_loader::igx_parse::<u32>(member, ctx)).unwrap_or_default();
+            },
+        }
+    });
+
+    let write_igx_fields = fields.iter().zip(&field_kinds).map(|(field, kind)| {
+        let name = field.ident.as_ref().expect("internal igStruct error #1");
+        let open_tag = format!("<{}>", name);
+        let close_tag = format!("</{}>", name);
+        match kind {
+            StructFieldKind::String => quote! {
+                crate::core::save::ig_igx_saver::igx_write_raw(handle, #open_tag);
+                match &value.#name {
+                    Some(string) => crate::core::save::ig_igx_saver::igx_write_text(handle, string),
+                    None => crate::core::save::ig_igx_saver::igx_write_null(handle),
+                }
+                crate::core::save::ig_igx_saver::igx_write_raw(handle, #close_tag);
+            },
+            StructFieldKind::U32 => quote! {
+                crate::core::save::ig_igx_saver::igx_write_raw(handle, #open_tag);
+                crate::core::save::ig_igx_saver::igx_write_raw(handle, &value.#name.to_string());
+                crate::core::save::ig_igx_saver::igx_write_raw(handle, #close_tag);
+            },
+        }
+    });
+
+    let read_igb_fields = fields.iter().zip(&field_kinds).map(|(field, kind)| {
+        let name = field.ident.as_ref().expect("internal igStruct error #1");
+        let name_lit = name.to_string();
+        match kind {
+            StructFieldKind::String => quote! {
+                let #name = igStringMetaField.value_from_igb(registry, metadata_manager, object_stream_manager, handle, endian.clone(), ctx)
+                    .map(|s| s.read().unwrap().downcast_ref::<Arc<str>>().expect("igStruct string downcast failed.").to_string());
+            },
+            StructFieldKind::U32 => quote! {
+                let #name = match read_u32(handle, endian.clone()) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        ctx.report(crate::core::load::ig_loader::igLoadDiagnosticKind::FieldDecodeFailed, format!("Failed to read {}::{}: {}", stringify!(#struct_name), #name_lit, e));
+                        return None;
+                    }
+                };
+            },
+        }
+    });
+
+    let write_igb_fields = fields.iter().zip(&field_kinds).map(|(field, kind)| {
+        let name = field.ident.as_ref().expect("internal igStruct error #1");
+        match kind {
+            StructFieldKind::String => quote! {
+                let string = value.#name.as_ref().map(|s| std::sync::Arc::new(std::sync::RwLock::new(Arc::<str>::from(s.as_str()))) as igAny);
+                igStringMetaField.value_into_igb(metadata_manager, object_stream_manager, handle, string, endian.clone(), ctx)?;
+            },
+            StructFieldKind::U32 => quote! {
+                crate::util::byteorder_fixes::write_u32(handle, endian.clone(), value.#name).map_err(IgbSaverError::Io)?;
+            },
         }
     });
 
-    let init_fields = fields.iter().map(|f| {
+    // Both a null string and a u32 are a single 0 in igb
+    let field_count = fields.len();
+
+    let init_fields: Vec<_> = fields.iter().map(|f| {
         let name = &f.ident;
         quote!(#name,)
-    });
+    }).collect();
 
     let expanded = quote! {
         #input
@@ -89,10 +172,10 @@ pub fn igStruct(_attr: TokenStream, item: TokenStream) -> TokenStream {
             
             fn value_into_igz(
                 &self,
-                registry: &igMetafieldRegistry,
                 metadata_manager: &igMetadataManager,
                 object_stream_manager: &igObjectStreamManager,
                 handle: &mut Cursor<Vec<u8>>,
+                value: Option<igAny>,
                 endian: Endian,
                 ctx: &mut IgzSaverContext
             ) -> Result<(), IgzSaverError> {
@@ -108,19 +191,31 @@ pub fn igStruct(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 endian: Endian,
                 ctx: &mut IgxLoaderContext
             ) -> Option<igAny> {
-                todo!()
+                crate::core::load::ig_igx_loader::igx_read_text(handle)?;
+                let members = crate::core::load::ig_igx_loader::igx_read_members(handle);
+                #(#read_igx_fields)*
+                Some(std::sync::Arc::new(std::sync::RwLock::new(#struct_name {
+                    #(#init_fields)*
+                })))
             }
         
             fn value_into_igx(
-                &self, 
-                registry: &igMetafieldRegistry,
+                &self,
                 metadata_manager: &igMetadataManager,
                 object_stream_manager: &igObjectStreamManager,
-                handle: &mut Cursor<Vec<u8>>, 
+                handle: &mut Cursor<Vec<u8>>,
+                value: Option<igAny>,
                 endian: Endian, 
                 ctx: &mut IgxSaverContext
             ) -> Result<(), IgxSaverError> {
-                todo!()
+                let Some(value) = value else {
+                    crate::core::save::ig_igx_saver::igx_write_null(handle);
+                    return Ok(());
+                };
+                let guard = value.read().unwrap();
+                let value = guard.downcast_ref::<#struct_name>().ok_or(IgxSaverError::InvalidValueType(std::sync::Arc::from(stringify!(#struct_name))))?;
+                #(#write_igx_fields)*
+                Ok(())
             }
         
             fn value_from_igb(
@@ -132,21 +227,55 @@ pub fn igStruct(_attr: TokenStream, item: TokenStream) -> TokenStream {
                 endian: Endian,
                 ctx: &mut IgbLoaderContext,
             ) -> Option<igAny> {
-                todo!()
+                use crate::util::byteorder_fixes::*;
+                #(#read_igb_fields)*
+                Some(std::sync::Arc::new(std::sync::RwLock::new(#struct_name {
+                    #(#init_fields)*
+                })))
             }
         
             fn value_into_igb(
-                &self, registry: &igMetafieldRegistry,
+                &self,
                 metadata_manager: &igMetadataManager,
                 object_stream_manager: &igObjectStreamManager,
                 handle: &mut Cursor<Vec<u8>>,
+                value: Option<igAny>,
                 endian: Endian, 
                 ctx: &mut IgbSaverContext,
             ) -> Result<(), IgbSaverError> {
-                todo!()
+                let Some(value) = value else {
+                    // igb has no null for inline structs, so every field is written as empty instead
+                    for _ in 0..#field_count {
+                        crate::util::byteorder_fixes::write_u32(handle, endian.clone(), 0).map_err(IgbSaverError::Io)?;
+                    }
+                    return Ok(());
+                };
+                let guard = value.read().unwrap();
+                let value = guard.downcast_ref::<#struct_name>().ok_or(IgbSaverError::InvalidValueType(std::sync::Arc::from(stringify!(#struct_name))))?;
+                #(#write_igb_fields)*
+                Ok(())
             }
         }
     };
 
     TokenStream::from(expanded)
 }
+
+/// The field types #[igStruct] knows how to read and write
+enum StructFieldKind {
+    String,
+    U32,
+}
+
+impl StructFieldKind {
+    fn of(ty: &syn::Type) -> Option<StructFieldKind> {
+        let ty = quote!(#ty).to_string();
+        if ty.contains("Option < String") {
+            Some(StructFieldKind::String)
+        } else if ty == "u32" {
+            Some(StructFieldKind::U32)
+        } else {
+            None
+        }
+    }
+}
@@ -14,11 +14,12 @@ use egui_dock::DockState;
 use ig_library::client::precache::load_init_script;
 use ig_library::core::ig_ark_core::{EGame, igArkCore};
 use ig_library::core::ig_core_platform::IG_CORE_PLATFORM;
+use ig_library::core::ig_directory_cache::igDirectoryCache;
 use ig_library::core::ig_file_context::igFileContext;
 use ig_library::core::ig_registry::igRegistry;
 use ig_library::util::ig_common::igAlchemy;
 use image::{ImageFormat, ImageReader};
-use log::{LevelFilter, error, info};
+use log::{LevelFilter, error, info, warn};
 use serde::Serialize;
 use sonic_rs::writer::BufferedWriter;
 use sonic_rs::{Array, JsonContainerTrait, JsonValueTrait, Object, Value};
@@ -27,17 +28,46 @@ use std::fs;
 use std::fs::{File, metadata};
 use std::io::Cursor;
 use std::ops::Sub;
+use std::path::PathBuf;
 use std::string::ToString;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread::Builder;
 use std::time::Instant;
 
+/// Options passed on the command line. Read once at startup
+#[derive(Default)]
+struct LaunchOptions {
+    /// `--lenient`: keep loading files we only partially understand. Anything that failed to load gets flagged instead
+    lenient_loading: bool,
+    /// `--parallel-loading`: read and decode the files of each initscript package on worker threads. See [igObjectStreamManager::load_many](ig_library::core::ig_objects::igObjectStreamManager::load_many)
+    parallel_loading: bool,
+    /// `--directory-cache`: keep decoded igz files on disk so the next launch doesn't decode them again. Only games built with Alchemy Laboratory are cached, see [igFileContext::archive_file_stamp]
+    directory_cache: bool,
+}
+
+static LAUNCH_OPTIONS: OnceLock<LaunchOptions> = OnceLock::new();
+
+fn parse_launch_options() -> LaunchOptions {
+    let mut options = LaunchOptions::default();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--lenient" => options.lenient_loading = true,
+            "--parallel-loading" => options.parallel_loading = true,
+            "--directory-cache" => options.directory_cache = true,
+            _ => warn!("Unknown launch option {}", arg),
+        }
+    }
+    options
+}
+
 fn main() {
     #[cfg(debug_assertions)]
     init_logger(LevelFilter::Debug);
     #[cfg(not(debug_assertions))]
     init_logger(LevelFilter::Info);
 
+    LAUNCH_OPTIONS.get_or_init(parse_launch_options);
+
     let configs = init_config();
 
     let options = eframe::NativeOptions {
@@ -75,7 +105,14 @@ pub fn load_game_data(
                 }
             }
 
-            let ig_file_context = igFileContext::new(game_cfg.clone()._path, game_update_dir);
+            let launch_options = LAUNCH_OPTIONS.get_or_init(parse_launch_options);
+            let mut ig_file_context = igFileContext::new(game_cfg.clone()._path, game_update_dir);
+            if launch_options.directory_cache {
+                ig_file_context.directory_cache = Some(Arc::new(igDirectoryCache::new(
+                    format!("cache/{:?}", game_cfg._game),
+                    &PathBuf::from(format!("ArkCore/{:?}/", game_cfg._game)),
+                )));
+            }
             let ig_registry = igRegistry::new(game_cfg.clone()._platform);
 
             if !game_cfg._update_path.is_empty() {
@@ -89,6 +126,9 @@ pub fn load_game_data(
                 igArkCore::new(game_cfg.clone()._game, platform),
             );
 
+            ig_alchemy.object_stream_manager.lenient_loading = launch_options.lenient_loading;
+            ig_alchemy.object_stream_manager.parallel_loading = launch_options.parallel_loading;
+
             // Try out caching all metadata at the start only in debug to catch issues
             #[cfg(debug_assertions)]
             ig_alchemy.ark_core.metadata_manager.load_all();
@@ -1,13 +1,14 @@
 use crate::window::{LoadedGame, WorkshopTabImpl, WorkshopTabViewer};
-use egui::{include_image, Button, CentralPanel, Label, SidePanel, TextEdit, Ui, Vec2, Widget, WidgetText};
+use egui::{include_image, Button, CentralPanel, ColorImage, Context, Label, SidePanel, TextEdit, TextureHandle, TextureOptions, Ui, Vec2, Widget, WidgetText};
 use egui_ltreeview::{NodeBuilder, TreeView, TreeViewBuilder};
-use ig_library::core::ig_objects::{igObject, igObjectDirectory, ObjectExt};
+use ig_library::core::ig_objects::{igObject, igObjectDirectory, igThumbnail, ObjectExt};
 use ig_library::util::ig_name::igName;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, RwLock};
-use log::{error, info};
+use log::{debug, error, info, warn};
 use ig_library::core::ig_custom::igStringRefList;
 use ig_library::util::ig_hash::hash;
+use ig_library::core::load::ig_igz_inspector::read_igz_thumbnails;
 
 /// Tab specifically designed for usage with games made in Vicarious Visions Laboratory.
 pub struct VVLaboratoryEditor {
@@ -16,39 +17,103 @@ pub struct VVLaboratoryEditor {
     /// Packages that contain the search query
     filtered_packages: Vec<Arc<str>>,
     search_bar_contents: String,
-    old_search_bar_contents: String
+    old_search_bar_contents: String,
+    /// Decoded thumbnails keyed by [LaboratoryAsset::id]. [None] when the asset has no thumbnail or it failed to decode
+    thumbnails: HashMap<u32, Option<TextureHandle>>,
+    /// Assets whose thumbnail hasn't been looked up yet. Worked through a few per frame so opening the tab doesn't stall
+    pending_thumbnails: VecDeque<LaboratoryAsset>,
+}
+
+/// The amount of assets to read thumbnails from each frame
+const THUMBNAILS_PER_FRAME: usize = 8;
+
+/// A file listed in a package.
+#[derive(Clone)]
+struct LaboratoryAsset {
+    /// Hash of [LaboratoryAsset::path]. Used as the tree node id and to key thumbnails
+    pub id: u32,
+    pub path: Arc<str>,
+    /// The loaded directory, if the asset type is loaded when the tab opens
+    pub directory: Option<Arc<RwLock<igObjectDirectory>>>,
+}
+
+impl LaboratoryAsset {
+    fn new(path: Arc<str>, directory: Option<Arc<RwLock<igObjectDirectory>>>) -> LaboratoryAsset {
+        LaboratoryAsset {
+            id: hash(&path),
+            path,
+            directory,
+        }
+    }
 }
 
 struct LaboratoryPackage {
     #[allow(dead_code)] // used for referencing the internal ig_object_dir if needed in the future
     pub name: igName,
-    pub pkg_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub character_data_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub actor_skin_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub havok_anim_db_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub havok_rigid_body_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub havok_physics_system_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub texture_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub effect_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub shader_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub motion_path_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub igx_file_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub material_instances_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub igx_entities_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub gui_project_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub font_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub lang_file_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub spawn_mesh_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub model_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub sky_model_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub behavior_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub graph_data_behavior_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub events_behavior_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub asset_behavior_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub hkb_behavior_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub hkc_character_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub navmesh_list: Vec<Arc<RwLock<igObjectDirectory>>>,
-    pub script_list: Vec<String>, // TODO: vvl impl
+    pub pkg_list: Vec<LaboratoryAsset>,
+    pub character_data_list: Vec<LaboratoryAsset>,
+    pub actor_skin_list: Vec<LaboratoryAsset>,
+    pub havok_anim_db_list: Vec<LaboratoryAsset>,
+    pub havok_rigid_body_list: Vec<LaboratoryAsset>,
+    pub havok_physics_system_list: Vec<LaboratoryAsset>,
+    pub texture_list: Vec<LaboratoryAsset>,
+    pub effect_list: Vec<LaboratoryAsset>,
+    pub shader_list: Vec<LaboratoryAsset>,
+    pub motion_path_list: Vec<LaboratoryAsset>,
+    pub igx_file_list: Vec<LaboratoryAsset>,
+    pub material_instances_list: Vec<LaboratoryAsset>,
+    pub igx_entities_list: Vec<LaboratoryAsset>,
+    pub gui_project_list: Vec<LaboratoryAsset>,
+    pub font_list: Vec<LaboratoryAsset>,
+    pub lang_file_list: Vec<LaboratoryAsset>,
+    pub spawn_mesh_list: Vec<LaboratoryAsset>,
+    pub model_list: Vec<LaboratoryAsset>,
+    pub sky_model_list: Vec<LaboratoryAsset>,
+    pub behavior_list: Vec<LaboratoryAsset>,
+    pub graph_data_behavior_list: Vec<LaboratoryAsset>,
+    pub events_behavior_list: Vec<LaboratoryAsset>,
+    pub asset_behavior_list: Vec<LaboratoryAsset>,
+    pub hkb_behavior_list: Vec<LaboratoryAsset>,
+    pub hkc_character_list: Vec<LaboratoryAsset>,
+    pub navmesh_list: Vec<LaboratoryAsset>,
+    pub script_list: Vec<LaboratoryAsset>, // TODO: vvl impl
+}
+
+impl LaboratoryPackage {
+    /// Every asset listed in the package
+    fn assets(&self) -> impl Iterator<Item = &LaboratoryAsset> {
+        [
+            &self.pkg_list,
+            &self.character_data_list,
+            &self.actor_skin_list,
+            &self.havok_anim_db_list,
+            &self.havok_rigid_body_list,
+            &self.havok_physics_system_list,
+            &self.texture_list,
+            &self.effect_list,
+            &self.shader_list,
+            &self.motion_path_list,
+            &self.igx_file_list,
+            &self.material_instances_list,
+            &self.igx_entities_list,
+            &self.gui_project_list,
+            &self.font_list,
+            &self.lang_file_list,
+            &self.spawn_mesh_list,
+            &self.model_list,
+            &self.sky_model_list,
+            &self.behavior_list,
+            &self.graph_data_behavior_list,
+            &self.events_behavior_list,
+            &self.asset_behavior_list,
+            &self.hkb_behavior_list,
+            &self.hkc_character_list,
+            &self.navmesh_list,
+            &self.script_list,
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
 
 impl VVLaboratoryEditor {
@@ -137,47 +202,73 @@ impl VVLaboratoryEditor {
                 let file_data_type = &data[i];
                 let file_name = data[i + 1].clone();
 
-                match file_data_type.as_ref() {
-                    "lang_file" =>  {
-                        let igz = ig_object_stream_manager
-                            .load(
-                                ig_file_context,
-                                ig_registry,
-                                imm,
-                                ig_ext_ref_system,
-                                ig_object_handle_manager,
-                                file_name.to_string(),
-                            )
-                            .unwrap();
-
-                        package.lang_file_list.push(igz)
-                    },
+                let list = match file_data_type.as_ref() {
+                    "pkg" => &mut package.pkg_list,
+                    "character_data" => &mut package.character_data_list,
+                    "actorskin" => &mut package.actor_skin_list,
+                    "havokanimdb" => &mut package.havok_anim_db_list,
+                    "havokrigidbody" => &mut package.havok_rigid_body_list,
+                    "havokphysicssystem" => &mut package.havok_physics_system_list,
+                    "texture" => &mut package.texture_list,
+                    "effect" => &mut package.effect_list,
+                    "shader" => &mut package.shader_list,
+                    "motionpath" => &mut package.motion_path_list,
+                    "igx_file" => &mut package.igx_file_list,
+                    "material_instances" => &mut package.material_instances_list,
+                    "igx_entities" => &mut package.igx_entities_list,
+                    "gui_project" => &mut package.gui_project_list,
+                    "font" => &mut package.font_list,
+                    "lang_file" => &mut package.lang_file_list,
+                    "spawnmesh" => &mut package.spawn_mesh_list,
+                    "model" => &mut package.model_list,
+                    "sky_model" => &mut package.sky_model_list,
+                    "behavior" => &mut package.behavior_list,
+                    "graphdata_behavior" => &mut package.graph_data_behavior_list,
+                    "events_behavior" => &mut package.events_behavior_list,
+                    "asset_behavior" => &mut package.asset_behavior_list,
+                    "hkb_behavior" => &mut package.hkb_behavior_list,
+                    "hkc_character" => &mut package.hkc_character_list,
+                    "navmesh" => &mut package.navmesh_list,
+                    "script" => &mut package.script_list,
                     _ => {
                         error!("Unsupported data type {}", file_data_type);
-                        // if file_name.ends_with(".igz") {
-                        //     error!("Loading {} anyways", file_name);
-                        //     ig_object_stream_manager
-                        //         .load(
-                        //             ig_file_context,
-                        //             ig_registry,
-                        //             imm,
-                        //             ig_ext_ref_system,
-                        //             ig_object_handle_manager,
-                        //             file_name.to_string(),
-                        //         )
-                        //         .unwrap();
-                        // }
+                        continue;
                     }
-                }
+                };
+
+                // Only lang files can be loaded so far, everything else is listed by path
+                let directory = if file_data_type.as_ref() == "lang_file" {
+                    Some(ig_object_stream_manager
+                        .load(
+                            ig_file_context,
+                            ig_registry,
+                            imm,
+                            ig_ext_ref_system,
+                            ig_object_handle_manager,
+                            file_name.to_string(),
+                        )
+                        .unwrap())
+                } else {
+                    None
+                };
+                list.push(LaboratoryAsset::new(file_name, directory));
             }
         }
 
+        let pending_thumbnails = loaded_packages
+            .values()
+            .flat_map(|package| package.assets())
+            .cloned()
+            .collect();
+
         Box::new(Self {
             game,
             loaded_packages,
             filtered_packages: vec![],
             search_bar_contents: "".to_string(),
             old_search_bar_contents: "".to_string(),
+            thumbnails: HashMap::new(),
+            pending_thumbnails,
         })
     }
 
@@ -192,7 +283,85 @@ impl VVLaboratoryEditor {
         }
     }
 
-    fn render_package(builder: &mut TreeViewBuilder<u32>, package_name: Arc<str>, package: &LaboratoryPackage) {
+    /// Decodes an igz thumbnail to RGBA
+    fn decode_thumbnail(thumbnail: &igThumbnail) -> Option<ColorImage> {
+        match image::load_from_memory(&thumbnail.data) {
+            Ok(image) => {
+                let rgba = image.to_rgba8();
+                let size = [rgba.width() as usize, rgba.height() as usize];
+                Some(ColorImage::from_rgba_unmultiplied(size, rgba.as_raw()))
+            }
+            Err(e) => {
+                warn!("Failed to decode thumbnail at {:#X}: {}", thumbnail.offset, e);
+                None
+            }
+        }
+    }
+
+    /// Finds the thumbnail of an asset. Loaded directories already have theirs, anything else is read straight out of the igz without loading it
+    fn find_thumbnail(&self, asset: &LaboratoryAsset) -> Option<igThumbnail> {
+        if let Some(directory) = &asset.directory {
+            return directory.read().unwrap().thumbnails.first().cloned();
+        }
+
+        let ig_alchemy = &self.game.ig_alchemy;
+        let fd = ig_alchemy.file_context.open(&ig_alchemy.registry, &asset.path, 0);
+        let handle = fd._handle?;
+        match read_igz_thumbnails(handle.get_ref(), &ig_alchemy.ark_core.metadata_manager) {
+            Ok(thumbnails) => thumbnails.into_iter().next(),
+            Err(e) => {
+                debug!("No thumbnail read from {}: {}", asset.path, e);
+                None
+            }
+        }
+    }
+
+    /// Looks up and uploads the thumbnails of the next few assets that haven't been seen yet. Every asset is only looked up once
+    fn load_thumbnails(&mut self, ctx: &Context) {
+        for _ in 0..THUMBNAILS_PER_FRAME {
+            let Some(asset) = self.pending_thumbnails.pop_front() else {
+                return;
+            };
+            if self.thumbnails.contains_key(&asset.id) {
+                continue;
+            }
+
+            let texture = self
+                .find_thumbnail(&asset)
+                .and_then(|thumbnail| VVLaboratoryEditor::decode_thumbnail(&thumbnail))
+                .map(|image| {
+                    ctx.load_texture(
+                        format!("thumbnail_{}", asset.id),
+                        image,
+                        TextureOptions::default(),
+                    )
+                });
+            self.thumbnails.insert(asset.id, texture);
+        }
+        ctx.request_repaint();
+    }
+
+    /// Adds a leaf for every asset in `assets`. Loaded directories that can't be saved without losing data are marked with ⚠
+    fn render_assets(builder: &mut TreeViewBuilder<u32>, assets: &[LaboratoryAsset], thumbnails: &HashMap<u32, Option<TextureHandle>>) {
+        for asset in assets {
+            let mut label = asset.path.rsplit('/').next().unwrap_or(&asset.path).to_string();
+            if asset.directory.as_ref().is_some_and(|dir| !dir.read().unwrap().can_safely_save()) {
+                // Something failed to load, saving this file would lose data
+                label = format!("⚠ {}", label);
+            }
+            match thumbnails.get(&asset.id) {
+                Some(Some(texture)) => {
+                    builder.node(NodeBuilder::leaf(asset.id).label_ui(|ui| {
+                        ui.image((texture.id(), Vec2::splat(16.0)));
+                        ui.add(Label::new(label.clone()).selectable(false));
+                    }));
+                }
+                _ => builder.leaf(asset.id, WidgetText::from(label)),
+            }
+        }
+    }
+
+    fn render_package(builder: &mut TreeViewBuilder<u32>, package_name: Arc<str>, package: &LaboratoryPackage, thumbnails: &HashMap<u32, Option<TextureHandle>>) {
         builder.node(
             NodeBuilder::dir(package.name.hash)
                 .default_open(false)
@@ -211,66 +380,87 @@ impl VVLaboratoryEditor {
             ui.image(include_image!("../../data/character_data.png"));
             ui.add(Label::new("Character Data").selectable(false));
         }));
+        VVLaboratoryEditor::render_assets(builder, &package.character_data_list, thumbnails);
         builder.close_dir();
 
         builder.node(NodeBuilder::dir(package.name.hash + 2).label_ui(|ui| {
             ui.image(include_image!("../../data/actor_skins.png"));
             ui.add(Label::new("Actor Skins").selectable(false));
         }));
+        VVLaboratoryEditor::render_assets(builder, &package.actor_skin_list, thumbnails);
         builder.close_dir();
 
         builder.dir(package.name.hash + 3, "Havok Animation Databases");
+        VVLaboratoryEditor::render_assets(builder, &package.havok_anim_db_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 4, "Havok Rigid Bodies");
+        VVLaboratoryEditor::render_assets(builder, &package.havok_rigid_body_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 5, "Havok Physics Systems");
+        VVLaboratoryEditor::render_assets(builder, &package.havok_physics_system_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 6, "Textures");
+        VVLaboratoryEditor::render_assets(builder, &package.texture_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 7, "Effects");
+        VVLaboratoryEditor::render_assets(builder, &package.effect_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 8, "Shaders");
+        VVLaboratoryEditor::render_assets(builder, &package.shader_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 9, "Motion Paths");
+        VVLaboratoryEditor::render_assets(builder, &package.motion_path_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 10, "igx Files");
+        VVLaboratoryEditor::render_assets(builder, &package.igx_file_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 11, "Material Instances");
+        VVLaboratoryEditor::render_assets(builder, &package.material_instances_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 12, "igx Entities");
+        VVLaboratoryEditor::render_assets(builder, &package.igx_entities_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 13, "Gui Projects");
+        VVLaboratoryEditor::render_assets(builder, &package.gui_project_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 14, "Fonts");
+        VVLaboratoryEditor::render_assets(builder, &package.font_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 15, "Lang Files");
-        for lang_file in &package.lang_file_list {
-            if let Ok(lang_file) = lang_file.read() {
-                builder.leaf(lang_file.name.hash, WidgetText::from(lang_file.name.string.clone().unwrap().replace("strings/", "")))
-            }
-        }
+        VVLaboratoryEditor::render_assets(builder, &package.lang_file_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 16, "Spawn Meshes");
+        VVLaboratoryEditor::render_assets(builder, &package.spawn_mesh_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 17, "Models");
+        VVLaboratoryEditor::render_assets(builder, &package.model_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 18, "Sky Models");
+        VVLaboratoryEditor::render_assets(builder, &package.sky_model_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 19, "Behaviours");
+        VVLaboratoryEditor::render_assets(builder, &package.behavior_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 20, "Graph Data");
+        VVLaboratoryEditor::render_assets(builder, &package.graph_data_behavior_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 21, "Events Behaviours");
+        VVLaboratoryEditor::render_assets(builder, &package.events_behavior_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 22, "Asset Behaviours");
+        VVLaboratoryEditor::render_assets(builder, &package.asset_behavior_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 23, "Havok Binary Behaviors");
+        VVLaboratoryEditor::render_assets(builder, &package.hkb_behavior_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 24, "Havok Char Characters");
+        VVLaboratoryEditor::render_assets(builder, &package.hkc_character_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 25, "Navigation Meshes");
+        VVLaboratoryEditor::render_assets(builder, &package.navmesh_list, thumbnails);
         builder.close_dir();
         builder.dir(package.name.hash + 26, "Scripts");
+        VVLaboratoryEditor::render_assets(builder, &package.script_list, thumbnails);
         builder.close_dir();
 
         builder.close_dir();
@@ -282,6 +472,8 @@ impl WorkshopTabImpl for VVLaboratoryEditor {
     }
 
     fn ui(&mut self, ui: &mut Ui, _viewer: &mut WorkshopTabViewer) {
+        self.load_thumbnails(ui.ctx());
+
         SidePanel::left(ui.make_persistent_id("left_file_panel"))
             .resizable(true)
             .min_width(50.0)
@@ -305,7 +497,7 @@ impl WorkshopTabImpl for VVLaboratoryEditor {
                     TreeView::new(id).show(ui, |builder| {
                         if self.search_bar_contents.is_empty() {
                             for (package_name, package) in &self.loaded_packages {
-                                VVLaboratoryEditor::render_package(builder, package_name.clone(), package);
+                                VVLaboratoryEditor::render_package(builder, package_name.clone(), package, &self.thumbnails);
                             }
                         } else {
                             if self.old_search_bar_contents != self.search_bar_contents {
@@ -313,7 +505,7 @@ impl WorkshopTabImpl for VVLaboratoryEditor {
                             }
 
                             for package_name in &self.filtered_packages {
-                                VVLaboratoryEditor::render_package(builder, package_name.clone(), self.loaded_packages.get_mut(package_name).unwrap());
+                                VVLaboratoryEditor::render_package(builder, package_name.clone(), self.loaded_packages.get_mut(package_name).unwrap(), &self.thumbnails);
                             }
                         }
                     });